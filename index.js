@@ -574,20 +574,48 @@ if (!nativeBinding) {
 module.exports = nativeBinding
 module.exports.Agent = nativeBinding.Agent
 module.exports.AgentStats = nativeBinding.AgentStats
+module.exports.BodyChunks = nativeBinding.BodyChunks
+module.exports.FaithHeaders = nativeBinding.FaithHeaders
 module.exports.FaithResponse = nativeBinding.FaithResponse
+module.exports.PreparedRequest = nativeBinding.PreparedRequest
 module.exports.StreamBody = nativeBinding.StreamBody
 module.exports.StreamBodySender = nativeBinding.StreamBodySender
+module.exports.TestServer = nativeBinding.TestServer
+module.exports.AgentProfile = nativeBinding.AgentProfile
+module.exports.AltSvcSource = nativeBinding.AltSvcSource
 module.exports.CacheMode = nativeBinding.CacheMode
 module.exports.RequestCacheMode = nativeBinding.RequestCacheMode
 module.exports.CacheStore = nativeBinding.CacheStore
+module.exports.capabilities = nativeBinding.capabilities
+module.exports.ChecksumAlgorithm = nativeBinding.ChecksumAlgorithm
+module.exports.configureRuntime = nativeBinding.configureRuntime
 module.exports.createStreamBodyPair = nativeBinding.createStreamBodyPair
 module.exports.CredentialsOption = nativeBinding.CredentialsOption
+module.exports.DnsFamily = nativeBinding.DnsFamily
 module.exports.DuplexOption = nativeBinding.DuplexOption
 module.exports.errorCodes = nativeBinding.errorCodes
 module.exports.FAITH_VERSION = nativeBinding.FAITH_VERSION
+module.exports.FaithAbortError = nativeBinding.FaithAbortError
+module.exports.FaithError = nativeBinding.FaithError
+module.exports.FaithHttpError = nativeBinding.FaithHttpError
 module.exports.FaithErrorKind = nativeBinding.FaithErrorKind
 module.exports.faithFetch = nativeBinding.faithFetch
+module.exports.faithFetchAll = nativeBinding.faithFetchAll
+module.exports.FaithNetworkError = nativeBinding.FaithNetworkError
+module.exports.faithShutdown = nativeBinding.faithShutdown
+module.exports.FaithSyntaxError = nativeBinding.FaithSyntaxError
+module.exports.FaithTimeoutError = nativeBinding.FaithTimeoutError
+module.exports.FaithTypeError = nativeBinding.FaithTypeError
+module.exports.faithUpload = nativeBinding.faithUpload
+module.exports.getGlobalAgent = nativeBinding.getGlobalAgent
 module.exports.Http3Congestion = nativeBinding.Http3Congestion
+module.exports.MiddlewareStage = nativeBinding.MiddlewareStage
+module.exports.ProxyMode = nativeBinding.ProxyMode
 module.exports.Redirect = nativeBinding.Redirect
 module.exports.REQWEST_VERSION = nativeBinding.REQWEST_VERSION
+module.exports.setGlobalAgent = nativeBinding.setGlobalAgent
+module.exports.startTestServer = nativeBinding.startTestServer
+module.exports.UrlCredentials = nativeBinding.UrlCredentials
+module.exports.UrlIdn = nativeBinding.UrlIdn
 module.exports.USER_AGENT = nativeBinding.USER_AGENT
+module.exports.VersionOption = nativeBinding.VersionOption
@@ -0,0 +1,50 @@
+use std::{
+	mem::take,
+	sync::{Arc, Mutex, OnceLock},
+};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{FaithError, FaithErrorKind};
+
+/// Aborts the requests in flight on an agent, for `agent.abortAll()`.
+///
+/// Requests hold on to the generation current when they start. Aborting cancels that generation and
+/// replaces it with a fresh one, so requests started afterwards are unaffected.
+#[derive(Debug, Default)]
+pub(crate) struct AgentAborter {
+	current: Mutex<Arc<AbortGeneration>>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct AbortGeneration {
+	token: CancellationToken,
+	reason: OnceLock<Option<String>>,
+}
+
+impl AgentAborter {
+	pub(crate) fn current(&self) -> Arc<AbortGeneration> {
+		self.current
+			.lock()
+			.unwrap_or_else(|err| err.into_inner())
+			.clone()
+	}
+
+	pub(crate) fn abort(&self, reason: Option<String>) {
+		let aborted = take(&mut *self.current.lock().unwrap_or_else(|err| err.into_inner()));
+		let _ = aborted.reason.set(reason);
+		aborted.token.cancel();
+	}
+}
+
+impl AbortGeneration {
+	/// Resolves with the error to reject with, once this generation is aborted.
+	pub(crate) async fn aborted(&self) -> FaithError {
+		self.token.cancelled().await;
+		FaithError::new(
+			FaithErrorKind::Aborted,
+			self.reason.get().cloned().flatten(),
+		)
+		.with_cause("agent.abortAll")
+	}
+}
@@ -1,5 +1,5 @@
 use std::{
-	sync::Arc,
+	sync::{Arc, atomic::Ordering},
 	time::{Duration, Instant},
 };
 
@@ -8,6 +8,13 @@ use moka::sync::Cache;
 use reqwest::{Request, Response};
 use reqwest_middleware::{Middleware, Next, Result};
 
+use crate::{
+	agent::InnerAgentStats,
+	clock::Clock,
+	options::VersionOption,
+	response::{AltSvcDecision, AltSvcSource},
+};
+
 #[derive(Debug, Clone)]
 pub struct AltSvcEntry {
 	pub port: u16,
@@ -18,10 +25,13 @@ pub struct AltSvcEntry {
 pub struct AltSvcCache {
 	advertised: Cache<String, AltSvcEntry>,
 	confirmed: Cache<String, AltSvcEntry>,
-	failed: Cache<String, ()>,
+	/// When each failure stops counting, by the agent's clock.
+	failed: Cache<String, Instant>,
 
 	advertised_ttl: Duration,
 	confirmed_ttl: Duration,
+	failed_ttl: Duration,
+	clock: Clock,
 }
 
 impl std::fmt::Debug for AltSvcCache {
@@ -40,6 +50,7 @@ impl AltSvcCache {
 		confirmed_ttl: Duration,
 		failed_ttl: Duration,
 		capacity: u64,
+		clock: Clock,
 	) -> Self {
 		Self {
 			advertised: Cache::builder()
@@ -56,6 +67,8 @@ impl AltSvcCache {
 				.build(),
 			advertised_ttl,
 			confirmed_ttl,
+			failed_ttl,
+			clock,
 		}
 	}
 
@@ -70,7 +83,7 @@ impl AltSvcCache {
 			return;
 		};
 
-		if self.failed.contains_key(&origin) {
+		if self.origin_failed(&origin) {
 			return;
 		}
 
@@ -81,7 +94,7 @@ impl AltSvcCache {
 		let ttl = max_age.unwrap_or(self.advertised_ttl);
 		let entry = AltSvcEntry {
 			port: h3_port,
-			expires: Instant::now() + ttl,
+			expires: self.clock.now() + ttl,
 		};
 
 		self.advertised.insert(origin, entry);
@@ -90,13 +103,13 @@ impl AltSvcCache {
 	pub fn add_hint(&self, host: &str, port: u16) {
 		let origin = format!("https://{}:{}", host, port);
 
-		if self.failed.contains_key(&origin) {
+		if self.origin_failed(&origin) {
 			return;
 		}
 
 		let entry = AltSvcEntry {
 			port,
-			expires: Instant::now() + Duration::from_hours(10_000), // forever
+			expires: self.clock.now() + Duration::from_hours(10_000), // forever
 		};
 
 		self.advertised.insert(origin, entry);
@@ -105,18 +118,18 @@ impl AltSvcCache {
 	pub fn should_use_h3(&self, url: &reqwest::Url) -> Option<u16> {
 		let origin = Self::origin_key(url)?;
 
-		if self.failed.contains_key(&origin) {
+		if self.origin_failed(&origin) {
 			return None;
 		}
 
 		if let Some(entry) = self.confirmed.get(&origin) {
-			if entry.expires > Instant::now() {
+			if entry.expires > self.clock.now() {
 				return Some(entry.port);
 			}
 		}
 
 		if let Some(entry) = self.advertised.get(&origin) {
-			if entry.expires > Instant::now() {
+			if entry.expires > self.clock.now() {
 				return Some(entry.port);
 			}
 		}
@@ -124,6 +137,38 @@ impl AltSvcCache {
 		None
 	}
 
+	/// Which entry `should_use_h3` would use for the origin, disregarding recent failures.
+	pub fn entry_source(&self, url: &reqwest::Url) -> Option<AltSvcSource> {
+		let origin = Self::origin_key(url)?;
+		let now = self.clock.now();
+
+		if self
+			.confirmed
+			.get(&origin)
+			.is_some_and(|entry| entry.expires > now)
+		{
+			Some(AltSvcSource::Confirmed)
+		} else if self
+			.advertised
+			.get(&origin)
+			.is_some_and(|entry| entry.expires > now)
+		{
+			Some(AltSvcSource::Advertised)
+		} else {
+			None
+		}
+	}
+
+	fn origin_failed(&self, origin: &str) -> bool {
+		self.failed
+			.get(origin)
+			.is_some_and(|expires| expires > self.clock.now())
+	}
+
+	pub fn has_failed(&self, url: &reqwest::Url) -> bool {
+		Self::origin_key(url).is_some_and(|origin| self.origin_failed(&origin))
+	}
+
 	pub fn confirm_h3(&self, url: &reqwest::Url) {
 		let Some(origin) = Self::origin_key(url) else {
 			return;
@@ -140,12 +185,17 @@ impl AltSvcCache {
 
 		let entry = AltSvcEntry {
 			port,
-			expires: Instant::now() + self.confirmed_ttl,
+			expires: self.clock.now() + self.confirmed_ttl,
 		};
 
 		self.confirmed.insert(origin, entry);
 	}
 
+	/// Forgets every HTTP/3 failure, so upgrades are attempted again.
+	pub fn reset_failures(&self) {
+		self.failed.invalidate_all();
+	}
+
 	pub fn record_h3_failure(&self, url: &reqwest::Url) {
 		let Some(origin) = Self::origin_key(url) else {
 			return;
@@ -153,7 +203,8 @@ impl AltSvcCache {
 
 		self.advertised.invalidate(&origin);
 		self.confirmed.invalidate(&origin);
-		self.failed.insert(origin, ());
+		self.failed
+			.insert(origin, self.clock.now() + self.failed_ttl);
 	}
 }
 
@@ -215,6 +266,7 @@ pub fn parse_alt_svc_header(value: &str) -> Option<(u16, Option<Duration>)> {
 pub struct AltSvcMiddleware {
 	cache: Arc<AltSvcCache>,
 	enabled: bool,
+	stats: Arc<InnerAgentStats>,
 }
 
 impl std::fmt::Debug for AltSvcMiddleware {
@@ -227,8 +279,12 @@ impl std::fmt::Debug for AltSvcMiddleware {
 }
 
 impl AltSvcMiddleware {
-	pub fn new(cache: Arc<AltSvcCache>, enabled: bool) -> Self {
-		Self { cache, enabled }
+	pub fn new(cache: Arc<AltSvcCache>, enabled: bool, stats: Arc<InnerAgentStats>) -> Self {
+		Self {
+			cache,
+			enabled,
+			stats,
+		}
 	}
 
 	#[allow(dead_code)]
@@ -245,18 +301,27 @@ impl Middleware for AltSvcMiddleware {
 		extensions: &mut Extensions,
 		next: Next<'_>,
 	) -> Result<Response> {
-		if !self.enabled {
+		if !self.enabled || extensions.get::<VersionOption>().is_some() {
 			return next.run(req, extensions).await;
 		}
 
 		let url = req.url().clone();
 		let trying_h3 = self.cache.should_use_h3(&url).is_some();
+		let mut decision = AltSvcDecision {
+			entry: self.cache.entry_source(&url),
+			attempted: false,
+			recently_failed: self.cache.has_failed(&url),
+			fell_back: false,
+			fallback_after: None,
+		};
 
-		if trying_h3 {
+		let result = if trying_h3 {
 			// Clone the request before attempting HTTP/3 so we can retry with TCP if it fails
 			if let Some(req_clone) = req.try_clone() {
 				*req.version_mut() = http::Version::HTTP_3;
+				decision.attempted = true;
 
+				let started = Instant::now();
 				let result = next.clone().run(req, extensions).await;
 
 				match result {
@@ -278,6 +343,9 @@ impl Middleware for AltSvcMiddleware {
 					Err(_) => {
 						// HTTP/3 failed, record the failure and retry with HTTP/2 (or /1)
 						self.cache.record_h3_failure(&url);
+						self.stats.retries.fetch_add(1, Ordering::Relaxed);
+						decision.fell_back = true;
+						decision.fallback_after = Some(started.elapsed().as_secs_f64() * 1000.0);
 
 						// Use the cloned request (which still has default HTTP version)
 						next.run(req_clone, extensions).await
@@ -302,7 +370,12 @@ impl Middleware for AltSvcMiddleware {
 			}
 
 			result
-		}
+		};
+
+		result.map(|mut response| {
+			response.extensions_mut().insert(decision);
+			response
+		})
 	}
 }
 
@@ -358,6 +431,7 @@ mod tests {
 			Duration::from_secs(86400),
 			Duration::from_secs(300),
 			10_000,
+			Clock::mock(),
 		)
 	}
 
@@ -370,9 +444,11 @@ mod tests {
 
 		cache.record_alt_svc(&url, 443, Some(Duration::from_secs(3600)));
 		assert_eq!(cache.should_use_h3(&url), Some(443));
+		assert_eq!(cache.entry_source(&url), Some(AltSvcSource::Advertised));
 
 		cache.confirm_h3(&url);
 		assert_eq!(cache.should_use_h3(&url), Some(443));
+		assert_eq!(cache.entry_source(&url), Some(AltSvcSource::Confirmed));
 		assert!(
 			!cache
 				.advertised
@@ -395,11 +471,26 @@ mod tests {
 
 		cache.record_h3_failure(&url);
 		assert!(cache.should_use_h3(&url).is_none());
+		assert!(cache.has_failed(&url));
 
 		cache.record_alt_svc(&url, 443, None);
 		assert!(cache.should_use_h3(&url).is_none());
 	}
 
+	#[test]
+	fn test_cache_expiry() {
+		let cache = test_cache();
+		let url = reqwest::Url::parse("https://example.com/path").unwrap();
+
+		cache.record_alt_svc(&url, 443, Some(Duration::from_secs(60)));
+		cache.clock.advance(Duration::from_secs(61));
+		assert!(cache.should_use_h3(&url).is_none());
+
+		cache.record_h3_failure(&url);
+		cache.clock.advance(Duration::from_secs(301));
+		assert!(!cache.has_failed(&url));
+	}
+
 	#[test]
 	fn test_hint() {
 		let cache = test_cache();
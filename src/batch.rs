@@ -0,0 +1,135 @@
+use std::result::Result;
+
+use futures::{StreamExt, TryStreamExt, stream};
+use napi::{
+	Env,
+	bindgen_prelude::*,
+	sys::{napi_env, napi_value},
+};
+use napi_derive::napi;
+
+use crate::{
+	agent::Agent,
+	async_task::{Async, FaithAsyncResult},
+	error::{FaithError, FaithErrorKind},
+	fetch::{abort_receiver, fetch},
+	options::{FaithOptions, FaithOptionsAndBody},
+	response::FaithResponse,
+};
+
+/// A request in a `faithFetchAll()` batch.
+#[napi(object)]
+pub struct FaithBatchRequest {
+	pub url: String,
+	/// The same options as for `faithFetch()`, except streaming bodies aren't supported.
+	pub options: Option<FaithOptionsAndBody>,
+}
+
+/// Settings for `faithFetchAll()`.
+#[napi(object)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FaithFetchAllOptions {
+	/// Maximum number of requests in flight at once. Requests are started in order as others complete.
+	///
+	/// Default: 16.
+	pub concurrency: Option<u32>,
+	/// Reject with the first error, instead of settling every request. Requests in flight are then
+	/// cancelled, and requests not yet started are not sent.
+	///
+	/// Default: false.
+	pub fail_fast: Option<bool>,
+}
+
+/// The outcome of a request in a batch, as an object shaped like those of `Promise.allSettled()`.
+pub struct Settled(Result<FaithResponse, FaithError>);
+
+impl TypeName for Settled {
+	fn type_name() -> &'static str {
+		"FaithSettledResult"
+	}
+
+	fn value_type() -> ValueType {
+		ValueType::Object
+	}
+}
+
+impl ToNapiValue for Settled {
+	unsafe fn to_napi_value(env: napi_env, val: Self) -> Result<napi_value, napi::Error> {
+		let env = Env::from_raw(env);
+		let mut obj = env.create_object()?;
+		match val.0 {
+			Ok(response) => {
+				obj.set("status", "fulfilled")?;
+				obj.set("value", response)?;
+			}
+			Err(err) => {
+				obj.set("status", "rejected")?;
+				obj.set("reason", err.into_js_error(&env))?;
+			}
+		}
+		unsafe { Object::to_napi_value(env.raw(), obj) }
+	}
+}
+
+/// Custom to Fáith.
+///
+/// Fetches many resources at once, with a single task for the whole batch instead of one per request.
+/// This avoids most of the per-request overhead of crossing between JavaScript and native code, which
+/// adds up when making hundreds of small requests.
+///
+/// Resolves to an array of results in the same order as the requests, shaped like those of
+/// `Promise.allSettled()`: `{ status: "fulfilled", value }` with the response, or
+/// `{ status: "rejected", reason }` with the error. With `failFast`, rejects with the first error
+/// instead.
+///
+/// The `signal` aborts the whole batch.
+#[napi(
+	ts_return_type = "Async<Array<{ status: 'fulfilled', value: FaithResponse } | { status: 'rejected', reason: Error }>>"
+)]
+pub fn faith_fetch_all(
+	env: Env,
+	requests: Vec<FaithBatchRequest>,
+	options: Option<FaithFetchAllOptions>,
+	signal: Option<AbortSignal>,
+) -> Async<Vec<Settled>> {
+	let options = options.unwrap_or_default();
+	let concurrency = options.concurrency.unwrap_or(16).max(1) as usize;
+	let fail_fast = options.fail_fast.unwrap_or(false);
+
+	let requests: Vec<_> = requests
+		.into_iter()
+		.map(|FaithBatchRequest { url, options }| {
+			let (options, agent, body) = FaithOptions::extract(options.unwrap_or_default());
+			let agent = agent.map_or_else(|| Agent::global(&env), Ok);
+			(url, options, agent, body)
+		})
+		.collect();
+
+	let abort = abort_receiver(signal.as_ref());
+	FaithAsyncResult::run(async move || {
+		let results = stream::iter(requests)
+			.map(|(url, options, agent, body)| async move {
+				fetch(url, options, agent?, body, None, None).await
+			})
+			.buffered(concurrency);
+
+		let batch = async move {
+			if fail_fast {
+				results
+					.try_collect::<Vec<_>>()
+					.await
+					.map(|responses| responses.into_iter().map(Ok).map(Settled).collect())
+			} else {
+				Ok(results.map(Settled).collect().await)
+			}
+		};
+
+		match abort {
+			Some(mut abort) => tokio::select! {
+				results = batch => results,
+				_ = abort.recv() => Err(FaithError::from(FaithErrorKind::Aborted).with_cause("signal")),
+			},
+			None => batch.await,
+		}
+	})
+}
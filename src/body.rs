@@ -4,17 +4,20 @@ use std::{
 	pin::Pin,
 	sync::{
 		Arc,
-		atomic::{AtomicBool, Ordering},
+		atomic::{AtomicBool, AtomicU64, Ordering},
 	},
+	task::Poll,
 };
 
-use bytes::Bytes;
-use futures::{Stream, StreamExt};
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt, stream};
 use http_body_util::BodyExt;
 use reqwest::Version;
 use stream_shared::SharedStream;
 use tokio::sync::Mutex;
 
+use crate::{agent::AgentStreamOptions, runtime};
+
 pub(crate) type DynStream = dyn Stream<Item = std::result::Result<Bytes, String>> + Send + Sync;
 
 pub(crate) enum Body {
@@ -23,11 +26,126 @@ pub(crate) enum Body {
 	Stream(SharedStream<Pin<Box<DynStream>>>),
 }
 
+/// How the body is being read, for `response.bodyStats()`.
+#[derive(Debug, Default)]
+pub(crate) struct BodyStreamStats {
+	pub(crate) chunks_delivered: AtomicU64,
+	pub(crate) bytes_delivered: AtomicU64,
+	pub(crate) consumers: AtomicU64,
+	/// How far the furthest consumer has read. The `Body::Stream` keeps everything from the start of
+	/// the body so new consumers can read it, so this is also how much of it is held in memory.
+	pub(crate) buffered_bytes: AtomicU64,
+}
+
+impl BodyStreamStats {
+	/// Returns a new consumer of the shared body, which counts what it reads.
+	pub(crate) fn track(
+		self: &Arc<Self>,
+		stream: SharedStream<Pin<Box<DynStream>>>,
+	) -> Pin<Box<DynStream>> {
+		struct Consumer(Arc<BodyStreamStats>);
+		impl Drop for Consumer {
+			fn drop(&mut self) {
+				self.0.consumers.fetch_sub(1, Ordering::Relaxed);
+			}
+		}
+
+		self.consumers.fetch_add(1, Ordering::Relaxed);
+		let consumer = Consumer(self.clone());
+		let mut position = 0;
+		Box::pin(stream.inspect(move |chunk| {
+			if let Ok(chunk) = chunk {
+				let stats = &consumer.0;
+				position += chunk.len() as u64;
+				stats.chunks_delivered.fetch_add(1, Ordering::Relaxed);
+				stats
+					.bytes_delivered
+					.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+				stats.buffered_bytes.fetch_max(position, Ordering::Relaxed);
+			}
+		}))
+	}
+}
+
+/// Merges and splits the chunks of a body as they're handed to JS, following the agent's `stream`
+/// options.
+pub(crate) fn rechunk(
+	mut inner: Pin<Box<DynStream>>,
+	options: AgentStreamOptions,
+) -> Pin<Box<DynStream>> {
+	let coalesce = options.coalesce_small_chunks.unwrap_or(false);
+	let max = options.max_chunk_bytes.map(|max| max as usize);
+	if !coalesce && max.is_none() {
+		return inner;
+	}
+
+	let high_water_mark = options.high_water_mark_bytes.unwrap_or(64 * 1024) as usize;
+	let mut rest = Bytes::new();
+	let mut error = None;
+	let mut done = false;
+	Box::pin(stream::poll_fn(move |cx| {
+		if !rest.is_empty() {
+			let chunk = std::mem::take(&mut rest);
+			return Poll::Ready(Some(Ok(split(chunk, max, &mut rest))));
+		}
+		if let Some(err) = error.take() {
+			return Poll::Ready(Some(Err(err)));
+		}
+		if done {
+			return Poll::Ready(None);
+		}
+
+		let mut merged = BytesMut::new();
+		loop {
+			match inner.poll_next_unpin(cx) {
+				Poll::Ready(Some(Ok(chunk))) if !coalesce => {
+					return Poll::Ready(Some(Ok(split(chunk, max, &mut rest))));
+				}
+				Poll::Ready(Some(Ok(chunk))) => {
+					merged.extend_from_slice(&chunk);
+					if merged.len() >= high_water_mark {
+						break;
+					}
+				}
+				Poll::Ready(Some(Err(err))) if merged.is_empty() => {
+					return Poll::Ready(Some(Err(err)));
+				}
+				Poll::Ready(Some(Err(err))) => {
+					error = Some(err);
+					break;
+				}
+				Poll::Ready(None) => {
+					done = true;
+					if merged.is_empty() {
+						return Poll::Ready(None);
+					}
+					break;
+				}
+				Poll::Pending if merged.is_empty() => return Poll::Pending,
+				Poll::Pending => break,
+			}
+		}
+		Poll::Ready(Some(Ok(split(merged.freeze(), max, &mut rest))))
+	}))
+}
+
+/// Keeps the first `max` bytes of the chunk, and puts the rest aside for the next one.
+fn split(mut chunk: Bytes, max: Option<usize>, rest: &mut Bytes) -> Bytes {
+	if let Some(max) = max
+		&& chunk.len() > max
+	{
+		*rest = chunk.split_off(max);
+	}
+	chunk
+}
+
 /// Wrapper around the body that auto-drains on drop to release the connection.
 pub(crate) struct BodyHolder {
 	pub body: Option<Arc<Mutex<Body>>>,
 	/// Flag to prevent drain if body was properly consumed
 	pub(crate) drained: Arc<AtomicBool>,
+	/// Shared by the holders of the same body, like `drained`
+	pub(crate) stats: Arc<BodyStreamStats>,
 	/// HTTP version - HTTP/2+ doesn't need draining for connection reuse
 	pub(crate) version: Version,
 }
@@ -38,6 +156,7 @@ impl BodyHolder {
 			body,
 			version,
 			drained: Arc::new(AtomicBool::new(false)),
+			stats: Default::default(),
 		}
 	}
 
@@ -46,6 +165,7 @@ impl BodyHolder {
 			body: None,
 			version: Version::HTTP_11,
 			drained: Arc::new(AtomicBool::new(true)),
+			stats: Default::default(),
 		}
 	}
 
@@ -66,6 +186,7 @@ impl Clone for BodyHolder {
 		Self {
 			body: self.body.clone(),
 			drained: self.drained.clone(),
+			stats: self.stats.clone(),
 			version: self.version,
 		}
 	}
@@ -76,6 +197,7 @@ impl Debug for BodyHolder {
 		f.debug_struct("BodyHolder")
 			.field("body", &self.body)
 			.field("drained", &self.drained.load(Ordering::SeqCst))
+			.field("stats", &self.stats)
 			.field("version", &self.version)
 			.finish()
 	}
@@ -97,15 +219,14 @@ impl Drop for BodyHolder {
 			// Only spawn drain task if we're the last holder
 			if Arc::strong_count(arc) == 1 {
 				let arc = self.body.take().unwrap();
-				// Only spawn if we're in a tokio runtime context
-				// (Drop might be called during GC outside of async context)
-				if let Ok(handle) = tokio::runtime::Handle::try_current() {
+				// Drop might be called during GC outside of async context, so spawn on the runtime
+				// explicitly rather than the current one
+				if let Ok(handle) = runtime::handle() {
 					handle.spawn(async move {
 						drain_body_inner(arc).await;
 					});
 				}
-				// If no runtime, the connection will be closed rather than reused
-				// This is acceptable as a fallback
+				// If the runtime couldn't start, the connection will be closed rather than reused
 			}
 		}
 	}
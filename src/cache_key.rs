@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use http::{Extensions, HeaderName, HeaderValue, header::VARY, request::Parts};
+use reqwest::{Request, Response, Url};
+use reqwest_middleware::{Middleware, Next};
+
+use crate::{
+	agent::AgentCacheKeyOptions,
+	error::{FaithError, FaithErrorKind},
+};
+
+/// Computes the HTTP cache's keys from `cache.key`.
+#[derive(Debug, Clone)]
+pub(crate) struct CacheKeyer {
+	headers: Vec<HeaderName>,
+	ignore_query: Vec<String>,
+	sort_query: bool,
+}
+
+impl CacheKeyer {
+	/// Returns `None` if the options don't change the default key.
+	pub(crate) fn new(options: &AgentCacheKeyOptions) -> Result<Option<Self>, FaithError> {
+		let headers = options
+			.headers
+			.iter()
+			.flatten()
+			.map(|name| {
+				HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+					FaithError::new(
+						FaithErrorKind::InvalidHeader,
+						Some(format!("invalid header name in cache.key.headers: {name}")),
+					)
+				})
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let keyer = Self {
+			headers,
+			ignore_query: options.ignore_query.clone().unwrap_or_default(),
+			sort_query: options.sort_query.unwrap_or(false),
+		};
+		Ok(
+			(!keyer.headers.is_empty() || !keyer.ignore_query.is_empty() || keyer.sort_query)
+				.then_some(keyer),
+		)
+	}
+
+	pub(crate) fn into_cache_key(self) -> Arc<dyn Fn(&Parts) -> String + Send + Sync> {
+		Arc::new(move |parts: &Parts| self.key(parts))
+	}
+
+	/// The method and normalised URL, as in the default `{method}:{url}` key, followed by the
+	/// values of the key's headers.
+	fn key(&self, parts: &Parts) -> String {
+		let uri = parts.uri.to_string();
+		let mut key = format!(
+			"{}:{}",
+			parts.method,
+			Url::parse(&uri).map_or(uri, |url| self.normalise(url).into())
+		);
+
+		for name in &self.headers {
+			key.push('\n');
+			key.push_str(name.as_str());
+			key.push(':');
+			let values = parts
+				.headers
+				.get_all(name)
+				.iter()
+				.map(HeaderValue::as_bytes);
+			for (i, value) in values.enumerate() {
+				if i > 0 {
+					key.push(',');
+				}
+				key.push_str(&String::from_utf8_lossy(value));
+			}
+		}
+
+		key
+	}
+
+	fn normalise(&self, mut url: Url) -> Url {
+		if url.query().is_none() || (self.ignore_query.is_empty() && !self.sort_query) {
+			return url;
+		}
+
+		let mut pairs: Vec<(String, String)> = url
+			.query_pairs()
+			.filter(|(name, _)| !self.ignore_query.iter().any(|ignored| ignored == name))
+			.map(|(name, value)| (name.into_owned(), value.into_owned()))
+			.collect();
+		if self.sort_query {
+			pairs.sort();
+		}
+
+		if pairs.is_empty() {
+			url.set_query(None);
+		} else {
+			url.query_pairs_mut().clear().extend_pairs(pairs);
+		}
+		url
+	}
+}
+
+/// Removes `*` from `Vary` response headers, for `cache.key.ignoreVaryStar`.
+///
+/// This is added inside the cache middleware, so the cache sees the response without it.
+#[derive(Debug)]
+pub(crate) struct IgnoreVaryStarMiddleware;
+
+#[async_trait::async_trait]
+impl Middleware for IgnoreVaryStarMiddleware {
+	async fn handle(
+		&self,
+		req: Request,
+		extensions: &mut Extensions,
+		next: Next<'_>,
+	) -> reqwest_middleware::Result<Response> {
+		let mut response = next.run(req, extensions).await?;
+
+		let headers = response.headers_mut();
+		let has_star = headers.get_all(VARY).iter().any(|value| {
+			value
+				.to_str()
+				.is_ok_and(|v| v.split(',').any(|v| v.trim() == "*"))
+		});
+		if !has_star {
+			return Ok(response);
+		}
+
+		let vary: Vec<String> = headers
+			.get_all(VARY)
+			.iter()
+			.filter_map(|value| value.to_str().ok())
+			.flat_map(|value| value.split(','))
+			.map(str::trim)
+			.filter(|name| !name.is_empty() && *name != "*")
+			.map(Into::into)
+			.collect();
+		headers.remove(VARY);
+		if !vary.is_empty()
+			&& let Ok(value) = HeaderValue::try_from(vary.join(", "))
+		{
+			headers.insert(VARY, value);
+		}
+
+		Ok(response)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn key(options: AgentCacheKeyOptions, uri: &str, headers: &[(&'static str, &str)]) -> String {
+		let mut request = http::Request::get(uri);
+		for (name, value) in headers {
+			request = request.header(*name, *value);
+		}
+		let (parts, ()) = request.body(()).unwrap().into_parts();
+		CacheKeyer::new(&options).unwrap().unwrap().key(&parts)
+	}
+
+	#[test]
+	fn query_normalisation() {
+		let options = AgentCacheKeyOptions {
+			ignore_query: Some(vec!["utm_source".into()]),
+			sort_query: Some(true),
+			..Default::default()
+		};
+		assert_eq!(
+			key(
+				options.clone(),
+				"https://example.com/a?b=2&utm_source=x&a=1",
+				&[]
+			),
+			"GET:https://example.com/a?a=1&b=2"
+		);
+		assert_eq!(
+			key(options, "https://example.com/a?utm_source=x", &[]),
+			"GET:https://example.com/a"
+		);
+	}
+
+	#[test]
+	fn headers() {
+		let options = AgentCacheKeyOptions {
+			headers: Some(vec!["Accept-Language".into()]),
+			..Default::default()
+		};
+		assert_eq!(
+			key(
+				options.clone(),
+				"https://example.com/",
+				&[("accept-language", "fr")]
+			),
+			"GET:https://example.com/\naccept-language:fr"
+		);
+		assert_ne!(
+			key(
+				options.clone(),
+				"https://example.com/",
+				&[("accept-language", "fr")]
+			),
+			key(
+				options,
+				"https://example.com/",
+				&[("accept-language", "en")]
+			),
+		);
+	}
+
+	#[test]
+	fn default_key_is_unchanged() {
+		assert!(
+			CacheKeyer::new(&AgentCacheKeyOptions::default())
+				.unwrap()
+				.is_none()
+		);
+	}
+}
@@ -0,0 +1,60 @@
+use napi_derive::napi;
+
+use crate::tls::TlsCryptoProvider;
+
+/// Custom to Fáith.
+///
+/// Which optional features this build of Fáith has, from `capabilities()`:
+///
+/// - `brotli`, `deflate`, `gzip`, `zstd`: whether responses with this `Content-Encoding` are
+///   decompressed, and the encoding is advertised in `Accept-Encoding`.
+/// - `cookies`: whether agents can be given a cookie jar with `cookies`.
+/// - `cryptoProvider`: the cryptography library agents use for TLS, unless `tls.cryptoProvider` is
+///   set. See `TlsCryptoProvider`.
+/// - `cryptoProviders`: the cryptography libraries available for `tls.cryptoProvider`.
+/// - `ech`: whether Encrypted Client Hello is supported, with `tls.ech`.
+/// - `http3`: whether HTTP/3 is supported, both via Alt-Svc upgrades and `version: "h3"`.
+/// - `socketMark`: whether `socket.mark` is supported (Linux only).
+/// - `socketTos`: whether `socket.tos` is supported (not on Windows).
+/// - `systemProxy`: whether the system's proxy configuration is used.
+/// - `testing`: whether `faith/testing` is available.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+	pub brotli: bool,
+	pub cookies: bool,
+	pub crypto_provider: TlsCryptoProvider,
+	pub crypto_providers: Vec<TlsCryptoProvider>,
+	pub deflate: bool,
+	pub ech: bool,
+	pub gzip: bool,
+	pub http3: bool,
+	pub socket_mark: bool,
+	pub socket_tos: bool,
+	pub system_proxy: bool,
+	pub testing: bool,
+	pub zstd: bool,
+}
+
+/// Custom to Fáith.
+///
+/// Returns which optional features this build of Fáith has, so they can be detected before use
+/// rather than by trying them and handling the error. See `Capabilities`.
+#[napi]
+pub fn capabilities() -> Capabilities {
+	Capabilities {
+		brotli: cfg!(feature = "brotli"),
+		cookies: true,
+		crypto_provider: TlsCryptoProvider::default(),
+		crypto_providers: TlsCryptoProvider::available(),
+		deflate: cfg!(feature = "deflate"),
+		ech: cfg!(feature = "ech"),
+		gzip: cfg!(feature = "gzip"),
+		http3: cfg!(feature = "http3"),
+		socket_mark: cfg!(target_os = "linux"),
+		socket_tos: cfg!(unix),
+		system_proxy: cfg!(feature = "system-proxy"),
+		testing: cfg!(feature = "testing"),
+		zstd: cfg!(feature = "zstd"),
+	}
+}
@@ -0,0 +1,268 @@
+use std::sync::{
+	Arc, OnceLock,
+	atomic::{AtomicBool, Ordering},
+};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use md5::{Digest, Md5};
+use napi_derive::napi;
+use reqwest::header::HeaderMap;
+
+use crate::error::{FaithError, FaithErrorKind};
+
+/// A checksum header to validate the response body against, for the `checksums` option:
+///
+/// - `content-md5`: the MD5 digest in `Content-Md5`, or in `md5=` of `x-goog-hash`.
+/// - `crc32c`: the CRC32C in `x-amz-checksum-crc32c`, or in `crc32c=` of `x-goog-hash`.
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+	#[napi(value = "content-md5")]
+	ContentMd5,
+
+	#[napi(value = "crc32c")]
+	Crc32c,
+}
+
+/// The checksums a response is validated against, shared by its clones.
+#[derive(Debug)]
+pub(crate) struct ChecksumCheck {
+	algorithms: Vec<ChecksumAlgorithm>,
+	mismatch: OnceLock<String>,
+	/// Set when the body is validated as sent, before Fáith decompresses it.
+	raw: AtomicBool,
+}
+
+impl ChecksumCheck {
+	/// Returns `None` if no checksum is asked for.
+	pub(crate) fn new(algorithms: Option<Vec<ChecksumAlgorithm>>) -> Option<Arc<Self>> {
+		let algorithms = algorithms.filter(|algorithms| !algorithms.is_empty())?;
+		Some(Arc::new(Self {
+			algorithms,
+			mismatch: OnceLock::new(),
+			raw: AtomicBool::new(false),
+		}))
+	}
+
+	/// Starts validating a body, if the response has any of the checksum headers asked for, and it
+	/// isn't validated before it's decompressed instead.
+	pub(crate) fn verifier(self: &Arc<Self>, headers: &HeaderMap) -> Option<ChecksumVerifier> {
+		if self.raw.load(Ordering::Relaxed) {
+			return None;
+		}
+		self.expected(headers)
+	}
+
+	/// Starts validating a body as sent, before it's decompressed, if the response has any of the
+	/// checksum headers asked for. The decompressed body isn't validated again.
+	pub(crate) fn raw_verifier(self: &Arc<Self>, headers: &HeaderMap) -> Option<ChecksumVerifier> {
+		self.raw.store(true, Ordering::Relaxed);
+		self.expected(headers)
+	}
+
+	fn expected(self: &Arc<Self>, headers: &HeaderMap) -> Option<ChecksumVerifier> {
+		let mut md5 = None;
+		let mut crc32c = None;
+		let mut expect = |algorithm, value: &str| {
+			let Ok(value) = BASE64_STANDARD.decode(value.trim()) else {
+				return;
+			};
+			match algorithm {
+				ChecksumAlgorithm::ContentMd5 => {
+					if let Ok(digest) = value.try_into() {
+						md5 = Some((Md5::new(), digest));
+					}
+				}
+				ChecksumAlgorithm::Crc32c => {
+					if let Ok(crc) = value.try_into() {
+						crc32c = Some((!0, u32::from_be_bytes(crc)));
+					}
+				}
+			}
+		};
+
+		for &algorithm in &self.algorithms {
+			let header = match algorithm {
+				ChecksumAlgorithm::ContentMd5 => "content-md5",
+				ChecksumAlgorithm::Crc32c => "x-amz-checksum-crc32c",
+			};
+			if let Some(value) = headers.get(header).and_then(|v| v.to_str().ok()) {
+				expect(algorithm, value);
+			}
+		}
+
+		for value in headers.get_all("x-goog-hash") {
+			for hash in value.to_str().unwrap_or_default().split(',') {
+				let Some((name, value)) = hash.trim().split_once('=') else {
+					continue;
+				};
+				let algorithm = match name {
+					"md5" => ChecksumAlgorithm::ContentMd5,
+					"crc32c" => ChecksumAlgorithm::Crc32c,
+					_ => continue,
+				};
+				if self.algorithms.contains(&algorithm) {
+					expect(algorithm, value);
+				}
+			}
+		}
+
+		(md5.is_some() || crc32c.is_some()).then(|| ChecksumVerifier {
+			check: self.clone(),
+			md5,
+			crc32c,
+		})
+	}
+
+	/// The error for a body stream failure: `IntegrityMismatch` if a checksum didn't match.
+	pub(crate) fn stream_error(check: Option<&Arc<Self>>, err: String) -> FaithError {
+		match check.and_then(|check| check.mismatch.get()) {
+			Some(mismatch) => {
+				FaithError::new(FaithErrorKind::IntegrityMismatch, Some(mismatch.clone()))
+			}
+			None => FaithError::new(FaithErrorKind::BodyStream, Some(err)),
+		}
+	}
+}
+
+/// Computes the checksums of a body as it's read.
+pub(crate) struct ChecksumVerifier {
+	check: Arc<ChecksumCheck>,
+	md5: Option<(Md5, [u8; 16])>,
+	crc32c: Option<(u32, u32)>,
+}
+
+impl ChecksumVerifier {
+	pub(crate) fn update(&mut self, data: &[u8]) {
+		if let Some((md5, _)) = &mut self.md5 {
+			md5.update(data);
+		}
+		if let Some((crc, _)) = &mut self.crc32c {
+			*crc = crc32c_update(*crc, data);
+		}
+	}
+
+	/// Returns the mismatch, if any, which is also kept for `stream_error()`.
+	pub(crate) fn finish(self) -> Result<(), String> {
+		let mismatch = if let Some((md5, expected)) = self.md5
+			&& md5.finalize()[..] != expected
+		{
+			Some("body does not match its MD5 checksum")
+		} else if let Some((crc, expected)) = self.crc32c
+			&& !crc != expected
+		{
+			Some("body does not match its CRC32C checksum")
+		} else {
+			None
+		};
+
+		match mismatch {
+			Some(mismatch) => Err(self.check.mismatch.get_or_init(|| mismatch.into()).clone()),
+			None => Ok(()),
+		}
+	}
+}
+
+const CRC32C_TABLE: [u32; 256] = {
+	let mut table = [0; 256];
+	let mut i = 0;
+	while i < 256 {
+		let mut crc = i as u32;
+		let mut bit = 0;
+		while bit < 8 {
+			crc = if crc & 1 == 1 {
+				(crc >> 1) ^ 0x82F6_3B78
+			} else {
+				crc >> 1
+			};
+			bit += 1;
+		}
+		table[i] = crc;
+		i += 1;
+	}
+	table
+};
+
+fn crc32c_update(mut crc: u32, data: &[u8]) -> u32 {
+	for &byte in data {
+		crc = CRC32C_TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8);
+	}
+	crc
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn verify(
+		algorithms: &[ChecksumAlgorithm],
+		headers: &[(&'static str, &str)],
+	) -> Result<(), String> {
+		let check = ChecksumCheck::new(Some(algorithms.to_vec())).unwrap();
+		let mut map = HeaderMap::new();
+		for (name, value) in headers {
+			map.append(*name, value.parse().unwrap());
+		}
+		let mut verifier = check.verifier(&map).expect("a checksum header");
+		verifier.update(b"hello ");
+		verifier.update(b"world");
+		verifier.finish()
+	}
+
+	#[test]
+	fn crc32c_check_value() {
+		assert_eq!(!crc32c_update(!0, b"123456789"), 0xE306_9283);
+	}
+
+	#[test]
+	fn content_md5() {
+		let md5 = [ChecksumAlgorithm::ContentMd5];
+		assert!(verify(&md5, &[("content-md5", "XrY7u+Ae7tCTyyK7j1rNww==")]).is_ok());
+		assert!(verify(&md5, &[("content-md5", "AAAAAAAAAAAAAAAAAAAAAA==")]).is_err());
+	}
+
+	#[test]
+	fn amz_crc32c() {
+		let crc32c = [ChecksumAlgorithm::Crc32c];
+		assert!(verify(&crc32c, &[("x-amz-checksum-crc32c", "yZRlqg==")]).is_ok());
+		assert!(verify(&crc32c, &[("x-amz-checksum-crc32c", "AAAAAA==")]).is_err());
+	}
+
+	#[test]
+	fn goog_hash() {
+		let both = [ChecksumAlgorithm::ContentMd5, ChecksumAlgorithm::Crc32c];
+		let headers = [
+			("x-goog-hash", "crc32c=yZRlqg=="),
+			("x-goog-hash", "md5=XrY7u+Ae7tCTyyK7j1rNww=="),
+		];
+		assert!(verify(&both, &headers).is_ok());
+		assert!(
+			verify(
+				&both,
+				&[(
+					"x-goog-hash",
+					"crc32c=AAAAAA==,md5=XrY7u+Ae7tCTyyK7j1rNww=="
+				)]
+			)
+			.is_err()
+		);
+	}
+
+	#[test]
+	fn only_asked_for_algorithms() {
+		let check = ChecksumCheck::new(Some(vec![ChecksumAlgorithm::Crc32c])).unwrap();
+		let mut map = HeaderMap::new();
+		map.insert("content-md5", "AAAAAAAAAAAAAAAAAAAAAA==".parse().unwrap());
+		assert!(check.verifier(&map).is_none());
+		assert!(ChecksumCheck::new(Some(Vec::new())).is_none());
+	}
+
+	#[test]
+	fn raw_replaces_decompressed() {
+		let check = ChecksumCheck::new(Some(vec![ChecksumAlgorithm::ContentMd5])).unwrap();
+		let mut map = HeaderMap::new();
+		map.insert("content-md5", "XrY7u+Ae7tCTyyK7j1rNww==".parse().unwrap());
+		assert!(check.raw_verifier(&map).is_some());
+		assert!(check.verifier(&map).is_none());
+	}
+}
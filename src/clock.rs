@@ -0,0 +1,162 @@
+use std::{
+	sync::Arc,
+	time::{Duration, Instant, SystemTime},
+};
+
+use http_cache_reqwest::{CacheManager, HttpResponse};
+use http_cache_semantics::CachePolicy;
+use tokio::sync::watch;
+
+use crate::error::{FaithError, FaithErrorKind};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// An agent's time source, for the expiry of cached responses, Alt-Svc entries, and cookies, and
+/// for retry backoff.
+///
+/// This is the system's clock, unless the agent has `mockClock`: then it runs with the system's
+/// clock, plus however far it was advanced with `agent.advanceClock()`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Clock {
+	offset: Option<Arc<watch::Sender<Duration>>>,
+}
+
+impl Clock {
+	pub(crate) fn mock() -> Self {
+		Self {
+			offset: Some(Arc::new(watch::Sender::new(Duration::ZERO))),
+		}
+	}
+
+	pub(crate) fn is_mock(&self) -> bool {
+		self.offset.is_some()
+	}
+
+	fn offset(&self) -> Duration {
+		self.offset
+			.as_ref()
+			.map_or(Duration::ZERO, |offset| *offset.borrow())
+	}
+
+	pub(crate) fn now(&self) -> Instant {
+		Instant::now() + self.offset()
+	}
+
+	pub(crate) fn system_now(&self) -> SystemTime {
+		SystemTime::now() + self.offset()
+	}
+
+	/// Moves a mock clock forward, waking the sleeps which are then due. Returns false if the clock
+	/// isn't a mock.
+	pub(crate) fn advance(&self, by: Duration) -> bool {
+		let Some(offset) = &self.offset else {
+			return false;
+		};
+		offset.send_modify(|offset| *offset += by);
+		true
+	}
+
+	/// Sleeps until the clock has moved forward by `duration`, whether by itself or by being
+	/// advanced.
+	pub(crate) async fn sleep(&self, duration: Duration) {
+		let Some(offset) = &self.offset else {
+			tokio::time::sleep(duration).await;
+			return;
+		};
+
+		let deadline = self.now() + duration;
+		let mut changes = offset.subscribe();
+		loop {
+			let remaining = deadline.saturating_duration_since(self.now());
+			if remaining.is_zero() {
+				return;
+			}
+			tokio::select! {
+				_ = tokio::time::sleep(remaining) => {}
+				_ = changes.changed() => {}
+			}
+		}
+	}
+}
+
+/// Wraps the HTTP cache's store so that, with a mock clock, responses which have gone stale by the
+/// agent's clock are treated as missing. The cache itself checks freshness by the system's clock.
+///
+/// The store's errors are also made into `Cache` errors here.
+#[derive(Debug)]
+pub(crate) struct ClockedCacheManager<M> {
+	pub(crate) inner: M,
+	pub(crate) clock: Clock,
+}
+
+#[async_trait::async_trait]
+impl<M: CacheManager> CacheManager for ClockedCacheManager<M> {
+	async fn get(&self, cache_key: &str) -> Result<Option<(HttpResponse, CachePolicy)>, BoxError> {
+		let cached = self.inner.get(cache_key).await.map_err(cache_error)?;
+		if self.clock.is_mock()
+			&& let Some((_, policy)) = &cached
+			&& policy.is_stale(self.clock.system_now())
+		{
+			return Ok(None);
+		}
+		Ok(cached)
+	}
+
+	async fn put(
+		&self,
+		cache_key: String,
+		response: HttpResponse,
+		policy: CachePolicy,
+	) -> Result<HttpResponse, BoxError> {
+		self.inner
+			.put(cache_key, response, policy)
+			.await
+			.map_err(cache_error)
+	}
+
+	async fn delete(&self, cache_key: &str) -> Result<(), BoxError> {
+		self.inner.delete(cache_key).await.map_err(cache_error)
+	}
+}
+
+fn cache_error(err: BoxError) -> BoxError {
+	Box::new(FaithError::new(
+		FaithErrorKind::Cache,
+		Some(err.to_string()),
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn system_clock_cannot_advance() {
+		let clock = Clock::default();
+		assert!(!clock.is_mock());
+		assert!(!clock.advance(Duration::from_secs(60)));
+	}
+
+	#[test]
+	fn mock_clock_advances() {
+		let clock = Clock::mock();
+		let before = clock.system_now();
+		assert!(clock.advance(Duration::from_secs(3600)));
+		assert!(clock.system_now() >= before + Duration::from_secs(3600));
+	}
+
+	#[tokio::test]
+	async fn advancing_wakes_sleeps() {
+		let clock = Clock::mock();
+		let sleeping = tokio::spawn({
+			let clock = clock.clone();
+			async move { clock.sleep(Duration::from_secs(3600)).await }
+		});
+		tokio::task::yield_now().await;
+		clock.advance(Duration::from_secs(3600));
+		tokio::time::timeout(Duration::from_secs(5), sleeping)
+			.await
+			.expect("sleep woke up")
+			.unwrap();
+	}
+}
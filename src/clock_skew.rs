@@ -0,0 +1,115 @@
+use std::{
+	sync::Mutex,
+	time::{Duration, SystemTime},
+};
+
+use napi_derive::napi;
+use reqwest::header::{DATE, HeaderMap};
+
+/// How much each new sample moves the estimate.
+const SMOOTHING: f64 = 0.2;
+
+/// The estimate of `agent.clockSkew()`.
+#[napi(object)]
+#[derive(Debug, Clone, Copy)]
+pub struct ClockSkewEstimate {
+	/// How far ahead of the local clock servers' clocks are, in milliseconds, or behind when
+	/// negative.
+	pub offset: f64,
+	/// How many responses the estimate is based on.
+	pub samples: u32,
+}
+
+/// Estimates the skew between the local clock and servers' clocks from the `Date` headers of
+/// responses, for `clockSkew`.
+///
+/// `Date` headers only have a resolution of a second, so each sample is taken against the midpoint
+/// of the exchange, and half a second is added to account for the truncation. Samples are smoothed
+/// with an exponentially weighted moving average, so that a single slow or misconfigured server
+/// doesn't swing the estimate.
+#[derive(Debug, Default)]
+pub(crate) struct ClockSkew {
+	estimate: Mutex<Option<ClockSkewEstimate>>,
+}
+
+impl ClockSkew {
+	/// Records the `Date` of a response received from the network, where `sent` and `received` are
+	/// the times by the agent's clock at which the request was sent and its response received.
+	pub(crate) fn record(&self, headers: &HeaderMap, sent: SystemTime, received: SystemTime) {
+		let Some(date) = headers
+			.get(DATE)
+			.and_then(|value| value.to_str().ok())
+			.and_then(|value| httpdate::parse_http_date(value).ok())
+		else {
+			return;
+		};
+
+		let local = sent + received.duration_since(sent).unwrap_or_default() / 2;
+		let server = date + Duration::from_millis(500);
+		let sample = match server.duration_since(local) {
+			Ok(ahead) => ahead.as_secs_f64() * 1000.0,
+			Err(behind) => -behind.duration().as_secs_f64() * 1000.0,
+		};
+
+		let mut estimate = self.estimate.lock().unwrap_or_else(|err| err.into_inner());
+		*estimate = Some(match *estimate {
+			None => ClockSkewEstimate {
+				offset: sample,
+				samples: 1,
+			},
+			Some(previous) => ClockSkewEstimate {
+				offset: previous.offset + SMOOTHING * (sample - previous.offset),
+				samples: previous.samples.saturating_add(1),
+			},
+		});
+	}
+
+	pub(crate) fn estimate(&self) -> Option<ClockSkewEstimate> {
+		*self.estimate.lock().unwrap_or_else(|err| err.into_inner())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn date(time: SystemTime) -> HeaderMap {
+		let mut headers = HeaderMap::new();
+		headers.insert(DATE, httpdate::fmt_http_date(time).parse().unwrap());
+		headers
+	}
+
+	#[test]
+	fn no_estimate_without_samples() {
+		let skew = ClockSkew::default();
+		let now = SystemTime::now();
+		skew.record(&HeaderMap::new(), now, now);
+		assert!(skew.estimate().is_none());
+	}
+
+	#[test]
+	fn servers_ahead_and_behind() {
+		let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+		let ahead = ClockSkew::default();
+		ahead.record(&date(now + Duration::from_secs(60)), now, now);
+		let estimate = ahead.estimate().unwrap();
+		assert_eq!(estimate.samples, 1);
+		assert_eq!(estimate.offset, 60_500.0);
+
+		let behind = ClockSkew::default();
+		behind.record(&date(now - Duration::from_secs(60)), now, now);
+		assert_eq!(behind.estimate().unwrap().offset, -59_500.0);
+	}
+
+	#[test]
+	fn samples_are_smoothed() {
+		let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+		let skew = ClockSkew::default();
+		skew.record(&date(now), now, now);
+		skew.record(&date(now + Duration::from_secs(10)), now, now);
+		let estimate = skew.estimate().unwrap();
+		assert_eq!(estimate.samples, 2);
+		assert_eq!(estimate.offset, 500.0 + SMOOTHING * 10_000.0);
+	}
+}
@@ -6,12 +6,17 @@ mod macos;
 mod windows;
 
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use moka::Expiry;
+use moka::notification::RemovalCause;
 use moka::{ops::compute::Op, sync::Cache};
-use napi::{Env, JsDate};
+use napi::{
+	Env, JsDate, Status,
+	bindgen_prelude::{Buffer, Function},
+	threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode},
+};
 use napi_derive::napi;
 use tokio::{spawn, task::AbortHandle, time::sleep};
 
@@ -21,8 +26,17 @@ pub struct ConnectionKey {
 	pub remote_addr: SocketAddr,
 }
 
+/// What is known about a connection from the first response received on it.
+#[derive(Debug, Clone)]
+pub struct ConnectionDetails {
+	pub origin: String,
+	pub protocol: &'static str,
+	pub peer_certificate: Option<Arc<[u8]>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct TrackedConnection {
+	pub details: ConnectionDetails,
 	pub first_seen: SystemTime,
 	pub last_seen: SystemTime,
 	pub response_count: u64,
@@ -100,6 +114,87 @@ pub struct ConnectionInfo<'env> {
 	pub delivery_rate_bps: Option<i64>,
 }
 
+/// Custom to Fáith.
+///
+/// Passed to the `agent.onConnect()`, `agent.onConnectionClosed()`, and
+/// `agent.onConnectionUnhealthy()` callbacks.
+///
+/// - `origin`: the origin (scheme, host, and port) of the first request made on the connection.
+/// - `protocol`: the ALPN identifier of the HTTP version used, e.g. `http/1.1` or `h2`.
+/// - `localAddress`, `localPort`, `remoteAddress`, `remotePort`: the TCP endpoints.
+/// - `peerCertificate`: when connected over HTTPS, the DER-encoded leaf certificate of the peer.
+/// - `reuseCount`: how many responses were received on the connection after the first one.
+#[napi(object)]
+pub struct ConnectionEvent {
+	pub origin: String,
+	pub protocol: String,
+	pub local_address: String,
+	pub local_port: u16,
+	pub remote_address: String,
+	pub remote_port: u16,
+	pub peer_certificate: Option<Buffer>,
+	pub reuse_count: i64,
+}
+
+impl ConnectionEvent {
+	fn new(key: &ConnectionKey, conn: &TrackedConnection) -> Self {
+		Self {
+			origin: conn.details.origin.clone(),
+			protocol: conn.details.protocol.to_string(),
+			local_address: key.local_addr.ip().to_string(),
+			local_port: key.local_addr.port(),
+			remote_address: key.remote_addr.ip().to_string(),
+			remote_port: key.remote_addr.port(),
+			peer_certificate: conn.details.peer_certificate.as_deref().map(Buffer::from),
+			reuse_count: conn
+				.response_count
+				.saturating_sub(1)
+				.try_into()
+				.unwrap_or(i64::MAX),
+		}
+	}
+}
+
+pub(crate) type ConnectionCallback =
+	ThreadsafeFunction<ConnectionEvent, (), ConnectionEvent, Status, false, true>;
+
+#[derive(Default)]
+struct Callback(RwLock<Option<ConnectionCallback>>);
+
+impl std::fmt::Debug for Callback {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let set = self.0.read().map(|cb| cb.is_some()).unwrap_or_default();
+		f.debug_tuple("Callback").field(&set).finish()
+	}
+}
+
+impl Callback {
+	fn set(&self, callback: Option<Function<ConnectionEvent, ()>>) -> napi::Result<()> {
+		let tsfn = callback
+			.map(|callback| {
+				callback
+					.build_threadsafe_function()
+					.callee_handled::<false>()
+					.weak::<true>()
+					.build()
+			})
+			.transpose()?;
+		*self.0.write().unwrap_or_else(|err| err.into_inner()) = tsfn;
+		Ok(())
+	}
+
+	fn fire(&self, key: &ConnectionKey, conn: &TrackedConnection) {
+		if let Ok(callback) = self.0.read()
+			&& let Some(callback) = callback.as_ref()
+		{
+			callback.call(
+				ConnectionEvent::new(key, conn),
+				ThreadsafeFunctionCallMode::NonBlocking,
+			);
+		}
+	}
+}
+
 type Conns = Cache<ConnectionKey, TrackedConnection>;
 
 #[derive(Debug)]
@@ -107,6 +202,9 @@ pub struct ConnectionTracker {
 	connections: Conns,
 	timeout: Duration,
 	task_abort: AbortHandle,
+	on_connect: Arc<Callback>,
+	on_closed: Arc<Callback>,
+	on_unhealthy: Callback,
 }
 
 impl Drop for ConnectionTracker {
@@ -117,14 +215,25 @@ impl Drop for ConnectionTracker {
 
 impl ConnectionTracker {
 	pub fn new(timeout: Duration) -> Arc<Self> {
+		let on_connect = Arc::new(Callback::default());
+		let on_closed = Arc::new(Callback::default());
+
+		let closed = on_closed.clone();
 		let connections = Cache::builder()
 			.expire_after(ExpireAfterTimeout(timeout))
+			.eviction_listener(move |key, conn, cause| {
+				// updates replace the entry, every other cause is the connection going away
+				if cause != RemovalCause::Replaced {
+					closed.fire(&key, &conn);
+				}
+			})
 			.build();
 
 		let conns = connections.clone();
 		let task_abort = spawn(async move {
 			loop {
 				let _ = update_all(conns.clone());
+				conns.run_pending_tasks();
 				sleep(Duration::from_secs(1)).await;
 			}
 		})
@@ -134,15 +243,60 @@ impl ConnectionTracker {
 			connections,
 			timeout,
 			task_abort,
+			on_connect,
+			on_closed,
+			on_unhealthy: Callback::default(),
 		})
 	}
 
-	pub fn track(&self, local_addr: SocketAddr, remote_addr: SocketAddr) {
+	pub fn set_on_connect(
+		&self,
+		callback: Option<Function<ConnectionEvent, ()>>,
+	) -> napi::Result<()> {
+		self.on_connect.set(callback)
+	}
+
+	pub fn set_on_closed(
+		&self,
+		callback: Option<Function<ConnectionEvent, ()>>,
+	) -> napi::Result<()> {
+		self.on_closed.set(callback)
+	}
+
+	pub fn set_on_unhealthy(
+		&self,
+		callback: Option<Function<ConnectionEvent, ()>>,
+	) -> napi::Result<()> {
+		self.on_unhealthy.set(callback)
+	}
+
+	/// Reports the HTTP/2 connections to this origin as dead, after one of them stopped answering
+	/// pings, and stops tracking them. Which of them it was isn't known, but the client only keeps
+	/// one HTTP/2 connection per origin.
+	pub fn unhealthy(&self, origin: &str) {
+		let dead: Vec<_> = self
+			.connections
+			.iter()
+			.filter(|(_, conn)| conn.details.protocol == "h2" && conn.details.origin == origin)
+			.collect();
+		for (key, conn) in dead {
+			self.on_unhealthy.fire(&key, &conn);
+			self.connections.invalidate(&*key);
+		}
+	}
+
+	pub fn track(
+		&self,
+		local_addr: SocketAddr,
+		remote_addr: SocketAddr,
+		details: impl FnOnce() -> ConnectionDetails,
+	) {
 		let now = SystemTime::now();
 		let key = ConnectionKey {
 			local_addr,
 			remote_addr,
 		};
+		let mut created = None;
 		self.connections.entry(key).and_compute_with(|entry| {
 			if let Some(entry) = entry {
 				let mut conn = entry.into_value();
@@ -150,14 +304,21 @@ impl ConnectionTracker {
 				conn.response_count += 1;
 				Op::Put(conn)
 			} else {
-				Op::Put(TrackedConnection {
+				let conn = TrackedConnection {
+					details: details(),
 					first_seen: now,
 					last_seen: now,
 					response_count: 1,
 					latest_stats: None,
-				})
+				};
+				created = Some(conn.clone());
+				Op::Put(conn)
 			}
 		});
+
+		if let Some(conn) = created {
+			self.on_connect.fire(&key, &conn);
+		}
 	}
 
 	pub fn get_for_napi<'env>(&self, env: &'env Env) -> Vec<ConnectionInfo<'env>> {
@@ -0,0 +1,93 @@
+use std::{
+	future::Future,
+	net::SocketAddr,
+	pin::Pin,
+	sync::LazyLock,
+	task::{Context, Poll},
+	time::{Duration, SystemTime},
+};
+
+use http::Extensions;
+use hyper_util::client::legacy::connect::{Connection, HttpInfo};
+use moka::sync::Cache;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::observe::{Observer, RequestEventType};
+
+/// When new connections started and finished connecting, by their local and peer addresses, until
+/// the first response on them picks it up. Entries for connections which never carry a response
+/// (e.g. when the request was aborted) expire on their own.
+static CONNECTS: LazyLock<Cache<(SocketAddr, SocketAddr), (SystemTime, SystemTime)>> =
+	LazyLock::new(|| {
+		Cache::builder()
+			.max_capacity(10_000)
+			.time_to_live(Duration::from_secs(60))
+			.build()
+	});
+
+/// Returns when the connection was established, if it's new and this is the first time it's asked.
+pub(crate) fn take_connect_timing(
+	local: SocketAddr,
+	remote: SocketAddr,
+) -> Option<(SystemTime, SystemTime)> {
+	CONNECTS.remove(&(local, remote))
+}
+
+/// Records how long each new connection takes to establish: resolving, connecting, going through
+/// the proxy (including its `CONNECT` exchange), and the TLS handshake.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ConnectTimingLayer;
+
+impl<S> Layer<S> for ConnectTimingLayer {
+	type Service = ConnectTimingService<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		ConnectTimingService { inner }
+	}
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ConnectTimingService<S> {
+	inner: S,
+}
+
+impl<S, R> Service<R> for ConnectTimingService<S>
+where
+	S: Service<R>,
+	S::Response: Connection,
+	S::Future: Send + 'static,
+{
+	type Response = S::Response;
+	type Error = S::Error;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, request: R) -> Self::Future {
+		let start = SystemTime::now();
+		let observer = Observer::current();
+		if let Some(observer) = &observer {
+			observer.emit(RequestEventType::ConnectStart);
+		}
+		let connecting = self.inner.call(request);
+		Box::pin(async move {
+			let conn = connecting.await?;
+			let end = SystemTime::now();
+
+			let mut extras = Extensions::new();
+			conn.connected().get_extras(&mut extras);
+			let info = extras.get::<HttpInfo>();
+			if let Some(info) = info {
+				CONNECTS.insert((info.local_addr(), info.remote_addr()), (start, end));
+			}
+			if let Some(observer) = &observer {
+				observer.connect_end(info.map(HttpInfo::remote_addr));
+			}
+
+			Ok(conn)
+		})
+	}
+}
@@ -0,0 +1,139 @@
+use std::{
+	future, io,
+	pin::Pin,
+	sync::{Arc, Mutex},
+};
+
+use futures::{StreamExt, stream};
+use http::{
+	HeaderMap, HeaderValue,
+	header::{CONTENT_ENCODING, CONTENT_LENGTH},
+};
+use http_body_util::BodyStream;
+use tokio::io::{AsyncBufRead, AsyncRead};
+use tokio_util::io::{ReaderStream, StreamReader};
+
+use crate::checksum::ChecksumCheck;
+
+/// Request extension to get the response body as it was sent, still compressed, so that its
+/// `checksums` can be validated: Fáith then decompresses it itself, with [`decode()`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Undecoded;
+
+/// The `Accept-Encoding` the client sends, for requests made with a client which doesn't
+/// decompress, so that the same encodings are still offered.
+pub(crate) fn accept_encoding() -> Option<HeaderValue> {
+	let encodings: &[&str] = &[
+		#[cfg(feature = "gzip")]
+		"gzip",
+		#[cfg(feature = "brotli")]
+		"br",
+		#[cfg(feature = "zstd")]
+		"zstd",
+		#[cfg(feature = "deflate")]
+		"deflate",
+	];
+	if encodings.is_empty() {
+		return None;
+	}
+	HeaderValue::try_from(encodings.join(", ")).ok()
+}
+
+/// A `Content-Encoding` which Fáith decompresses.
+#[derive(Debug, Clone, Copy)]
+enum Encoding {
+	#[cfg(feature = "gzip")]
+	Gzip,
+	#[cfg(feature = "brotli")]
+	Brotli,
+	#[cfg(feature = "zstd")]
+	Zstd,
+	#[cfg(feature = "deflate")]
+	Deflate,
+}
+
+impl Encoding {
+	fn parse(value: &str) -> Option<Self> {
+		match value.trim().to_ascii_lowercase().as_str() {
+			#[cfg(feature = "gzip")]
+			"gzip" => Some(Self::Gzip),
+			#[cfg(feature = "brotli")]
+			"br" => Some(Self::Brotli),
+			#[cfg(feature = "zstd")]
+			"zstd" => Some(Self::Zstd),
+			#[cfg(feature = "deflate")]
+			"deflate" => Some(Self::Deflate),
+			_ => None,
+		}
+	}
+
+	fn decoder(self, reader: impl AsyncBufRead + Send + 'static) -> Pin<Box<dyn AsyncRead + Send>> {
+		use async_compression::tokio::bufread;
+
+		match self {
+			#[cfg(feature = "gzip")]
+			Self::Gzip => {
+				let mut decoder = bufread::GzipDecoder::new(reader);
+				decoder.multiple_members(true);
+				Box::pin(decoder)
+			}
+			#[cfg(feature = "brotli")]
+			Self::Brotli => Box::pin(bufread::BrotliDecoder::new(reader)),
+			#[cfg(feature = "zstd")]
+			Self::Zstd => Box::pin(bufread::ZstdDecoder::new(reader)),
+			#[cfg(feature = "deflate")]
+			Self::Deflate => Box::pin(bufread::ZlibDecoder::new(reader)),
+		}
+	}
+}
+
+/// Decompresses a response body which is still compressed: from a client which doesn't decompress,
+/// or from the cache, when such a response was stored. The `Content-Encoding` and `Content-Length`
+/// headers are removed, as the client does when it decompresses. Bodies without an encoding Fáith
+/// supports are returned as they are.
+///
+/// With `checksums`, the body is validated as it was sent, before it's decompressed: the end of the
+/// decompressed body is an error if it didn't match.
+pub(crate) fn decode(
+	headers: &mut HeaderMap,
+	body: reqwest::Body,
+	checksum: Option<&Arc<ChecksumCheck>>,
+) -> reqwest::Body {
+	let Some(encoding) = headers
+		.get(CONTENT_ENCODING)
+		.and_then(|value| Encoding::parse(value.to_str().ok()?))
+	else {
+		return body;
+	};
+
+	let verifier = Arc::new(Mutex::new(
+		checksum.and_then(|check| check.raw_verifier(headers)),
+	));
+	headers.remove(CONTENT_ENCODING);
+	headers.remove(CONTENT_LENGTH);
+
+	let hashing = verifier.clone();
+	let sent = BodyStream::new(body).filter_map(move |frame| {
+		let data = match frame {
+			Ok(frame) => frame.into_data().ok().map(Ok),
+			Err(err) => Some(Err(io::Error::other(err))),
+		};
+		if let (Some(Ok(data)), Some(verifier)) = (&data, hashing.lock().unwrap().as_mut()) {
+			verifier.update(data);
+		}
+		future::ready(data)
+	});
+
+	// the decoder may stop at the end of the compressed data without reading the body's end, so
+	// the checksum is checked once the decompressed body ends instead
+	let end = stream::once(async move {
+		let verifier = verifier.lock().unwrap().take()?;
+		let mismatch = verifier.finish().err()?;
+		Some(Err(io::Error::other(mismatch)))
+	})
+	.filter_map(future::ready);
+
+	reqwest::Body::wrap_stream(
+		ReaderStream::new(encoding.decoder(StreamReader::new(sent))).chain(end),
+	)
+}
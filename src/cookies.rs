@@ -0,0 +1,617 @@
+use std::{
+	borrow::Cow,
+	collections::{HashMap, HashSet},
+	future::Future,
+	sync::{Arc, LazyLock, Mutex, RwLock, Weak},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use napi::{
+	Status,
+	bindgen_prelude::Function,
+	threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode},
+};
+use napi_derive::napi;
+use publicsuffix::{List, Psl as _};
+use reqwest::{
+	Url,
+	cookie::{CookieStore, Jar},
+	header::HeaderValue,
+};
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+use crate::{
+	agent::AgentCookiePolicy,
+	clock::Clock,
+	error::{FaithError, FaithErrorKind},
+	tls,
+};
+
+/// The Public Suffix List bundled with Fáith, from <https://publicsuffix.org/list/>.
+static PUBLIC_SUFFIXES: LazyLock<Arc<List>> = LazyLock::new(|| {
+	Arc::new(
+		include_str!("public_suffix_list.dat")
+			.parse()
+			.expect("the bundled public suffix list is valid"),
+	)
+});
+
+/// Parses the agent's `publicSuffixList`, or returns the bundled one.
+pub(crate) fn public_suffixes(list: Option<&str>) -> Result<Arc<List>, FaithError> {
+	let Some(list) = list else {
+		return Ok(PUBLIC_SUFFIXES.clone());
+	};
+
+	list.parse().map(Arc::new).map_err(|err| {
+		FaithError::new(
+			FaithErrorKind::Config,
+			Some(format!("invalid publicSuffixList: {err}")),
+		)
+	})
+}
+
+/// Custom to Fáith.
+///
+/// Passed to the `agent.onCookieSet()` callback and the `agent.setCookieFilter()` predicate when a
+/// response sets a cookie.
+///
+/// - `url`: the URL of the response which set the cookie.
+/// - `name`, `value`: the cookie itself.
+/// - `domain`: the `Domain` attribute, without a leading dot, if the cookie has one; otherwise
+///   it's only sent to the host of `url`.
+/// - `path`: the `Path` attribute, or the default path from `url` if it doesn't have one.
+/// - `expires`: when the cookie expires, in milliseconds since the epoch, absent for a session
+///   cookie. `Max-Age` takes precedence over `Expires`.
+/// - `secure`, `httpOnly`: the `Secure` and `HttpOnly` attributes.
+/// - `sameSite`: the `SameSite` attribute as given, if any.
+/// - `removed`: whether the cookie was removed from the jar rather than stored, as servers do by
+///   setting an expiry in the past.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct CookieEvent {
+	pub url: String,
+	pub name: String,
+	pub value: String,
+	pub domain: Option<String>,
+	pub path: String,
+	pub expires: Option<f64>,
+	pub secure: bool,
+	pub http_only: bool,
+	pub same_site: Option<String>,
+	pub removed: bool,
+}
+
+pub(crate) type CookieCallback =
+	ThreadsafeFunction<CookieEvent, (), CookieEvent, Status, false, true>;
+
+#[derive(Default)]
+struct OnCookieSet(RwLock<Option<CookieCallback>>);
+
+impl std::fmt::Debug for OnCookieSet {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let set = self.0.read().map(|cb| cb.is_some()).unwrap_or_default();
+		f.debug_tuple("OnCookieSet").field(&set).finish()
+	}
+}
+
+pub(crate) type CookieFilterCallback =
+	ThreadsafeFunction<CookieEvent, bool, CookieEvent, Status, false, true>;
+
+#[derive(Default)]
+struct CookieFilter(RwLock<Option<Arc<CookieFilterCallback>>>);
+
+impl std::fmt::Debug for CookieFilter {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let set = self.0.read().map(|cb| cb.is_some()).unwrap_or_default();
+		f.debug_tuple("CookieFilter").field(&set).finish()
+	}
+}
+
+/// The parts of a `Set-Cookie` header value which matter here.
+#[derive(Debug, Default)]
+struct SetCookie<'a> {
+	name: &'a str,
+	value: &'a str,
+	domain: Option<String>,
+	path: Option<&'a str>,
+	max_age: Option<i64>,
+	expires: Option<SystemTime>,
+	secure: bool,
+	http_only: bool,
+	same_site: Option<&'a str>,
+}
+
+impl<'a> SetCookie<'a> {
+	fn parse(cookie: &'a str) -> Option<Self> {
+		let mut attributes = cookie.split(';');
+		let (name, value) = attributes.next()?.split_once('=')?;
+		let mut parsed = Self {
+			name: name.trim(),
+			value: value.trim(),
+			..Default::default()
+		};
+		if parsed.name.is_empty() {
+			return None;
+		}
+
+		for attribute in attributes {
+			let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+			let (key, value) = (key.trim(), value.trim());
+			if key.eq_ignore_ascii_case("max-age") {
+				parsed.max_age = value.parse::<i64>().ok();
+			} else if key.eq_ignore_ascii_case("expires") {
+				parsed.expires = httpdate::parse_http_date(value).ok();
+			} else if key.eq_ignore_ascii_case("domain") && !value.is_empty() {
+				parsed.domain = Some(value.trim_start_matches('.').to_ascii_lowercase());
+			} else if key.eq_ignore_ascii_case("path") && value.starts_with('/') {
+				parsed.path = Some(value);
+			} else if key.eq_ignore_ascii_case("secure") {
+				parsed.secure = true;
+			} else if key.eq_ignore_ascii_case("httponly") {
+				parsed.http_only = true;
+			} else if key.eq_ignore_ascii_case("samesite") && !value.is_empty() {
+				parsed.same_site = Some(value);
+			}
+		}
+		Some(parsed)
+	}
+
+	/// When the cookie expires, by the clock. Max-Age takes precedence over Expires.
+	fn expiry(&self, clock: &Clock) -> Option<SystemTime> {
+		match self.max_age {
+			Some(secs) => Some(clock.system_now() + Duration::from_secs(secs.max(0) as u64)),
+			None => self.expires,
+		}
+	}
+
+	/// Whether the cookie has `SameSite=Strict` or `SameSite=Lax`, so isn't sent cross-site.
+	fn restricted(&self) -> bool {
+		self.same_site.is_some_and(|same_site| {
+			same_site.eq_ignore_ascii_case("strict") || same_site.eq_ignore_ascii_case("lax")
+		})
+	}
+
+	/// The path the cookie applies to, defaulting to the directory of the URL's path (RFC 6265).
+	fn path(&self, url: &Url) -> String {
+		if let Some(path) = self.path {
+			return path.into();
+		}
+		match url.path().rfind('/') {
+			Some(0) | None => "/".into(),
+			Some(end) => url.path()[..end].into(),
+		}
+	}
+}
+
+tokio::task_local! {
+	/// The request's top-level site, for the cookie policy.
+	static TOP_LEVEL_SITE: Option<String>;
+}
+
+/// Runs a request with its `topLevelSite`, which the cookie store then reads to apply the agent's
+/// cookie policy. Requests without one use the policy's `topLevelSite`.
+///
+/// reqwest reads and stores cookies within the request's future, so this is available to the store.
+pub(crate) async fn with_top_level_site<F: Future>(site: Option<&str>, request: F) -> F::Output {
+	TOP_LEVEL_SITE.scope(site.map(site_host), request).await
+}
+
+/// The host of a top-level site, given as a URL or as a bare host.
+fn site_host(site: &str) -> String {
+	Url::parse(site)
+		.ok()
+		.and_then(|url| url.host_str().map(str::to_owned))
+		.unwrap_or_else(|| site.to_owned())
+		.to_ascii_lowercase()
+}
+
+/// Whether the host is the domain or one of its subdomains (RFC 6265, section 5.1.3).
+fn domain_matches(host: &str, domain: &str) -> bool {
+	host == domain
+		|| host
+			.strip_suffix(domain)
+			.is_some_and(|sub| sub.ends_with('.'))
+}
+
+/// The agent's cookie store.
+///
+/// Cookies are kept in reqwest's store, which expires them by the system's clock. With a mock
+/// clock, their expiry is also tracked here, so that cookies which have expired by the agent's
+/// clock aren't sent.
+///
+/// reqwest's store doesn't check the Public Suffix List, so cookies for a public suffix like
+/// `co.uk` are filtered out here before they reach it. The agent's `cookiePolicy` is applied here
+/// too, which is why `SameSite` cookies are tracked alongside reqwest's store.
+#[derive(Debug)]
+pub(crate) struct AgentJar {
+	this: Weak<AgentJar>,
+	jar: Jar,
+	clock: Clock,
+	public_suffixes: Arc<List>,
+	block_third_party: bool,
+	secure_only: bool,
+	/// The policy's `topLevelSite`, as a host.
+	top_level_site: Option<String>,
+	/// When cookies expire by the agent's clock, by domain and name. Only used with a mock clock.
+	expiries: Mutex<HashMap<(String, String), SystemTime>>,
+	/// The cookies with `SameSite=Strict` or `SameSite=Lax`, by domain and name.
+	same_site: Mutex<HashSet<(String, String)>>,
+	on_set: OnCookieSet,
+	filter: CookieFilter,
+}
+
+impl AgentJar {
+	pub(crate) fn new(
+		clock: Clock,
+		public_suffixes: Arc<List>,
+		policy: &AgentCookiePolicy,
+	) -> Arc<Self> {
+		Arc::new_cyclic(|this| Self {
+			this: this.clone(),
+			jar: Jar::default(),
+			clock,
+			public_suffixes,
+			block_third_party: policy.block_third_party.unwrap_or(false),
+			secure_only: policy.secure_only.unwrap_or(false),
+			top_level_site: policy.top_level_site.as_deref().map(site_host),
+			expiries: Default::default(),
+			same_site: Default::default(),
+			on_set: Default::default(),
+			filter: Default::default(),
+		})
+	}
+
+	/// Adds a cookie as if it was set by a response from the URL.
+	///
+	/// Only the public suffix rule applies: the cookie policy is for cookies set by responses.
+	pub(crate) fn add_cookie_str(&self, cookie: &str, url: &Url) {
+		let Some(cookie) = self.check_public_suffix(cookie, url) else {
+			return;
+		};
+		self.record(&cookie, url);
+		self.jar.add_cookie_str(&cookie, url);
+	}
+
+	/// Applies the public suffix rule of RFC 6265 (section 5.3, step 5) to a cookie from the URL.
+	///
+	/// A cookie whose `Domain` is a public suffix is rejected, as it would be sent to every site
+	/// under that suffix, unless the domain is the URL's host itself: then it's only sent to that
+	/// host, so the `Domain` attribute is removed.
+	fn check_public_suffix<'a>(&self, cookie: &'a str, url: &Url) -> Option<Cow<'a, str>> {
+		let Some(domain) = SetCookie::parse(cookie).and_then(|cookie| cookie.domain) else {
+			return Some(Cow::Borrowed(cookie));
+		};
+		let public = self
+			.public_suffixes
+			.suffix(domain.as_bytes())
+			.is_some_and(|suffix| suffix.as_bytes() == domain.as_bytes());
+		if !public {
+			return Some(Cow::Borrowed(cookie));
+		}
+
+		if !url
+			.host_str()
+			.is_some_and(|host| host.eq_ignore_ascii_case(&domain))
+		{
+			return None;
+		}
+
+		let host_only: Vec<&str> = cookie
+			.split(';')
+			.filter(|attribute| {
+				!attribute
+					.split_once('=')
+					.is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case("domain"))
+			})
+			.collect();
+		Some(Cow::Owned(host_only.join(";")))
+	}
+
+	/// The site of a host: its registrable domain, like `example.co.uk` for `www.example.co.uk`,
+	/// or the host itself if it doesn't have one, like IP addresses.
+	fn site(&self, host: &str) -> String {
+		let host = host.to_ascii_lowercase();
+		self.public_suffixes
+			.domain(host.as_bytes())
+			.and_then(|domain| {
+				std::str::from_utf8(domain.as_bytes())
+					.ok()
+					.map(str::to_owned)
+			})
+			.unwrap_or(host)
+	}
+
+	/// Whether a request to the URL is cross-site, from the request's or the policy's top-level
+	/// site. Requests are same-site if there's no top-level site, and the store is used as is
+	/// outside of requests, as by `agent.getCookie()`.
+	fn cross_site(&self, url: &Url) -> bool {
+		let Ok(top_level_site) = TOP_LEVEL_SITE.try_with(Clone::clone) else {
+			return false;
+		};
+		let Some(top_level_site) = top_level_site.or_else(|| self.top_level_site.clone()) else {
+			return false;
+		};
+		self.site(&top_level_site) != self.site(url.host_str().unwrap_or_default())
+	}
+
+	/// Applies the cookie policy to a cookie set by a response from the URL.
+	fn allowed(&self, cookie: &SetCookie, url: &Url, cross_site: bool) -> bool {
+		if self.secure_only && !(cookie.secure && url.scheme() == "https") {
+			return false;
+		}
+		// cross-site responses can't set cookies which wouldn't be sent back to them
+		!(cross_site && cookie.restricted())
+	}
+
+	/// Sets the callback for cookies set by responses, or removes it.
+	pub(crate) fn set_on_set(
+		&self,
+		callback: Option<Function<CookieEvent, ()>>,
+	) -> napi::Result<()> {
+		let tsfn = callback
+			.map(|callback| {
+				callback
+					.build_threadsafe_function()
+					.callee_handled::<false>()
+					.weak::<true>()
+					.build()
+			})
+			.transpose()?;
+		*self.on_set.0.write().unwrap_or_else(|err| err.into_inner()) = tsfn;
+		Ok(())
+	}
+
+	/// Sets the predicate which decides whether to store cookies set by responses, or removes it.
+	pub(crate) fn set_filter(
+		&self,
+		filter: Option<Function<CookieEvent, bool>>,
+	) -> napi::Result<()> {
+		let tsfn = filter
+			.map(|filter| {
+				filter
+					.build_threadsafe_function()
+					.callee_handled::<false>()
+					.weak::<true>()
+					.build()
+					.map(Arc::new)
+			})
+			.transpose()?;
+		*self.filter.0.write().unwrap_or_else(|err| err.into_inner()) = tsfn;
+		Ok(())
+	}
+
+	/// Stores cookies set by a response from the URL, which passed the policy.
+	fn store(&self, headers: &[HeaderValue], url: &Url) {
+		for header in headers {
+			if let Ok(cookie) = header.to_str() {
+				self.record(cookie, url);
+			}
+		}
+		self.jar.set_cookies(&mut headers.iter(), url);
+		for header in headers {
+			if let Ok(cookie) = header.to_str() {
+				self.notify(cookie, url);
+			}
+		}
+	}
+
+	/// Tracks what reqwest's store doesn't: the cookie's `SameSite`, and its expiry by the agent's
+	/// clock if it's a mock.
+	fn record(&self, cookie: &str, url: &Url) {
+		let Some(cookie) = SetCookie::parse(cookie) else {
+			return;
+		};
+		let domain = cookie
+			.domain
+			.clone()
+			.unwrap_or_else(|| url.host_str().unwrap_or_default().to_ascii_lowercase());
+		let key = (domain, cookie.name.to_string());
+
+		let mut same_site = self.same_site.lock().unwrap_or_else(|err| err.into_inner());
+		if cookie.restricted() {
+			same_site.insert(key.clone());
+		} else {
+			same_site.remove(&key);
+		}
+		drop(same_site);
+
+		if !self.clock.is_mock() {
+			return;
+		}
+		let mut expiries = self.expiries.lock().unwrap_or_else(|err| err.into_inner());
+		match cookie.expiry(&self.clock) {
+			Some(expiry) => expiries.insert(key, expiry),
+			None => expiries.remove(&key),
+		};
+	}
+
+	/// Whether the cookie expired by the agent's clock, for the URL's host.
+	fn expired(&self, name: &str, host: &str, now: SystemTime) -> bool {
+		let expiries = self.expiries.lock().unwrap_or_else(|err| err.into_inner());
+		expiries.iter().any(|((domain, cookie), expiry)| {
+			cookie == name && *expiry <= now && domain_matches(host, domain)
+		})
+	}
+
+	/// Whether the cookie has `SameSite=Strict` or `SameSite=Lax`, for the URL's host.
+	fn restricted(&self, name: &str, host: &str) -> bool {
+		let same_site = self.same_site.lock().unwrap_or_else(|err| err.into_inner());
+		same_site
+			.iter()
+			.any(|(domain, cookie)| cookie == name && domain_matches(host, domain))
+	}
+
+	fn event(&self, cookie: &SetCookie, url: &Url) -> CookieEvent {
+		let expiry = cookie.expiry(&self.clock);
+		CookieEvent {
+			url: url.to_string(),
+			name: cookie.name.into(),
+			value: cookie.value.into(),
+			domain: cookie.domain.clone(),
+			path: cookie.path(url),
+			expires: expiry.map(|expiry| match expiry.duration_since(UNIX_EPOCH) {
+				Ok(since) => since.as_millis() as f64,
+				Err(before) => -(before.duration().as_millis() as f64),
+			}),
+			secure: cookie.secure,
+			http_only: cookie.http_only,
+			same_site: cookie.same_site.map(Into::into),
+			removed: expiry.is_some_and(|expiry| expiry <= self.clock.system_now()),
+		}
+	}
+
+	/// Calls the `onCookieSet()` callback for a cookie set by a response, if the jar accepted it.
+	///
+	/// reqwest's store doesn't say whether it accepted a cookie, so this checks that it's now sent
+	/// to where it applies, unless it was removed.
+	fn notify(&self, cookie: &str, url: &Url) {
+		let Ok(callback) = self.on_set.0.read() else {
+			return;
+		};
+		let Some(callback) = callback.as_ref() else {
+			return;
+		};
+		let Some(cookie) = SetCookie::parse(cookie) else {
+			return;
+		};
+
+		let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+		if let Some(domain) = &cookie.domain
+			&& !domain_matches(&host, domain)
+		{
+			return;
+		}
+
+		let event = self.event(&cookie, url);
+		if !event.removed {
+			let mut applies = url.clone();
+			applies.set_path(&event.path);
+			applies.set_query(None);
+			if cookie.secure {
+				let _ = applies.set_scheme("https");
+			}
+			let pair = format!("{}={}", cookie.name, cookie.value);
+			let stored = self.jar.cookies(&applies).is_some_and(|cookies| {
+				cookies
+					.to_str()
+					.is_ok_and(|cookies| cookies.split("; ").any(|sent| sent == pair))
+			});
+			if !stored {
+				return;
+			}
+		}
+
+		callback.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+	}
+}
+
+impl CookieStore for AgentJar {
+	fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+		let cross_site = self.cross_site(url);
+		if cross_site && self.block_third_party {
+			return;
+		}
+
+		let headers: Vec<HeaderValue> = cookie_headers
+			// reqwest's store also ignores cookies which aren't UTF-8
+			.filter_map(|header| {
+				let cookie = std::str::from_utf8(header.as_bytes()).ok()?;
+				if !self.allowed(&SetCookie::parse(cookie)?, url, cross_site) {
+					return None;
+				}
+				match self.check_public_suffix(cookie, url)? {
+					Cow::Borrowed(_) => Some(header.clone()),
+					Cow::Owned(cookie) => HeaderValue::from_bytes(cookie.as_bytes()).ok(),
+				}
+			})
+			.collect();
+
+		let filter = self
+			.filter
+			.0
+			.read()
+			.unwrap_or_else(|err| err.into_inner())
+			.clone();
+		let Some(filter) = filter else {
+			self.store(&headers, url);
+			return;
+		};
+
+		let events: Vec<(HeaderValue, CookieEvent)> = headers
+			.into_iter()
+			.filter_map(|header| {
+				let cookie = SetCookie::parse(header.to_str().ok()?)?;
+				let event = self.event(&cookie, url);
+				Some((header, event))
+			})
+			.collect();
+
+		// The filter runs on the JS thread. The request waits for its decisions, as the TLS verify
+		// proc does, so that the cookies it allows are sent on if the response redirects; except
+		// while `faithFetchSync()` blocks the JS thread, when each cookie is stored once allowed.
+		let handle = Handle::try_current()
+			.ok()
+			.filter(|handle| handle.runtime_flavor() == RuntimeFlavor::MultiThread);
+		let (Some(handle), false) = (handle, tls::is_blocking()) else {
+			for (header, event) in events {
+				let this = self.this.clone();
+				let url = url.clone();
+				filter.call_with_return_value(
+					event,
+					ThreadsafeFunctionCallMode::NonBlocking,
+					move |allowed, _| {
+						if let Ok(true) = allowed
+							&& let Some(jar) = this.upgrade()
+						{
+							jar.store(&[header], &url);
+						}
+						Ok(())
+					},
+				);
+			}
+			return;
+		};
+
+		let allowed: Vec<HeaderValue> = tokio::task::block_in_place(|| {
+			handle.block_on(async {
+				let mut allowed = Vec::new();
+				for (header, event) in events {
+					if let Ok(true) = filter.call_async(event).await {
+						allowed.push(header);
+					}
+				}
+				allowed
+			})
+		});
+		self.store(&allowed, url);
+	}
+
+	fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+		let cross_site = self.cross_site(url);
+		if cross_site && self.block_third_party {
+			return None;
+		}
+
+		let cookies = self.jar.cookies(url)?;
+		if !self.clock.is_mock() && !cross_site {
+			return Some(cookies);
+		}
+
+		let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+		let now = self.clock.system_now();
+		let kept: Vec<&str> = cookies
+			.to_str()
+			.ok()?
+			.split("; ")
+			.filter(|pair| {
+				let name = pair.split_once('=').map_or(*pair, |(name, _)| name);
+				!(self.clock.is_mock() && self.expired(name, &host, now))
+					&& !(cross_site && self.restricted(name, &host))
+			})
+			.collect();
+		if kept.is_empty() {
+			return None;
+		}
+		HeaderValue::from_str(&kept.join("; ")).ok()
+	}
+}
@@ -0,0 +1,82 @@
+use std::{sync::Arc, time::Duration};
+
+use hickory_resolver::{
+	TokioResolver,
+	config::ResolverConfig,
+	name_server::TokioConnectionProvider,
+	proto::rr::{
+		RData, RecordType,
+		rdata::svcb::{SvcParamKey, SvcParamValue},
+	},
+};
+use moka::sync::Cache;
+
+use crate::agent::EchDohProvider;
+
+/// Looks up servers' ECH configurations from their `HTTPS` DNS records, over DNS-over-HTTPS so the
+/// lookup doesn't give away the name ECH hides.
+pub(crate) struct EchLookup {
+	resolver: TokioResolver,
+	/// The configuration found for each host, or `None` if it has none.
+	configs: Cache<String, Option<Arc<[u8]>>>,
+}
+
+impl std::fmt::Debug for EchLookup {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("EchLookup")
+			.field("configs_count", &self.configs.entry_count())
+			.finish()
+	}
+}
+
+impl EchLookup {
+	pub(crate) fn new(doh: EchDohProvider) -> Self {
+		let config = match doh {
+			EchDohProvider::Cloudflare => ResolverConfig::cloudflare_https(),
+			EchDohProvider::Google => ResolverConfig::google_https(),
+			EchDohProvider::Quad9 => ResolverConfig::quad9_https(),
+		};
+		Self {
+			resolver: TokioResolver::builder_with_config(
+				config,
+				TokioConnectionProvider::default(),
+			)
+			.build(),
+			configs: Cache::builder()
+				.max_capacity(1024)
+				.time_to_live(Duration::from_secs(300))
+				.build(),
+		}
+	}
+
+	/// The `ECHConfigList` published for the host, if any.
+	///
+	/// Failed lookups aren't remembered, and connect without ECH.
+	pub(crate) async fn config_list(&self, host: &str) -> Option<Arc<[u8]>> {
+		if let Some(found) = self.configs.get(host) {
+			return found;
+		}
+
+		let found = match self.resolver.lookup(host, RecordType::HTTPS).await {
+			Ok(lookup) => lookup.iter().find_map(|rdata| {
+				let RData::HTTPS(https) = rdata else {
+					return None;
+				};
+				https
+					.0
+					.svc_params()
+					.iter()
+					.find_map(|(key, value)| match (key, value) {
+						(SvcParamKey::EchConfigList, SvcParamValue::EchConfigList(list)) => {
+							Some(Arc::from(list.0.as_slice()))
+						}
+						_ => None,
+					})
+			}),
+			Err(err) if err.is_no_records_found() => None,
+			Err(_) => return None,
+		};
+		self.configs.insert(host.into(), found.clone());
+		found
+	}
+}
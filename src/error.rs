@@ -1,36 +1,76 @@
 use std::{
 	error::Error,
 	fmt::{Debug, Display},
+	sync::Arc,
 };
 
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
+use reqwest::header::HeaderMap;
 use strum::{EnumIter, IntoEnumIterator};
 
-/// Fáith produces fine-grained errors, but maps them to a few javascript error types for fetch
-/// compatibility. The `.code` property on errors thrown from Fáith is set to a stable name for each
-/// error kind, documented in this comprehensive mapping:
+use crate::{headers::FaithHeaders, instance::Instance};
+
+/// Fáith produces fine-grained errors, but maps them to a few javascript error classes, which keep
+/// the names of the errors thrown by fetch for compatibility. These classes are exported, so errors
+/// can be told apart with e.g. `err instanceof FaithTimeoutError`. `FaithAbortError`,
+/// `FaithHttpError`, `FaithNetworkError`, and `FaithTimeoutError` extend `FaithError`;
+/// `FaithSyntaxError` and `FaithTypeError` extend `SyntaxError` and `TypeError` instead.
+///
+/// The `.code` property on errors thrown from Fáith is set to a stable name for each error kind,
+/// documented in this comprehensive mapping:
 ///
-/// - JS `AbortError`:
-///   - `Aborted` — request was aborted using `signal`
+/// - `FaithAbortError`, named `AbortError`:
+///   - `Aborted` — request was aborted using `signal`, `agent.abortAll()`, or `faithShutdown()`
+/// - `FaithTimeoutError`, named `TimeoutError`:
 ///   - `Timeout` — request timed out
-/// - JS `NetworkError`:
+/// - `FaithHttpError`, named `HttpError`:
+///   - `HttpStatus` — the response has a non-2xx status, with `throwOnError` or the agent's
+///     `json-api` profile; the error has the response's `status`, `statusText`, and `headers`, and
+///     the start of its body as `bodyPreview`
+/// - `FaithNetworkError`, named `NetworkError`:
+///   - `BodyNotReplayable` — the request had to be sent again, e.g. to follow a `307` redirect, but
+///     its streamed body was larger than the agent's `limits.replayBuffer`
+///   - `CertificateInvalid` — the server's TLS certificate couldn't be verified
+///   - `Dns` — the host name couldn't be resolved
+///   - `ForbiddenAddress` — connection to a private network address, when the agent has
+///     `blockPrivateNetworks`
+///   - `ForbiddenHost` — connection to a host not allowed by the agent's `allowedHosts` or
+///     `blockedHosts`
 ///   - `Network` — network error
+///   - `NotCached` — no cached response for a request with `cache: "only-if-cached"`, or the agent
+///     has no cache
+///   - `Proxy` — the proxy couldn't be used, e.g. it refused to open a tunnel
+///   - `ProxyAuth` — the proxy requires authentication, and the agent has no `proxyAuth` or the
+///     proxy rejected it
 ///   - `Redirect` — when the agent is configured to error on redirects
-/// - JS `SyntaxError`:
+///   - `RedirectDowngrade` — redirect from HTTPS to HTTP, when the agent has `tls.noDowngrade`
+///   - `TlsHandshake` — the TLS handshake failed, other than because of the server's certificate
+/// - `FaithSyntaxError`, a `SyntaxError`:
+///   - `AddressParse` — invalid IP address in `dns.overrides` or `connectTo`
+///   - `GrpcWeb` — malformed response to `agent.grpcWeb()`
+///   - `InvalidIntegrity` — invalid `integrity` value
 ///   - `JsonParse` — JSON parse error for `response.json()`
 ///   - `PemParse` — PEM parse error for `AgentOptions.tls.identity`
 ///   - `Utf8Parse` — UTF8 decoding error for `response.text()`
-/// - JS `TypeError`:
+/// - `FaithTypeError`, a `TypeError`:
+///   - `BodyNotAllowed` — request body given with a `GET` or `HEAD` method
 ///   - `InvalidHeader` — invalid header name or value
 ///   - `InvalidMethod` — invalid HTTP method
 ///   - `InvalidUrl` — invalid URL string
+///   - `RequestTooLarge` — request body or headers larger than the agent's `limits`
 ///   - `ResponseAlreadyDisturbed` — body already read (mutually exclusive operations)
 ///   - `ResponseBodyNotAvailable` — body is null or not available
-/// - JS generic `Error`:
+///   - `ResponseTooLarge` — response body larger than the agent's `limits`
+/// - `FaithError`, named `Error`:
 ///   - `BodyStream` — internal stream handling error
+///   - `Cache` — the HTTP cache's store failed
 ///   - `Config` — invalid agent configuration
+///   - `Graphql` — the result of `agent.graphql()` has errors; the error has the result's `errors`,
+///     and its `data` if any
+///   - `IntegrityMismatch` — response body doesn't match the `integrity` or `checksums`
 ///   - `RuntimeThread` — failed to start or schedule threads on the internal tokio runtime
+///   - `Upload` — file couldn't be read, or a part was refused, in `faithUpload()`
 ///
 /// The library exports an `ERROR_CODES` object which has every error code the library throws, and
 /// every error thrown also has a `code` property that is set to one of those codes. So you can
@@ -38,6 +78,15 @@ use strum::{EnumIter, IntoEnumIterator};
 /// constant from `ERROR_CODES`, instead of doing string matching on the error message, or coarse
 /// `instance of` matching.
 ///
+/// Errors from aborts and timeouts also have a `cause` property which says what fired: `signal` (or
+/// the signal's reason, when using the `fetch()` wrapper), `agent.abortAll`, `faithShutdown`,
+/// `timeout` or `timeouts.connect`, `timeouts.read`, and `timeouts.total` for the request's own
+/// timeouts, or `agent.timeout.connect`, `agent.timeout.read`, or `agent.timeout.total` for the
+/// agent's timeouts.
+///
+/// Errors of requests made with a `correlationId` have it as their `correlationId` property, as do
+/// errors from reading their response bodies with `bytes()`, `text()`, and `json()`.
+///
 /// Due to technical limitations, when reading a body stream, reads might fail, but that error
 /// will not have a `code` property.
 #[napi(string_enum)]
@@ -45,8 +94,18 @@ use strum::{EnumIter, IntoEnumIterator};
 pub enum FaithErrorKind {
 	Aborted,
 	AddressParse,
+	BodyNotAllowed,
+	BodyNotReplayable,
 	BodyStream,
+	Cache,
+	CertificateInvalid,
 	Config,
+	Dns,
+	ForbiddenAddress,
+	ForbiddenHost,
+	Graphql,
+	GrpcWeb,
+	HttpStatus,
 	IntegrityMismatch,
 	InvalidHeader,
 	InvalidIntegrity,
@@ -54,30 +113,79 @@ pub enum FaithErrorKind {
 	InvalidUrl,
 	JsonParse,
 	Network,
+	NotCached,
 	PemParse,
+	Proxy,
+	ProxyAuth,
 	Redirect,
+	RedirectDowngrade,
+	RequestTooLarge,
 	ResponseAlreadyDisturbed,
 	ResponseBodyNotAvailable,
+	ResponseTooLarge,
 	RuntimeThread,
 	Timeout,
+	TlsHandshake,
+	Upload,
 	Utf8Parse,
 }
 
 #[derive(Debug, Clone, Copy)]
 enum JsErrorType {
+	AbortError,
 	GenericError,
-	NamedError(&'static str),
+	HttpError,
+	NetworkError,
 	SyntaxError,
+	TimeoutError,
 	TypeError,
 }
 
+impl JsErrorType {
+	/// The error's `name`, as for the errors thrown by fetch().
+	fn name(self) -> &'static str {
+		match self {
+			Self::AbortError => "AbortError",
+			Self::GenericError => "Error",
+			Self::HttpError => "HttpError",
+			Self::NetworkError => "NetworkError",
+			Self::SyntaxError => "SyntaxError",
+			Self::TimeoutError => "TimeoutError",
+			Self::TypeError => "TypeError",
+		}
+	}
+
+	/// The name of the error class from errors.js.
+	fn class(self) -> &'static str {
+		match self {
+			Self::AbortError => "FaithAbortError",
+			Self::GenericError => "FaithError",
+			Self::HttpError => "FaithHttpError",
+			Self::NetworkError => "FaithNetworkError",
+			Self::SyntaxError => "FaithSyntaxError",
+			Self::TimeoutError => "FaithTimeoutError",
+			Self::TypeError => "FaithTypeError",
+		}
+	}
+}
+
 impl FaithErrorKind {
-	fn default_message(self) -> &'static str {
+	pub(crate) fn default_message(self) -> &'static str {
 		match self {
 			Self::Aborted => "the request was aborted",
 			Self::AddressParse => "invalid IP address and/or port",
+			Self::BodyNotAllowed => "request with GET/HEAD method cannot have body",
+			Self::BodyNotReplayable => "the streamed request body can't be sent again",
 			Self::BodyStream => "internal response body stream copy error",
+			Self::Cache => "HTTP cache error",
+			Self::CertificateInvalid => "invalid server certificate",
 			Self::Config => "invalid agent configuration",
+			Self::Dns => "could not resolve host name",
+			Self::ForbiddenAddress => "refusing to connect to a private network address",
+			Self::ForbiddenHost => "refusing to connect to a host not allowed by the agent",
+			Self::Graphql => "the GraphQL request failed",
+			Self::GrpcWeb => "invalid gRPC-web response",
+			Self::HttpStatus => "the response has an error status",
 			Self::IntegrityMismatch => "resource integrity check failed",
 			Self::InvalidHeader => "invalid header name or value",
 			Self::InvalidIntegrity => "invalid integrity value",
@@ -85,33 +193,75 @@ impl FaithErrorKind {
 			Self::InvalidUrl => "invalid URL",
 			Self::JsonParse => "invalid json in response body",
 			Self::Network => "network error",
+			Self::NotCached => "no cached response for the request",
 			Self::PemParse => "invalid client certificate or key",
+			Self::Proxy => "proxy error",
+			Self::ProxyAuth => "proxy authentication failed",
 			Self::Redirect => "got a redirect",
+			Self::RedirectDowngrade => "refusing to follow redirect from https to http",
+			Self::RequestTooLarge => "request is larger than the agent's limits",
 			Self::ResponseAlreadyDisturbed => "response body already disturbed",
 			Self::ResponseBodyNotAvailable => "response body not available",
+			Self::ResponseTooLarge => "response is larger than the agent's limits",
 			Self::RuntimeThread => "internal tokio runtime thread error",
 			Self::Timeout => "timed out",
+			Self::TlsHandshake => "TLS handshake failed",
+			Self::Upload => "upload failed",
 			Self::Utf8Parse => "invalid utf-8 in response body",
 		}
 	}
 
+	/// Errors which are likely to be transient: the network, the server, or the runtime failed,
+	/// rather than the request or the configuration being wrong.
+	pub(crate) fn retriable(self) -> bool {
+		matches!(
+			self,
+			Self::BodyStream
+				| Self::Dns | Self::Network
+				| Self::Proxy
+				| Self::RuntimeThread
+				| Self::Timeout
+		)
+	}
+
 	fn js_type(self) -> JsErrorType {
 		match self {
-			Self::BodyStream | Self::Config | Self::IntegrityMismatch | Self::RuntimeThread => {
-				JsErrorType::GenericError
-			}
-			Self::Aborted | Self::Timeout => JsErrorType::NamedError("AbortError"),
-			Self::Network | Self::Redirect => JsErrorType::NamedError("NetworkError"),
+			Self::BodyStream
+			| Self::Cache
+			| Self::Config
+			| Self::Graphql
+			| Self::IntegrityMismatch
+			| Self::RuntimeThread
+			| Self::Upload => JsErrorType::GenericError,
+			Self::Aborted => JsErrorType::AbortError,
+			Self::HttpStatus => JsErrorType::HttpError,
+			Self::Timeout => JsErrorType::TimeoutError,
+			Self::BodyNotReplayable
+			| Self::CertificateInvalid
+			| Self::Dns
+			| Self::ForbiddenAddress
+			| Self::ForbiddenHost
+			| Self::Network
+			| Self::NotCached
+			| Self::Proxy
+			| Self::ProxyAuth
+			| Self::Redirect
+			| Self::RedirectDowngrade
+			| Self::TlsHandshake => JsErrorType::NetworkError,
 			Self::AddressParse
+			| Self::GrpcWeb
 			| Self::InvalidIntegrity
 			| Self::JsonParse
 			| Self::PemParse
 			| Self::Utf8Parse => JsErrorType::SyntaxError,
-			Self::InvalidHeader
+			Self::BodyNotAllowed
+			| Self::InvalidHeader
 			| Self::InvalidMethod
 			| Self::InvalidUrl
+			| Self::RequestTooLarge
 			| Self::ResponseAlreadyDisturbed
-			| Self::ResponseBodyNotAvailable => JsErrorType::TypeError,
+			| Self::ResponseBodyNotAvailable
+			| Self::ResponseTooLarge => JsErrorType::TypeError,
 		}
 	}
 }
@@ -121,6 +271,10 @@ impl From<FaithErrorKind> for FaithError {
 		Self {
 			kind,
 			message: None,
+			cause: None,
+			response: None,
+			graphql: None,
+			correlation_id: None,
 		}
 	}
 }
@@ -129,6 +283,35 @@ impl From<FaithErrorKind> for FaithError {
 pub struct FaithError {
 	pub kind: FaithErrorKind,
 	pub message: Option<String>,
+	/// What caused an abort or timeout, exposed as the JS error's `cause`.
+	pub cause: Option<&'static str>,
+	/// The response an `HttpStatus` error is for, exposed as properties of the JS error.
+	pub response: Option<Box<ErrorResponse>>,
+	/// The result a `Graphql` error is for, exposed as properties of the JS error.
+	pub graphql: Option<Box<GraphqlErrors>>,
+	/// The `correlationId` of the request the error is for, exposed as the JS error's
+	/// `correlationId`.
+	pub correlation_id: Option<Arc<str>>,
+}
+
+/// The parts of a response which are kept in an `HttpStatus` error.
+#[derive(Debug, Clone)]
+pub struct ErrorResponse {
+	pub status: u16,
+	pub status_text: String,
+	pub headers: HeaderMap,
+	/// The start of the body, decoded as UTF-8.
+	pub body_preview: Option<String>,
+	/// The whole body, for the `json-api` profile.
+	pub body: Option<serde_json::Value>,
+}
+
+/// The parts of a GraphQL result which are kept in a `Graphql` error.
+#[derive(Debug, Clone)]
+pub struct GraphqlErrors {
+	pub errors: serde_json::Value,
+	/// The partial `data`, if the result has any.
+	pub data: Option<serde_json::Value>,
 }
 
 impl FaithError {
@@ -136,9 +319,36 @@ impl FaithError {
 		Self {
 			kind,
 			message: message.map(|m| m.into()),
+			cause: None,
+			response: None,
+			graphql: None,
+			correlation_id: None,
 		}
 	}
 
+	pub fn with_cause(mut self, cause: &'static str) -> Self {
+		self.cause = Some(cause);
+		self
+	}
+
+	pub fn with_response(mut self, response: ErrorResponse) -> Self {
+		self.response = Some(Box::new(response));
+		self
+	}
+
+	/// Sets the error's correlation ID, unless it already has one.
+	pub fn with_correlation_id(mut self, correlation_id: Option<Arc<str>>) -> Self {
+		if self.correlation_id.is_none() {
+			self.correlation_id = correlation_id;
+		}
+		self
+	}
+
+	pub fn with_graphql(mut self, graphql: GraphqlErrors) -> Self {
+		self.graphql = Some(Box::new(graphql));
+		self
+	}
+
 	// we make this explicit instead of adding a From<> so that we can't accidentally do it
 	pub fn into_napi(self) -> napi::Error {
 		self.to_napi()
@@ -148,20 +358,34 @@ impl FaithError {
 	}
 
 	// whenever possible, we should prefer to use this so that the error types are correct
-	pub fn into_js_error<'env>(self, env: &'env Env) -> Unknown<'env> {
+	pub fn into_js_error<'env>(mut self, env: &'env Env) -> Unknown<'env> {
 		let code = format!("{:?}", self.kind);
-		let unk = match self.kind.js_type() {
-			JsErrorType::TypeError => JsTypeError::from(self.into_napi()).into_unknown(*env),
-			JsErrorType::SyntaxError => JsSyntaxError::from(self.into_napi()).into_unknown(*env),
-			JsErrorType::GenericError => JsError::from(self.into_napi()).into_unknown(*env),
-			JsErrorType::NamedError(name) => env
-				.create_error(self.to_napi())
-				.and_then(|mut err| {
-					err.set_named_property("name", name)?;
-					Ok(err)
-				})
-				.and_then(|err| err.into_unknown(env))
-				.unwrap_or_else(|_| JsError::from(self.into_napi()).into_unknown(*env)),
+		let cause = self.cause;
+		let response = self.response.take().map(|response| *response);
+		let graphql = self.graphql.take().map(|graphql| *graphql);
+		let correlation_id = self.correlation_id.take();
+		let typ = self.kind.js_type();
+		let unk = match ErrorClasses::construct(env, typ, self.to_string()) {
+			Ok(unk) => unk,
+			// if the classes aren't available, fall back to plain errors with the same names
+			Err(_) => match typ {
+				JsErrorType::TypeError => JsTypeError::from(self.into_napi()).into_unknown(*env),
+				JsErrorType::SyntaxError => {
+					JsSyntaxError::from(self.into_napi()).into_unknown(*env)
+				}
+				JsErrorType::GenericError => JsError::from(self.into_napi()).into_unknown(*env),
+				JsErrorType::AbortError
+				| JsErrorType::HttpError
+				| JsErrorType::NetworkError
+				| JsErrorType::TimeoutError => env
+					.create_error(self.to_napi())
+					.and_then(|mut err| {
+						err.set_named_property("name", typ.name())?;
+						Ok(err)
+					})
+					.and_then(|err| err.into_unknown(env))
+					.unwrap_or_else(|_| JsError::from(self.into_napi()).into_unknown(*env)),
+			},
 		};
 
 		// we do this manually instead of using the TryFrom so we can return the untouched Unknown if we fail
@@ -175,12 +399,84 @@ impl FaithError {
 		};
 
 		let _ = obj.set("code", code);
+		if let Some(cause) = cause {
+			let _ = obj.set("cause", cause);
+		}
+		if let Some(correlation_id) = correlation_id {
+			let _ = obj.set("correlationId", correlation_id.to_string());
+		}
+		if let Some(response) = response {
+			let _ = obj.set("status", response.status);
+			let _ = obj.set("statusText", response.status_text);
+			let _ = obj.set("headers", FaithHeaders::from_map(response.headers));
+			let _ = obj.set("bodyPreview", response.body_preview);
+			if let Some(body) = response.body {
+				let _ = obj.set("body", body);
+			}
+		}
+		if let Some(graphql) = graphql {
+			let _ = obj.set("errors", graphql.errors);
+			if let Some(data) = graphql.data {
+				let _ = obj.set("data", data);
+			}
+		}
 		obj.into_unknown(env).unwrap_or(unk)
 	}
 }
 
+/// The error classes defined by errors.js, kept in the env's instance data so that errors can be
+/// constructed from them.
+struct ErrorClasses;
+
+impl ErrorClasses {
+	const TYPES: [JsErrorType; 7] = [
+		JsErrorType::AbortError,
+		JsErrorType::GenericError,
+		JsErrorType::HttpError,
+		JsErrorType::NetworkError,
+		JsErrorType::SyntaxError,
+		JsErrorType::TimeoutError,
+		JsErrorType::TypeError,
+	];
+
+	fn register(env: &Env, exports: &mut Object) -> Result<()> {
+		let classes: Object = env.run_script(include_str!("errors.js"))?;
+		for typ in Self::TYPES {
+			exports.set_named_property(
+				typ.class(),
+				classes.get_named_property::<Unknown>(typ.class())?,
+			)?;
+		}
+		Instance::init(env, classes.create_ref()?)
+	}
+
+	fn construct<'env>(env: &'env Env, typ: JsErrorType, message: String) -> Result<Unknown<'env>> {
+		let instance = Instance::get(env).map_err(|err| err.into_napi())?;
+		let class: Function<String, Unknown> = instance
+			.error_classes
+			.get_value(env)?
+			.get_named_property(typ.class())?;
+		class.new_instance(message)
+	}
+}
+
+/// Exports the error classes, so that `err instanceof FaithTimeoutError` and so on work.
+#[napi(module_exports)]
+pub fn register_error_classes(mut exports: Object, env: Env) -> Result<()> {
+	ErrorClasses::register(&env, &mut exports)
+}
+
 impl From<reqwest::Error> for FaithError {
 	fn from(err: reqwest::Error) -> Self {
+		// Errors raised by Fáith inside reqwest (e.g. from the redirect policy) are passed through
+		let mut source = err.source();
+		while let Some(e) = source {
+			if let Some(err) = e.downcast_ref::<FaithError>() {
+				return err.clone();
+			}
+			source = e.source();
+		}
+
 		// Always include full error chain for debugging
 		let mut msg = format!("{err:?}");
 		let mut source = err.source();
@@ -189,7 +485,18 @@ impl From<reqwest::Error> for FaithError {
 			source = e.source();
 		}
 
-		if err.is_timeout() {
+		// The connector's error for a 407 to the CONNECT which opens a tunnel isn't public
+		let mut source = err.source();
+		while let Some(e) = source {
+			if e.to_string() == "proxy authorization required" {
+				return FaithError::new(FaithErrorKind::ProxyAuth, Some(msg));
+			}
+			source = e.source();
+		}
+
+		if let Some(kind) = connect_error_kind(&err) {
+			FaithError::new(kind, Some(msg))
+		} else if err.is_timeout() {
 			FaithError::new(FaithErrorKind::Timeout, Some(msg))
 		} else {
 			FaithError::new(FaithErrorKind::Network, Some(msg))
@@ -197,12 +504,64 @@ impl From<reqwest::Error> for FaithError {
 	}
 }
 
+/// Tells apart the causes of connection failures which have their own error kinds. The connector's
+/// errors aren't public, so some are recognised by their message.
+fn connect_error_kind(err: &reqwest::Error) -> Option<FaithErrorKind> {
+	let mut source = err.source();
+	while let Some(e) = source {
+		// TLS errors are io::Errors from the TLS stream, whose source() skips the rustls::Error
+		let tls = e.downcast_ref::<rustls::Error>().or_else(|| {
+			e.downcast_ref::<std::io::Error>()?
+				.get_ref()?
+				.downcast_ref::<rustls::Error>()
+		});
+		match tls {
+			Some(rustls::Error::InvalidCertificate(_)) => {
+				return Some(FaithErrorKind::CertificateInvalid);
+			}
+			Some(_) => return Some(FaithErrorKind::TlsHandshake),
+			None => {}
+		}
+
+		let message = e.to_string();
+		if message.starts_with("dns error") {
+			return Some(FaithErrorKind::Dns);
+		}
+		if message.contains("tunnel") {
+			return Some(FaithErrorKind::Proxy);
+		}
+		source = e.source();
+	}
+	None
+}
+
+/// Whether the error is from an HTTP/2 connection which stopped answering the `keepalive` pings.
+/// hyper's error for it isn't public, so it's recognised by its message.
+pub(crate) fn is_keepalive_timeout(err: &(dyn Error + 'static)) -> bool {
+	let mut source = Some(err);
+	while let Some(e) = source {
+		if e.to_string() == "keep-alive timed out" {
+			return true;
+		}
+		source = e.source();
+	}
+	false
+}
+
 impl From<reqwest_middleware::Error> for FaithError {
 	fn from(err: reqwest_middleware::Error) -> Self {
 		match err {
-			reqwest_middleware::Error::Middleware(err) => {
-				FaithError::new(FaithErrorKind::Network, Some(err.to_string()))
-			}
+			reqwest_middleware::Error::Middleware(err) => match err.downcast::<FaithError>() {
+				Ok(err) => err,
+				// errors from the cache's store are boxed by the cache middleware
+				Err(err) => match err
+					.downcast_ref::<Box<dyn Error + Send + Sync>>()
+					.and_then(|err| err.downcast_ref::<FaithError>())
+				{
+					Some(err) => err.clone(),
+					None => FaithError::new(FaithErrorKind::Network, Some(err.to_string())),
+				},
+			},
 			reqwest_middleware::Error::Reqwest(err) => err.into(),
 		}
 	}
@@ -235,9 +594,30 @@ impl Display for FaithError {
 	}
 }
 
+/// An error code, with what's known about it, from `errorCodes()`.
+#[napi(object)]
+pub struct FaithErrorCode {
+	pub code: FaithErrorKind,
+	/// The `name` of the errors thrown with this code, e.g. `TypeError` or `NetworkError`.
+	pub js_error_name: String,
+	/// Whether the request may succeed if it's made again, as the error is likely to be transient.
+	pub retriable: bool,
+	/// The message of errors with this code, when there are no more specific details.
+	pub default_message: String,
+}
+
+/// Custom to Fáith.
+///
+/// Every error code that Fáith may throw, with metadata, e.g. to generate retry policies or
+/// documentation from.
 #[napi]
-pub fn error_codes() -> Vec<String> {
+pub fn error_codes() -> Vec<FaithErrorCode> {
 	FaithErrorKind::iter()
-		.map(|kind| format!("{:?}", kind))
+		.map(|kind| FaithErrorCode {
+			code: kind,
+			js_error_name: kind.js_type().name().into(),
+			retriable: kind.retriable(),
+			default_message: kind.default_message().into(),
+		})
 		.collect()
 }
@@ -0,0 +1,53 @@
+// The error classes thrown by Fáith. This is evaluated by the addon when it's loaded, and the
+// classes are exported from it: see `ErrorClasses` in error.rs.
+//
+// The classes keep the `name` of the plain errors that fetch() throws, so `err.name` is still
+// e.g. "AbortError" or "TypeError". Each error also has a `code` from `ERROR_CODES`.
+(() => {
+	function init(error, options) {
+		if (options?.code !== undefined) error.code = options.code;
+	}
+
+	class FaithError extends Error {
+		constructor(message, options) {
+			super(message, options);
+			init(this, options);
+		}
+	}
+
+	class FaithAbortError extends FaithError {}
+	FaithAbortError.prototype.name = "AbortError";
+
+	class FaithHttpError extends FaithError {}
+	FaithHttpError.prototype.name = "HttpError";
+
+	class FaithNetworkError extends FaithError {}
+	FaithNetworkError.prototype.name = "NetworkError";
+
+	class FaithTimeoutError extends FaithError {}
+	FaithTimeoutError.prototype.name = "TimeoutError";
+
+	class FaithSyntaxError extends SyntaxError {
+		constructor(message, options) {
+			super(message, options);
+			init(this, options);
+		}
+	}
+
+	class FaithTypeError extends TypeError {
+		constructor(message, options) {
+			super(message, options);
+			init(this, options);
+		}
+	}
+
+	return {
+		FaithAbortError,
+		FaithError,
+		FaithHttpError,
+		FaithNetworkError,
+		FaithSyntaxError,
+		FaithTimeoutError,
+		FaithTypeError,
+	};
+})();
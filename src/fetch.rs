@@ -1,187 +1,1091 @@
-use std::sync::{
-	Arc,
-	atomic::{AtomicBool, Ordering},
+use std::{
+	borrow::Cow,
+	collections::{HashMap, HashSet},
+	fmt::Write,
+	future::{self, pending},
+	num::NonZeroU32,
+	sync::{
+		Arc,
+		atomic::{AtomicBool, Ordering},
+	},
+	time::{Instant, SystemTime},
 };
 
+use bytes::Bytes;
+use futures::TryStreamExt;
 use http_cache_reqwest::CacheMode;
 use hyper_util::client::legacy::connect::HttpInfo;
-use napi::bindgen_prelude::AbortSignal;
+use napi::{
+	Env,
+	bindgen_prelude::{AbortSignal, Buffer, Either3, Uint8Array},
+};
 use napi_derive::napi;
-use reqwest::{Method, StatusCode};
+use reqwest::{Method, StatusCode, Version};
 use reqwest::{
-	header::{HeaderName, HeaderValue},
+	header::{
+		CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, Entry, HeaderMap, HeaderName, HeaderValue,
+		LOCATION, TRANSFER_ENCODING, USER_AGENT,
+	},
 	tls::TlsInfo,
 };
 use tokio::sync::{Mutex, mpsc};
 
 use crate::{
-	async_task::{Async, FaithAsyncResult},
+	agent::{
+		Agent, AgentProfile, AgentUrlOptions, UrlCredentials, UrlIdn, parse_address, user_agent,
+	},
+	async_task::{Async, FaithAsyncResult, Value},
 	body::{Body, BodyHolder},
-	error::{FaithError, FaithErrorKind},
-	options::{CredentialsOption, FaithOptions, FaithOptionsAndBody},
-	response::{FaithResponse, PeerInformation},
-	stream_body::StreamBody,
+	checksum::ChecksumCheck,
+	conn_tracker::ConnectionDetails,
+	connect_timing::take_connect_timing,
+	content_encoding::{self, Undecoded},
+	cookies,
+	error::{FaithError, FaithErrorKind, is_keepalive_timeout},
+	headers::{is_forbidden_method, is_forbidden_request_header},
+	history,
+	observe::{self, RequestEventType, sent_at_end},
+	options::{
+		CredentialsOption, FaithOptions, FaithOptionsAndBody, RequestCacheMode, timeout_millis,
+	},
+	pacing::paced,
+	pinned::ConnectTo,
+	private_network::{check_address, check_url},
+	proxy::ProxyDecision,
+	proxy_resolver,
+	resource_timing::{MarkResourceTiming, ResourceTiming},
+	response::{AltSvcDecision, FaithResponse, PeerInformation, Timings},
+	runtime,
+	stream_body::{DEFAULT_REPLAY_BUFFER, SharedStreamBodyReceiver, StreamBody},
+	timeouts::{self, Progress, progressing},
+	tls_sessions,
+	version::version_parts,
 };
 
 #[napi]
 pub fn faith_fetch(
+	env: Env,
 	url: String,
-	options: FaithOptionsAndBody,
+	options: Option<FaithOptionsAndBody>,
 	signal: Option<AbortSignal>,
 	stream_body: Option<&StreamBody>,
 ) -> Async<FaithResponse> {
-	let (options, agent, body) = FaithOptions::extract(options);
-	let (s, abort) = mpsc::channel(8);
-	if let Some(signal) = &signal {
-		signal.on_abort(move || {
-			let _ = s.try_send(());
-		});
-	}
-	let has_signal = signal.is_some();
+	let (mut options, agent, body) = FaithOptions::extract(options.unwrap_or_default());
+	options.resource_timing = MarkResourceTiming::for_thread(&env);
+	let agent = agent.map_or_else(|| Agent::global(&env), Ok);
+	let abort = abort_receiver(signal.as_ref());
 
 	// Get the stream body receiver if provided
 	let stream_receiver = stream_body.map(|sb| sb.receiver.clone());
 
-	FaithAsyncResult::with_signal(signal, async move || {
-		let mut abort = abort;
-		let method = options
-			.method
-			.map(|m| m.to_uppercase())
-			.unwrap_or_else(|| "GET".to_string());
-
-		let method =
-			Method::from_bytes(method.as_bytes()).map_err(|_| FaithErrorKind::InvalidMethod)?;
-		let is_head = method == Method::HEAD;
-
-		let mut parsed_url = reqwest::Url::parse(&url).map_err(|_| FaithErrorKind::InvalidUrl)?;
-
-		// Handle credentials based on credentials option
-		if options.credentials == CredentialsOption::Omit {
-			// Remove credentials from URL if omit is specified
-			let _ = parsed_url.set_username("");
-			let _ = parsed_url.set_password(None);
-		}
-
-		let mut request = agent
-			.client
-			.request(method, parsed_url.clone())
-			.with_extension(CacheMode::from(options.cache));
-
-		if let Some(headers) = &options.headers {
-			for (key, value) in headers {
-				// Skip Cookie header if credentials is omit
-				if options.credentials == CredentialsOption::Omit
-					&& key.eq_ignore_ascii_case("cookie")
-				{
-					continue;
-				}
-
-				// Validate header name and value before adding to request
-				let header_name = HeaderName::from_bytes(key.as_bytes()).map_err(|_| {
-					FaithError::new(
-						FaithErrorKind::InvalidHeader,
-						Some(format!("invalid header name: {key}")),
-					)
-				})?;
-				let header_value = HeaderValue::from_str(value).map_err(|_| {
-					FaithError::new(
-						FaithErrorKind::InvalidHeader,
-						Some(format!("invalid header value: {value}")),
-					)
-				})?;
-				request = request.header(header_name, header_value);
+	FaithAsyncResult::run(async move || {
+		fetch(url, options, agent?, body, stream_receiver, abort).await
+	})
+}
+
+/// Returns a channel that receives when the signal is aborted, if there's a signal.
+pub(crate) fn abort_receiver(signal: Option<&AbortSignal>) -> Option<mpsc::Receiver<()>> {
+	let signal = signal?;
+	let (s, abort) = mpsc::channel(8);
+	signal.on_abort(move || {
+		let _ = s.try_send(());
+	});
+	Some(abort)
+}
+
+/// Parses the request URL, applying the agent's `url` settings.
+fn parse_url(url: &str, options: &AgentUrlOptions) -> Result<reqwest::Url, FaithError> {
+	if options.strict_encoding == Some(true) && has_unencoded(url) {
+		return Err(FaithError::new(
+			FaithErrorKind::InvalidUrl,
+			Some("URL contains characters that must be percent-encoded"),
+		));
+	}
+
+	let mut parsed = reqwest::Url::parse(url).map_err(|_| FaithErrorKind::InvalidUrl)?;
+
+	if options.idn == Some(UrlIdn::Reject)
+		&& parsed
+			.host_str()
+			.is_some_and(|host| host.split('.').any(|label| label.starts_with("xn--")))
+	{
+		return Err(FaithError::new(
+			FaithErrorKind::InvalidUrl,
+			Some("URL contains an internationalized domain name"),
+		));
+	}
+
+	if !parsed.username().is_empty() || parsed.password().is_some() {
+		match options.credentials.unwrap_or_default() {
+			UrlCredentials::Use => {}
+			UrlCredentials::Strip => {
+				let _ = parsed.set_username("");
+				let _ = parsed.set_password(None);
+			}
+			UrlCredentials::Reject => {
+				return Err(FaithError::new(
+					FaithErrorKind::InvalidUrl,
+					Some("URL contains embedded credentials"),
+				));
 			}
 		}
+	}
 
-		// Handle body: prefer streaming body over buffered body
-		if let Some(receiver_arc) = stream_receiver {
-			// Take the receiver from the Arc<Mutex<Option<...>>>
-			let receiver = {
-				let mut guard = receiver_arc.lock().await;
-				guard.take()
-			};
+	Ok(parsed)
+}
+
+/// Fills the `{name}` placeholders in a URL template with the percent-encoded `params`.
+fn fill_template(template: &str, params: &HashMap<String, String>) -> Result<String, FaithError> {
+	let mut url = String::with_capacity(template.len());
+	let mut rest = template;
+	while let Some(start) = rest.find('{') {
+		url.push_str(&rest[..start]);
+		let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+			return Err(FaithError::new(
+				FaithErrorKind::InvalidUrl,
+				Some("URL template has an unclosed `{`"),
+			));
+		};
+
+		let name = &rest[start + 1..end];
+		let value = params.get(name).ok_or_else(|| {
+			FaithError::new(
+				FaithErrorKind::InvalidUrl,
+				Some(format!("missing URL template parameter: {name}")),
+			)
+		})?;
+
+		// Everything but unreserved characters is encoded, so a value is always a single segment
+		for byte in value.bytes() {
+			if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+				url.push(byte as char);
+			} else {
+				let _ = write!(url, "%{byte:02X}");
+			}
+		}
+
+		rest = &rest[end + 1..];
+	}
+
+	url.push_str(rest);
+	Ok(url)
+}
+
+/// Resolves a relative URL against the agent's `baseUrl`, or returns `None` if it's absolute.
+fn join_base_url(base: &reqwest::Url, url: &str) -> Option<String> {
+	if reqwest::Url::parse(url).is_ok() {
+		return None;
+	}
+
+	let base = base.as_str();
+	Some(if url.is_empty() || url.starts_with(['?', '#']) {
+		format!("{base}{url}")
+	} else {
+		format!(
+			"{}/{}",
+			base.trim_end_matches('/'),
+			url.trim_start_matches('/')
+		)
+	})
+}
+
+/// Returns whether the URL string has characters that the parser would percent-encode or remove.
+///
+/// Non-ASCII characters are allowed in the host, as they're handled by the `idn` setting instead.
+fn has_unencoded(url: &str) -> bool {
+	let authority = url.find("://").map_or(0, |index| index + 3);
+	let path = url[authority..]
+		.find(['/', '\\', '?', '#'])
+		.map_or(url.len(), |index| authority + index);
+
+	url.char_indices().any(|(index, c)| {
+		c.is_ascii_control()
+			|| c == ' '
+			|| (index >= path && (!c.is_ascii() || matches!(c, '"' | '<' | '>' | '`' | '{' | '}')))
+	})
+}
+
+/// Checks `Content-Length` and `Transfer-Encoding` headers given with `unsafe` against how the
+/// body will actually be sent, as a mismatch would corrupt the request framing.
+fn check_framing_headers(
+	headers: &HeaderMap,
+	length: Option<usize>,
+	chunked: bool,
+) -> Result<(), FaithError> {
+	let conflict = |message: String| -> Result<(), FaithError> {
+		Err(FaithError::new(
+			FaithErrorKind::InvalidHeader,
+			Some(message),
+		))
+	};
+
+	if let Some(value) = headers.get(CONTENT_LENGTH) {
+		if headers.contains_key(TRANSFER_ENCODING) {
+			return conflict("Content-Length and Transfer-Encoding can't both be set".into());
+		}
+
+		let given = value
+			.to_str()
+			.ok()
+			.and_then(|v| v.trim().parse::<usize>().ok());
+		match length {
+			_ if chunked => {
+				return conflict("Content-Length can't be set for a chunked body".into());
+			}
+			Some(length) if given != Some(length) => {
+				return conflict(format!(
+					"Content-Length header doesn't match the body length of {length} bytes"
+				));
+			}
+			None => return conflict("Content-Length can't be set without a body".into()),
+			Some(_) => {}
+		}
+	}
+
+	if headers.contains_key(TRANSFER_ENCODING) && !chunked {
+		return conflict("Transfer-Encoding can't be set for a body which isn't chunked".into());
+	}
+
+	Ok(())
+}
+
+/// Whether the status is one which never has a response body, as per the fetch spec.
+fn is_null_body_status(status: StatusCode) -> bool {
+	matches!(status.as_u16(), 101 | 103 | 204 | 205 | 304)
+}
+
+/// Whether the status is a redirect status in the Fetch spec.
+fn is_redirect_status(status: StatusCode) -> bool {
+	matches!(status.as_u16(), 301 | 302 | 303 | 307 | 308)
+}
+
+/// Rewrites a plain-text URL to HTTPS, unless its host is excluded, for `upgradeInsecureRequests`.
+fn upgrade_insecure(url: &mut reqwest::Url, exclude: &[String]) {
+	if url.scheme() != "http" {
+		return;
+	}
+
+	let host = url.host_str().unwrap_or_default().trim_end_matches('.');
+	if exclude.iter().any(|excluded| {
+		host == excluded
+			|| host
+				.strip_suffix(excluded.as_str())
+				.is_some_and(|sub| sub.ends_with('.'))
+	}) {
+		return;
+	}
+
+	// the default port of 80 is elided by the parser, so this also switches to 443
+	let _ = url.set_scheme("https");
+}
+
+/// The parts of a request which don't depend on its body, parsed and validated ahead of sending.
+#[derive(Clone, Debug)]
+pub(crate) struct Prepared {
+	pub(crate) method: Method,
+	pub(crate) url: reqwest::Url,
+	connect_to: Option<ConnectTo>,
+	headers: HeaderMap,
+}
+
+/// Parses and validates the method, URL and headers of a request, layering them over the agent's
+/// settings.
+pub(crate) fn prepare(
+	url: &str,
+	options: &FaithOptions,
+	agent: &Agent,
+) -> Result<Prepared, FaithError> {
+	prepare_inner(url, options, agent)
+		.map_err(|err| err.with_correlation_id(options.correlation_id.clone()))
+}
+
+fn prepare_inner(url: &str, options: &FaithOptions, agent: &Agent) -> Result<Prepared, FaithError> {
+	let method = options
+		.method
+		.as_deref()
+		.map(|m| m.to_uppercase())
+		.unwrap_or_else(|| "GET".to_string());
+
+	if !agent.allow_unsafe && is_forbidden_method(&method) {
+		return Err(FaithError::new(
+			FaithErrorKind::InvalidMethod,
+			Some(format!("forbidden method: {method}")),
+		));
+	}
 
-			if let Some(receiver) = receiver {
-				// Convert the receiver into a stream for reqwest
-				let byte_stream = receiver.into_stream();
-				request = request.body(reqwest::Body::wrap_stream(byte_stream));
+	let method =
+		Method::from_bytes(method.as_bytes()).map_err(|_| FaithErrorKind::InvalidMethod)?;
+
+	let mut url = Cow::Borrowed(url);
+	if let Some(params) = &options.params {
+		url = fill_template(&url, params)?.into();
+	}
+	if let Some(joined) = agent
+		.base_url
+		.as_ref()
+		.and_then(|base| join_base_url(base, &url))
+	{
+		url = joined.into();
+	}
+
+	let mut parsed_url = parse_url(&url, &agent.url)?;
+	if let Some(exclude) = &agent.upgrade_insecure {
+		upgrade_insecure(&mut parsed_url, exclude);
+	}
+
+	// Handle credentials based on credentials option
+	if options.credentials == CredentialsOption::Omit {
+		// Remove credentials from URL if omit is specified
+		let _ = parsed_url.set_username("");
+		let _ = parsed_url.set_password(None);
+	}
+
+	if let Some(filter) = &agent.host_filter {
+		filter.check(&parsed_url)?;
+	}
+
+	if agent.block_private_networks {
+		check_url(&parsed_url)?;
+	}
+
+	let connect_to = match &options.connect_to {
+		Some(addr) => {
+			let addr = parse_address(addr)?;
+			if agent.block_private_networks {
+				check_address(addr.ip())?;
 			}
-		} else if let Some(body) = &body {
-			request = request.body(body.to_vec());
+			Some(ConnectTo(addr))
+		}
+		None => None,
+	};
+
+	// Layer the request headers over the agent's default headers: a request header replaces all
+	// values of the default header of the same name, and a `null` value removes the header. The
+	// `userAgent` option replaces the default, but not a `User-Agent` given in the headers.
+	let mut headers = agent.default_headers.clone();
+	if let Some(ua) = &options.user_agent {
+		headers.insert(
+			USER_AGENT,
+			user_agent(ua, agent.user_agent_suffix.as_deref())?,
+		);
+	}
+
+	let mut overridden = HashSet::new();
+	for (key, value) in options.headers.iter().flatten() {
+		// Skip Cookie header if credentials is omit
+		if options.credentials == CredentialsOption::Omit && key.eq_ignore_ascii_case("cookie") {
+			continue;
+		}
+
+		// Validate header name and value before adding to request
+		let header_name = HeaderName::from_bytes(key.as_bytes()).map_err(|_| {
+			FaithError::new(
+				FaithErrorKind::InvalidHeader,
+				Some(format!("invalid header name: {key}")),
+			)
+		})?;
+
+		let Some(value) = value else {
+			headers.remove(&header_name);
+			continue;
+		};
+
+		// Forbidden headers are dropped silently, as browsers do
+		if !agent.allow_unsafe && is_forbidden_request_header(&header_name, value) {
+			continue;
 		}
 
-		if let Some(dur) = options.timeout {
-			request = request.timeout(dur);
+		let header_value = HeaderValue::from_str(value).map_err(|_| {
+			FaithError::new(
+				FaithErrorKind::InvalidHeader,
+				Some(format!("invalid header value: {value}")),
+			)
+		})?;
+
+		if overridden.insert(header_name.clone()) {
+			headers.remove(&header_name);
 		}
+		headers.append(header_name, header_value);
+	}
 
-		agent.stats.requests_sent.fetch_add(1, Ordering::Relaxed);
+	for name in options.sensitive_headers.iter().flatten() {
+		let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+			FaithError::new(
+				FaithErrorKind::InvalidHeader,
+				Some(format!("invalid header name: {name}")),
+			)
+		})?;
 
-		// Race the request with the abort signal if signal was provided
-		let response = if has_signal {
-			tokio::select! {
-				result = request.send() => result?,
-				_ = abort.recv() => {
-					return Err(FaithErrorKind::Aborted.into());
-				}
+		if let Entry::Occupied(mut entry) = headers.entry(header_name) {
+			for value in entry.iter_mut() {
+				value.set_sensitive(true);
 			}
-		} else {
-			request.send().await?
+		}
+	}
+
+	Ok(Prepared {
+		method,
+		url: parsed_url,
+		connect_to,
+		headers,
+	})
+}
+
+/// Performs the request with the agent, returning once the response headers are received.
+pub(crate) async fn fetch(
+	url: String,
+	options: FaithOptions,
+	agent: Agent,
+	body: Option<Bytes>,
+	stream_receiver: Option<SharedStreamBodyReceiver>,
+	abort: Option<mpsc::Receiver<()>>,
+) -> Result<FaithResponse, FaithError> {
+	let prepared = prepare(&url, &options, &agent).inspect_err(|err| {
+		if let Some(observer) = &options.observe {
+			observer.error(err);
+		}
+	})?;
+	send(prepared, options, agent, body, stream_receiver, abort).await
+}
+
+/// Sends a prepared request with the agent, returning once the response headers are received.
+pub(crate) async fn send(
+	prepared: Prepared,
+	options: FaithOptions,
+	agent: Agent,
+	body: Option<Bytes>,
+	stream_receiver: Option<SharedStreamBodyReceiver>,
+	abort: Option<mpsc::Receiver<()>>,
+) -> Result<FaithResponse, FaithError> {
+	let correlation_id = options.correlation_id.clone();
+	let observer = options.observe.clone();
+	if let Some(observer) = &observer {
+		observer.emit(RequestEventType::Queued);
+	}
+	send_inner(prepared, options, agent, body, stream_receiver, abort)
+		.await
+		.map_err(|err| {
+			if let Some(observer) = &observer {
+				observer.error(&err);
+			}
+			err.with_correlation_id(correlation_id)
+		})
+}
+
+async fn send_inner(
+	prepared: Prepared,
+	mut options: FaithOptions,
+	agent: Agent,
+	body: Option<Bytes>,
+	stream_receiver: Option<SharedStreamBodyReceiver>,
+	abort: Option<mpsc::Receiver<()>>,
+) -> Result<FaithResponse, FaithError> {
+	runtime::check_accepting()?;
+	let aborted = agent.aborter.current();
+
+	// Serialising here rather than in `extract()` keeps it off the JS thread
+	let is_json = options.json.is_some();
+	let body = match options.json.take() {
+		Some(_) if body.is_some() || stream_receiver.is_some() => {
+			return Err(FaithError::new(
+				FaithErrorKind::Config,
+				Some("the body and json options can't be used together"),
+			));
+		}
+		Some(json) => Some(Bytes::from(serde_json::to_vec(&json).map_err(|err| {
+			FaithError::new(FaithErrorKind::Config, Some(err.to_string()))
+		})?)),
+		None => body,
+	};
+
+	let Prepared {
+		method,
+		url: parsed_url,
+		connect_to,
+		mut headers,
+	} = prepared;
+	let is_head = method == Method::HEAD;
+	let expects_body = method == Method::POST || method == Method::PUT;
+
+	let has_body = body.is_some() || stream_receiver.is_some();
+	if has_body && (is_head || (method == Method::GET && !agent.allow_get_body)) {
+		return Err(FaithErrorKind::BodyNotAllowed.into());
+	}
+
+	let only_if_cached = matches!(options.cache, RequestCacheMode::OnlyIfCached);
+	if only_if_cached && !agent.http_cache {
+		return Err(FaithError::new(
+			FaithErrorKind::NotCached,
+			Some("only-if-cached request with an agent without cache"),
+		));
+	}
+
+	let mut request = agent
+		.client
+		.request(method, parsed_url.clone())
+		.with_extension(CacheMode::from(options.cache));
+
+	if let Some(version) = options.version {
+		#[cfg(not(feature = "http3"))]
+		if version == crate::options::VersionOption::H3 {
+			return Err(FaithError::new(
+				FaithErrorKind::Config,
+				Some("HTTP/3 support is not enabled in this build"),
+			));
+		}
+
+		request = request.with_extension(version);
+	}
+
+	if let Some(connect_to) = connect_to {
+		request = request.with_extension(connect_to);
+	}
+
+	// checksums are of the body as sent, so it's decompressed here after they're checked
+	if options
+		.checksums
+		.as_ref()
+		.is_some_and(|checksums| !checksums.is_empty())
+	{
+		request = request.with_extension(Undecoded);
+	}
+
+	if agent.http10 {
+		request = request
+			.version(Version::HTTP_10)
+			.header(CONNECTION, HeaderValue::from_static("close"));
+	}
+
+	if is_json && !headers.contains_key(CONTENT_TYPE) {
+		headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+	}
+
+	// Sized bodies are sent with a Content-Length, streamed ones are chunked by hyper. As in the
+	// spec, a POST or PUT without a body has a Content-Length of zero.
+	let chunked = stream_receiver.is_some() || (body.is_some() && options.force_chunked);
+	let length = match &body {
+		_ if chunked => None,
+		Some(body) => Some(body.len()),
+		None if expects_body => Some(0),
+		None => None,
+	};
+	check_framing_headers(&headers, length, chunked)?;
+	if let Some(length) = length {
+		headers.insert(CONTENT_LENGTH, HeaderValue::from(length));
+	}
+
+	if let Some(limit) = agent.limits.request_headers {
+		let size: usize = headers
+			.iter()
+			.map(|(name, value)| name.as_str().len() + value.len() + 4)
+			.sum();
+		if size > limit as usize {
+			return Err(FaithError::new(
+				FaithErrorKind::RequestTooLarge,
+				Some(format!(
+					"request headers are {size} bytes, over the limit of {limit}"
+				)),
+			));
+		}
+	}
+
+	request = request.headers(headers);
+
+	let upload_rate_limit = options
+		.upload_rate_limit
+		.map(|rate| {
+			NonZeroU32::new(rate).ok_or_else(|| {
+				FaithError::new(
+					FaithErrorKind::Config,
+					Some("uploadRateLimit must be greater than zero"),
+				)
+			})
+		})
+		.transpose()?;
+
+	let traffic = agent.stats.host(&parsed_url);
+	let progress = Progress::new();
+
+	// Whether reqwest can send the body again, which it needs to to follow a 307 or 308 redirect
+	let mut replayable = true;
+	let mut read_ahead = None;
+
+	// Handle body: prefer streaming body over buffered body
+	if let Some(receiver_arc) = stream_receiver {
+		if agent.http10 {
+			return Err(FaithError::new(
+				FaithErrorKind::Config,
+				Some("streaming request bodies are not supported with http1.version10"),
+			));
+		}
+
+		// Take the receiver from the Arc<Mutex<Option<...>>>
+		let receiver = {
+			let mut guard = receiver_arc.lock().await;
+			guard.take()
 		};
 
+		// Small bodies are read in full before sending, within the request's future so that it can
+		// be aborted, and then sent as buffered bodies. Paced bodies are always streamed.
+		if let Some(receiver) = receiver {
+			let limit = match upload_rate_limit {
+				Some(_) => 0,
+				None => agent.limits.replay_buffer.unwrap_or(DEFAULT_REPLAY_BUFFER),
+			};
+			read_ahead = Some((receiver, limit as usize));
+		}
+	} else if let Some(body) = body {
+		if agent.http10 && chunked {
+			return Err(FaithError::new(
+				FaithErrorKind::Config,
+				Some("chunked request bodies are not supported with http1.version10"),
+			));
+		}
+
+		if let Some(limit) = agent
+			.limits
+			.request_body
+			.filter(|&limit| body.len() > limit as usize)
+		{
+			return Err(FaithError::new(
+				FaithErrorKind::RequestTooLarge,
+				Some(format!(
+					"request body is {} bytes, over the limit of {limit}",
+					body.len()
+				)),
+			));
+		}
+
 		agent
 			.stats
-			.responses_received
-			.fetch_add(1, Ordering::Relaxed);
+			.bytes_sent
+			.fetch_add(body.len() as u64, Ordering::Relaxed);
+		traffic
+			.bytes_sent
+			.fetch_add(body.len() as u64, Ordering::Relaxed);
+		let once = || futures::stream::once(future::ready(Ok::<_, std::io::Error>(body.clone())));
+		replayable = upload_rate_limit.is_none() && !chunked;
+		let observer = options.observe.clone();
+		request = if let Some(rate) = upload_rate_limit {
+			request.body(reqwest::Body::wrap_stream(sent_at_end(
+				progressing(paced(once(), rate), progress.clone()),
+				observer,
+			)))
+		} else if chunked {
+			request.body(reqwest::Body::wrap_stream(sent_at_end(once(), observer)))
+		} else {
+			request.body(body)
+		};
+	}
 
-		let status_code = response.status();
-		let empty = status_code == StatusCode::NO_CONTENT || is_head;
+	let own_total = options.total_timeout();
+	if let Some((dur, _)) = own_total {
+		request = request.timeout(dur);
+	}
 
-		let response_url = response.url().clone();
-		let redirected = parsed_url != response_url;
+	// Race the request with the abort signal if signal was provided, and with `agent.abortAll()`
+	let signal = async move {
+		match abort {
+			Some(mut abort) => {
+				abort.recv().await;
+				FaithError::from(FaithErrorKind::Aborted).with_cause("signal")
+			}
+			None => pending().await,
+		}
+	};
+	tokio::pin!(signal);
 
-		let version = response.version();
+	// The app's proxy resolver may take a while to answer, so it's raced with the signals too
+	let route = match &agent.proxy_resolver {
+		Some(resolver) => tokio::select! {
+			route = resolver.resolve(&parsed_url) => route?,
+			err = &mut signal => {
+				agent.stats.aborted.fetch_add(1, Ordering::Relaxed);
+				return Err(err);
+			}
+			err = aborted.aborted() => {
+				agent.stats.aborted.fetch_add(1, Ordering::Relaxed);
+				return Err(err);
+			}
+		},
+		None => None,
+	};
 
-		// Track connection for TCP stats (if we can get both local and remote addr)
-		if let Some(http_info) = response.extensions().get::<HttpInfo>() {
-			let local_addr = http_info.local_addr();
-			let remote_addr = http_info.remote_addr();
-			agent.conn_tracker.track(local_addr, remote_addr);
-		}
+	agent.stats.requests_sent.fetch_add(1, Ordering::Relaxed);
+	traffic.requests.fetch_add(1, Ordering::Relaxed);
 
-		let peer = PeerInformation {
-			address: response.remote_addr(),
-			certificate: response
-				.extensions()
-				.get::<TlsInfo>()
-				.and_then(|info| info.peer_certificate())
-				.map(|cert| cert.into()),
+	let stats = agent.stats.clone();
+	let observer = options.observe.clone();
+	let sending = progress.clone();
+	let send = async move {
+		let Some((receiver, limit)) = read_ahead else {
+			return (request.send().await, replayable);
 		};
 
-		let mut headers = response.headers().clone();
-		if options.credentials == CredentialsOption::Omit {
-			headers.remove("set-cookie");
+		let count = move |bytes: &Bytes| {
+			stats
+				.bytes_sent
+				.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+			traffic
+				.bytes_sent
+				.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+		};
+		match receiver.read_ahead(limit).await {
+			Ok(body) => {
+				count(&body);
+				(request.body(body).send().await, true)
+			}
+			Err((read, receiver)) => {
+				let byte_stream = receiver.into_stream(read).inspect_ok(count);
+				let body = match upload_rate_limit {
+					Some(rate) => reqwest::Body::wrap_stream(sent_at_end(
+						progressing(paced(byte_stream, rate), sending),
+						observer,
+					)),
+					None => reqwest::Body::wrap_stream(sent_at_end(
+						progressing(byte_stream, sending),
+						observer,
+					)),
+				};
+				(request.body(body).send().await, false)
+			}
 		}
+	};
 
-		Ok(FaithResponse {
-			body: if empty {
-				BodyHolder::none()
-			} else {
-				let http_response: http::Response<_> = response.into();
-				BodyHolder::new(
-					Some(Arc::new(Mutex::new(Body::Inner(http_response.into_body())))),
-					version,
-				)
-			},
-			disturbed: Arc::new(AtomicBool::new(false)),
-			headers,
-			integrity: options.integrity,
-			peer: Arc::new(peer),
-			redirected,
-			stats: agent.stats.clone(),
+	let read_timeout = timeout_millis(options.timeouts.read, "timeouts.read")
+		.or_else(|| timeout_millis(agent.timeouts.read, "agent.timeout.read"));
+	let started = Instant::now();
+	let request_start = SystemTime::now();
+	let sent = agent.clock.system_now();
+	let ((result, replayable), redirects) = tokio::select! {
+		collected = observe::with_observer(
+			options.observe.clone(),
+			cookies::with_top_level_site(
+				options.top_level_site.as_deref(),
+				proxy_resolver::with_route(
+					route.clone(),
+					timeouts::with_request(
+						timeout_millis(options.timeouts.connect, "timeouts.connect"),
+						progress.clone(),
+						history::collect(send),
+					),
+				),
+			),
+		) => collected,
+		(dur, cause) = timeouts::idle(read_timeout, &progress) => {
+			agent.stats.timeouts.fetch_add(1, Ordering::Relaxed);
+			return Err(FaithError::new(
+				FaithErrorKind::Timeout,
+				Some(format!("no progress for {}ms before the response", dur.as_millis())),
+			)
+			.with_cause(cause));
+		}
+		err = &mut signal => {
+			agent.stats.aborted.fetch_add(1, Ordering::Relaxed);
+			return Err(err);
+		}
+		err = aborted.aborted() => {
+			agent.stats.aborted.fetch_add(1, Ordering::Relaxed);
+			return Err(err);
+		}
+	};
+
+	let response = result.map_err(|err| {
+		if is_keepalive_timeout(&err) {
+			agent
+				.conn_tracker
+				.unhealthy(&parsed_url.origin().ascii_serialization());
+		}
+
+		let is_connect = err.is_connect();
+		let err = FaithError::from(err);
+		if !matches!(err.kind, FaithErrorKind::Timeout) {
+			return err;
+		}
+
+		agent.stats.timeouts.fetch_add(1, Ordering::Relaxed);
+
+		// Connect timeouts are Fáith's own, and already say which one fired
+		if err.cause.is_some() {
+			return err;
+		}
+
+		// The request's own total timeout replaces the agent's, and both cover the whole exchange,
+		// so whichever is set and has elapsed is the one that fired. Otherwise, it was one of the
+		// connection's own timeouts.
+		let elapsed = started.elapsed();
+		let total =
+			own_total.or_else(|| timeout_millis(agent.timeouts.total, "agent.timeout.total"));
+		err.with_cause(match total {
+			_ if is_connect => "agent.timeout.connect",
+			Some((dur, cause)) if elapsed >= dur => cause,
+			_ => "agent.timeout.read",
+		})
+	})?;
+
+	// The cache answers a miss with a synthetic 504 rather than an error. A 504 which was cached
+	// is a hit, and is passed on as is.
+	if only_if_cached
+		&& response.status() == StatusCode::GATEWAY_TIMEOUT
+		&& response
+			.headers()
+			.get("x-cache-lookup")
+			.is_none_or(|lookup| lookup != "HIT")
+	{
+		return Err(FaithError::new(
+			FaithErrorKind::NotCached,
+			Some(format!("no cached response for {parsed_url}")),
+		));
+	}
+
+	agent
+		.stats
+		.responses_received
+		.fetch_add(1, Ordering::Relaxed);
+
+	let status_code = response.status();
+	agent.stats.record_status(status_code);
+	if let Some(observer) = &options.observe {
+		observer.headers_done(status_code.as_u16());
+	}
+
+	if let Some(clock_skew) = &agent.clock_skew
+		&& response
+			.headers()
+			.get("x-cache")
+			.is_none_or(|cache| cache != "HIT")
+	{
+		clock_skew.record(response.headers(), sent, agent.clock.system_now());
+	}
+
+	// reqwest returns 307 and 308 redirects as they are when it can't send the body again, but the
+	// fetch spec says they're network errors
+	if !replayable
+		&& agent.follow_redirects
+		&& matches!(
 			status_code,
-			trailers: Default::default(),
-			url: response_url,
-			version,
+			StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT
+		) && response.headers().contains_key(LOCATION)
+	{
+		return Err(FaithError::new(
+			FaithErrorKind::BodyNotReplayable,
+			Some(format!(
+				"can't follow a {} redirect from {} without sending the streamed body again",
+				status_code.as_u16(),
+				response.url()
+			)),
+		));
+	}
+	// strict agents with `redirect: "manual"` return redirects as opaque-redirect filtered responses
+	let opaque_redirect = agent.opaque_redirects && is_redirect_status(status_code);
+	let empty = is_head || is_null_body_status(status_code) || opaque_redirect;
+	if empty && let Some(observer) = &options.observe {
+		observer.emit(RequestEventType::BodyDone);
+	}
+
+	if let Some(limit) = agent.limits.response_body
+		&& !empty
+		&& let Some(length) = response.content_length()
+		&& length > limit.into()
+	{
+		return Err(FaithError::new(
+			FaithErrorKind::ResponseTooLarge,
+			Some(format!(
+				"response body is {length} bytes, over the limit of {limit}"
+			)),
+		));
+	}
+
+	let response_url = response.url().clone();
+	let redirected = parsed_url != response_url;
+
+	let version = response.version();
+
+	let certificate = response
+		.extensions()
+		.get::<TlsInfo>()
+		.and_then(|info| info.peer_certificate());
+
+	// Track connection for TCP stats (if we can get both local and remote addr)
+	let mut connect = None;
+	if let Some(http_info) = response.extensions().get::<HttpInfo>() {
+		let local_addr = http_info.local_addr();
+		let remote_addr = http_info.remote_addr();
+		connect = take_connect_timing(local_addr, remote_addr);
+		agent
+			.conn_tracker
+			.track(local_addr, remote_addr, || ConnectionDetails {
+				origin: response_url.origin().ascii_serialization(),
+				protocol: version_parts(version).2,
+				peer_certificate: certificate.map(Into::into),
+			});
+	}
+
+	let peer = PeerInformation {
+		address: response.remote_addr(),
+		certificate: certificate.map(|cert| cert.into()),
+		// Responses from the cache or over HTTP/3 don't go through the proxy, and have no HttpInfo
+		proxy: match &route {
+			_ if response.extensions().get::<HttpInfo>().is_none() => None,
+			Some(route) => ProxyDecision::resolved(route, &response_url),
+			None => agent
+				.proxy
+				.as_ref()
+				.and_then(|proxy| proxy.decide(&response_url)),
+		},
+		tls: agent
+			.tls
+			.as_ref()
+			.and(certificate)
+			.and(response.extensions().get::<HttpInfo>())
+			.and_then(tls_sessions::peer),
+	};
+
+	let alt_svc = response.extensions().get::<AltSvcDecision>().cloned();
+
+	// hyper only keeps reason phrases which differ from the canonical one
+	let reason_phrase = match response.extensions().get::<hyper::ext::ReasonPhrase>() {
+		Some(reason) => Some(Bytes::copy_from_slice(reason.as_bytes())),
+		None if response.extensions().get::<HttpInfo>().is_some()
+			&& matches!(
+				version,
+				Version::HTTP_09 | Version::HTTP_10 | Version::HTTP_11
+			) =>
+		{
+			status_code.canonical_reason().map(Bytes::from_static)
+		}
+		None => None,
+	};
+
+	let mut headers = if opaque_redirect {
+		HeaderMap::new()
+	} else {
+		response.headers().clone()
+	};
+	if options.credentials == CredentialsOption::Omit || agent.strict {
+		headers.remove("set-cookie");
+	}
+	if agent.strict {
+		headers.remove("set-cookie2");
+	}
+
+	if let Some(preloader) = &agent.preloader
+		&& !options.preload
+		&& status_code.is_success()
+	{
+		preloader.harvest(&agent, &response_url, &headers);
+	}
+
+	let throw_on_error = options.throw_on_error.unwrap_or(agent.throw_on_error);
+	let resource_timing = options
+		.resource_timing
+		.filter(|_| agent.resource_timing)
+		.map(|mark| {
+			Arc::new(ResourceTiming::new(
+				mark,
+				parsed_url.to_string(),
+				headers.get("x-cache").is_some_and(|cache| cache == "HIT"),
+				response_url.scheme() == "https",
+				status_code.as_u16(),
+				version,
+			))
+		});
+	let checksum = ChecksumCheck::new(options.checksums);
+	let response = FaithResponse {
+		alt_svc,
+		body: if empty {
+			BodyHolder::none()
+		} else {
+			let http_response: http::Response<_> = response.into();
+			let body = content_encoding::decode(
+				&mut headers,
+				http_response.into_body(),
+				checksum.as_ref(),
+			);
+			BodyHolder::new(
+				Some(Arc::new(Mutex::new(Body::Inner(timeouts::body(
+					body,
+					read_timeout,
+					timeout_millis(options.timeouts.body, "timeouts.body"),
+				))))),
+				version,
+			)
+		},
+		body_limit: agent.limits.response_body,
+		buffered: Default::default(),
+		disturbed: Arc::new(AtomicBool::new(false)),
+		headers,
+		history: redirects.into(),
+		host_traffic: agent.stats.host(&response_url),
+		integrity: options.integrity,
+		observer: options.observe,
+		opaque_redirect,
+		checksum,
+		chunking: agent.stream,
+		conn_tracker: agent.conn_tracker.clone(),
+		correlation_id: options.correlation_id,
+		peer: Arc::new(peer),
+		reason_phrase: reason_phrase.filter(|_| !opaque_redirect),
+		canonical_status_text: agent.canonical_status_text,
+		redirected,
+		resource_timing,
+		spill: agent.spill.clone(),
+		spool: Default::default(),
+		stats: agent.stats.clone(),
+		status_code,
+		timings: Arc::new(Timings::new(request_start, connect, empty)),
+		trailers: Default::default(),
+		url: response_url,
+		version,
+	};
+	if empty && let Some(resource_timing) = &response.resource_timing {
+		resource_timing.finish(&response.timings);
+	}
+
+	if throw_on_error && !opaque_redirect && !status_code.is_success() {
+		return Err(response
+			.into_status_error(agent.profile == Some(AgentProfile::JsonApi))
+			.await);
+	}
+
+	Ok(response)
+}
+
+pub(crate) type BodyInit = Either3<String, Buffer, Uint8Array>;
+
+#[napi]
+impl Agent {
+	/// Fetches the URL and parses the response body as JSON, in one go.
+	///
+	/// This is equivalent to `fetch(url, { agent, ...options }).then(res => res.json())`, but without
+	/// the intermediate response object crossing into JavaScript. The request is sent with an
+	/// `Accept: application/json` header unless one is given in `options.headers`.
+	///
+	/// Like `fetch()`, this does not reject on HTTP error statuses: the body is parsed regardless.
+	#[napi]
+	pub fn json(
+		&self,
+		env: Env,
+		url: String,
+		options: Option<FaithOptionsAndBody>,
+		signal: Option<AbortSignal>,
+	) -> Async<Value> {
+		let (mut options, _, body) = FaithOptions::extract(options.unwrap_or_default());
+		options.resource_timing = MarkResourceTiming::for_thread(&env);
+		let headers = options.headers.get_or_insert_default();
+		if !headers
+			.iter()
+			.any(|(name, _)| name.eq_ignore_ascii_case("accept"))
+		{
+			headers.push(("accept".into(), Some("application/json".into())));
+		}
+
+		let agent = self.clone();
+		let abort = abort_receiver(signal.as_ref());
+
+		FaithAsyncResult::run(async move || {
+			let response = fetch(url, options, agent, body, None, abort).await?;
+			response.parse_json().await
 		})
-	})
+	}
 }
@@ -0,0 +1,278 @@
+use reqwest::{
+	Url,
+	header::{CONTENT_DISPOSITION, HeaderMap},
+};
+
+/// The longest filename most filesystems allow, in bytes.
+const MAX_LENGTH: usize = 255;
+
+/// Names which Windows reserves for devices, whatever their extension.
+const RESERVED_NAMES: &[&str] = &[
+	"CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+	"COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Returns a safe filename for a response's body, for `response.suggestedFilename()`.
+///
+/// This is the `filename*` or `filename` parameter of the `Content-Disposition` header, or else the
+/// last segment of the URL's path, made safe by `sanitize()`.
+pub(crate) fn suggested_filename(headers: &HeaderMap, url: &Url) -> Option<String> {
+	headers
+		.get(CONTENT_DISPOSITION)
+		.and_then(|value| disposition_filename(&header_text(value.as_bytes())))
+		.and_then(|name| sanitize(&name))
+		.or_else(|| {
+			url.path_segments()?
+				.next_back()
+				.map(|segment| String::from_utf8_lossy(&percent_decode(segment)).into_owned())
+				.and_then(|name| sanitize(&name))
+		})
+}
+
+/// Header values are nominally ASCII, but filenames are often sent as raw UTF-8 or Latin-1.
+fn header_text(bytes: &[u8]) -> String {
+	match std::str::from_utf8(bytes) {
+		Ok(text) => text.to_owned(),
+		Err(_) => latin1(bytes),
+	}
+}
+
+fn latin1(bytes: &[u8]) -> String {
+	bytes.iter().map(|&byte| char::from(byte)).collect()
+}
+
+/// Returns the filename of a `Content-Disposition` header value (RFC 6266), preferring the RFC 8187
+/// (formerly RFC 5987) encoded `filename*` parameter over `filename`.
+fn disposition_filename(value: &str) -> Option<String> {
+	let mut filename = None;
+	let mut extended = None;
+
+	// the first part is the disposition type, which doesn't matter here
+	for param in split_params(value).into_iter().skip(1) {
+		let Some((name, value)) = param.split_once('=') else {
+			continue;
+		};
+		let value = value.trim();
+		match name.trim().to_ascii_lowercase().as_str() {
+			"filename" if filename.is_none() => filename = Some(unquote(value)),
+			"filename*" if extended.is_none() => extended = decode_extended(value),
+			_ => {}
+		}
+	}
+
+	extended.or(filename)
+}
+
+/// Splits a header value on the semicolons which aren't in quoted strings.
+fn split_params(value: &str) -> Vec<&str> {
+	let mut params = Vec::new();
+	let mut quoted = false;
+	let mut escaped = false;
+	let mut start = 0;
+	for (i, c) in value.char_indices() {
+		match c {
+			_ if escaped => escaped = false,
+			'\\' if quoted => escaped = true,
+			'"' => quoted = !quoted,
+			';' if !quoted => {
+				params.push(&value[start..i]);
+				start = i + 1;
+			}
+			_ => {}
+		}
+	}
+	params.push(&value[start..]);
+	params
+}
+
+fn unquote(value: &str) -> String {
+	let Some(inner) = value
+		.strip_prefix('"')
+		.map(|value| value.strip_suffix('"').unwrap_or(value))
+	else {
+		return value.to_owned();
+	};
+
+	let mut unquoted = String::with_capacity(inner.len());
+	let mut chars = inner.chars();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' => unquoted.extend(chars.next()),
+			c => unquoted.push(c),
+		}
+	}
+	unquoted
+}
+
+/// Decodes an RFC 8187 `charset'language'value` extended value. Only UTF-8 and ISO-8859-1 are
+/// supported, as the RFC requires.
+fn decode_extended(value: &str) -> Option<String> {
+	let value = unquote(value);
+	let mut parts = value.splitn(3, '\'');
+	let charset = parts.next()?;
+	let _language = parts.next()?;
+	let bytes = percent_decode(parts.next()?);
+	if charset.eq_ignore_ascii_case("utf-8") {
+		String::from_utf8(bytes).ok()
+	} else if charset.eq_ignore_ascii_case("iso-8859-1") {
+		Some(latin1(&bytes))
+	} else {
+		None
+	}
+}
+
+fn percent_decode(value: &str) -> Vec<u8> {
+	let bytes = value.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		let escaped = value
+			.get(i + 1..i + 3)
+			.filter(|_| bytes[i] == b'%')
+			.and_then(|hex| u8::from_str_radix(hex, 16).ok());
+		match escaped {
+			Some(byte) => {
+				decoded.push(byte);
+				i += 3;
+			}
+			None => {
+				decoded.push(bytes[i]);
+				i += 1;
+			}
+		}
+	}
+	decoded
+}
+
+/// Makes a filename safe to use on any common filesystem, or returns `None` if nothing is left.
+///
+/// - Only the last component of a path is kept, so the name can't point to another directory.
+/// - Control characters are removed, and characters Windows doesn't allow are replaced with `_`.
+/// - Leading dots, and trailing dots and spaces, are removed, so the name isn't hidden, `.` or
+///   `..`, and Windows doesn't change it.
+/// - Names Windows reserves for devices, like `CON` or `nul.txt`, are prefixed with `_`.
+/// - Long names are shortened to 255 bytes, keeping their extension.
+fn sanitize(name: &str) -> Option<String> {
+	let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+	let name: String = name
+		.chars()
+		.filter(|c| !c.is_control())
+		.map(|c| match c {
+			'<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+			c => c,
+		})
+		.collect();
+	let name = name
+		.trim_start_matches(|c: char| c == '.' || c.is_whitespace())
+		.trim_end_matches(|c: char| c == '.' || c.is_whitespace());
+	if name.is_empty() {
+		return None;
+	}
+
+	let stem = name.split('.').next().unwrap_or(name).trim_end();
+	let mut name = if RESERVED_NAMES
+		.iter()
+		.any(|reserved| stem.eq_ignore_ascii_case(reserved))
+	{
+		format!("_{name}")
+	} else {
+		name.to_owned()
+	};
+
+	if name.len() > MAX_LENGTH {
+		let extension = name
+			.rfind('.')
+			.map(|dot| name[dot..].to_owned())
+			.filter(|extension| extension.len() <= 16)
+			.unwrap_or_default();
+		let mut end = MAX_LENGTH - extension.len();
+		while !name.is_char_boundary(end) {
+			end -= 1;
+		}
+		name.truncate(end);
+		name.push_str(&extension);
+	}
+
+	Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn disposition_parameters() {
+		assert_eq!(
+			disposition_filename("attachment; filename=report.pdf").as_deref(),
+			Some("report.pdf")
+		);
+		assert_eq!(
+			disposition_filename(r#"attachment; filename="a \"quoted\"; name.txt""#).as_deref(),
+			Some(r#"a "quoted"; name.txt"#)
+		);
+		assert_eq!(
+			disposition_filename(
+				"attachment; filename=\"fallback.txt\"; filename*=UTF-8''na%C3%AFve%20file.txt"
+			)
+			.as_deref(),
+			Some("naïve file.txt")
+		);
+		assert_eq!(
+			disposition_filename("attachment; FILENAME*=iso-8859-1'en'%A3%20rates.txt").as_deref(),
+			Some("£ rates.txt")
+		);
+		assert_eq!(
+			disposition_filename("attachment; filename*=KOI8-R''%F0; filename=plain.txt")
+				.as_deref(),
+			Some("plain.txt")
+		);
+		assert_eq!(disposition_filename("inline"), None);
+	}
+
+	#[test]
+	fn sanitizing() {
+		assert_eq!(sanitize("../../etc/passwd").as_deref(), Some("passwd"));
+		assert_eq!(
+			sanitize(r"C:\Windows\evil.exe").as_deref(),
+			Some("evil.exe")
+		);
+		assert_eq!(
+			sanitize("what?<is>:this*.txt").as_deref(),
+			Some("what__is__this_.txt")
+		);
+		assert_eq!(
+			sanitize("line\nbreak\u{7}.txt").as_deref(),
+			Some("linebreak.txt")
+		);
+		assert_eq!(sanitize(".hidden").as_deref(), Some("hidden"));
+		assert_eq!(sanitize("trailing. . ").as_deref(), Some("trailing"));
+		assert_eq!(sanitize("nul.txt").as_deref(), Some("_nul.txt"));
+		assert_eq!(sanitize("console.log").as_deref(), Some("console.log"));
+		assert_eq!(sanitize(".."), None);
+		assert_eq!(sanitize("dir/"), None);
+
+		let long = format!("{}.tar.gz", "é".repeat(200));
+		let short = sanitize(&long).unwrap();
+		assert!(short.len() <= MAX_LENGTH);
+		assert!(short.ends_with("é.gz"));
+	}
+
+	#[test]
+	fn url_fallback() {
+		let url = Url::parse("https://example.com/files/my%20report.pdf?v=1").unwrap();
+		assert_eq!(
+			suggested_filename(&HeaderMap::new(), &url).as_deref(),
+			Some("my report.pdf")
+		);
+
+		let mut headers = HeaderMap::new();
+		headers.insert(CONTENT_DISPOSITION, "attachment".parse().unwrap());
+		assert_eq!(
+			suggested_filename(&headers, &url).as_deref(),
+			Some("my report.pdf")
+		);
+
+		let root = Url::parse("https://example.com/").unwrap();
+		assert_eq!(suggested_filename(&HeaderMap::new(), &root), None);
+	}
+}
@@ -0,0 +1,267 @@
+use napi::bindgen_prelude::AbortSignal;
+use napi_derive::napi;
+use reqwest::header::CONTENT_TYPE;
+use serde_json::{Map, Value as JsonValue, json};
+use ssri::{Algorithm, IntegrityOpts};
+use tokio::sync::mpsc;
+
+use crate::{
+	agent::{Agent, AgentProfile},
+	async_task::{Async, FaithAsyncResult, Value},
+	error::{ErrorResponse, FaithError, FaithErrorKind, GraphqlErrors},
+	fetch::{abort_receiver, fetch},
+	options::{FaithOptions, FaithOptionsAndBody},
+};
+
+/// The error code servers send when they don't know a persisted query's hash.
+const PERSISTED_QUERY_NOT_FOUND: &str = "PERSISTED_QUERY_NOT_FOUND";
+
+/// A GraphQL operation for `agent.graphql()`.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct GraphqlRequest {
+	/// The GraphQL document. Can be omitted with `persisted` if `sha256Hash` is given and the server
+	/// is known to have the query already.
+	pub query: Option<String>,
+	pub variables: Option<serde_json::Value>,
+	pub operation_name: Option<String>,
+	/// Extra `extensions` to send in the envelope. With `persisted`, the `persistedQuery` extension
+	/// is added to these.
+	pub extensions: Option<serde_json::Value>,
+	/// Use automatic persisted queries: the query's hash is sent first, without the query, and the
+	/// request is retried with the full query only if the server doesn't know the hash yet.
+	///
+	/// Default: false.
+	pub persisted: Option<bool>,
+	/// The hex-encoded SHA-256 hash of the query, for `persisted`. Computed from `query` if not given.
+	pub sha256_hash: Option<String>,
+}
+
+impl GraphqlRequest {
+	/// Builds the JSON envelope. With `send_query` false, the query is left out, for the first
+	/// attempt of a persisted query.
+	fn envelope(&self, hash: Option<&str>, send_query: bool) -> Result<JsonValue, FaithError> {
+		let mut envelope = Map::new();
+		if let Some(query) = self.query.as_ref().filter(|_| send_query) {
+			envelope.insert("query".into(), query.clone().into());
+		}
+		if let Some(variables) = &self.variables {
+			envelope.insert("variables".into(), variables.clone());
+		}
+		if let Some(operation_name) = &self.operation_name {
+			envelope.insert("operationName".into(), operation_name.clone().into());
+		}
+
+		let mut extensions = match &self.extensions {
+			None | Some(JsonValue::Null) => Map::new(),
+			Some(JsonValue::Object(extensions)) => extensions.clone(),
+			Some(_) => {
+				return Err(FaithError::new(
+					FaithErrorKind::Config,
+					Some("graphql extensions must be an object"),
+				));
+			}
+		};
+		if let Some(hash) = hash {
+			extensions.insert(
+				"persistedQuery".into(),
+				json!({ "version": 1, "sha256Hash": hash }),
+			);
+		}
+		if !extensions.is_empty() {
+			envelope.insert("extensions".into(), extensions.into());
+		}
+
+		Ok(envelope.into())
+	}
+}
+
+/// The hex-encoded SHA-256 hash of a query, as used by automatic persisted queries.
+fn query_hash(query: &str) -> String {
+	IntegrityOpts::new()
+		.algorithm(Algorithm::Sha256)
+		.chain(query)
+		.result()
+		.to_hex()
+		.1
+}
+
+/// Sends a GraphQL envelope and parses the result. Responses with a non-empty `errors` array are
+/// turned into `Graphql` errors; error statuses without a GraphQL result are `HttpStatus` errors.
+async fn execute(
+	url: String,
+	mut options: FaithOptions,
+	agent: Agent,
+	envelope: JsonValue,
+	abort: Option<mpsc::Receiver<()>>,
+) -> Result<Map<String, JsonValue>, FaithError> {
+	options.json = Some(envelope);
+	let json = agent.profile == Some(AgentProfile::JsonApi);
+	let response = fetch(url, options, agent, None, None, abort).await?;
+
+	let status = response.status_code;
+	let is_json = response
+		.headers
+		.get(CONTENT_TYPE)
+		.and_then(|value| value.to_str().ok())
+		.is_some_and(|value| value.contains("json"));
+	if !status.is_success() && !is_json {
+		return Err(response.into_status_error(json).await);
+	}
+
+	let mut result = match response.parse_json().await?.0 {
+		JsonValue::Object(result) => result,
+		_ => {
+			return Err(FaithError::new(
+				FaithErrorKind::JsonParse,
+				Some("the GraphQL response is not an object"),
+			));
+		}
+	};
+
+	match result.remove("errors") {
+		Some(JsonValue::Array(errors)) if !errors.is_empty() => {
+			let message = errors
+				.first()
+				.and_then(|error| error.get("message"))
+				.and_then(JsonValue::as_str)
+				.map(|message| match errors.len() {
+					1 => message.to_string(),
+					n => format!("{message} (and {} more errors)", n - 1),
+				});
+			Err(
+				FaithError::new(FaithErrorKind::Graphql, message).with_graphql(GraphqlErrors {
+					errors: JsonValue::Array(errors),
+					data: result.remove("data"),
+				}),
+			)
+		}
+		_ if !status.is_success() => Err(FaithError::new(
+			FaithErrorKind::HttpStatus,
+			Some(format!(
+				"request failed with status {} {}",
+				status.as_u16(),
+				response.status_text()
+			)),
+		)
+		.with_response(ErrorResponse {
+			status: status.as_u16(),
+			status_text: response.status_text(),
+			headers: response.headers.clone(),
+			body_preview: None,
+			body: Some(result.into()),
+		})),
+		_ => Ok(result),
+	}
+}
+
+/// Whether a `Graphql` error is the server not knowing a persisted query.
+fn is_persisted_query_not_found(err: &FaithError) -> bool {
+	let Some(graphql) = &err.graphql else {
+		return false;
+	};
+	graphql
+		.errors
+		.as_array()
+		.into_iter()
+		.flatten()
+		.any(|error| {
+			error
+				.pointer("/extensions/code")
+				.and_then(JsonValue::as_str)
+				== Some(PERSISTED_QUERY_NOT_FOUND)
+				|| error.get("message").and_then(JsonValue::as_str)
+					== Some("PersistedQueryNotFound")
+		})
+}
+
+#[napi]
+impl Agent {
+	/// Custom to Fáith.
+	///
+	/// Sends a GraphQL operation with this agent, as a `POST` of the standard JSON envelope, and
+	/// resolves to the parsed result, with its `data` and any `extensions`.
+	///
+	/// This takes the same options as `fetch()`, except that the method is always `POST`, and the
+	/// `body` and `json` options are replaced by the envelope. The request is sent with an
+	/// `Accept: application/graphql-response+json, application/json` header unless one is given in
+	/// `options.headers`.
+	///
+	/// Rejects with a `Graphql` error when the result has a non-empty `errors` array, with the
+	/// `errors` and any partial `data` as properties of the error. Error statuses which don't come
+	/// with a GraphQL result reject with an `HttpStatus` error.
+	///
+	/// With `persisted`, the query is first sent as only its hash, and again in full if the server
+	/// answers with a `PERSISTED_QUERY_NOT_FOUND` error.
+	#[napi]
+	pub fn graphql(
+		&self,
+		url: String,
+		request: GraphqlRequest,
+		options: Option<FaithOptionsAndBody>,
+		signal: Option<AbortSignal>,
+	) -> Async<Value> {
+		let (mut options, _, _) = FaithOptions::extract(options.unwrap_or_default());
+		options.method = Some("POST".into());
+		// error statuses are handled here, as they may come with GraphQL errors
+		options.throw_on_error = Some(false);
+		let headers = options.headers.get_or_insert_default();
+		if !headers
+			.iter()
+			.any(|(name, _)| name.eq_ignore_ascii_case("accept"))
+		{
+			headers.push((
+				"accept".into(),
+				Some("application/graphql-response+json, application/json".into()),
+			));
+		}
+
+		let agent = self.clone();
+		let persisted = request.persisted.unwrap_or(false);
+		let abort = abort_receiver(signal.as_ref());
+		// the full query might need to be sent again, which needs its own abort receiver
+		let retry_abort = persisted.then(|| abort_receiver(signal.as_ref()));
+
+		let correlation_id = options.correlation_id.clone();
+		FaithAsyncResult::run(async move || {
+			async {
+				let hash = match (persisted, &request.sha256_hash, &request.query) {
+					(false, _, _) => None,
+					(true, Some(hash), _) => Some(hash.clone()),
+					(true, None, Some(query)) => Some(query_hash(query)),
+					(true, None, None) => {
+						return Err(FaithError::new(
+							FaithErrorKind::Config,
+							Some("persisted graphql requests need a query or a sha256Hash"),
+						));
+					}
+				};
+				if !persisted && request.query.is_none() {
+					return Err(FaithError::new(
+						FaithErrorKind::Config,
+						Some("graphql requests need a query"),
+					));
+				}
+
+				let envelope = request.envelope(hash.as_deref(), !persisted)?;
+				let result =
+					match execute(url.clone(), options.clone(), agent.clone(), envelope, abort)
+						.await
+					{
+						Err(err)
+							if persisted
+								&& request.query.is_some()
+								&& is_persisted_query_not_found(&err) =>
+						{
+							let envelope = request.envelope(hash.as_deref(), true)?;
+							execute(url, options, agent, envelope, retry_abort.flatten()).await?
+						}
+						result => result?,
+					};
+				Ok::<_, FaithError>(Value(result.into()))
+			}
+			.await
+			.map_err(|err| err.with_correlation_id(correlation_id))
+		})
+	}
+}
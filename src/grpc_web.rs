@@ -0,0 +1,216 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use napi::bindgen_prelude::{AbortSignal, Buffer};
+use napi_derive::napi;
+use reqwest::header::{CONTENT_TYPE, HeaderMap};
+
+use crate::{
+	agent::Agent,
+	async_task::{Async, FaithAsyncResult},
+	error::{FaithError, FaithErrorKind},
+	fetch::{abort_receiver, fetch},
+	options::{FaithOptions, FaithOptionsAndBody},
+};
+
+const CONTENT_TYPE_GRPC_WEB: &str = "application/grpc-web+proto";
+
+/// Set on trailer frames, which carry the status instead of a message.
+const TRAILER_FLAG: u8 = 0x80;
+
+/// Set on compressed message frames.
+const COMPRESSED_FLAG: u8 = 0x01;
+
+/// The outcome of a gRPC-web unary call.
+#[napi(object)]
+pub struct GrpcWebResponse {
+	/// The gRPC status code, from the `grpc-status` trailer: `0` is `OK`.
+	pub status: u32,
+	/// The `grpc-message` trailer, percent-decoded, if any.
+	pub status_message: Option<String>,
+	/// The serialised response message, or `null` if the server sent none, as with error statuses.
+	pub message: Option<Buffer>,
+	/// The response headers, which carry the call's initial metadata.
+	pub headers: Vec<(String, String)>,
+	/// The trailers, which carry the call's trailing metadata, with lowercase names.
+	pub trailers: Vec<(String, String)>,
+}
+
+/// Frames a message for the request body: an uncompressed message frame.
+fn frame(message: &[u8]) -> Bytes {
+	let mut body = BytesMut::with_capacity(5 + message.len());
+	body.put_u8(0);
+	body.put_u32(message.len() as u32);
+	body.put_slice(message);
+	body.freeze()
+}
+
+fn invalid(message: &'static str) -> FaithError {
+	FaithError::new(FaithErrorKind::GrpcWeb, Some(message))
+}
+
+/// Splits a response body into its message, if any, and its trailers.
+fn unframe(mut body: &[u8]) -> Result<(Option<Vec<u8>>, Vec<(String, String)>), FaithError> {
+	let mut message = None;
+	let mut trailers = Vec::new();
+	while !body.is_empty() {
+		let [flags, a, b, c, d, ..] = *body else {
+			return Err(invalid("truncated gRPC-web frame header"));
+		};
+		let length = u32::from_be_bytes([a, b, c, d]) as usize;
+		let Some(payload) = body.get(5..5 + length) else {
+			return Err(invalid("truncated gRPC-web frame"));
+		};
+		body = &body[5 + length..];
+
+		if flags & TRAILER_FLAG != 0 {
+			trailers.extend(parse_trailers(payload));
+		} else if flags & COMPRESSED_FLAG != 0 {
+			return Err(invalid("compressed gRPC-web messages are not supported"));
+		} else if message.is_some() {
+			return Err(invalid("unary gRPC-web response has more than one message"));
+		} else {
+			message = Some(payload.to_vec());
+		}
+	}
+	Ok((message, trailers))
+}
+
+/// Parses a trailer frame, which is encoded like an HTTP/1 header block.
+fn parse_trailers(block: &[u8]) -> impl Iterator<Item = (String, String)> + '_ {
+	block.split(|&byte| byte == b'\n').filter_map(|line| {
+		let line = String::from_utf8_lossy(line);
+		let (name, value) = line.split_once(':')?;
+		Some((name.trim().to_ascii_lowercase(), value.trim().to_string()))
+	})
+}
+
+/// Decodes a `grpc-message`, which is percent-encoded.
+fn percent_decode(value: &str) -> String {
+	let bytes = value.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		let escaped = value
+			.get(i + 1..i + 3)
+			.filter(|_| bytes[i] == b'%')
+			.and_then(|hex| u8::from_str_radix(hex, 16).ok());
+		match escaped {
+			Some(byte) => {
+				decoded.push(byte);
+				i += 3;
+			}
+			None => {
+				decoded.push(bytes[i]);
+				i += 1;
+			}
+		}
+	}
+	String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Finds a status trailer, falling back to the headers for trailers-only responses.
+fn status_field<'a>(
+	trailers: &'a [(String, String)],
+	headers: &'a HeaderMap,
+	name: &str,
+) -> Option<&'a str> {
+	trailers
+		.iter()
+		.find(|(trailer, _)| trailer == name)
+		.map(|(_, value)| value.as_str())
+		.or_else(|| headers.get(name).and_then(|value| value.to_str().ok()))
+}
+
+#[napi]
+impl Agent {
+	/// Custom to Fáith.
+	///
+	/// Makes a gRPC-web unary call with this agent: `url` is the method's URL, like
+	/// `https://api.example.com/package.Service/Method`, and `message` is the serialised request
+	/// message, e.g. protobuf bytes. Resolves to the response message and the call's status and
+	/// metadata.
+	///
+	/// This takes the same options as `fetch()`, except that the method is always `POST` and the
+	/// body is the framed message. The `headers` are sent as the call's metadata, and the `timeout`
+	/// (or `timeouts.total`) is also sent to the server as the call's deadline.
+	///
+	/// Like `fetch()`, this doesn't reject on error statuses: check `status`, which is `0` for `OK`.
+	/// HTTP error statuses reject with an `HttpStatus` error, and malformed responses with a
+	/// `GrpcWeb` error. Compressed messages and the `grpc-web-text` encoding are not supported.
+	#[napi]
+	pub fn grpc_web(
+		&self,
+		url: String,
+		message: Buffer,
+		options: Option<FaithOptionsAndBody>,
+		signal: Option<AbortSignal>,
+	) -> Async<GrpcWebResponse> {
+		let (mut options, _, _) = FaithOptions::extract(options.unwrap_or_default());
+		options.method = Some("POST".into());
+		options.json = None;
+		// error statuses are in the trailers, which are read here
+		options.throw_on_error = Some(false);
+
+		let timeout = options.total_timeout().map(|(dur, _)| dur);
+		let headers = options.headers.get_or_insert_default();
+		headers.retain(|(name, _)| {
+			!name.eq_ignore_ascii_case("content-type") && !name.eq_ignore_ascii_case("x-grpc-web")
+		});
+		headers.push(("content-type".into(), Some(CONTENT_TYPE_GRPC_WEB.into())));
+		headers.push(("x-grpc-web".into(), Some("1".into())));
+		if !headers
+			.iter()
+			.any(|(name, _)| name.eq_ignore_ascii_case("accept"))
+		{
+			headers.push(("accept".into(), Some(CONTENT_TYPE_GRPC_WEB.into())));
+		}
+		if let Some(timeout) = timeout {
+			// the value has at most 8 digits
+			let value = match timeout.as_millis() {
+				millis @ ..100_000_000 => format!("{millis}m"),
+				_ => format!("{}S", timeout.as_secs()),
+			};
+			headers.push(("grpc-timeout".into(), Some(value)));
+		}
+
+		let body = frame(&message);
+		let agent = self.clone();
+		let abort = abort_receiver(signal.as_ref());
+
+		let correlation_id = options.correlation_id.clone();
+		FaithAsyncResult::run(async move || {
+			async {
+				let response = fetch(url, options, agent, Some(body), None, abort).await?;
+				if !response.status_code.is_success() {
+					return Err(response.into_status_error(false).await);
+				}
+
+				let is_grpc_web = response
+					.headers
+					.get(CONTENT_TYPE)
+					.and_then(|value| value.to_str().ok())
+					.is_some_and(|value| value.starts_with("application/grpc-web"));
+				if !is_grpc_web && !response.headers.contains_key("grpc-status") {
+					return Err(invalid("the response is not a gRPC-web response"));
+				}
+
+				let (message, trailers) = unframe(&response.read_bytes().await?)?;
+				let status = status_field(&trailers, &response.headers, "grpc-status")
+					.ok_or_else(|| invalid("the gRPC-web response has no grpc-status"))?
+					.parse()
+					.map_err(|_| invalid("invalid grpc-status in the gRPC-web response"))?;
+				let status_message =
+					status_field(&trailers, &response.headers, "grpc-message").map(percent_decode);
+
+				Ok::<_, FaithError>(GrpcWebResponse {
+					status,
+					status_message,
+					message: message.map(Into::into),
+					headers: response.headers(),
+					trailers,
+				})
+			}
+			.await
+			.map_err(|err| err.with_correlation_id(correlation_id))
+		})
+	}
+}
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use napi::{Either, Env};
+use napi_derive::napi;
+use reqwest::{
+	Method,
+	header::{HeaderMap, HeaderName, HeaderValue, SET_COOKIE},
+};
+
+use crate::error::{FaithError, FaithErrorKind};
+
+/// Custom to Fáith.
+///
+/// The `FaithHeaders` class implements the semantics of the Fetch API's `Headers` interface (header
+/// names are matched case-insensitively, values are trimmed, multiple values are combined with `, `
+/// except for `Set-Cookie`) on top of Fáith's native header storage.
+///
+/// It can be passed as `headers` to `fetch()`, and obtained from a response with `faithHeaders`.
+/// Unlike the Web API `Headers`, it has no guard: all headers may be set or removed. Forbidden request
+/// headers are instead dropped when the request is made, unless the agent is created with `unsafe`.
+#[napi]
+#[derive(Debug, Clone, Default)]
+pub struct FaithHeaders {
+	pub(crate) map: HeaderMap,
+}
+
+fn parse_name(name: &str) -> Result<HeaderName, FaithError> {
+	HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+		FaithError::new(
+			FaithErrorKind::InvalidHeader,
+			Some(format!("invalid header name: {name}")),
+		)
+	})
+}
+
+fn parse_value(value: &str) -> Result<HeaderValue, FaithError> {
+	// strip leading and trailing HTTP whitespace, as per the fetch spec's value normalisation
+	let value = value.trim_matches(|c| matches!(c, ' ' | '\t' | '\n' | '\r'));
+	HeaderValue::from_str(value).map_err(|_| {
+		FaithError::new(
+			FaithErrorKind::InvalidHeader,
+			Some(format!("invalid header value: {value}")),
+		)
+	})
+}
+
+/// Whether the method is forbidden by the Fetch spec: `CONNECT`, `TRACE`, and `TRACK`.
+pub(crate) fn is_forbidden_method(method: &str) -> bool {
+	["CONNECT", "TRACE", "TRACK"]
+		.iter()
+		.any(|forbidden| method.eq_ignore_ascii_case(forbidden))
+}
+
+/// Whether the header is a forbidden request header in the Fetch spec, which browsers don't let
+/// scripts set because they're controlled by the user agent.
+pub(crate) fn is_forbidden_request_header(name: &HeaderName, value: &str) -> bool {
+	const FORBIDDEN: &[&str] = &[
+		"accept-charset",
+		"accept-encoding",
+		"access-control-request-headers",
+		"access-control-request-method",
+		"connection",
+		"content-length",
+		"cookie",
+		"cookie2",
+		"date",
+		"dnt",
+		"expect",
+		"host",
+		"keep-alive",
+		"origin",
+		"referer",
+		"set-cookie",
+		"te",
+		"trailer",
+		"transfer-encoding",
+		"upgrade",
+		"via",
+	];
+
+	let name = name.as_str();
+	if FORBIDDEN.contains(&name) || name.starts_with("proxy-") || name.starts_with("sec-") {
+		return true;
+	}
+
+	// method override headers are forbidden only when they'd smuggle a forbidden method
+	matches!(
+		name,
+		"x-http-method" | "x-http-method-override" | "x-method-override"
+	) && value
+		.split(',')
+		.any(|method| is_forbidden_method(method.trim()))
+}
+
+fn value_to_string(value: &HeaderValue) -> String {
+	String::from_utf8_lossy(value.as_bytes()).into_owned()
+}
+
+impl FaithHeaders {
+	pub(crate) fn from_map(map: HeaderMap) -> Self {
+		Self { map }
+	}
+
+	pub(crate) fn try_append(&mut self, name: &str, value: &str) -> Result<(), FaithError> {
+		self.map.append(parse_name(name)?, parse_value(value)?);
+		Ok(())
+	}
+
+	pub(crate) fn try_set(&mut self, name: &str, value: &str) -> Result<(), FaithError> {
+		self.map.insert(parse_name(name)?, parse_value(value)?);
+		Ok(())
+	}
+
+	fn combined(&self, name: &HeaderName) -> Option<String> {
+		let mut values = self.map.get_all(name).iter().peekable();
+		values.peek()?;
+		Some(values.map(value_to_string).collect::<Vec<_>>().join(", "))
+	}
+}
+
+#[napi]
+impl FaithHeaders {
+	/// Creates a new `FaithHeaders` object, optionally from an array of `[name, value]` pairs or an
+	/// object literal of names to values.
+	///
+	/// Throws a `TypeError` if any of the names or values are invalid.
+	#[napi(constructor)]
+	pub fn construct(
+		env: Env,
+		init: Option<Either<Vec<(String, String)>, HashMap<String, String>>>,
+	) -> Result<Self, napi::Error> {
+		let mut headers = Self::default();
+		let pairs = match init {
+			None => Vec::new(),
+			Some(Either::A(pairs)) => pairs,
+			Some(Either::B(object)) => object.into_iter().collect(),
+		};
+
+		for (name, value) in pairs {
+			headers
+				.try_append(&name, &value)
+				.map_err(|err| napi::Error::from(err.into_js_error(&env)))?;
+		}
+
+		Ok(headers)
+	}
+
+	/// Appends a new value onto an existing header, or adds the header if it does not already exist.
+	#[napi]
+	pub fn append(&mut self, env: Env, name: String, value: String) -> Result<(), napi::Error> {
+		self.try_append(&name, &value)
+			.map_err(|err| napi::Error::from(err.into_js_error(&env)))
+	}
+
+	/// Sets a new value for an existing header, or adds the header if it does not already exist.
+	/// Unlike `append()`, this replaces all existing values.
+	#[napi]
+	pub fn set(&mut self, env: Env, name: String, value: String) -> Result<(), napi::Error> {
+		self.try_set(&name, &value)
+			.map_err(|err| napi::Error::from(err.into_js_error(&env)))
+	}
+
+	/// Deletes a header. Does nothing if the header doesn't exist or the name is invalid.
+	#[napi]
+	pub fn delete(&mut self, name: String) {
+		if let Ok(name) = parse_name(&name) {
+			self.map.remove(name);
+		}
+	}
+
+	/// Returns all the values of a header, combined with `, `, or `null` if the header doesn't exist.
+	#[napi]
+	pub fn get(&self, name: String) -> Option<String> {
+		parse_name(&name).ok().and_then(|name| self.combined(&name))
+	}
+
+	/// Returns all the values of the `Set-Cookie` header, without combining them.
+	#[napi]
+	pub fn get_set_cookie(&self) -> Vec<String> {
+		self.map
+			.get_all(SET_COOKIE)
+			.iter()
+			.map(value_to_string)
+			.collect()
+	}
+
+	/// Returns whether the header exists.
+	#[napi]
+	pub fn has(&self, name: String) -> bool {
+		parse_name(&name).is_ok_and(|name| self.map.contains_key(name))
+	}
+
+	/// Returns all `[name, value]` pairs, sorted by lowercased name, with values of the same header
+	/// combined (except for `Set-Cookie`, which is returned as one pair per value).
+	#[napi]
+	pub fn entries(&self) -> Vec<(String, String)> {
+		let mut names: Vec<&HeaderName> = self.map.keys().collect();
+		names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+		names
+			.into_iter()
+			.flat_map(|name| {
+				if name == SET_COOKIE {
+					self.get_set_cookie()
+						.into_iter()
+						.map(|value| (name.to_string(), value))
+						.collect::<Vec<_>>()
+				} else {
+					self.combined(name)
+						.map(|value| (name.to_string(), value))
+						.into_iter()
+						.collect()
+				}
+			})
+			.collect()
+	}
+
+	/// Returns all header names, as in `entries()`.
+	#[napi]
+	pub fn keys(&self) -> Vec<String> {
+		self.entries().into_iter().map(|(name, _)| name).collect()
+	}
+
+	/// Returns all header values, as in `entries()`.
+	#[napi]
+	pub fn values(&self) -> Vec<String> {
+		self.entries().into_iter().map(|(_, value)| value).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_case_insensitive() {
+		let mut headers = FaithHeaders::default();
+		headers.try_append("Content-Type", "text/plain").unwrap();
+		assert!(headers.has("content-type".into()));
+		assert_eq!(
+			headers.get("CONTENT-TYPE".into()),
+			Some("text/plain".into())
+		);
+	}
+
+	#[test]
+	fn test_combining() {
+		let mut headers = FaithHeaders::default();
+		headers.try_append("Accept", "text/html").unwrap();
+		headers.try_append("accept", "application/json").unwrap();
+		assert_eq!(
+			headers.get("accept".into()),
+			Some("text/html, application/json".into())
+		);
+	}
+
+	#[test]
+	fn test_set_cookie_not_combined() {
+		let mut headers = FaithHeaders::default();
+		headers.try_append("Set-Cookie", "a=1").unwrap();
+		headers.try_append("Set-Cookie", "b=2").unwrap();
+		assert_eq!(headers.get_set_cookie(), vec!["a=1", "b=2"]);
+		assert_eq!(
+			headers.entries(),
+			vec![
+				("set-cookie".to_string(), "a=1".to_string()),
+				("set-cookie".to_string(), "b=2".to_string()),
+			]
+		);
+	}
+
+	#[test]
+	fn test_entries_sorted() {
+		let mut headers = FaithHeaders::default();
+		headers.try_append("X-B", "2").unwrap();
+		headers.try_append("x-a", "1").unwrap();
+		headers.try_append("X-B", "3").unwrap();
+		assert_eq!(
+			headers.entries(),
+			vec![
+				("x-a".to_string(), "1".to_string()),
+				("x-b".to_string(), "2, 3".to_string()),
+			]
+		);
+	}
+
+	#[test]
+	fn test_value_trimmed() {
+		let mut headers = FaithHeaders::default();
+		headers.try_set("X-Trim", "  value\t").unwrap();
+		assert_eq!(headers.get("x-trim".into()), Some("value".into()));
+	}
+
+	#[test]
+	fn test_set_replaces() {
+		let mut headers = FaithHeaders::default();
+		headers.try_append("X-Set", "1").unwrap();
+		headers.try_append("X-Set", "2").unwrap();
+		headers.try_set("X-Set", "3").unwrap();
+		assert_eq!(headers.get("x-set".into()), Some("3".into()));
+	}
+
+	#[test]
+	fn test_invalid() {
+		let mut headers = FaithHeaders::default();
+		assert!(matches!(
+			headers
+				.try_append("Invalid Name", "value")
+				.unwrap_err()
+				.kind,
+			FaithErrorKind::InvalidHeader
+		));
+		assert!(matches!(
+			headers
+				.try_append("X-Valid", "bad\nvalue")
+				.unwrap_err()
+				.kind,
+			FaithErrorKind::InvalidHeader
+		));
+		assert!(!headers.has("Invalid Name".into()));
+	}
+}
@@ -0,0 +1,65 @@
+use std::{cell::RefCell, future::Future};
+
+use napi_derive::napi;
+use reqwest::{StatusCode, Url, redirect::Attempt};
+
+/// A redirect followed on the way to a response, in `response.history`.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct RedirectSnapshot {
+	/// The URL which answered with the redirect.
+	pub url: String,
+	/// The redirect's status code, like `301` or `302`.
+	pub status: u16,
+	/// Where the redirect pointed, resolved against `url`. This is the URL of the next entry, or of
+	/// the response itself for the last entry.
+	pub location: String,
+}
+
+tokio::task_local! {
+	static HISTORY: RefCell<Vec<RedirectSnapshot>>;
+}
+
+/// Runs a request, collecting the redirects which are followed while it's running.
+///
+/// reqwest follows redirects within the request's future, so the redirect policy runs in the same
+/// task and can record each redirect it follows with `record()`.
+pub(crate) async fn collect<F: Future>(request: F) -> (F::Output, Vec<RedirectSnapshot>) {
+	HISTORY
+		.scope(RefCell::default(), async {
+			let output = request.await;
+			(output, HISTORY.with(RefCell::take))
+		})
+		.await
+}
+
+/// Records a redirect which the redirect policy is following, if the request is being collected.
+pub(crate) fn record(attempt: &Attempt) {
+	let Some(url) = attempt.previous().last() else {
+		return;
+	};
+	// a retried request (e.g. over HTTP/2 after HTTP/3 failed) starts its redirect chain over
+	push(
+		url,
+		attempt.status(),
+		attempt.url(),
+		attempt.previous().len() == 1,
+	);
+}
+
+/// Records a redirect from `url` to `location`, if the request is being collected. `first` starts
+/// the history over.
+pub(crate) fn push(url: &Url, status: StatusCode, location: &Url, first: bool) {
+	let snapshot = RedirectSnapshot {
+		url: url.to_string(),
+		status: status.as_u16(),
+		location: location.to_string(),
+	};
+	let _ = HISTORY.try_with(|history| {
+		let mut history = history.borrow_mut();
+		if first {
+			history.clear();
+		}
+		history.push(snapshot);
+	});
+}
@@ -0,0 +1,108 @@
+use reqwest::Url;
+
+use crate::{
+	agent::AgentOptions,
+	error::{FaithError, FaithErrorKind},
+};
+
+/// Restricts the hosts an agent may connect to, for `allowedHosts` and `blockedHosts`.
+#[derive(Debug, Clone)]
+pub(crate) struct HostFilter {
+	allowed: Option<Vec<String>>,
+	blocked: Vec<String>,
+}
+
+impl HostFilter {
+	/// Returns `None` if neither list is set.
+	pub(crate) fn new(options: &AgentOptions) -> Option<Self> {
+		if options.allowed_hosts.is_none() && options.blocked_hosts.is_none() {
+			return None;
+		}
+
+		let normalise = |patterns: &Vec<String>| {
+			patterns
+				.iter()
+				.map(|pattern| pattern.trim_end_matches('.').to_ascii_lowercase())
+				.collect::<Vec<_>>()
+		};
+		Some(Self {
+			allowed: options.allowed_hosts.as_ref().map(normalise),
+			blocked: options
+				.blocked_hosts
+				.as_ref()
+				.map(normalise)
+				.unwrap_or_default(),
+		})
+	}
+
+	/// Returns a `ForbiddenHost` error if the URL's host isn't allowed.
+	pub(crate) fn check(&self, url: &Url) -> Result<(), FaithError> {
+		let host = url.host_str().unwrap_or_default().trim_end_matches('.');
+		let message = if self.blocked.iter().any(|pattern| glob_match(pattern, host)) {
+			format!("{host} is in blockedHosts")
+		} else if self
+			.allowed
+			.as_ref()
+			.is_some_and(|allowed| !allowed.iter().any(|pattern| glob_match(pattern, host)))
+		{
+			format!("{host} is not in allowedHosts")
+		} else {
+			return Ok(());
+		};
+
+		Err(FaithError::new(
+			FaithErrorKind::ForbiddenHost,
+			Some(message),
+		))
+	}
+}
+
+/// Matches a host against a pattern where `*` stands for any run of characters, including none.
+fn glob_match(pattern: &str, host: &str) -> bool {
+	let (pattern, host) = (pattern.as_bytes(), host.as_bytes());
+	let (mut p, mut h) = (0, 0);
+	// where the last `*` was in the pattern, and where in the host it started matching
+	let mut backtrack = None;
+
+	while h < host.len() {
+		if p < pattern.len() && pattern[p] == b'*' {
+			backtrack = Some((p, h));
+			p += 1;
+		} else if p < pattern.len() && pattern[p].eq_ignore_ascii_case(&host[h]) {
+			p += 1;
+			h += 1;
+		} else if let Some((star, start)) = backtrack {
+			p = star + 1;
+			h = start + 1;
+			backtrack = Some((star, start + 1));
+		} else {
+			return false;
+		}
+	}
+
+	pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_glob_match_exact() {
+		assert!(glob_match("api.example.com", "api.example.com"));
+		assert!(glob_match("api.example.com", "API.Example.com"));
+		assert!(!glob_match("api.example.com", "example.com"));
+		assert!(!glob_match("api.example.com", "api.example.com.evil"));
+	}
+
+	#[test]
+	fn test_glob_match_wildcards() {
+		assert!(glob_match("*.example.com", "api.example.com"));
+		assert!(glob_match("*.example.com", "a.b.example.com"));
+		assert!(!glob_match("*.example.com", "example.com"));
+		assert!(!glob_match("*.example.com", "evilexample.com"));
+		assert!(glob_match("api-*.example.com", "api-eu.example.com"));
+		assert!(glob_match("*", "anything"));
+		assert!(glob_match("*example*", "example"));
+	}
+}
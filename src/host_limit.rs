@@ -0,0 +1,96 @@
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+};
+
+use futures::StreamExt;
+use http::Extensions;
+use http_body_util::{BodyStream, StreamBody};
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next, Result};
+use tokio::sync::Semaphore;
+
+/// Above this many tracked hosts, semaphores which aren't in use are dropped.
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Limits the amount of concurrent requests per origin, queueing the excess.
+///
+/// A permit is held from when the request is sent until its response body is dropped (after it has
+/// been consumed, discarded, or when the response is garbage collected), which is how long a HTTP/1.1
+/// connection is busy with the request. Responses served from the HTTP cache don't take a permit.
+pub struct HostLimitMiddleware {
+	max: usize,
+	hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl std::fmt::Debug for HostLimitMiddleware {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("HostLimitMiddleware")
+			.field("max", &self.max)
+			.finish()
+	}
+}
+
+impl HostLimitMiddleware {
+	pub fn new(max: u32) -> Self {
+		Self {
+			max: max.max(1) as usize,
+			hosts: Default::default(),
+		}
+	}
+
+	fn origin_key(url: &reqwest::Url) -> Option<String> {
+		let host = url.host_str()?;
+		let port = url.port_or_known_default()?;
+		Some(format!("{}://{}:{}", url.scheme(), host, port))
+	}
+
+	fn semaphore(&self, origin: String) -> Arc<Semaphore> {
+		let mut hosts = self.hosts.lock().unwrap_or_else(|err| err.into_inner());
+		if hosts.len() > PRUNE_THRESHOLD {
+			hosts.retain(|_, sem| Arc::strong_count(sem) > 1 || sem.available_permits() < self.max);
+		}
+
+		hosts
+			.entry(origin)
+			.or_insert_with(|| Arc::new(Semaphore::new(self.max)))
+			.clone()
+	}
+}
+
+#[async_trait::async_trait]
+impl Middleware for HostLimitMiddleware {
+	async fn handle(
+		&self,
+		req: Request,
+		extensions: &mut Extensions,
+		next: Next<'_>,
+	) -> Result<Response> {
+		let Some(origin) = Self::origin_key(req.url()) else {
+			return next.run(req, extensions).await;
+		};
+
+		let permit = self
+			.semaphore(origin)
+			.acquire_owned()
+			.await
+			.expect("host limit semaphore is never closed");
+
+		let response = next.run(req, extensions).await?;
+
+		// move the permit into the body, so it's released when the body is done with
+		let url = response.url().clone();
+		let (parts, body) = http::Response::from(response).into_parts();
+		let body = BodyStream::new(body).map(move |frame| {
+			let _ = &permit;
+			frame
+		});
+
+		let mut response = Response::from(http::Response::from_parts(
+			parts,
+			reqwest::Body::wrap(StreamBody::new(body)),
+		));
+		*response.url_mut() = url;
+		Ok(response)
+	}
+}
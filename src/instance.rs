@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use napi::{Env, bindgen_prelude::ObjectRef};
+
+use crate::{
+	agent::Agent,
+	error::{FaithError, FaithErrorKind},
+	runtime::Scope,
+};
+
+/// Fáith's state for one JS environment: the main thread, or a worker thread, each of which loads
+/// the addon separately. It's kept in the environment's instance data, which there is one of per
+/// addon, and is only accessed from that environment's thread.
+pub(crate) struct Instance {
+	/// The error classes defined by errors.js, so that errors can be constructed from them.
+	pub(crate) error_classes: ObjectRef,
+	/// The agent used by `fetch()` calls which don't specify one.
+	pub(crate) global_agent: Option<Agent>,
+	/// The environment's requests, for `faithShutdown()`.
+	pub(crate) scope: Arc<Scope>,
+}
+
+impl Instance {
+	/// Sets up the environment's instance data, when the addon is loaded into it, and shuts down its
+	/// requests when it exits.
+	pub(crate) fn init(env: &Env, error_classes: ObjectRef) -> napi::Result<()> {
+		let scope = Scope::new();
+		let mut env = *env;
+		env.add_env_cleanup_hook(scope.clone(), |scope| scope.close())?;
+
+		let instance = Self {
+			error_classes,
+			global_agent: None,
+			scope,
+		};
+		env.set_instance_data(instance, (), |ctx| {
+			let _ = ctx.value.error_classes.unref(&ctx.env);
+		})
+	}
+
+	/// Returns the environment's instance data.
+	pub(crate) fn get(env: &Env) -> Result<&'static mut Self, FaithError> {
+		env.get_instance_data::<Self>()
+			.ok()
+			.flatten()
+			.ok_or_else(|| {
+				FaithError::new(
+					FaithErrorKind::RuntimeThread,
+					Some("Fáith isn't loaded in this environment"),
+				)
+			})
+	}
+}
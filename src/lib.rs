@@ -1,19 +1,82 @@
+mod abort;
 mod agent;
 #[cfg(feature = "http3")]
 mod alt_svc;
 mod async_task;
+mod batch;
 mod body;
+mod cache_key;
+mod capabilities;
+mod checksum;
+mod clock;
+mod clock_skew;
 mod conn_tracker;
+mod connect_timing;
+mod content_encoding;
+mod cookies;
+#[cfg(feature = "ech")]
+mod ech;
 mod error;
 mod fetch;
+mod filename;
+mod graphql;
+mod grpc_web;
+mod headers;
+mod history;
+mod host_filter;
+mod host_limit;
+mod instance;
 mod integrity;
+mod network_change;
+mod observe;
+mod ocsp;
 mod options;
+mod pacing;
+mod pinned;
+mod prefetch;
+mod preload;
+mod prepared;
+mod private_network;
+mod proxy;
+mod proxy_auth;
+mod proxy_resolver;
+mod resolver;
+mod resource_timing;
 mod response;
+mod runtime;
+mod socket_options;
+mod spool;
 mod stream_body;
+mod sync;
+#[cfg(feature = "testing")]
+mod testing;
+mod timeouts;
+mod tls;
+mod tls_sessions;
+mod transfer;
+mod upload;
+mod version;
 
 pub use agent::*;
-pub use error::error_codes;
+pub use batch::{FaithBatchRequest, FaithFetchAllOptions, faith_fetch_all};
+pub use capabilities::capabilities;
+pub use checksum::ChecksumAlgorithm;
+pub use clock_skew::ClockSkewEstimate;
+pub use cookies::CookieEvent;
+pub use error::{FaithErrorCode, error_codes};
 pub use fetch::faith_fetch;
+pub use graphql::GraphqlRequest;
+pub use grpc_web::GrpcWebResponse;
+pub use headers::FaithHeaders;
+pub use history::RedirectSnapshot;
+pub use observe::{RequestEvent, RequestEventType};
 pub use options::{FaithOptionsAndBody, RequestCacheMode as CacheMode};
+pub use prepared::PreparedRequest;
 pub use response::FaithResponse;
+pub use runtime::{RuntimeOptions, ShutdownOptions, configure_runtime, faith_shutdown};
 pub use stream_body::{StreamBody, StreamBodySender, create_stream_body_pair};
+pub use sync::{FaithSyncResponse, faith_fetch_sync};
+#[cfg(feature = "testing")]
+pub use testing::{TestRequest, TestRoute, TestServer, start_test_server};
+pub use transfer::{TransferredResponse, receive_response};
+pub use upload::{FaithUploadOptions, FaithUploadPart, faith_upload};
@@ -0,0 +1,148 @@
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+use std::{
+	sync::{Arc, OnceLock},
+	time::Duration,
+};
+
+use tokio::{spawn, sync::watch, task::AbortHandle, time::sleep};
+
+#[cfg(feature = "http3")]
+use crate::alt_svc::AltSvcCache;
+use crate::{
+	error::{FaithError, FaithErrorKind},
+	pinned::PinnedClientMiddleware,
+};
+
+/// How long the OS must stop reporting changes before they're acted on, as switching networks comes
+/// with a burst of changes to links and addresses.
+const SETTLE: Duration = Duration::from_secs(1);
+
+/// Counts the changes reported by the OS, from a thread shared by all the agents watching them.
+static CHANGES: OnceLock<Result<watch::Receiver<u64>, String>> = OnceLock::new();
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn start() -> Result<watch::Receiver<u64>, String> {
+	#[cfg(target_os = "linux")]
+	use linux::Watcher;
+	#[cfg(target_os = "macos")]
+	use macos::Watcher;
+	#[cfg(target_os = "windows")]
+	use windows::Watcher;
+
+	let mut watcher = Watcher::new().map_err(|err| err.to_string())?;
+	let (changes, receiver) = watch::channel(0);
+	std::thread::Builder::new()
+		.name("faith-network-watch".into())
+		.spawn(move || {
+			while watcher.wait().is_ok() {
+				changes.send_modify(|count| *count += 1);
+			}
+		})
+		.map_err(|err| err.to_string())?;
+	Ok(receiver)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn start() -> Result<watch::Receiver<u64>, String> {
+	Err("this platform is not supported".into())
+}
+
+/// What an agent resets when the network changes.
+#[derive(Debug, Clone)]
+struct Reset {
+	pinned: Arc<PinnedClientMiddleware>,
+	#[cfg(feature = "http3")]
+	alt_svc: Option<Arc<AltSvcCache>>,
+}
+
+impl Reset {
+	fn run(&self) {
+		self.pinned.replace_clients();
+
+		#[cfg(feature = "http3")]
+		if let Some(alt_svc) = &self.alt_svc {
+			alt_svc.reset_failures();
+		}
+	}
+}
+
+/// Resets an agent's connections when the network changes, as told by `agent.notifyNetworkChange()`
+/// or, with `detectNetworkChanges`, by the OS.
+///
+/// Connections made on the previous network may never get an answer on the new one, so requests are
+/// then made with new clients: their pools start empty, and their resolvers read the system's DNS
+/// configuration anew and start with an empty cache. HTTP/3 failures are forgotten, as they may
+/// have been down to the previous network.
+#[derive(Debug)]
+pub(crate) struct NetworkChanges {
+	reset: Reset,
+	task: Option<AbortHandle>,
+}
+
+impl Drop for NetworkChanges {
+	fn drop(&mut self) {
+		if let Some(task) = &self.task {
+			task.abort();
+		}
+	}
+}
+
+impl NetworkChanges {
+	pub(crate) fn new(
+		pinned: Arc<PinnedClientMiddleware>,
+		#[cfg(feature = "http3")] alt_svc: Option<Arc<AltSvcCache>>,
+		detect: bool,
+	) -> Result<Self, FaithError> {
+		let reset = Reset {
+			pinned,
+			#[cfg(feature = "http3")]
+			alt_svc,
+		};
+		if !detect {
+			return Ok(Self { reset, task: None });
+		}
+
+		let mut changes = CHANGES.get_or_init(start).clone().map_err(|err| {
+			FaithError::new(
+				FaithErrorKind::Config,
+				Some(format!(
+					"detectNetworkChanges: can't watch the network: {err}"
+				)),
+			)
+		})?;
+		changes.mark_unchanged();
+
+		let on_change = reset.clone();
+		let task = spawn(async move {
+			while changes.changed().await.is_ok() {
+				loop {
+					tokio::select! {
+						changed = changes.changed() => {
+							if changed.is_err() {
+								return;
+							}
+						}
+						_ = sleep(SETTLE) => break,
+					}
+				}
+				on_change.run();
+			}
+		})
+		.abort_handle();
+
+		Ok(Self {
+			reset,
+			task: Some(task),
+		})
+	}
+
+	pub(crate) fn notify(&self) {
+		self.reset.run();
+	}
+}
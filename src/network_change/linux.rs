@@ -0,0 +1,33 @@
+use std::io;
+
+use netlink_sys::{Socket, SocketAddr, protocols::NETLINK_ROUTE};
+
+const RTMGRP_LINK: u32 = 0x1;
+const RTMGRP_IPV4_IFADDR: u32 = 0x10;
+const RTMGRP_IPV6_IFADDR: u32 = 0x100;
+
+/// A netlink socket subscribed to changes of the network links and addresses.
+pub struct Watcher {
+	socket: Socket,
+	buf: Vec<u8>,
+}
+
+impl Watcher {
+	pub fn new() -> io::Result<Self> {
+		let mut socket = Socket::new(NETLINK_ROUTE)?;
+		socket.bind(&SocketAddr::new(
+			0,
+			RTMGRP_LINK | RTMGRP_IPV4_IFADDR | RTMGRP_IPV6_IFADDR,
+		))?;
+		Ok(Self {
+			socket,
+			buf: vec![0u8; 65536],
+		})
+	}
+
+	/// Blocks until something changes.
+	pub fn wait(&mut self) -> io::Result<()> {
+		self.socket.recv(&mut &mut self.buf[..], 0)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,55 @@
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+use libc::{AF_UNSPEC, PF_ROUTE, RTM_DELADDR, RTM_IFINFO, RTM_NEWADDR, SOCK_RAW, c_int};
+
+/// A routing socket, which is told of every change to the routing tables, network interfaces, and
+/// their addresses.
+pub struct Watcher {
+	socket: OwnedFd,
+	buf: Vec<u8>,
+}
+
+impl Watcher {
+	pub fn new() -> io::Result<Self> {
+		let fd = unsafe { libc::socket(PF_ROUTE, SOCK_RAW, AF_UNSPEC) };
+		if fd < 0 {
+			return Err(io::Error::last_os_error());
+		}
+
+		Ok(Self {
+			socket: unsafe { OwnedFd::from_raw_fd(fd) },
+			buf: vec![0u8; 2048],
+		})
+	}
+
+	/// Blocks until an interface or address changes. Route changes are skipped, as the routing
+	/// table also caches every destination that is connected to.
+	pub fn wait(&mut self) -> io::Result<()> {
+		loop {
+			let read = unsafe {
+				libc::read(
+					self.socket.as_raw_fd(),
+					self.buf.as_mut_ptr().cast(),
+					self.buf.len(),
+				)
+			};
+			if read < 0 {
+				let err = io::Error::last_os_error();
+				if err.kind() == io::ErrorKind::Interrupted {
+					continue;
+				}
+				return Err(err);
+			}
+
+			// each message starts with its length (u16), version (u8), and type (u8)
+			if read >= 4
+				&& matches!(
+					c_int::from(self.buf[3]),
+					RTM_NEWADDR | RTM_DELADDR | RTM_IFINFO
+				) {
+				return Ok(());
+			}
+		}
+	}
+}
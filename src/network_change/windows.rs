@@ -0,0 +1,25 @@
+use std::io;
+use std::ptr;
+
+use windows::Win32::NetworkManagement::IpHelper::NotifyAddrChange;
+
+const NO_ERROR: u32 = 0;
+
+/// Waits on the IP helper's notifications of changes to the addresses of the network interfaces.
+pub struct Watcher;
+
+impl Watcher {
+	pub fn new() -> io::Result<Self> {
+		Ok(Self)
+	}
+
+	/// Blocks until an address changes.
+	pub fn wait(&mut self) -> io::Result<()> {
+		// without a handle nor an overlapped structure, the call waits for the change
+		let ret = unsafe { NotifyAddrChange(ptr::null_mut(), ptr::null()) };
+		if ret != NO_ERROR {
+			return Err(io::Error::from_raw_os_error(ret as i32));
+		}
+		Ok(())
+	}
+}
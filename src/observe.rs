@@ -0,0 +1,198 @@
+use std::{
+	fmt::Debug,
+	future::Future,
+	net::SocketAddr,
+	sync::Arc,
+	task::Poll,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use futures::{Stream, StreamExt as _, stream};
+use napi::{
+	Status,
+	threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode},
+};
+use napi_derive::napi;
+
+use crate::error::{FaithError, FaithErrorKind};
+
+/// What happened to a request, in a `RequestEvent`, in the order they happen:
+///
+/// - `queued`: the request was started, before looking in the cache or waiting for a connection.
+/// - `connectStart` and `connectEnd`: a new connection is being opened for the request. This covers
+///   DNS resolution, the TCP connection, going through the proxy, and the TLS handshake, which isn't
+///   reported on its own. Requests on reused connections, and HTTP/3 requests, have neither.
+/// - `dnsStart` and `dnsEnd`: the host name of the new connection is being resolved, between
+///   `connectStart` and `connectEnd`. Not for hosts in `dns.overrides`, nor for IP addresses.
+/// - `requestSent`: the request body was sent in full. Only for bodies sent as a stream: streamed
+///   bodies larger than `limits.replayBuffer`, and bodies sent with `forceChunked` or
+///   `uploadRateLimit`. Other bodies are sent along with the request headers.
+/// - `headersDone`: the response headers were received, with the `status`.
+/// - `firstByte`: the first chunk of the response body was received, as it's being read.
+/// - `bodyDone`: the response body was received in full, as it's being read, or along with the
+///   headers for responses without a body.
+/// - `error`: the request or reading its response body failed, with the error's `code` and
+///   `message`. This ends the events.
+///
+/// Events from following redirects repeat from `connectStart`, for each redirect which needs a new
+/// connection.
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestEventType {
+	#[napi(value = "queued")]
+	Queued,
+	#[napi(value = "connectStart")]
+	ConnectStart,
+	#[napi(value = "dnsStart")]
+	DnsStart,
+	#[napi(value = "dnsEnd")]
+	DnsEnd,
+	#[napi(value = "connectEnd")]
+	ConnectEnd,
+	#[napi(value = "requestSent")]
+	RequestSent,
+	#[napi(value = "headersDone")]
+	HeadersDone,
+	#[napi(value = "firstByte")]
+	FirstByte,
+	#[napi(value = "bodyDone")]
+	BodyDone,
+	#[napi(value = "error")]
+	Error,
+}
+
+/// An event in the lifecycle of a request, passed to its `observe` callback.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct RequestEvent {
+	/// What happened.
+	#[napi(js_name = "type")]
+	pub kind: RequestEventType,
+	/// When it happened, in milliseconds since the Unix epoch, like `response.timestamps`.
+	pub time: f64,
+	/// The host name being resolved, for `dnsStart` and `dnsEnd`.
+	pub host: Option<String>,
+	/// The addresses the host resolved to, for `dnsEnd`, or the address connected to, for
+	/// `connectEnd`. When going through a proxy, that's the proxy's address.
+	pub addresses: Option<Vec<String>>,
+	/// The response status, for `headersDone`.
+	pub status: Option<u16>,
+	/// The error code, for `error`.
+	pub code: Option<FaithErrorKind>,
+	/// The error message, for `error`.
+	pub message: Option<String>,
+}
+
+impl RequestEvent {
+	pub(crate) fn new(kind: RequestEventType) -> Self {
+		Self {
+			kind,
+			time: SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.map_or(0.0, |duration| duration.as_secs_f64() * 1000.0),
+			host: None,
+			addresses: None,
+			status: None,
+			code: None,
+			message: None,
+		}
+	}
+}
+
+pub(crate) type ObserveCallback =
+	ThreadsafeFunction<RequestEvent, (), RequestEvent, Status, false, true>;
+
+/// A request's `observe` callback. Events are queued to the main thread in the order they happen.
+#[derive(Clone)]
+pub(crate) struct Observer(Arc<ObserveCallback>);
+
+impl Debug for Observer {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_tuple("Observer").finish_non_exhaustive()
+	}
+}
+
+tokio::task_local! {
+	static OBSERVER: Option<Observer>;
+}
+
+/// Runs a request, so that the connector and resolver can report its events.
+///
+/// They run within the request's future, except for connections which carry on in the background
+/// once the request is given another one from the pool, which are then no longer reported.
+pub(crate) async fn with_observer<F: Future>(observer: Option<Observer>, request: F) -> F::Output {
+	OBSERVER.scope(observer, request).await
+}
+
+/// Reports `requestSent` once a request body stream has been sent in full.
+pub(crate) fn sent_at_end<S: Stream>(
+	body: S,
+	mut observer: Option<Observer>,
+) -> impl Stream<Item = S::Item> {
+	body.chain(stream::poll_fn(move |_| {
+		if let Some(observer) = observer.take() {
+			observer.emit(RequestEventType::RequestSent);
+		}
+		Poll::Ready(None)
+	}))
+}
+
+impl Observer {
+	pub(crate) fn new(callback: ObserveCallback) -> Self {
+		Self(Arc::new(callback))
+	}
+
+	/// The observer of the request being run, if it has one.
+	pub(crate) fn current() -> Option<Self> {
+		OBSERVER.try_with(Clone::clone).ok().flatten()
+	}
+
+	pub(crate) fn emit(&self, kind: RequestEventType) {
+		self.event(RequestEvent::new(kind));
+	}
+
+	pub(crate) fn event(&self, event: RequestEvent) {
+		self.0.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+	}
+
+	pub(crate) fn dns_start(&self, host: &str) {
+		self.event(RequestEvent {
+			host: Some(host.into()),
+			..RequestEvent::new(RequestEventType::DnsStart)
+		});
+	}
+
+	pub(crate) fn dns_end(&self, host: &str, addresses: &[SocketAddr]) {
+		self.event(RequestEvent {
+			host: Some(host.into()),
+			addresses: Some(addresses.iter().map(|addr| addr.ip().to_string()).collect()),
+			..RequestEvent::new(RequestEventType::DnsEnd)
+		});
+	}
+
+	pub(crate) fn connect_end(&self, address: Option<SocketAddr>) {
+		self.event(RequestEvent {
+			addresses: address.map(|address| vec![address.to_string()]),
+			..RequestEvent::new(RequestEventType::ConnectEnd)
+		});
+	}
+
+	pub(crate) fn headers_done(&self, status: u16) {
+		self.event(RequestEvent {
+			status: Some(status),
+			..RequestEvent::new(RequestEventType::HeadersDone)
+		});
+	}
+
+	pub(crate) fn error(&self, err: &FaithError) {
+		self.event(RequestEvent {
+			code: Some(err.kind),
+			message: Some(
+				err.message
+					.clone()
+					.unwrap_or_else(|| err.kind.default_message().into()),
+			),
+			..RequestEvent::new(RequestEventType::Error)
+		});
+	}
+}
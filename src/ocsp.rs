@@ -0,0 +1,796 @@
+use napi_derive::napi;
+use rustls::pki_types::{SignatureVerificationAlgorithm, UnixTime};
+
+/// Custom to Fáith.
+///
+/// The status of a server's certificate in the OCSP response it stapled:
+///
+/// - `good`: the certificate isn't revoked.
+/// - `revoked`: the certificate is revoked.
+/// - `unknown`: the OCSP responder doesn't know the certificate.
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcspStatus {
+	#[napi(value = "good")]
+	Good,
+	#[napi(value = "revoked")]
+	Revoked,
+	#[napi(value = "unknown")]
+	Unknown,
+}
+
+const BOOLEAN: u8 = 0x01;
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const OCTET_STRING: u8 = 0x04;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+const ENUMERATED: u8 = 0x0a;
+const GENERALIZED_TIME: u8 = 0x18;
+const SEQUENCE: u8 = 0x30;
+const EXPLICIT_0: u8 = 0xa0;
+const EXPLICIT_1: u8 = 0xa1;
+const EXPLICIT_2: u8 = 0xa2;
+const EXPLICIT_3: u8 = 0xa3;
+
+/// The `id-pkix-ocsp-basic` response type, 1.3.6.1.5.5.7.48.1.1.
+const OCSP_BASIC: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x01];
+/// The `id-ce-extKeyUsage` extension, 2.5.29.37.
+const EXT_KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x25];
+/// The `id-kp-OCSPSigning` key purpose, 1.3.6.1.5.5.7.3.9.
+const OCSP_SIGNING: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x09];
+
+/// How far in the future a response's `thisUpdate` may be, for clocks which are slightly off.
+const CLOCK_SKEW: u64 = 5 * 60;
+
+/// Reads a DER element: its tag, its contents, and what comes after it.
+fn element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+	let (&tag, input) = input.split_first()?;
+	let (&first, input) = input.split_first()?;
+	let (length, input) = match first {
+		0x00..=0x7f => (usize::from(first), input),
+		0x81..=0x84 => {
+			let count = usize::from(first & 0x7f);
+			if input.len() < count {
+				return None;
+			}
+			let (bytes, input) = input.split_at(count);
+			let length = bytes
+				.iter()
+				.fold(0, |length, &byte| (length << 8) | usize::from(byte));
+			(length, input)
+		}
+		_ => return None,
+	};
+	if input.len() < length {
+		return None;
+	}
+	let (contents, rest) = input.split_at(length);
+	Some((tag, contents, rest))
+}
+
+/// Reads a DER element which must have this tag: its contents, and what comes after it.
+fn expect(input: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+	let (found, contents, rest) = element(input)?;
+	(found == tag).then_some((contents, rest))
+}
+
+/// Reads a DER element which must have this tag: all of it including its header, its contents, and
+/// what comes after it. Signatures are made over whole elements.
+fn expect_whole(input: &[u8], tag: u8) -> Option<(&[u8], &[u8], &[u8])> {
+	let (contents, rest) = expect(input, tag)?;
+	Some((&input[..input.len() - rest.len()], contents, rest))
+}
+
+/// Reads a BIT STRING which is a whole number of bytes, as keys and signatures are.
+fn bits(input: &[u8]) -> Option<(&[u8], &[u8])> {
+	let (contents, rest) = expect(input, BIT_STRING)?;
+	match contents.split_first()? {
+		(0, bits) => Some((bits, rest)),
+		_ => None,
+	}
+}
+
+/// Reads a GeneralizedTime, as seconds since the epoch. Fractions of seconds are ignored.
+fn time(input: &[u8]) -> Option<(u64, &[u8])> {
+	let (time, rest) = expect(input, GENERALIZED_TIME)?;
+	if time.len() < 15 || time.last() != Some(&b'Z') || !time[..14].iter().all(u8::is_ascii_digit) {
+		return None;
+	}
+	let number = |range: std::ops::Range<usize>| {
+		time[range]
+			.iter()
+			.fold(0, |number, &digit| number * 10 + u64::from(digit - b'0'))
+	};
+	let (year, month, day) = (number(0..4), number(4..6), number(6..8));
+	if !(1..=12).contains(&month) || !(1..=31).contains(&day) || year < 1970 {
+		return None;
+	}
+
+	// days from the civil date, from Howard Hinnant's algorithm
+	let (year, month) = if month <= 2 {
+		(year - 1, month + 9)
+	} else {
+		(year, month - 3)
+	};
+	let era = year / 400;
+	let year_of_era = year - era * 400;
+	let day_of_year = (153 * month + 2) / 5 + day - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	let days = (era * 146_097 + day_of_era).checked_sub(719_468)?;
+
+	let seconds = days * 86_400 + number(8..10) * 3_600 + number(10..12) * 60 + number(12..14);
+	Some((seconds, rest))
+}
+
+/// The parts of a DER-encoded certificate which matter here.
+struct Certificate<'a> {
+	/// The signed part, which is the whole `tbsCertificate` element.
+	tbs: &'a [u8],
+	serial: &'a [u8],
+	/// The whole `issuer` and `subject` names, which are compared as they are.
+	issuer: &'a [u8],
+	subject: &'a [u8],
+	/// The contents of the key's `AlgorithmIdentifier`, and the key.
+	key_algorithm: &'a [u8],
+	key: &'a [u8],
+	extensions: Option<&'a [u8]>,
+	/// The contents of the signature's `AlgorithmIdentifier`, and the signature.
+	signature_algorithm: &'a [u8],
+	signature: &'a [u8],
+}
+
+impl<'a> Certificate<'a> {
+	fn parse(certificate: &'a [u8]) -> Option<Self> {
+		let (certificate, _) = expect(certificate, SEQUENCE)?;
+		let (tbs, fields, rest) = expect_whole(certificate, SEQUENCE)?;
+		let (signature_algorithm, rest) = expect(rest, SEQUENCE)?;
+		let (signature, _) = bits(rest)?;
+
+		// TBSCertificate: an optional version, the serial number, the signature algorithm, the
+		// issuer, the validity, the subject, the key, optional unique IDs, then the extensions
+		let (mut tag, mut contents, mut rest) = element(fields)?;
+		if tag == EXPLICIT_0 {
+			(tag, contents, rest) = element(rest)?;
+		}
+		if tag != INTEGER {
+			return None;
+		}
+		let serial = contents;
+		let (_, rest) = expect(rest, SEQUENCE)?;
+		let (issuer, _, rest) = expect_whole(rest, SEQUENCE)?;
+		let (_, rest) = expect(rest, SEQUENCE)?;
+		let (subject, _, rest) = expect_whole(rest, SEQUENCE)?;
+		let (key_info, mut rest) = expect(rest, SEQUENCE)?;
+		let (key_algorithm, key_info) = expect(key_info, SEQUENCE)?;
+		let (key, _) = bits(key_info)?;
+
+		let mut extensions = None;
+		while !rest.is_empty() {
+			let (tag, contents, after) = element(rest)?;
+			if tag == EXPLICIT_3 {
+				extensions = Some(expect(contents, SEQUENCE)?.0);
+			}
+			rest = after;
+		}
+
+		Some(Self {
+			tbs,
+			serial,
+			issuer,
+			subject,
+			key_algorithm,
+			key,
+			extensions,
+			signature_algorithm,
+			signature,
+		})
+	}
+
+	/// Whether the certificate may sign OCSP responses for its issuer (RFC 6960, section 4.2.2.2).
+	fn ocsp_signing(&self) -> bool {
+		let Some(mut extensions) = self.extensions else {
+			return false;
+		};
+		while let Some((extension, rest)) = expect(extensions, SEQUENCE) {
+			extensions = rest;
+			let Some((id, mut value)) = expect(extension, OBJECT_IDENTIFIER) else {
+				return false;
+			};
+			if id != EXT_KEY_USAGE {
+				continue;
+			}
+			if let Some((_, rest)) = expect(value, BOOLEAN) {
+				value = rest;
+			}
+			let Some((mut purposes, _)) =
+				expect(value, OCTET_STRING).and_then(|(value, _)| expect(value, SEQUENCE))
+			else {
+				return false;
+			};
+			while let Some((purpose, rest)) = expect(purposes, OBJECT_IDENTIFIER) {
+				if purpose == OCSP_SIGNING {
+					return true;
+				}
+				purposes = rest;
+			}
+			return false;
+		}
+		false
+	}
+
+	/// Whether this certificate's key made the signature over the message.
+	fn signed(
+		&self,
+		algorithms: &[&dyn SignatureVerificationAlgorithm],
+		signature_algorithm: &[u8],
+		message: &[u8],
+		signature: &[u8],
+	) -> bool {
+		algorithms.iter().any(|algorithm| {
+			algorithm.public_key_alg_id().as_ref() == self.key_algorithm
+				&& algorithm.signature_alg_id().as_ref() == signature_algorithm
+				&& algorithm
+					.verify_signature(self.key, message, signature)
+					.is_ok()
+		})
+	}
+
+	/// Whether this certificate issued the other one.
+	fn issued(
+		&self,
+		other: &Certificate<'_>,
+		algorithms: &[&dyn SignatureVerificationAlgorithm],
+	) -> bool {
+		other.issuer == self.subject
+			&& self.signed(
+				algorithms,
+				other.signature_algorithm,
+				other.tbs,
+				other.signature,
+			)
+	}
+}
+
+/// The parts of an OCSP response which matter here, for one certificate.
+struct Response<'a> {
+	status: OcspStatus,
+	/// When the status was known to be correct, and when newer information will be available.
+	this_update: u64,
+	next_update: Option<u64>,
+	/// The signed part, which is the whole `tbsResponseData` element.
+	tbs: &'a [u8],
+	/// The contents of the signature's `AlgorithmIdentifier`, and the signature.
+	signature_algorithm: &'a [u8],
+	signature: &'a [u8],
+	/// The certificates which may have signed the response, if the issuer delegated that.
+	certificates: &'a [u8],
+}
+
+impl<'a> Response<'a> {
+	/// Reads what a DER-encoded OCSP response says of a certificate, which is found in it by its
+	/// serial number.
+	fn parse(response: &'a [u8], serial: &[u8]) -> Option<Self> {
+		// OCSPResponse: the response status, then the response if it's successful (0)
+		let (response, _) = expect(response, SEQUENCE)?;
+		let (response_status, rest) = expect(response, ENUMERATED)?;
+		if response_status != [0] {
+			return None;
+		}
+		let (response_bytes, _) = expect(rest, EXPLICIT_0)?;
+		let (response_bytes, _) = expect(response_bytes, SEQUENCE)?;
+		let (response_type, rest) = expect(response_bytes, OBJECT_IDENTIFIER)?;
+		if response_type != OCSP_BASIC {
+			return None;
+		}
+
+		// BasicOCSPResponse: the response data, its signature, then optionally the certificates
+		// of a responder the issuer delegated signing to
+		let (basic, _) = expect(rest, OCTET_STRING)?;
+		let (basic, _) = expect(basic, SEQUENCE)?;
+		let (tbs, data, rest) = expect_whole(basic, SEQUENCE)?;
+		let (signature_algorithm, rest) = expect(rest, SEQUENCE)?;
+		let (signature, rest) = bits(rest)?;
+		let certificates = match expect(rest, EXPLICIT_0) {
+			Some((certificates, _)) => expect(certificates, SEQUENCE)?.0,
+			None => &[],
+		};
+
+		// ResponseData: an optional version, the responder's name or key hash, when it was
+		// produced, then the responses
+		let (mut tag, _, mut rest) = element(data)?;
+		if tag == EXPLICIT_0 {
+			(tag, _, rest) = element(rest)?;
+		}
+		if tag != EXPLICIT_1 && tag != EXPLICIT_2 {
+			return None;
+		}
+		let (_, rest) = expect(rest, GENERALIZED_TIME)?;
+		let (mut responses, _) = expect(rest, SEQUENCE)?;
+
+		while !responses.is_empty() {
+			let (single, rest) = expect(responses, SEQUENCE)?;
+			responses = rest;
+
+			// CertID: the hash algorithm, the issuer's name and key hashes, and the serial number
+			let (cert_id, rest) = expect(single, SEQUENCE)?;
+			let (_, cert_id) = expect(cert_id, SEQUENCE)?;
+			let (_, cert_id) = expect(cert_id, OCTET_STRING)?;
+			let (_, cert_id) = expect(cert_id, OCTET_STRING)?;
+			let (response_serial, _) = expect(cert_id, INTEGER)?;
+			if response_serial != serial {
+				continue;
+			}
+
+			// CertStatus: good and unknown are implicitly tagged NULLs, revoked is constructed;
+			// then when the status was correct, and optionally when it will next be updated
+			let (tag, _, rest) = element(rest)?;
+			let status = match tag {
+				0x80 => OcspStatus::Good,
+				0xa1 => OcspStatus::Revoked,
+				0x82 => OcspStatus::Unknown,
+				_ => return None,
+			};
+			let (this_update, rest) = time(rest)?;
+			let next_update = match expect(rest, EXPLICIT_0) {
+				Some((next_update, _)) => Some(time(next_update)?.0),
+				None => None,
+			};
+			return Some(Self {
+				status,
+				this_update,
+				next_update,
+				tbs,
+				signature_algorithm,
+				signature,
+				certificates,
+			});
+		}
+
+		None
+	}
+
+	/// Whether the response was signed by the certificate's issuer, or by a responder the issuer
+	/// delegated signing to.
+	fn signed_by(
+		&self,
+		issuer: &Certificate<'_>,
+		algorithms: &[&dyn SignatureVerificationAlgorithm],
+	) -> bool {
+		let signed = |signer: &Certificate<'_>| {
+			signer.signed(
+				algorithms,
+				self.signature_algorithm,
+				self.tbs,
+				self.signature,
+			)
+		};
+		if signed(issuer) {
+			return true;
+		}
+
+		let mut certificates = self.certificates;
+		while let Some((certificate, _, rest)) = expect_whole(certificates, SEQUENCE) {
+			certificates = rest;
+			let Some(responder) = Certificate::parse(certificate) else {
+				continue;
+			};
+			if responder.ocsp_signing()
+				&& issuer.issued(&responder, algorithms)
+				&& signed(&responder)
+			{
+				return true;
+			}
+		}
+		false
+	}
+
+	/// Whether the response is current: it's valid from `thisUpdate` to `nextUpdate`. Responses
+	/// without a `nextUpdate` aren't relied on.
+	fn current(&self, now: UnixTime) -> bool {
+		let now = now.as_secs();
+		self.this_update <= now + CLOCK_SKEW && self.next_update.is_some_and(|next| now < next)
+	}
+}
+
+/// Reads the status of a certificate from a DER-encoded OCSP response, as stapled by servers.
+///
+/// The certificate is found in the response by its serial number. Returns `None` if the response
+/// wasn't successful, isn't about the certificate, or can't be read. This doesn't check the
+/// response's signature nor whether it is current: it says what the server stapled, not whether
+/// that can be relied on, which is what `check()` is for.
+pub(crate) fn status(response: &[u8], certificate: &[u8]) -> Option<OcspStatus> {
+	let certificate = Certificate::parse(certificate)?;
+	Response::parse(response, certificate.serial).map(|response| response.status)
+}
+
+/// Checks that a DER-encoded OCSP response, as stapled by servers, says that the certificate is
+/// good, and that it can be relied on: that it's signed by the certificate's issuer or a responder
+/// it delegated to, and that it is current. Returns why not otherwise.
+///
+/// The issuer is looked for among the certificates the server sent. Certificates issued directly by
+/// a root, which servers don't send, can't be checked.
+pub(crate) fn check(
+	response: &[u8],
+	certificate: &[u8],
+	intermediates: &[&[u8]],
+	algorithms: &[&dyn SignatureVerificationAlgorithm],
+	now: UnixTime,
+) -> Result<(), &'static str> {
+	if response.is_empty() {
+		return Err("the server didn't staple an OCSP response");
+	}
+	let unreadable = "the stapled OCSP response can't be read";
+	let certificate = Certificate::parse(certificate).ok_or(unreadable)?;
+	let response = Response::parse(response, certificate.serial).ok_or(unreadable)?;
+	match response.status {
+		OcspStatus::Good => {}
+		OcspStatus::Revoked => {
+			return Err("the stapled OCSP response says the certificate is revoked");
+		}
+		OcspStatus::Unknown => {
+			return Err("the stapled OCSP response doesn't know the certificate");
+		}
+	}
+
+	let issuer = intermediates
+		.iter()
+		.filter_map(|intermediate| Certificate::parse(intermediate))
+		.find(|intermediate| intermediate.subject == certificate.issuer)
+		.ok_or(
+			"the certificate's issuer wasn't sent, so the stapled OCSP response can't be verified",
+		)?;
+	if !response.signed_by(&issuer, algorithms) {
+		return Err("the stapled OCSP response isn't signed by the certificate's issuer");
+	}
+	if !response.current(now) {
+		return Err("the stapled OCSP response isn't current");
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use rustls::pki_types::{AlgorithmIdentifier, InvalidSignature};
+
+	use super::*;
+
+	const NOW: u64 = 1_792_022_400;
+
+	/// Signatures are the signer's key followed by the length of the message.
+	#[derive(Debug)]
+	struct TestAlgorithm;
+
+	impl SignatureVerificationAlgorithm for TestAlgorithm {
+		fn verify_signature(
+			&self,
+			key: &[u8],
+			message: &[u8],
+			signature: &[u8],
+		) -> Result<(), InvalidSignature> {
+			(signature == sign(key, message))
+				.then_some(())
+				.ok_or(InvalidSignature)
+		}
+
+		fn public_key_alg_id(&self) -> AlgorithmIdentifier {
+			AlgorithmIdentifier::from_slice(&[0x06, 0x01, 0x01])
+		}
+
+		fn signature_alg_id(&self) -> AlgorithmIdentifier {
+			AlgorithmIdentifier::from_slice(&[0x06, 0x01, 0x02])
+		}
+	}
+
+	const ALGORITHMS: &[&dyn SignatureVerificationAlgorithm] = &[&TestAlgorithm];
+
+	fn sign(key: &[u8], message: &[u8]) -> Vec<u8> {
+		[key, &(message.len() as u32).to_be_bytes()].concat()
+	}
+
+	fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+		let mut out = vec![tag];
+		match contents.len() {
+			len @ 0..=0x7f => out.push(len as u8),
+			len @ 0x80..=0xff => out.extend([0x81, len as u8]),
+			len => out.extend([0x82, (len >> 8) as u8, len as u8]),
+		}
+		out.extend_from_slice(contents);
+		out
+	}
+
+	fn seq(parts: &[Vec<u8>]) -> Vec<u8> {
+		der(SEQUENCE, &parts.concat())
+	}
+
+	fn name(name: &str) -> Vec<u8> {
+		seq(&[der(
+			0x31,
+			&seq(&[
+				der(OBJECT_IDENTIFIER, &[0x55, 0x04, 0x03]),
+				der(0x0c, name.as_bytes()),
+			]),
+		)])
+	}
+
+	fn bit_string(bytes: &[u8]) -> Vec<u8> {
+		der(BIT_STRING, &[&[0], bytes].concat())
+	}
+
+	fn signed(tbs: Vec<u8>, signer_key: &[u8]) -> Vec<u8> {
+		let signature = sign(signer_key, &tbs);
+		vec![
+			tbs,
+			seq(&[der(OBJECT_IDENTIFIER, &[0x02])]),
+			bit_string(&signature),
+		]
+		.concat()
+	}
+
+	/// A certificate for `subject`, with the key `[n; 32]`, issued by `issuer` with the key
+	/// `[issuer_n; 32]`.
+	fn certificate(
+		serial: &[u8],
+		subject: (&str, u8),
+		issuer: (&str, u8),
+		extensions: Option<Vec<u8>>,
+	) -> Vec<u8> {
+		let mut tbs = vec![
+			der(EXPLICIT_0, &der(INTEGER, &[2])),
+			der(INTEGER, serial),
+			seq(&[der(OBJECT_IDENTIFIER, &[0x02])]),
+			name(issuer.0),
+			seq(&[
+				der(GENERALIZED_TIME, b"20260101000000Z"),
+				der(GENERALIZED_TIME, b"20270101000000Z"),
+			]),
+			name(subject.0),
+			seq(&[
+				seq(&[der(OBJECT_IDENTIFIER, &[0x01])]),
+				bit_string(&[subject.1; 32]),
+			]),
+		];
+		if let Some(extensions) = extensions {
+			tbs.push(der(EXPLICIT_3, &extensions));
+		}
+		seq(&[signed(seq(&tbs), &[issuer.1; 32])])
+	}
+
+	fn ocsp_signing() -> Vec<u8> {
+		seq(&[seq(&[
+			der(OBJECT_IDENTIFIER, EXT_KEY_USAGE),
+			der(OCTET_STRING, &seq(&[der(OBJECT_IDENTIFIER, OCSP_SIGNING)])),
+		])])
+	}
+
+	fn leaf(serial: &[u8]) -> Vec<u8> {
+		certificate(serial, ("leaf", 1), ("issuer", 2), None)
+	}
+
+	fn issuer() -> Vec<u8> {
+		certificate(&[0x09], ("issuer", 2), ("root", 3), None)
+	}
+
+	fn single(serial: &[u8], status: Vec<u8>, next_update: Option<&[u8]>) -> Vec<u8> {
+		let mut single = vec![
+			seq(&[
+				seq(&[der(OBJECT_IDENTIFIER, &[0x2b, 0x0e, 0x03, 0x02, 0x1a])]),
+				der(OCTET_STRING, &[1; 20]),
+				der(OCTET_STRING, &[2; 20]),
+				der(INTEGER, serial),
+			]),
+			status,
+			der(GENERALIZED_TIME, b"20261014000000Z"),
+		];
+		if let Some(next_update) = next_update {
+			single.push(der(EXPLICIT_0, &der(GENERALIZED_TIME, next_update)));
+		}
+		seq(&single)
+	}
+
+	fn response_signed(singles: &[Vec<u8>], signer_key: u8, certificates: &[Vec<u8>]) -> Vec<u8> {
+		let data = seq(&[
+			der(EXPLICIT_2, &der(OCTET_STRING, &[3; 20])),
+			der(GENERALIZED_TIME, b"20261015000000Z"),
+			seq(singles),
+		]);
+		let mut basic = vec![signed(data, &[signer_key; 32])];
+		if !certificates.is_empty() {
+			basic.push(der(EXPLICIT_0, &seq(certificates)));
+		}
+		seq(&[
+			der(ENUMERATED, &[0]),
+			der(
+				EXPLICIT_0,
+				&seq(&[
+					der(OBJECT_IDENTIFIER, OCSP_BASIC),
+					der(OCTET_STRING, &seq(&basic)),
+				]),
+			),
+		])
+	}
+
+	fn response(singles: &[Vec<u8>]) -> Vec<u8> {
+		response_signed(singles, 2, &[])
+	}
+
+	fn good() -> Vec<u8> {
+		der(0x80, &[])
+	}
+
+	fn check_at(response: &[u8], intermediates: &[&[u8]], now: u64) -> Result<(), &'static str> {
+		check(
+			response,
+			&leaf(&[0x01, 0x23]),
+			intermediates,
+			ALGORITHMS,
+			UnixTime::since_unix_epoch(std::time::Duration::from_secs(now)),
+		)
+	}
+
+	#[test]
+	fn statuses() {
+		let cert = leaf(&[0x01, 0x23]);
+		let revoked = der(0xa1, &der(GENERALIZED_TIME, b"20261014000000Z"));
+		let unknown = der(0x82, &[]);
+
+		assert_eq!(
+			status(&response(&[single(&[0x01, 0x23], good(), None)]), &cert),
+			Some(OcspStatus::Good)
+		);
+		assert_eq!(
+			status(
+				&response(&[single(&[0x01, 0x23], revoked.clone(), None)]),
+				&cert
+			),
+			Some(OcspStatus::Revoked)
+		);
+		assert_eq!(
+			status(&response(&[single(&[0x01, 0x23], unknown, None)]), &cert),
+			Some(OcspStatus::Unknown)
+		);
+		assert_eq!(
+			status(
+				&response(&[
+					single(&[0x45], good(), None),
+					single(&[0x01, 0x23], revoked, None)
+				]),
+				&cert
+			),
+			Some(OcspStatus::Revoked),
+			"finds the certificate among several"
+		);
+	}
+
+	#[test]
+	fn unusable_responses() {
+		let cert = leaf(&[0x01, 0x23]);
+
+		assert_eq!(
+			status(&response(&[single(&[0x45], good(), None)]), &cert),
+			None,
+			"about another certificate"
+		);
+		assert_eq!(
+			status(&seq(&[der(ENUMERATED, &[6])]), &cert),
+			None,
+			"unauthorized"
+		);
+		let full = response(&[single(&[0x01, 0x23], good(), None)]);
+		assert_eq!(status(&full[..full.len() - 10], &cert), None, "truncated");
+		assert_eq!(status(b"not der", &cert), None, "garbage");
+		assert_eq!(status(&full, b"not a certificate"), None, "bad certificate");
+	}
+
+	#[test]
+	fn times() {
+		let time = |value: &[u8]| super::time(&der(GENERALIZED_TIME, value)).map(|(time, _)| time);
+		assert_eq!(time(b"20261015000000Z"), Some(NOW));
+		assert_eq!(time(b"20000229123456Z"), Some(951_827_696));
+		assert_eq!(time(b"20000229123456.789Z"), Some(951_827_696));
+		assert_eq!(time(b"20001329123456Z"), None, "bad month");
+		assert_eq!(time(b"20000229123456"), None, "not UTC");
+	}
+
+	#[test]
+	fn checks() {
+		let issuer = issuer();
+		let current = single(&[0x01, 0x23], good(), Some(b"20261021000000Z"));
+		let signed = response(&[current.clone()]);
+
+		assert_eq!(check_at(&signed, &[&issuer], NOW), Ok(()));
+		assert!(check_at(&[], &[&issuer], NOW).is_err(), "not stapled");
+		assert!(
+			check_at(
+				&response_signed(&[current.clone()], 4, &[]),
+				&[&issuer],
+				NOW
+			)
+			.is_err(),
+			"signed by another key"
+		);
+		assert!(check_at(&signed, &[], NOW).is_err(), "issuer not sent");
+		assert!(
+			check_at(&signed, &[&leaf(&[0x09])], NOW).is_err(),
+			"issuer not among those sent"
+		);
+		assert!(
+			check_at(&signed, &[&issuer], NOW + 7 * 86_400).is_err(),
+			"after nextUpdate"
+		);
+		assert!(
+			check_at(&signed, &[&issuer], NOW - 2 * 86_400).is_err(),
+			"before thisUpdate"
+		);
+		assert!(
+			check_at(
+				&response(&[single(&[0x01, 0x23], good(), None)]),
+				&[&issuer],
+				NOW
+			)
+			.is_err(),
+			"without nextUpdate"
+		);
+		assert!(
+			check_at(
+				&response(&[single(
+					&[0x01, 0x23],
+					der(0x82, &[]),
+					Some(b"20261021000000Z")
+				)]),
+				&[&issuer],
+				NOW
+			)
+			.is_err(),
+			"unknown"
+		);
+	}
+
+	#[test]
+	fn delegated_responders() {
+		let issuer = issuer();
+		let current = single(&[0x01, 0x23], good(), Some(b"20261021000000Z"));
+
+		let responder = certificate(
+			&[0x0a],
+			("responder", 5),
+			("issuer", 2),
+			Some(ocsp_signing()),
+		);
+		assert_eq!(
+			check_at(
+				&response_signed(&[current.clone()], 5, &[responder]),
+				&[&issuer],
+				NOW
+			),
+			Ok(()),
+			"delegated by the issuer"
+		);
+
+		let responder = certificate(&[0x0a], ("responder", 5), ("issuer", 2), None);
+		assert!(
+			check_at(
+				&response_signed(&[current.clone()], 5, &[responder]),
+				&[&issuer],
+				NOW
+			)
+			.is_err(),
+			"not for OCSP signing"
+		);
+
+		let responder = certificate(
+			&[0x0a],
+			("responder", 5),
+			("issuer", 6),
+			Some(ocsp_signing()),
+		);
+		assert!(
+			check_at(
+				&response_signed(&[current], 5, &[responder]),
+				&[&issuer],
+				NOW
+			)
+			.is_err(),
+			"not issued by the issuer"
+		);
+	}
+}
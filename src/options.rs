@@ -1,10 +1,16 @@
-use std::{fmt::Debug, sync::Arc, time::Duration};
+use std::{collections::HashMap, fmt::Debug, sync::Arc, time::Duration};
 
+use bytes::Bytes;
 use http_cache_reqwest::CacheMode;
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use crate::agent::Agent;
+use crate::{
+	agent::Agent,
+	checksum::ChecksumAlgorithm,
+	observe::{ObserveCallback, Observer},
+	resource_timing::MarkResourceTiming,
+};
 
 /// The cache mode you want to use for the request. This may be any one of the following values:
 ///
@@ -36,7 +42,8 @@ use crate::agent::Agent;
 ///
 /// - `only-if-cached`: The client looks in its HTTP cache for a response matching the request.
 ///   - If there is a match, fresh or stale, it will be returned from the cache.
-///   - If there is no match, a network error is returned.
+///   - If there is no match, or the agent has no cache, a `NotCached` error is returned without
+///     making a request.
 ///
 /// - `ignore-rules`: Custom to Fáith. Overrides the check that determines if a response can be cached
 ///   to always return true on 200. Uses any response in the HTTP cache matching the request, not
@@ -129,31 +136,116 @@ pub enum DuplexOption {
 	Half,
 }
 
+/// Pins the HTTP version used for this request. This overrides both the `Agent` configuration and any
+/// HTTP/3 upgrade decision from the Alt-Svc cache, for this request only. This is intended for
+/// debugging protocol-specific server behaviour. This option may be any one of the following values:
+///
+/// - `http/1.1`: use HTTP/1.1 (or HTTP/1.0 if the agent is configured with `http1.version10`).
+/// - `h2`: use HTTP/2, without negotiation; the request fails if the server doesn't support it.
+/// - `h3`: use HTTP/3 directly, without needing an Alt-Svc advertisement; the request fails if the
+///   server doesn't support it.
+///
+/// Pinned HTTP/1.1 and HTTP/2 requests use a connection pool separate from the agent's main pool.
+///
+/// Custom to Fáith. Defaults to no pin.
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionOption {
+	#[napi(value = "http/1.1")]
+	Http11,
+	#[napi(value = "h2")]
+	H2,
+	#[napi(value = "h3")]
+	H3,
+}
+
+/// Timeouts for this request, which replace the agent's `timeout` options of the same name. This
+/// is a nested object.
+///
+/// Custom to Fáith.
+#[napi(object)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestTimeouts {
+	/// Set a timeout for connecting, in milliseconds, if the request needs a new connection.
+	pub connect: Option<u32>,
+	/// Set a timeout for read operations, in milliseconds: without progress until the response
+	/// arrives, and then between each chunk of its body.
+	pub read: Option<u32>,
+	/// Set a timeout for the entire request-response cycle, in milliseconds. This takes precedence
+	/// over the request's `timeout`.
+	pub total: Option<u32>,
+	/// Set a timeout for reading the response body in full, in milliseconds, from when the response
+	/// arrived.
+	pub body: Option<u32>,
+}
+
+/// A timeout, with the `cause` of the error when it fires.
+pub(crate) type Timeout = (Duration, &'static str);
+
+/// Converts a timeout option in milliseconds.
+pub(crate) fn timeout_millis(millis: Option<u32>, cause: &'static str) -> Option<Timeout> {
+	millis.map(|millis| (Duration::from_millis(millis.into()), cause))
+}
+
 #[napi(object)]
+#[derive(Default)]
 pub struct FaithOptionsAndBody {
-	pub agent: Reference<Agent>,
+	pub agent: Option<Reference<Agent>>,
 	pub body: Option<Either3<String, Buffer, Uint8Array>>,
 	pub cache: Option<RequestCacheMode>,
+	pub checksums: Option<Vec<ChecksumAlgorithm>>,
+	pub connect_to: Option<String>,
+	pub correlation_id: Option<String>,
 	pub credentials: Option<CredentialsOption>,
 	pub duplex: Option<DuplexOption>,
-	pub headers: Option<Vec<(String, String)>>,
+	pub force_chunked: Option<bool>,
+	pub headers: Option<Vec<(String, Option<String>)>>,
 	pub integrity: Option<String>,
+	pub json: Option<serde_json::Value>,
 	pub method: Option<String>,
+	#[napi(ts_type = "(event: RequestEvent) => void")]
+	pub observe: Option<ObserveCallback>,
+	pub params: Option<HashMap<String, String>>,
+	pub sensitive_headers: Option<Vec<String>>,
+	pub throw_on_error: Option<bool>,
 	pub timeout: Option<u32>,
+	pub timeouts: Option<RequestTimeouts>,
+	pub top_level_site: Option<String>,
+	pub upload_rate_limit: Option<u32>,
+	pub user_agent: Option<String>,
+	pub version: Option<VersionOption>,
 }
 
 #[derive(Clone, Debug, Default)]
 pub(crate) struct FaithOptions {
 	pub(crate) cache: RequestCacheMode,
+	pub(crate) checksums: Option<Vec<ChecksumAlgorithm>>,
+	pub(crate) connect_to: Option<String>,
+	pub(crate) correlation_id: Option<Arc<str>>,
 	pub(crate) credentials: CredentialsOption,
-	pub(crate) headers: Option<Vec<(String, String)>>,
+	pub(crate) force_chunked: bool,
+	pub(crate) headers: Option<Vec<(String, Option<String>)>>,
 	pub(crate) integrity: Option<String>,
+	pub(crate) json: Option<serde_json::Value>,
 	pub(crate) method: Option<String>,
+	pub(crate) observe: Option<Observer>,
+	pub(crate) params: Option<HashMap<String, String>>,
+	/// Set on the agent's own `cache.preload` requests, whose links aren't preloaded in turn.
+	pub(crate) preload: bool,
+	/// Set for requests made from JS, to add their entries to the thread's performance timeline.
+	pub(crate) resource_timing: Option<MarkResourceTiming>,
+	pub(crate) sensitive_headers: Option<Vec<String>>,
+	pub(crate) throw_on_error: Option<bool>,
 	pub(crate) timeout: Option<Duration>,
+	pub(crate) timeouts: RequestTimeouts,
+	pub(crate) top_level_site: Option<String>,
+	pub(crate) upload_rate_limit: Option<u32>,
+	pub(crate) user_agent: Option<String>,
+	pub(crate) version: Option<VersionOption>,
 }
 
 impl FaithOptions {
-	pub(crate) fn extract(opts: FaithOptionsAndBody) -> (Self, Agent, Option<Arc<Buffer>>) {
+	pub(crate) fn extract(opts: FaithOptionsAndBody) -> (Self, Option<Agent>, Option<Bytes>) {
 		let credentials = opts.credentials.unwrap_or_default();
 		// Transform same-origin to include
 		let credentials = if credentials == CredentialsOption::SameOrigin {
@@ -165,18 +257,45 @@ impl FaithOptions {
 		(
 			Self {
 				cache: opts.cache.unwrap_or_default(),
+				checksums: opts.checksums,
+				connect_to: opts.connect_to,
+				correlation_id: opts.correlation_id.map(Into::into),
 				credentials,
+				force_chunked: opts.force_chunked.unwrap_or(false),
 				headers: opts.headers,
 				integrity: opts.integrity,
+				json: opts.json,
 				method: opts.method,
+				observe: opts.observe.map(Observer::new),
+				params: opts.params,
+				preload: false,
+				resource_timing: None,
+				sensitive_headers: opts.sensitive_headers,
+				throw_on_error: opts.throw_on_error,
 				timeout: opts.timeout.map(Into::into).map(Duration::from_millis),
+				timeouts: opts.timeouts.unwrap_or_default(),
+				top_level_site: opts.top_level_site,
+				upload_rate_limit: opts.upload_rate_limit,
+				user_agent: opts.user_agent,
+				version: opts.version,
 			},
-			Agent::clone(&opts.agent),
-			opts.body.map(|either| match either {
-				Either3::A(s) => Arc::new(Buffer::from(s.as_bytes())),
-				Either3::B(b) => Arc::new(b),
-				Either3::C(u) => Arc::new(Buffer::from(u.as_ref())),
-			}),
+			opts.agent.as_deref().map(Agent::clone),
+			opts.body.map(Self::body),
 		)
 	}
+
+	/// The request's own total timeout: `timeouts.total`, or else `timeout`.
+	pub(crate) fn total_timeout(&self) -> Option<Timeout> {
+		timeout_millis(self.timeouts.total, "timeouts.total")
+			.or(self.timeout.map(|dur| (dur, "timeout")))
+	}
+
+	/// Takes ownership of the body's bytes, without copying them.
+	pub(crate) fn body(body: Either3<String, Buffer, Uint8Array>) -> Bytes {
+		match body {
+			Either3::A(s) => Bytes::from(s),
+			Either3::B(b) => Bytes::from_owner(b),
+			Either3::C(u) => Bytes::from_owner(u),
+		}
+	}
 }
@@ -0,0 +1,40 @@
+use std::{
+	num::NonZeroU32,
+	time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use futures::{Stream, StreamExt, stream};
+
+/// Paces a request body stream to `rate` bytes per second, for the `uploadRateLimit` option.
+///
+/// Chunks are split in slices of a tenth of a second's worth of bytes, and each slice is held back
+/// until the bytes before it are within the rate since the first was sent. This paces what's handed
+/// to the connection, so the rate holds over time rather than for each packet.
+pub(crate) fn paced<S, E>(inner: S, rate: NonZeroU32) -> impl Stream<Item = Result<Bytes, E>>
+where
+	S: Stream<Item = Result<Bytes, E>>,
+{
+	let rate = f64::from(rate.get());
+	let slice = ((rate / 10.0) as usize).max(1);
+
+	stream::unfold(
+		(Box::pin(inner), Bytes::new(), None::<Instant>, 0_u64),
+		move |(mut inner, mut pending, start, sent)| async move {
+			if pending.is_empty() {
+				match inner.next().await? {
+					Ok(bytes) => pending = bytes,
+					Err(err) => return Some((Err(err), (inner, pending, start, sent))),
+				}
+			}
+
+			let start = start.unwrap_or_else(Instant::now);
+			tokio::time::sleep_until((start + Duration::from_secs_f64(sent as f64 / rate)).into())
+				.await;
+
+			let chunk = pending.split_to(slice.min(pending.len()));
+			let sent = sent + chunk.len() as u64;
+			Some((Ok(chunk), (inner, pending, Some(start), sent)))
+		},
+	)
+}
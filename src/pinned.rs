@@ -0,0 +1,621 @@
+use std::{
+	net::{IpAddr, SocketAddr},
+	str::FromStr,
+	sync::{
+		Arc,
+		atomic::{AtomicBool, Ordering},
+	},
+	time::Duration,
+};
+
+use http::{
+	Extensions, HeaderValue, Method, StatusCode, Version,
+	header::{
+		ACCEPT_ENCODING, AUTHORIZATION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, COOKIE,
+		HOST, LOCATION, PROXY_AUTHORIZATION, REFERER, SET_COOKIE, TRANSFER_ENCODING,
+		WWW_AUTHENTICATE,
+	},
+};
+use moka::sync::Cache;
+use reqwest::{
+	Client, Request, Response, Url,
+	cookie::CookieStore,
+	dns::{Name, Resolve},
+	redirect::Policy,
+};
+use reqwest_middleware::{Middleware, Next, Result};
+
+use crate::{
+	agent::{
+		AgentOptions, DnsOverride, InnerAgentStats, Redirect, RedirectRules, client_builder,
+		parse_address,
+	},
+	content_encoding::{Undecoded, accept_encoding},
+	cookies::AgentJar,
+	error::{FaithError, FaithErrorKind},
+	history,
+	options::VersionOption,
+	private_network::check_address,
+	resolver::{AddressOrder, AgentResolver},
+	tls::{ALPN_HTTP1, ALPN_HTTP2, AgentTls, default_alpn},
+};
+
+/// The protocol a pinned client is restricted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Protocol {
+	Any,
+	Http1,
+	Http2,
+}
+
+/// Connects to these addresses for a host, instead of resolving it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ConnectRoute {
+	pub(crate) host: String,
+	pub(crate) addrs: Vec<SocketAddr>,
+}
+
+/// Request extension to connect to this address instead of resolving the URL's host.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ConnectTo(pub(crate) SocketAddr);
+
+/// Routes requests which can't be made with the agent's main client.
+///
+/// HTTP/3 only needs the request version to be set, as the client picks the QUIC transport from it.
+/// HTTP/1.1 and HTTP/2 are otherwise negotiated via ALPN when connecting, so pinning them requires
+/// clients that only offer that one protocol. Similarly, connecting to a different address than the
+/// URL's host resolves to (with `connectTo` or `tls.serverName`) requires a client with its own resolution
+/// override, so that its connections are not pooled with the main client's.
+///
+/// With `tls.ech`, when ECH configurations are looked up for each server, connecting to a server
+/// which has one requires a client with a TLS configuration using it.
+///
+/// The client picks the SNI from the URL, so `tls.serverName` is applied by rewriting the URL to it;
+/// the request's redirects and cookies are then handled here, so that they still apply to the URL as
+/// requested.
+///
+/// Requests with `checksums` need their response body as it was sent, so they go through clients
+/// which don't decompress it, and it's decompressed after the checksums are validated.
+///
+/// These clients are built on first use from the agent options, and kept in a bounded cache.
+///
+/// With `pool.maxConnectionAge`, all requests go through these clients, and they expire from the
+/// cache after that age: new requests then get a new client and so new connections, while the old
+/// client's connections are closed as soon as their in-flight requests complete.
+///
+/// After a network change, all requests go through these clients too, and the clients made until
+/// then are replaced in the same way.
+pub struct PinnedClientMiddleware {
+	options: AgentOptions,
+	cookie_jar: Option<Arc<AgentJar>>,
+	tls: Option<Arc<AgentTls>>,
+	stats: Arc<InnerAgentStats>,
+	server_name: Option<String>,
+	resolver: AgentResolver,
+	block_private_networks: bool,
+	redirects: Redirects,
+	rotating: bool,
+	/// Set once the clients were replaced after a network change.
+	replaced: AtomicBool,
+	clients: Cache<ClientKey, Client>,
+}
+
+/// What a client is made for, which it's kept by.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ClientKey {
+	protocol: Protocol,
+	route: Option<ConnectRoute>,
+	ech: Option<Arc<[u8]>>,
+	/// For requests rewritten to the server name, whose redirects and cookies aren't handled by the
+	/// client.
+	renamed: bool,
+	/// For requests whose body is decompressed by Fáith instead of the client, for `checksums`.
+	undecoded: bool,
+}
+
+/// What to do with the redirects of requests whose URL was rewritten to the server name, from the
+/// agent's `redirect` option.
+#[derive(Debug)]
+enum Redirects {
+	Follow(RedirectRules),
+	Error,
+	Return,
+}
+
+impl std::fmt::Debug for PinnedClientMiddleware {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("PinnedClientMiddleware")
+			.field("server_name", &self.server_name)
+			.field("rotating", &self.rotating)
+			.field("replaced", &self.replaced)
+			.field("clients_count", &self.clients.entry_count())
+			.finish()
+	}
+}
+
+impl PinnedClientMiddleware {
+	pub fn new(
+		options: AgentOptions,
+		cookie_jar: Option<Arc<AgentJar>>,
+		tls: Option<Arc<AgentTls>>,
+		stats: Arc<InnerAgentStats>,
+	) -> Self {
+		let server_name = options.tls.as_ref().and_then(|tls| tls.server_name.clone());
+		let max_age = options
+			.pool
+			.as_ref()
+			.and_then(|pool| pool.max_connection_age)
+			.map(|seconds| Duration::from_secs(seconds.into()));
+
+		let system_dns = options
+			.dns
+			.as_ref()
+			.and_then(|dns| dns.system)
+			.unwrap_or(false);
+		// the order was already checked when building the agent's client
+		let order = options
+			.dns
+			.as_ref()
+			.and_then(|dns| dns.order.as_ref())
+			.and_then(|order| AddressOrder::new(order).ok().flatten());
+		let block_private_networks = options.block_private_networks.unwrap_or(false);
+		let redirects = match options.redirect.unwrap_or_default() {
+			Redirect::Manual if options.strict.unwrap_or(false) => Redirects::Return,
+			Redirect::Follow | Redirect::Manual => Redirects::Follow(RedirectRules::new(&options)),
+			Redirect::Error => Redirects::Error,
+			Redirect::Stop => Redirects::Return,
+		};
+
+		let mut clients = Cache::builder().max_capacity(64);
+		if let Some(max_age) = max_age {
+			clients = clients.time_to_live(max_age);
+		}
+
+		Self {
+			options,
+			cookie_jar,
+			tls,
+			stats,
+			server_name,
+			resolver: AgentResolver::new(system_dns, block_private_networks, order),
+			block_private_networks,
+			redirects,
+			rotating: max_age.is_some(),
+			replaced: AtomicBool::new(false),
+			clients: clients.build(),
+		}
+	}
+
+	/// Replaces all clients, and so their connection pools, from the next request on. This includes
+	/// the agent's main client, which is no longer used afterwards.
+	pub(crate) fn replace_clients(&self) {
+		self.replaced.store(true, Ordering::Relaxed);
+		self.clients.invalidate_all();
+		self.clients.run_pending_tasks();
+	}
+
+	fn client(&self, key: ClientKey) -> std::result::Result<Client, FaithError> {
+		self.clients
+			.try_get_with(key.clone(), || {
+				let ClientKey {
+					protocol,
+					route,
+					ech,
+					renamed,
+					undecoded,
+				} = key;
+				let mut builder = client_builder(
+					&self.options,
+					self.cookie_jar.as_ref().filter(|_| !renamed),
+					self.tls.as_ref(),
+					&self.stats,
+					ech.is_some(),
+				)?;
+				if let Some(ConnectRoute { host, addrs }) = &route {
+					builder = builder.resolve_to_addrs(host, addrs);
+				}
+				if renamed {
+					builder = builder.redirect(Policy::none());
+				}
+				if undecoded {
+					builder = builder.no_gzip().no_brotli().no_deflate().no_zstd();
+				}
+
+				// the builder leaves the ALPN of the agent's own TLS configuration as it is, so it's
+				// set for the pinned protocol here, as is ECH
+				let tls = |alpn| {
+					self.tls
+						.as_ref()
+						.map(|tls| match &ech {
+							Some(list) => tls.client_config_with_ech(alpn, list),
+							None => Ok(tls.client_config(alpn)),
+						})
+						.transpose()
+				};
+				match protocol {
+					Protocol::Any if ech.is_some() => match tls(default_alpn(&self.options))? {
+						Some(config) => builder.use_preconfigured_tls(config),
+						None => builder,
+					},
+					Protocol::Any => builder,
+					Protocol::Http1 => match tls(ALPN_HTTP1)? {
+						Some(config) => builder.http1_only().use_preconfigured_tls(config),
+						None => builder.http1_only(),
+					},
+					Protocol::Http2 => match tls(ALPN_HTTP2)? {
+						Some(config) => builder
+							.http2_prior_knowledge()
+							.use_preconfigured_tls(config),
+						None => builder.http2_prior_knowledge(),
+					},
+				}
+				.build()
+				.map_err(|e| FaithError::new(FaithErrorKind::Config, Some(format!("{e:?}"))))
+			})
+			.map_err(|err| FaithError::clone(&err))
+	}
+
+	/// Works out whether the request needs to connect somewhere else than its URL's host resolves to.
+	///
+	/// With `connectTo`, the route is to that address, for the URL's host. If `tls.serverName` applies
+	/// to this request, the URL is rewritten to use the server name, so that it is used for SNI and
+	/// certificate validation, and the route is to the `connectTo` address or else the addresses of the
+	/// original host, resolved like the agent's client would (with `dns.overrides` and the agent's
+	/// resolver); the original URL is returned too, and the `Host` header is set to the original host
+	/// if not present.
+	///
+	/// With `blockPrivateNetworks`, all the addresses of the route are checked.
+	async fn route(
+		&self,
+		req: &mut Request,
+		connect_to: Option<SocketAddr>,
+	) -> std::result::Result<Option<(ConnectRoute, Option<Url>)>, FaithError> {
+		let original = req.url().clone();
+		let Some(server_name) = self.server_name.as_ref().filter(|name| {
+			original.scheme() == "https" && original.host_str() != Some(name.as_str())
+		}) else {
+			let Some(addr) = connect_to else {
+				return Ok(None);
+			};
+
+			let Some(domain) = original.domain() else {
+				return Err(FaithError::new(
+					FaithErrorKind::Config,
+					Some("connectTo requires the URL's host to be a domain name"),
+				));
+			};
+
+			self.check_addresses(&[addr])?;
+			return Ok(Some((
+				ConnectRoute {
+					host: domain.into(),
+					addrs: vec![addr],
+				},
+				None,
+			)));
+		};
+
+		let (Some(host), Some(port)) = (original.host_str(), original.port_or_known_default())
+		else {
+			return Ok(None);
+		};
+
+		let addrs = match connect_to {
+			Some(addr) => vec![addr],
+			None => self.resolve(host, port).await?,
+		};
+		self.check_addresses(&addrs)?;
+
+		if !req.headers().contains_key(HOST) {
+			let authority = match original.port() {
+				Some(port) => format!("{host}:{port}"),
+				None => host.to_string(),
+			};
+			if let Ok(value) = HeaderValue::from_str(&authority) {
+				req.headers_mut().insert(HOST, value);
+			}
+		}
+
+		req.url_mut().set_host(Some(server_name)).map_err(|err| {
+			FaithError::new(
+				FaithErrorKind::Config,
+				Some(format!("tls.serverName: {err}")),
+			)
+		})?;
+
+		Ok(Some((
+			ConnectRoute {
+				host: server_name.clone(),
+				addrs,
+			},
+			Some(original),
+		)))
+	}
+
+	/// Resolves a host to the addresses the agent's client would connect to for it.
+	async fn resolve(
+		&self,
+		host: &str,
+		port: u16,
+	) -> std::result::Result<Vec<SocketAddr>, FaithError> {
+		let ip = host
+			.trim_start_matches('[')
+			.trim_end_matches(']')
+			.parse::<IpAddr>();
+		if let Ok(ip) = ip {
+			return Ok(vec![SocketAddr::new(ip, port)]);
+		}
+
+		let with_port = |addr: SocketAddr| match addr.port() {
+			0 => SocketAddr::new(addr.ip(), port),
+			_ => addr,
+		};
+
+		let overrides = self
+			.options
+			.dns
+			.as_ref()
+			.filter(|dns| !dns.system.unwrap_or(false))
+			.and_then(|dns| dns.overrides.as_ref());
+		if let Some(DnsOverride { addresses, .. }) = overrides
+			.into_iter()
+			.flatten()
+			.find(|entry| entry.domain.eq_ignore_ascii_case(host))
+		{
+			return addresses
+				.iter()
+				.map(|addr| parse_address(addr).map(with_port))
+				.collect();
+		}
+
+		let failed = |err: &dyn std::fmt::Display| {
+			FaithError::new(
+				FaithErrorKind::Network,
+				Some(format!("failed to resolve {host}: {err}")),
+			)
+		};
+		let name = Name::from_str(host).map_err(|err| failed(&err))?;
+		let addrs = self.resolver.resolve(name).await.map_err(|err| {
+			match err.downcast::<FaithError>() {
+				Ok(err) => *err,
+				Err(err) => failed(&err),
+			}
+		})?;
+		Ok(addrs.map(with_port).collect())
+	}
+
+	/// Returns a `ForbiddenAddress` error for a private address, with `blockPrivateNetworks`.
+	fn check_addresses(&self, addrs: &[SocketAddr]) -> std::result::Result<(), FaithError> {
+		if self.block_private_networks {
+			for addr in addrs {
+				check_address(addr.ip())?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Sends a request whose URL was rewritten to the server name.
+	///
+	/// Its client would follow redirects from, and store cookies for, the rewritten URL, so it's made
+	/// without doing either. Cookies are sent and stored here instead for the URL as requested, and
+	/// redirects are followed from it, like the agent's redirect policy would, with each new URL routed
+	/// again.
+	async fn send_renamed(
+		&self,
+		mut req: Request,
+		mut url: Url,
+		mut key: ClientKey,
+		connect_to: Option<SocketAddr>,
+		own_host: bool,
+	) -> Result<Response> {
+		let middleware = reqwest_middleware::Error::middleware;
+		let connect_host = url.host_str().map(str::to_owned);
+		let mut previous: Vec<Url> = Vec::new();
+		loop {
+			if let Some(jar) = &self.cookie_jar
+				&& (!previous.is_empty() || !req.headers().contains_key(COOKIE))
+				&& let Some(cookies) = jar.cookies(&url)
+			{
+				req.headers_mut().insert(COOKIE, cookies);
+			}
+
+			key.ech = match &self.tls {
+				Some(tls) => tls.ech_config_list(req.url()).await,
+				None => None,
+			};
+			let client = self.client(key.clone()).map_err(middleware)?;
+			let replay = req.try_clone();
+			let (method, mut headers, version, timeout) = (
+				req.method().clone(),
+				req.headers().clone(),
+				req.version(),
+				req.timeout().copied(),
+			);
+			let mut response = client
+				.execute(req)
+				.await
+				.map_err(reqwest_middleware::Error::Reqwest)?;
+
+			// present the response as coming from the URL that was requested, not the server name
+			*response.url_mut() = url.clone();
+			if let Some(jar) = &self.cookie_jar {
+				jar.set_cookies(&mut response.headers().get_all(SET_COOKIE).iter(), &url);
+			}
+
+			let status = response.status();
+			let location = response
+				.headers()
+				.get(LOCATION)
+				.and_then(|location| location.to_str().ok())
+				.and_then(|location| url.join(location).ok());
+			let (
+				StatusCode::MOVED_PERMANENTLY
+				| StatusCode::FOUND
+				| StatusCode::SEE_OTHER
+				| StatusCode::TEMPORARY_REDIRECT
+				| StatusCode::PERMANENT_REDIRECT,
+				Some(next),
+			) = (status, location)
+			else {
+				return Ok(response);
+			};
+
+			let rules = match &self.redirects {
+				Redirects::Return => return Ok(response),
+				Redirects::Error => {
+					return Err(middleware(FaithError::from(FaithErrorKind::Redirect)));
+				}
+				Redirects::Follow(rules) => rules,
+			};
+
+			previous.push(url.clone());
+			rules.check(&previous, &next).map_err(middleware)?;
+
+			let mut next_req = match status {
+				StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT => {
+					// returned as it is, like reqwest does, when the body can't be sent again
+					let Some(replay) = replay else {
+						return Ok(response);
+					};
+					replay
+				}
+				_ => {
+					for name in [
+						TRANSFER_ENCODING,
+						CONTENT_ENCODING,
+						CONTENT_TYPE,
+						CONTENT_LENGTH,
+					] {
+						headers.remove(name);
+					}
+					let method = match method {
+						Method::GET | Method::HEAD => method,
+						_ => Method::GET,
+					};
+					let mut next_req = Request::new(method, next.clone());
+					*next_req.headers_mut() = headers;
+					*next_req.version_mut() = version;
+					*next_req.timeout_mut() = timeout;
+					next_req
+				}
+			};
+
+			self.stats.redirects.fetch_add(1, Ordering::Relaxed);
+			history::push(&url, status, &next, previous.len() == 1);
+
+			*next_req.url_mut() = next.clone();
+			let headers = next_req.headers_mut();
+			if own_host {
+				headers.remove(HOST);
+			}
+			if next.origin() != url.origin() {
+				for name in [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, WWW_AUTHENTICATE] {
+					headers.remove(name);
+				}
+			}
+			if !(url.scheme() == "https" && next.scheme() == "http") {
+				let mut referer = url.clone();
+				let _ = referer.set_username("");
+				let _ = referer.set_password(None);
+				referer.set_fragment(None);
+				if let Ok(value) = HeaderValue::from_str(referer.as_str()) {
+					headers.insert(REFERER, value);
+				}
+			}
+
+			let connect_to = connect_to.filter(|_| next.host_str() == connect_host.as_deref());
+			key.route = self
+				.route(&mut next_req, connect_to)
+				.await
+				.map_err(middleware)?
+				.map(|(route, _)| route);
+			req = next_req;
+			url = next;
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl Middleware for PinnedClientMiddleware {
+	async fn handle(
+		&self,
+		mut req: Request,
+		extensions: &mut Extensions,
+		next: Next<'_>,
+	) -> Result<Response> {
+		let pin = extensions.get::<VersionOption>().copied();
+		let connect_to = extensions.get::<ConnectTo>().map(|ConnectTo(addr)| *addr);
+		let own_host = !req.headers().contains_key(HOST);
+		let routed = self
+			.route(&mut req, connect_to)
+			.await
+			.map_err(reqwest_middleware::Error::middleware)?;
+
+		let protocol = match pin {
+			None => None,
+			Some(VersionOption::H3) => {
+				*req.version_mut() = Version::HTTP_3;
+				None
+			}
+			Some(VersionOption::Http11) => {
+				if req.version() != Version::HTTP_10 {
+					*req.version_mut() = Version::HTTP_11;
+				}
+				Some(Protocol::Http1)
+			}
+			Some(VersionOption::H2) => {
+				*req.version_mut() = Version::HTTP_2;
+				Some(Protocol::Http2)
+			}
+		};
+
+		let undecoded = extensions.get::<Undecoded>().is_some();
+		if undecoded
+			&& !req.headers().contains_key(ACCEPT_ENCODING)
+			&& let Some(accept) = accept_encoding()
+		{
+			req.headers_mut().insert(ACCEPT_ENCODING, accept);
+		}
+
+		let protocol = protocol.unwrap_or(Protocol::Any);
+		if let Some((route, Some(url))) = routed {
+			let key = ClientKey {
+				protocol,
+				route: Some(route),
+				ech: None,
+				renamed: true,
+				undecoded,
+			};
+			return self.send_renamed(req, url, key, connect_to, own_host).await;
+		}
+
+		let ech = match &self.tls {
+			Some(tls) => tls.ech_config_list(req.url()).await,
+			None => None,
+		};
+
+		if protocol == Protocol::Any
+			&& routed.is_none()
+			&& ech.is_none()
+			&& !undecoded
+			&& !self.rotating
+			&& !self.replaced.load(Ordering::Relaxed)
+		{
+			return next.run(req, extensions).await;
+		}
+
+		let client = self
+			.client(ClientKey {
+				protocol,
+				route: routed.map(|(route, _)| route),
+				ech,
+				renamed: false,
+				undecoded,
+			})
+			.map_err(reqwest_middleware::Error::middleware)?;
+		client
+			.execute(req)
+			.await
+			.map_err(reqwest_middleware::Error::Reqwest)
+	}
+}
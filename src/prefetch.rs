@@ -0,0 +1,119 @@
+use std::{
+	collections::HashMap,
+	result::Result,
+	sync::{Arc, Mutex},
+};
+
+use futures::{
+	FutureExt,
+	future::{BoxFuture, Shared},
+};
+use napi::Env;
+use napi_derive::napi;
+use tokio::sync::Semaphore;
+
+use crate::{
+	agent::Agent,
+	async_task::{Async, FaithAsyncResult},
+	error::{FaithError, FaithErrorKind},
+	fetch::{prepare, send},
+	options::{FaithOptions, FaithOptionsAndBody},
+};
+
+type Prefetch = Shared<BoxFuture<'static, Result<(), FaithError>>>;
+
+/// The prefetches of an agent, for `agent.prefetch()`.
+///
+/// At most `cache.prefetchConcurrency` prefetches are in flight at once, so that they don't
+/// compete with the agent's other requests, and a prefetch of a URL which is already being
+/// prefetched joins it instead of making another request.
+#[derive(Debug)]
+pub(crate) struct Prefetcher {
+	permits: Semaphore,
+	in_flight: Mutex<HashMap<String, Prefetch>>,
+}
+
+impl Prefetcher {
+	pub(crate) fn new(concurrency: u32) -> Self {
+		Self {
+			permits: Semaphore::new(concurrency.max(1) as usize),
+			in_flight: Default::default(),
+		}
+	}
+
+	fn start(self: &Arc<Self>, url: &str, options: FaithOptions, agent: &Agent) -> Prefetch {
+		let prepared = match prepare(url, &options, agent) {
+			Ok(prepared) => prepared,
+			Err(err) => return futures::future::ready(Err(err)).boxed().shared(),
+		};
+
+		let key = prepared.url.to_string();
+		let mut in_flight = self.in_flight.lock().unwrap_or_else(|err| err.into_inner());
+		if let Some(prefetch) = in_flight.get(&key) {
+			return prefetch.clone();
+		}
+
+		let this = self.clone();
+		let agent = agent.clone();
+		let prefetch = {
+			let key = key.clone();
+			async move {
+				let result = async {
+					let _permit = this.permits.acquire().await;
+					let response = send(prepared, options, agent, None, None, None).await?;
+					// the response is only stored in the cache once its body has been read
+					response.read_bytes().await.map(drop)
+				}
+				.await;
+				this.in_flight
+					.lock()
+					.unwrap_or_else(|err| err.into_inner())
+					.remove(&key);
+				result
+			}
+		}
+		.boxed()
+		.shared();
+		in_flight.insert(key, prefetch.clone());
+		prefetch
+	}
+}
+
+#[napi]
+impl Agent {
+	/// Custom to Fáith.
+	///
+	/// Fetches a resource into the agent's HTTP cache, without returning the response, so that a
+	/// later request for it is answered quickly. Resolves once the response is stored, or rejects if
+	/// it couldn't be fetched.
+	///
+	/// This takes the same options as `fetch()`, except that the method is always `GET`, and bodies
+	/// are not supported. Prefetches are low priority: at most `cache.prefetchConcurrency` of them
+	/// are in flight at once, and the others wait their turn. Prefetching a URL which is already
+	/// being prefetched joins that prefetch instead of fetching it again.
+	///
+	/// The agent must have a `cache.store`, and whether the response is stored follows the usual
+	/// caching rules, like its `Cache-Control` header.
+	#[napi]
+	pub fn prefetch(
+		&self,
+		env: Env,
+		url: String,
+		options: Option<FaithOptionsAndBody>,
+	) -> Result<Async<()>, napi::Error> {
+		if !self.http_cache {
+			return Err(FaithError::new(
+				FaithErrorKind::Config,
+				Some("agent.prefetch() needs a cache.store"),
+			)
+			.into_js_error(&env)
+			.into());
+		}
+
+		let (mut options, _, _) = FaithOptions::extract(options.unwrap_or_default());
+		options.method = None;
+		options.json = None;
+		let prefetch = self.prefetcher.start(&url, options, self);
+		Ok(FaithAsyncResult::run(async move || prefetch.await))
+	}
+}
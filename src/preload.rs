@@ -0,0 +1,141 @@
+use std::{
+	collections::HashSet,
+	sync::{Arc, Mutex},
+};
+
+use napi::Either;
+use reqwest::{
+	Url,
+	header::{HeaderMap, LINK},
+};
+use tokio::sync::Semaphore;
+
+use crate::{
+	agent::{Agent, AgentCachePreloadOptions},
+	fetch::fetch,
+	options::FaithOptions,
+	runtime,
+};
+
+/// Preloads the resources of responses' `Link: rel=preload` headers into the HTTP cache, in the
+/// background, for `cache.preload`.
+///
+/// Preloads are shared by all requests of the agent: at most `concurrency` are in flight at once,
+/// and a URL which is already being preloaded isn't preloaded again until it's done.
+#[derive(Debug)]
+pub(crate) struct Preloader {
+	permits: Semaphore,
+	cross_origin: bool,
+	in_flight: Mutex<HashSet<String>>,
+}
+
+impl Preloader {
+	pub(crate) fn new(options: &Either<bool, AgentCachePreloadOptions>) -> Option<Self> {
+		let options = match options {
+			Either::A(false) => return None,
+			Either::A(true) => AgentCachePreloadOptions::default(),
+			Either::B(options) => options.clone(),
+		};
+		Some(Self {
+			permits: Semaphore::new(options.concurrency.unwrap_or(4).max(1) as usize),
+			cross_origin: options.cross_origin.unwrap_or(false),
+			in_flight: Default::default(),
+		})
+	}
+
+	/// Starts preloading the resources linked from a response, without waiting for them.
+	///
+	/// The preload requests are plain `GET` requests made with the agent, so they go through its
+	/// cache like any other request. Their bodies are read in full so that they can be stored, and
+	/// failures are ignored.
+	pub(crate) fn harvest(self: &Arc<Self>, agent: &Agent, base: &Url, headers: &HeaderMap) {
+		let links = headers
+			.get_all(LINK)
+			.iter()
+			.filter_map(|value| value.to_str().ok())
+			.flat_map(preload_links)
+			.filter_map(|target| base.join(target).ok())
+			.filter(|url| matches!(url.scheme(), "http" | "https"))
+			.filter(|url| self.cross_origin || url.origin() == base.origin());
+
+		for mut url in links {
+			url.set_fragment(None);
+			if !self
+				.in_flight
+				.lock()
+				.unwrap_or_else(|err| err.into_inner())
+				.insert(url.to_string())
+			{
+				continue;
+			}
+
+			let this = self.clone();
+			let agent = agent.clone();
+			runtime::spawn(
+				async move {
+					let result = match this.permits.acquire().await {
+						Ok(_permit) => {
+							let options = FaithOptions {
+								preload: true,
+								..Default::default()
+							};
+							match fetch(url.to_string(), options, agent, None, None, None).await {
+								Ok(response) => response.read_bytes().await.map(drop),
+								Err(err) => Err(err),
+							}
+						}
+						Err(_) => Ok(()),
+					};
+					this.in_flight
+						.lock()
+						.unwrap_or_else(|err| err.into_inner())
+						.remove(url.as_str());
+					result
+				},
+				|_| {},
+			);
+		}
+	}
+}
+
+/// Returns the targets of the `rel=preload` links in a `Link` header value (RFC 8288).
+fn preload_links(value: &str) -> Vec<&str> {
+	let mut links = Vec::new();
+	let mut rest = value;
+	while let Some(start) = rest.find('<') {
+		let Some(end) = rest[start..].find('>').map(|end| start + end) else {
+			break;
+		};
+		let target = &rest[start + 1..end];
+		rest = &rest[end + 1..];
+
+		// the link's parameters run until the next comma which isn't in a quoted string
+		let mut quoted = false;
+		let params_end = rest
+			.char_indices()
+			.find(|&(_, c)| {
+				if c == '"' {
+					quoted = !quoted;
+				}
+				c == ',' && !quoted
+			})
+			.map_or(rest.len(), |(i, _)| i);
+		let params = &rest[..params_end];
+		rest = &rest[params_end..];
+
+		let preload = params.split(';').any(|param| {
+			param.split_once('=').is_some_and(|(name, value)| {
+				name.trim().eq_ignore_ascii_case("rel")
+					&& value
+						.trim()
+						.trim_matches('"')
+						.split_ascii_whitespace()
+						.any(|rel| rel.eq_ignore_ascii_case("preload"))
+			})
+		});
+		if preload {
+			links.push(target.trim());
+		}
+	}
+	links
+}
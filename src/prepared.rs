@@ -0,0 +1,83 @@
+use std::result::Result;
+
+use bytes::Bytes;
+use napi::{Env, bindgen_prelude::AbortSignal};
+use napi_derive::napi;
+
+use crate::{
+	agent::Agent,
+	async_task::{Async, FaithAsyncResult},
+	fetch::{BodyInit, Prepared, abort_receiver, prepare, send},
+	options::{FaithOptions, FaithOptionsAndBody},
+	response::FaithResponse,
+};
+
+/// Custom to Fáith.
+///
+/// A request whose URL, method and headers are parsed and validated once, by `agent.prepare()`,
+/// and which can then be sent any number of times. Only the body can vary between sends.
+///
+/// This saves the per-call work of `fetch()` for requests made at a high rate, like pollers.
+#[napi]
+pub struct PreparedRequest {
+	prepared: Prepared,
+	options: FaithOptions,
+	body: Option<Bytes>,
+	agent: Agent,
+}
+
+#[napi]
+impl PreparedRequest {
+	/// The request method, normalised to uppercase.
+	#[napi(getter)]
+	pub fn method(&self) -> String {
+		self.prepared.method.to_string()
+	}
+
+	/// The request URL, after parsing and the agent's `url` and `upgradeInsecureRequests` settings.
+	#[napi(getter)]
+	pub fn url(&self) -> String {
+		self.prepared.url.to_string()
+	}
+
+	/// Sends the request. The `body` argument takes precedence over the `body` given to
+	/// `agent.prepare()`.
+	#[napi]
+	pub fn send(
+		&self,
+		body: Option<BodyInit>,
+		signal: Option<AbortSignal>,
+	) -> Async<FaithResponse> {
+		let prepared = self.prepared.clone();
+		let options = self.options.clone();
+		let body = body.map(FaithOptions::body).or_else(|| self.body.clone());
+		let agent = self.agent.clone();
+		let abort = abort_receiver(signal.as_ref());
+
+		FaithAsyncResult::run(async move || send(prepared, options, agent, body, None, abort).await)
+	}
+}
+
+#[napi]
+impl Agent {
+	/// Prepares a request with this agent, to be sent later with `.send()`, possibly many times.
+	///
+	/// This takes the same options as `fetch()`, except that `agent` is ignored, and streaming
+	/// bodies are not supported. Throws if the URL, method or headers are invalid.
+	#[napi]
+	pub fn prepare(
+		&self,
+		env: Env,
+		url: String,
+		options: Option<FaithOptionsAndBody>,
+	) -> Result<PreparedRequest, napi::Error> {
+		let (options, _, body) = FaithOptions::extract(options.unwrap_or_default());
+		let prepared = prepare(&url, &options, self).map_err(|err| err.into_js_error(&env))?;
+		Ok(PreparedRequest {
+			prepared,
+			options,
+			body,
+			agent: self.clone(),
+		})
+	}
+}
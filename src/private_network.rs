@@ -0,0 +1,74 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use reqwest::Url;
+
+use crate::error::{FaithError, FaithErrorKind};
+
+/// Whether the address is one that `blockPrivateNetworks` refuses to connect to: loopback,
+/// unspecified, private (RFC 1918 and IPv6 unique local), link-local (which includes the metadata
+/// services of most cloud providers), shared (RFC 6598, which includes Alibaba's metadata service),
+/// and broadcast addresses, including when embedded in IPv4-mapped or NAT64 IPv6 addresses.
+pub(crate) fn is_private(ip: IpAddr) -> bool {
+	match ip {
+		IpAddr::V4(ip) => is_private_v4(ip),
+		IpAddr::V6(ip) => is_private_v6(ip),
+	}
+}
+
+fn is_private_v4(ip: Ipv4Addr) -> bool {
+	let [a, b, ..] = ip.octets();
+	ip.is_loopback()
+		|| ip.is_unspecified()
+		|| ip.is_private()
+		|| ip.is_link_local()
+		|| ip.is_broadcast()
+		|| a == 0
+		|| (a == 100 && (b & 0xc0) == 64)
+}
+
+fn is_private_v6(ip: Ipv6Addr) -> bool {
+	if let Some(ip) = ip.to_ipv4_mapped() {
+		return is_private_v4(ip);
+	}
+
+	let segments = ip.segments();
+	if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+		let [.., a, b, c, d] = ip.octets();
+		return is_private_v4(Ipv4Addr::new(a, b, c, d));
+	}
+
+	ip.is_loopback()
+		|| ip.is_unspecified()
+		|| (segments[0] & 0xfe00) == 0xfc00
+		|| (segments[0] & 0xffc0) == 0xfe80
+}
+
+/// Returns a `ForbiddenAddress` error if the address is private.
+pub(crate) fn check_address(ip: IpAddr) -> Result<(), FaithError> {
+	if is_private(ip) {
+		Err(FaithError::new(
+			FaithErrorKind::ForbiddenAddress,
+			Some(format!("{ip} is a private network address")),
+		))
+	} else {
+		Ok(())
+	}
+}
+
+/// Returns a `ForbiddenAddress` error if the URL's host is a private IP address.
+///
+/// Domain names are checked when they're resolved instead, by [`AgentResolver`].
+///
+/// [`AgentResolver`]: crate::resolver::AgentResolver
+pub(crate) fn check_url(url: &Url) -> Result<(), FaithError> {
+	let ip = url.host_str().and_then(|host| {
+		host.trim_start_matches('[')
+			.trim_end_matches(']')
+			.parse()
+			.ok()
+	});
+	match ip {
+		Some(ip) => check_address(ip),
+		None => Ok(()),
+	}
+}
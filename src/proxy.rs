@@ -0,0 +1,85 @@
+use http::{Uri, uri::Scheme};
+use hyper_util::client::proxy::matcher::{Builder, Matcher};
+use napi::bindgen_prelude::Either;
+use reqwest::Url;
+
+use crate::agent::{AgentOptions, ProxyMode};
+
+/// Finds which proxy, if any, the agent's client uses for a URL, to report it on the response.
+///
+/// The client doesn't expose its proxy decisions, so this mirrors how it's configured from the
+/// agent's `proxy` option: the same matchers, built from the same settings, in the same order.
+#[derive(Debug)]
+pub(crate) struct ProxyMatcher {
+	matchers: Vec<Matcher>,
+}
+
+/// The proxy used for a request.
+#[derive(Debug, Clone)]
+pub(crate) struct ProxyDecision {
+	/// The proxy's URL, without credentials.
+	pub(crate) url: String,
+	/// Whether the connection is tunneled through the proxy with `CONNECT`.
+	pub(crate) tunnel: bool,
+}
+
+impl ProxyMatcher {
+	/// Returns `None` if proxies are disabled, or decided by the app for each request.
+	pub(crate) fn new(options: &AgentOptions) -> Option<Self> {
+		let matchers = match &options.proxy {
+			None | Some(Either::A(ProxyMode::System)) => vec![Matcher::from_system()],
+			Some(Either::A(ProxyMode::None | ProxyMode::Resolver)) => return None,
+			Some(Either::B(proxy)) => {
+				let no_proxy = proxy
+					.no_proxy
+					.as_ref()
+					.map(|hosts| hosts.join(","))
+					.unwrap_or_default();
+
+				[
+					(
+						&proxy.http,
+						Builder::http::<String> as fn(Builder, String) -> Builder,
+					),
+					(&proxy.https, Builder::https),
+					(&proxy.url, Builder::all),
+				]
+				.into_iter()
+				.filter_map(|(url, set)| {
+					Some(set(Matcher::builder().no(no_proxy.clone()), url.clone()?).build())
+				})
+				.collect()
+			}
+		};
+
+		Some(Self { matchers })
+	}
+
+	pub(crate) fn decide(&self, url: &Url) -> Option<ProxyDecision> {
+		let uri: Uri = url.as_str().parse().ok()?;
+		let intercept = self
+			.matchers
+			.iter()
+			.find_map(|matcher| matcher.intercept(&uri))?;
+
+		Some(ProxyDecision::new(intercept.uri(), &uri))
+	}
+}
+
+impl ProxyDecision {
+	fn new(proxy: &Uri, target: &Uri) -> Self {
+		let is_http = matches!(proxy.scheme(), Some(scheme) if *scheme == Scheme::HTTP || *scheme == Scheme::HTTPS);
+		Self {
+			url: proxy.to_string(),
+			tunnel: is_http && target.scheme() == Some(&Scheme::HTTPS),
+		}
+	}
+
+	/// The decision for a request to the URL through a proxy given by the app's resolver.
+	pub(crate) fn resolved(proxy: &Url, url: &Url) -> Option<Self> {
+		Some(Self::new(
+			&proxy.as_str().parse().ok()?,
+			&url.as_str().parse().ok()?,
+		))
+	}
+}
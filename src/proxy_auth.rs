@@ -0,0 +1,103 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
+use http::{
+	Extensions, HeaderValue, StatusCode,
+	header::{PROXY_AUTHENTICATE, PROXY_AUTHORIZATION},
+};
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next, Result};
+
+use crate::{
+	agent::AgentProxyAuth,
+	error::{FaithError, FaithErrorKind},
+};
+
+/// Answers `407 Proxy Authentication Required` responses with the agent's `proxyAuth` credentials.
+///
+/// This only sees the responses to requests forwarded by a proxy, i.e. to `http://` URLs. For
+/// `https://` URLs, the proxy answers the `CONNECT` which opens the tunnel instead, and a 407 there
+/// is an error from the connector. Proxies given in the agent's `proxy` option have the credentials
+/// attached so they're sent upfront in both cases, and then a 407 means they were rejected.
+#[derive(Debug)]
+pub struct ProxyAuthMiddleware {
+	authorization: HeaderValue,
+	upfront: bool,
+}
+
+impl ProxyAuthMiddleware {
+	pub fn new(auth: &AgentProxyAuth, upfront: bool) -> Self {
+		let credentials = BASE64_STANDARD.encode(format!("{}:{}", auth.username, auth.password));
+		let mut authorization = HeaderValue::try_from(format!("Basic {credentials}"))
+			.expect("base64 is a valid header value");
+		authorization.set_sensitive(true);
+		Self {
+			authorization,
+			upfront,
+		}
+	}
+}
+
+/// Whether the proxy accepts Basic authentication, which is the only scheme supported for now.
+fn offers_basic(response: &Response) -> bool {
+	response
+		.headers()
+		.get_all(PROXY_AUTHENTICATE)
+		.iter()
+		.filter_map(|value| value.to_str().ok())
+		.any(|value| {
+			value
+				.trim_start()
+				.get(..5)
+				.is_some_and(|scheme| scheme.eq_ignore_ascii_case("basic"))
+		})
+}
+
+fn rejected() -> reqwest_middleware::Error {
+	reqwest_middleware::Error::middleware(FaithError::new(
+		FaithErrorKind::ProxyAuth,
+		Some("the proxy rejected the credentials in proxyAuth"),
+	))
+}
+
+#[async_trait::async_trait]
+impl Middleware for ProxyAuthMiddleware {
+	async fn handle(
+		&self,
+		req: Request,
+		extensions: &mut Extensions,
+		next: Next<'_>,
+	) -> Result<Response> {
+		let retry = if self.upfront { None } else { req.try_clone() };
+
+		let response = next.clone().run(req, extensions).await?;
+		if response.status() != StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+			return Ok(response);
+		}
+
+		if self.upfront {
+			return Err(rejected());
+		}
+
+		// Without the Basic scheme, the 407 is returned as it is
+		if !offers_basic(&response) {
+			return Ok(response);
+		}
+
+		// A streamed body was sent as it came, so the request can't be sent again in full
+		let Some(mut retry) = retry else {
+			return Err(reqwest_middleware::Error::middleware(FaithError::new(
+				FaithErrorKind::BodyNotReplayable,
+				Some("can't answer the proxy's 407 without sending the streamed body again"),
+			)));
+		};
+
+		retry
+			.headers_mut()
+			.insert(PROXY_AUTHORIZATION, self.authorization.clone());
+		let response = next.run(retry, extensions).await?;
+		if response.status() == StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+			return Err(rejected());
+		}
+
+		Ok(response)
+	}
+}
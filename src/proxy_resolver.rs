@@ -0,0 +1,181 @@
+use std::{
+	fmt::Debug,
+	future::Future,
+	sync::{Arc, RwLock},
+};
+
+use napi::{
+	Either, Status,
+	bindgen_prelude::{Function, Promise},
+	threadsafe_function::ThreadsafeFunction,
+};
+use reqwest::{Proxy, Url};
+
+use crate::{
+	agent::AgentProxyAuth,
+	error::{FaithError, FaithErrorKind},
+};
+
+pub(crate) type ProxyResolverCallback =
+	ThreadsafeFunction<String, Either<String, Promise<String>>, String, Status, false, true>;
+
+/// Asks the app which proxy to use for each request, for agents with `proxy: "resolver"`.
+///
+/// The client's proxy can't wait for an answer from JS, so the request is only sent once it has
+/// one, and the client's proxy then reads it back from the request's task.
+#[derive(Default)]
+pub(crate) struct ProxyResolver(RwLock<Option<Arc<ProxyResolverCallback>>>);
+
+impl Debug for ProxyResolver {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let set = self.0.read().map(|cb| cb.is_some()).unwrap_or_default();
+		f.debug_tuple("ProxyResolver").field(&set).finish()
+	}
+}
+
+tokio::task_local! {
+	/// The proxy decided for the request being made, or `None` to connect directly.
+	static ROUTE: Option<Url>;
+}
+
+impl ProxyResolver {
+	/// Sets the callback which decides the proxy for each request, or removes it.
+	pub(crate) fn set(
+		&self,
+		callback: Option<Function<String, Either<String, Promise<String>>>>,
+	) -> napi::Result<()> {
+		let tsfn = callback
+			.map(|callback| {
+				callback
+					.build_threadsafe_function()
+					.callee_handled::<false>()
+					.weak::<true>()
+					.build()
+					.map(Arc::new)
+			})
+			.transpose()?;
+		*self.0.write().unwrap_or_else(|err| err.into_inner()) = tsfn;
+		Ok(())
+	}
+
+	/// Asks the callback for the proxy to use for a request to the URL, if one is set.
+	pub(crate) async fn resolve(&self, url: &Url) -> Result<Option<Url>, FaithError> {
+		let callback = self.0.read().unwrap_or_else(|err| err.into_inner()).clone();
+		let Some(callback) = callback else {
+			return Ok(None);
+		};
+
+		let failed = |err: napi::Error| {
+			FaithError::new(
+				FaithErrorKind::Proxy,
+				Some(format!("proxy resolver failed: {}", err.reason)),
+			)
+		};
+		let answer = match callback.call_async(url.to_string()).await.map_err(failed)? {
+			Either::A(answer) => answer,
+			Either::B(promise) => promise.await.map_err(failed)?,
+		};
+		parse_answer(&answer)
+	}
+}
+
+/// Parses the proxy to use from a PAC-style answer, like `PROXY host:port; DIRECT`, or a URL.
+fn parse_answer(answer: &str) -> Result<Option<Url>, FaithError> {
+	let invalid = |reason: &str| {
+		FaithError::new(
+			FaithErrorKind::Proxy,
+			Some(format!("proxy resolver answered {answer:?}: {reason}")),
+		)
+	};
+
+	for entry in answer
+		.split(';')
+		.map(str::trim)
+		.filter(|entry| !entry.is_empty())
+	{
+		let url = if entry.contains("://") {
+			entry.to_owned()
+		} else {
+			let (kind, host) = entry
+				.split_once(char::is_whitespace)
+				.map_or((entry, ""), |(kind, host)| (kind, host.trim()));
+			let scheme = match kind.to_ascii_uppercase().as_str() {
+				"DIRECT" => return Ok(None),
+				"PROXY" | "HTTP" => "http",
+				"HTTPS" => "https",
+				"SOCKS" | "SOCKS4" | "SOCKS5" => continue,
+				_ => return Err(invalid(&format!("unknown entry {entry:?}"))),
+			};
+			if host.is_empty() {
+				return Err(invalid(&format!("no host in {entry:?}")));
+			}
+			format!("{scheme}://{host}")
+		};
+
+		let url = Url::parse(&url).map_err(|err| invalid(&err.to_string()))?;
+		return match url.scheme() {
+			"http" | "https" => Ok(Some(url)),
+			_ => continue,
+		};
+	}
+
+	Err(invalid("no supported proxy"))
+}
+
+/// Runs a request with the proxy decided for it, which the client's proxy connects through.
+///
+/// Like the observer, this is only seen by connections made within the request's future.
+pub(crate) async fn with_route<F: Future>(route: Option<Url>, request: F) -> F::Output {
+	ROUTE.scope(route, request).await
+}
+
+/// The client's proxy for agents with `proxy: "resolver"`, which goes wherever the current request
+/// was routed, with the agent's `proxyAuth` credentials unless the proxy's URL has its own.
+pub(crate) fn proxy(auth: Option<&AgentProxyAuth>) -> Proxy {
+	let auth = auth.cloned();
+	Proxy::custom(move |_| {
+		let mut route = ROUTE.try_with(Clone::clone).ok().flatten()?;
+		if let Some(auth) = &auth
+			&& route.username().is_empty()
+		{
+			let _ = route.set_username(&auth.username);
+			let _ = route.set_password(Some(&auth.password));
+		}
+		Some(route)
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(answer: &str) -> Option<String> {
+		parse_answer(answer)
+			.unwrap()
+			.map(|url| url.as_str().to_owned())
+	}
+
+	#[test]
+	fn pac_answers() {
+		assert_eq!(parse("DIRECT"), None);
+		assert_eq!(parse("PROXY proxy:8080"), Some("http://proxy:8080/".into()));
+		assert_eq!(parse("HTTPS proxy:443"), Some("https://proxy/".into()));
+		assert_eq!(
+			parse("SOCKS5 socks:1080; PROXY proxy:8080; DIRECT"),
+			Some("http://proxy:8080/".into())
+		);
+		assert_eq!(parse("SOCKS socks:1080;DIRECT"), None);
+		assert_eq!(
+			parse("http://proxy:3128"),
+			Some("http://proxy:3128/".into())
+		);
+	}
+
+	#[test]
+	fn invalid_answers() {
+		assert!(parse_answer("").is_err());
+		assert!(parse_answer("SOCKS5 socks:1080").is_err());
+		assert!(parse_answer("PROXY").is_err());
+		assert!(parse_answer("BOGUS proxy:8080").is_err());
+	}
+}
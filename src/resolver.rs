@@ -0,0 +1,302 @@
+use std::{
+	error::Error,
+	net::{IpAddr, SocketAddr},
+	sync::{Arc, OnceLock},
+};
+
+use hickory_resolver::{
+	TokioResolver,
+	config::{LookupIpStrategy, ResolverConfig},
+	name_server::TokioConnectionProvider,
+};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+use crate::{
+	agent::{AgentDnsOrderOptions, DnsFamily},
+	error::{FaithError, FaithErrorKind},
+	observe::Observer,
+	private_network::is_private,
+};
+
+/// The agent's own DNS resolver, used instead of reqwest's so that lookups can be reported to the
+/// request's `observe` callback, and the addresses a name resolves to can be checked or reordered.
+///
+/// With `blockPrivateNetworks`, it fails with a `ForbiddenAddress` error when a name resolves to any
+/// private address. Any private address fails the whole resolution rather than being filtered out,
+/// so that a name which points to both public and private addresses can't be used to reach the
+/// private ones.
+///
+/// With `dns.order`, it sorts the addresses before they're connected to.
+pub(crate) struct AgentResolver {
+	system: bool,
+	block_private: bool,
+	order: Option<Arc<AddressOrder>>,
+	hickory: Arc<OnceLock<TokioResolver>>,
+}
+
+impl AgentResolver {
+	/// Resolves with the system's resolver if `system` is set, like `dns.system`, and with Hickory
+	/// otherwise.
+	pub(crate) fn new(system: bool, block_private: bool, order: Option<AddressOrder>) -> Self {
+		Self {
+			system,
+			block_private,
+			order: order.map(Arc::new),
+			hickory: Default::default(),
+		}
+	}
+}
+
+/// Builds a Hickory resolver set up like reqwest's own.
+fn new_hickory() -> TokioResolver {
+	let mut builder = TokioResolver::builder_tokio().unwrap_or_else(|_| {
+		TokioResolver::builder_with_config(
+			ResolverConfig::default(),
+			TokioConnectionProvider::default(),
+		)
+	});
+	builder.options_mut().ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+	builder.build()
+}
+
+impl Resolve for AgentResolver {
+	fn resolve(&self, name: Name) -> Resolving {
+		let observed = Observer::current().map(|observer| (observer, name.as_str().to_owned()));
+		let resolving = resolve(
+			self.system,
+			self.block_private,
+			self.order.clone(),
+			self.hickory.clone(),
+			name,
+		);
+		let Some((observer, host)) = observed else {
+			return Box::pin(resolving);
+		};
+
+		observer.dns_start(&host);
+		Box::pin(async move {
+			let addrs: Vec<SocketAddr> = resolving.await?.collect();
+			observer.dns_end(&host, &addrs);
+			Ok(Box::new(addrs.into_iter()) as Addrs)
+		})
+	}
+}
+
+async fn resolve(
+	system: bool,
+	block_private: bool,
+	order: Option<Arc<AddressOrder>>,
+	hickory: Arc<OnceLock<TokioResolver>>,
+	name: Name,
+) -> Result<Addrs, Box<dyn Error + Send + Sync>> {
+	let mut addrs: Vec<SocketAddr> = if system {
+		tokio::net::lookup_host((name.as_str(), 0)).await?.collect()
+	} else {
+		hickory
+			.get_or_init(new_hickory)
+			.lookup_ip(name.as_str())
+			.await?
+			.iter()
+			.map(|ip| SocketAddr::new(ip, 0))
+			.collect()
+	};
+
+	if block_private && let Some(addr) = addrs.iter().find(|addr| is_private(addr.ip())) {
+		return Err(Box::new(FaithError::new(
+			FaithErrorKind::ForbiddenAddress,
+			Some(format!(
+				"{} resolves to private network address {}",
+				name.as_str(),
+				addr.ip()
+			)),
+		)));
+	}
+
+	if let Some(order) = order {
+		order.sort(&mut addrs);
+	}
+
+	Ok(Box::new(addrs.into_iter()))
+}
+
+/// An IP address range, as in `10.0.0.0/8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IpRange {
+	network: IpAddr,
+	prefix: u8,
+}
+
+impl IpRange {
+	fn parse(range: &str) -> Option<Self> {
+		let (ip, prefix) = match range.split_once('/') {
+			Some((ip, prefix)) => (ip.parse::<IpAddr>().ok()?, Some(prefix.parse().ok()?)),
+			None => (range.parse().ok()?, None),
+		};
+		let max = if ip.is_ipv4() { 32 } else { 128 };
+		let prefix = prefix.unwrap_or(max);
+		(prefix <= max).then_some(Self {
+			network: ip,
+			prefix,
+		})
+	}
+
+	fn contains(&self, ip: IpAddr) -> bool {
+		let (network, ip, bits) = match (self.network, ip) {
+			(IpAddr::V4(network), IpAddr::V4(ip)) => {
+				(u128::from(network.to_bits()), u128::from(ip.to_bits()), 32)
+			}
+			(IpAddr::V6(network), IpAddr::V6(ip)) => (network.to_bits(), ip.to_bits(), 128),
+			_ => return false,
+		};
+		let host_bits = bits - u32::from(self.prefix);
+		host_bits >= 128 || (network ^ ip) >> host_bits == 0
+	}
+}
+
+/// The order in which to try a name's addresses, from `dns.order`.
+#[derive(Debug, Clone)]
+pub(crate) struct AddressOrder {
+	family: Option<DnsFamily>,
+	prefer: Vec<IpRange>,
+	avoid: Vec<IpRange>,
+}
+
+impl AddressOrder {
+	/// Returns `None` if the options don't reorder anything.
+	pub(crate) fn new(options: &AgentDnsOrderOptions) -> Result<Option<Self>, FaithError> {
+		let ranges = |ranges: &Option<Vec<String>>, option| {
+			ranges
+				.iter()
+				.flatten()
+				.map(|range| {
+					IpRange::parse(range).ok_or_else(|| {
+						FaithError::new(
+							FaithErrorKind::Config,
+							Some(format!(
+								"invalid address range in dns.order.{option}: {range}"
+							)),
+						)
+					})
+				})
+				.collect::<Result<Vec<_>, _>>()
+		};
+
+		let order = Self {
+			family: options.family,
+			prefer: ranges(&options.prefer, "prefer")?,
+			avoid: ranges(&options.avoid, "avoid")?,
+		};
+		Ok(
+			(order.family.is_some() || !order.prefer.is_empty() || !order.avoid.is_empty())
+				.then_some(order),
+		)
+	}
+
+	/// Sorts the addresses: avoided ones last, then preferred ones first in the order of their
+	/// ranges, then those of the preferred family first. Addresses which compare equal keep the
+	/// resolver's order.
+	fn sort(&self, addrs: &mut [SocketAddr]) {
+		addrs.sort_by_key(|addr| {
+			let ip = addr.ip();
+			let avoided = self.avoid.iter().any(|range| range.contains(ip));
+			let preferred = self
+				.prefer
+				.iter()
+				.position(|range| range.contains(ip))
+				.unwrap_or(usize::MAX);
+			let other_family = match self.family {
+				Some(DnsFamily::Ipv4) => ip.is_ipv6(),
+				Some(DnsFamily::Ipv6) => ip.is_ipv4(),
+				None => false,
+			};
+			(avoided, preferred, other_family)
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn order(family: Option<DnsFamily>, prefer: &[&str], avoid: &[&str]) -> AddressOrder {
+		let strings = |ranges: &[&str]| Some(ranges.iter().map(|r| r.to_string()).collect());
+		AddressOrder::new(&AgentDnsOrderOptions {
+			family,
+			prefer: strings(prefer),
+			avoid: strings(avoid),
+		})
+		.unwrap()
+		.unwrap()
+	}
+
+	fn sorted(order: &AddressOrder, addrs: &[&str]) -> Vec<String> {
+		let mut addrs: Vec<SocketAddr> = addrs
+			.iter()
+			.map(|ip| SocketAddr::new(ip.parse().unwrap(), 0))
+			.collect();
+		order.sort(&mut addrs);
+		addrs.iter().map(|addr| addr.ip().to_string()).collect()
+	}
+
+	#[test]
+	fn ranges() {
+		let range = IpRange::parse("10.1.0.0/16").unwrap();
+		assert!(range.contains("10.1.2.3".parse().unwrap()));
+		assert!(!range.contains("10.2.0.1".parse().unwrap()));
+		assert!(!range.contains("::ffff:10.1.2.3".parse().unwrap()));
+
+		let range = IpRange::parse("2001:db8::/32").unwrap();
+		assert!(range.contains("2001:db8:1::1".parse().unwrap()));
+		assert!(!range.contains("2001:db9::1".parse().unwrap()));
+
+		assert!(
+			IpRange::parse("0.0.0.0/0")
+				.unwrap()
+				.contains("1.2.3.4".parse().unwrap())
+		);
+		assert!(
+			IpRange::parse("1.2.3.4")
+				.unwrap()
+				.contains("1.2.3.4".parse().unwrap())
+		);
+		assert_eq!(IpRange::parse("1.2.3.4/33"), None);
+		assert_eq!(IpRange::parse("example.com/8"), None);
+	}
+
+	#[test]
+	fn prefers_ranges_in_order() {
+		let order = order(None, &["192.0.2.0/24", "198.51.100.0/24"], &[]);
+		assert_eq!(
+			sorted(&order, &["203.0.113.1", "198.51.100.1", "192.0.2.1"]),
+			["192.0.2.1", "198.51.100.1", "203.0.113.1"]
+		);
+	}
+
+	#[test]
+	fn avoids_ranges() {
+		let order = order(
+			Some(DnsFamily::Ipv6),
+			&["192.0.2.0/24"],
+			&["192.0.2.128/25"],
+		);
+		assert_eq!(
+			sorted(
+				&order,
+				&["192.0.2.200", "192.0.2.1", "203.0.113.1", "2001:db8::1"]
+			),
+			["192.0.2.1", "2001:db8::1", "203.0.113.1", "192.0.2.200"]
+		);
+	}
+
+	#[test]
+	fn keeps_resolver_order() {
+		let order = order(Some(DnsFamily::Ipv4), &[], &[]);
+		assert_eq!(
+			sorted(
+				&order,
+				&["2001:db8::1", "203.0.113.2", "2001:db8::2", "203.0.113.1"]
+			),
+			["203.0.113.2", "203.0.113.1", "2001:db8::1", "2001:db8::2"]
+		);
+	}
+}
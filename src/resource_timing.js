@@ -0,0 +1,45 @@
+// Adds an entry for a request to Node's performance timeline, as Node's own fetch() does, so that
+// `performance.getEntriesByType("resource")` lists the requests made with Fáith. This is evaluated
+// by the addon on first use in each thread: see `MarkResourceTiming` in resource_timing.rs.
+//
+// Times are given in milliseconds since the Unix epoch, and made relative to the time origin here.
+(() => {
+	const { performance } = globalThis;
+	if (typeof performance?.markResourceTiming !== "function") return null;
+
+	return function markResourceTiming(entry) {
+		const at = (time) => time - performance.timeOrigin;
+		const start = at(entry.start);
+		const connection = entry.connect && {
+			domainLookupStartTime: at(entry.connect[0]),
+			domainLookupEndTime: at(entry.connect[0]),
+			connectionStartTime: at(entry.connect[0]),
+			connectionEndTime: at(entry.connect[1]),
+			secureConnectionStartTime: entry.secure ? at(entry.connect[0]) : 0,
+			ALPNNegotiatedProtocol: entry.protocol,
+		};
+
+		performance.markResourceTiming(
+			{
+				startTime: start,
+				redirectStartTime: 0,
+				redirectEndTime: 0,
+				postRedirectStartTime: start,
+				finalServiceWorkerStartTime: 0,
+				finalNetworkRequestStartTime: entry.connect ? at(entry.connect[1]) : start,
+				finalNetworkResponseStartTime: at(entry.headers),
+				endTime: at(entry.end),
+				encodedBodySize: entry.bodySize,
+				decodedBodySize: entry.bodySize,
+				finalConnectionTimingInfo: connection || null,
+			},
+			entry.name,
+			"fetch",
+			globalThis,
+			entry.cacheMode,
+			{ encodedBodySize: entry.bodySize, decodedBodySize: entry.bodySize },
+			entry.status,
+			"",
+		);
+	};
+})();
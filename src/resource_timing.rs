@@ -0,0 +1,132 @@
+use std::{
+	cell::OnceCell,
+	fmt::Debug,
+	sync::{
+		Arc,
+		atomic::{AtomicU64, Ordering},
+	},
+};
+
+use http::Version;
+use napi::{
+	Env, Status,
+	bindgen_prelude::Function,
+	threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode},
+};
+use serde_json::json;
+
+use crate::{
+	response::{Timings, epoch_millis},
+	version::version_parts,
+};
+
+type MarkCallback =
+	ThreadsafeFunction<serde_json::Value, (), serde_json::Value, Status, false, true>;
+
+/// Adds entries to the performance timeline of the thread which made the request, with the helper
+/// from resource_timing.js.
+#[derive(Clone)]
+pub(crate) struct MarkResourceTiming(Arc<MarkCallback>);
+
+impl Debug for MarkResourceTiming {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_tuple("MarkResourceTiming").finish_non_exhaustive()
+	}
+}
+
+thread_local! {
+	static MARK: OnceCell<Option<MarkResourceTiming>> = const { OnceCell::new() };
+}
+
+impl MarkResourceTiming {
+	/// Returns the helper for the current JS thread, creating it on first use. Each thread (the main
+	/// one and workers) has its own env and performance timeline.
+	///
+	/// Returns `None` if the runtime doesn't have `performance.markResourceTiming()`.
+	pub(crate) fn for_thread(env: &Env) -> Option<Self> {
+		MARK.with(|mark| {
+			mark.get_or_init(|| Self::create(env).ok().flatten())
+				.clone()
+		})
+	}
+
+	fn create(env: &Env) -> napi::Result<Option<Self>> {
+		let mark: Option<Function<serde_json::Value, ()>> =
+			env.run_script(include_str!("resource_timing.js"))?;
+		let Some(mark) = mark else {
+			return Ok(None);
+		};
+
+		let tsfn = mark
+			.build_threadsafe_function()
+			.callee_handled::<false>()
+			.weak::<true>()
+			.build()?;
+		Ok(Some(Self(Arc::new(tsfn))))
+	}
+}
+
+/// The performance timeline entry for a response, added once its body has been received in full.
+#[derive(Debug)]
+pub(crate) struct ResourceTiming {
+	mark: MarkResourceTiming,
+	/// The URL requested, before redirects.
+	name: String,
+	/// Whether the response came from the cache.
+	local: bool,
+	secure: bool,
+	status: u16,
+	version: Version,
+	body_size: AtomicU64,
+}
+
+impl ResourceTiming {
+	pub(crate) fn new(
+		mark: MarkResourceTiming,
+		name: String,
+		local: bool,
+		secure: bool,
+		status: u16,
+		version: Version,
+	) -> Self {
+		Self {
+			mark,
+			name,
+			local,
+			secure,
+			status,
+			version,
+			body_size: AtomicU64::new(0),
+		}
+	}
+
+	/// Counts bytes of the body as they're received.
+	pub(crate) fn received(&self, bytes: usize) {
+		self.body_size.fetch_add(bytes as u64, Ordering::Relaxed);
+	}
+
+	/// Adds the entry, once the body has been received.
+	pub(crate) fn finish(&self, timings: &Timings) {
+		let Some(end) = timings.body_complete.get().copied() else {
+			return;
+		};
+
+		self.mark.0.call(
+			json!({
+				"name": self.name,
+				"cacheMode": if self.local { "local" } else { "" },
+				"status": self.status,
+				"protocol": version_parts(self.version).2,
+				"secure": self.secure,
+				"start": epoch_millis(timings.request_start),
+				"connect": timings
+					.connect
+					.map(|(start, end)| [epoch_millis(start), epoch_millis(end)]),
+				"headers": epoch_millis(timings.headers_complete),
+				"end": epoch_millis(end),
+				"bodySize": self.body_size.load(Ordering::Relaxed),
+			}),
+			ThreadsafeFunctionCallMode::NonBlocking,
+		);
+	}
+}
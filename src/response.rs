@@ -1,16 +1,19 @@
 use std::{
 	fmt::Debug,
 	hint::unreachable_unchecked,
+	io::Write,
 	mem::replace,
 	net::SocketAddr,
 	pin::Pin,
 	result::Result,
 	sync::{
-		Arc,
-		atomic::{AtomicBool, Ordering},
+		Arc, OnceLock,
+		atomic::{AtomicBool, AtomicU64, Ordering},
 	},
+	time::{SystemTime, UNIX_EPOCH},
 };
 
+use base64::{Engine, prelude::BASE64_STANDARD};
 use bytes::Bytes;
 use futures::{StreamExt, TryStreamExt, stream};
 use http_body_util::BodyStream;
@@ -19,14 +22,31 @@ use napi_derive::napi;
 use reqwest::{StatusCode, Url, Version, header::HeaderMap};
 use serde_json;
 use stream_shared::SharedStream;
-use tokio::{sync::RwLock, task::yield_now};
+use tokio::{
+	sync::{Mutex, RwLock, mpsc},
+	task::yield_now,
+};
+use tokio_stream::wrappers::ReceiverStream;
 
 use crate::{
-	agent::InnerAgentStats,
+	agent::{AgentSpillOptions, AgentStreamOptions, InnerAgentStats, InnerHostTraffic},
 	async_task::{Async, FaithAsyncResult, Value},
-	body::{Body, BodyHolder, DynStream, drain_body_inner},
-	error::{FaithError, FaithErrorKind},
+	body::{Body, BodyHolder, DynStream, drain_body_inner, rechunk},
+	checksum::ChecksumCheck,
+	conn_tracker::ConnectionTracker,
+	error::{ErrorResponse, FaithError, FaithErrorKind, is_keepalive_timeout},
+	filename::suggested_filename,
+	headers::FaithHeaders,
+	history::RedirectSnapshot,
 	integrity::verify_integrity,
+	observe::{Observer, RequestEventType},
+	proxy::ProxyDecision,
+	resource_timing::ResourceTiming,
+	runtime,
+	spool::Spool,
+	tls::PeerTls,
+	transfer::{self, TransferredResponse},
+	version::version_parts,
 };
 
 /// The `Response` interface of the Fetch API represents the response to a request.
@@ -36,30 +56,290 @@ use crate::{
 #[napi]
 #[derive(Debug, Clone)]
 pub struct FaithResponse {
+	pub(crate) alt_svc: Option<AltSvcDecision>,
 	pub(crate) body: BodyHolder,
+	/// The agent's `limits.responseBody`.
+	pub(crate) body_limit: Option<u32>,
+	/// The body, once it has been read in full by `bytes()` and co, for `toJSON()`.
+	pub(crate) buffered: Arc<OnceLock<Arc<[Bytes]>>>,
+	/// The agent's `http1.canonicalStatusText`.
+	pub(crate) canonical_status_text: bool,
+	pub(crate) checksum: Option<Arc<ChecksumCheck>>,
+	/// The agent's `stream` options, for handing the body to JS in chunks.
+	pub(crate) chunking: AgentStreamOptions,
+	/// The agent's connections, to report the one the body came on if it dies.
+	pub(crate) conn_tracker: Arc<ConnectionTracker>,
+	/// The request's `correlationId`.
+	pub(crate) correlation_id: Option<Arc<str>>,
 	pub(crate) disturbed: Arc<AtomicBool>,
 	pub(crate) headers: HeaderMap,
+	/// The redirects followed to get this response.
+	pub(crate) history: Arc<[RedirectSnapshot]>,
+	/// The agent's counters for the host of the response's URL.
+	pub(crate) host_traffic: Arc<InnerHostTraffic>,
 	pub(crate) integrity: Option<String>,
+	/// The request's `observe` callback.
+	pub(crate) observer: Option<Observer>,
+	/// Whether this is a redirect returned as an opaque-redirect response by a `strict` agent.
+	pub(crate) opaque_redirect: bool,
 	pub(crate) peer: Arc<PeerInformation>,
+	/// The reason phrase from the HTTP/1 status line.
+	pub(crate) reason_phrase: Option<Bytes>,
 	pub(crate) redirected: bool,
+	/// The performance timeline entry, added once the body has been received.
+	pub(crate) resource_timing: Option<Arc<ResourceTiming>>,
+	pub(crate) spill: Option<Arc<AgentSpillOptions>>,
+	/// Shared by the clones of a response when it's cloned with `spill`.
+	pub(crate) spool: Arc<OnceLock<Arc<Spool>>>,
 	pub(crate) stats: Arc<InnerAgentStats>,
 	pub(crate) status_code: StatusCode,
+	pub(crate) timings: Arc<Timings>,
 	pub(crate) trailers: Arc<RwLock<Trailers>>,
 	pub(crate) url: Url,
 	pub(crate) version: Version,
 }
 
+/// The chunks of a response body, from `response.chunks()`.
+#[napi]
+pub struct BodyChunks {
+	body: BodyHolder,
+	checksum: Option<Arc<ChecksumCheck>>,
+	stream: Option<Arc<Mutex<Pin<Box<DynStream>>>>>,
+}
+
+#[napi]
+impl BodyChunks {
+	/// Resolves with the next chunk of the body, or `null` once it has been read to the end.
+	#[napi]
+	pub fn next(&self) -> Async<Option<Buffer>> {
+		let body = self.body.clone();
+		let checksum = self.checksum.clone();
+		let stream = self.stream.clone();
+		FaithAsyncResult::run(async move || {
+			let Some(stream) = stream else {
+				return Ok(None);
+			};
+
+			match stream.lock().await.next().await {
+				Some(Ok(chunk)) => Ok(Some(Vec::from(chunk).into())),
+				Some(Err(err)) => Err(ChecksumCheck::stream_error(checksum.as_ref(), err)),
+				None => {
+					body.mark_drained();
+					Ok(None)
+				}
+			}
+		})
+	}
+}
+
+/// When things happened over the course of a request, for `response.timestamps`.
+#[derive(Debug)]
+pub(crate) struct Timings {
+	pub(crate) request_start: SystemTime,
+	/// When a new connection was started and established for this request.
+	pub(crate) connect: Option<(SystemTime, SystemTime)>,
+	pub(crate) headers_complete: SystemTime,
+	pub(crate) first_byte: OnceLock<SystemTime>,
+	pub(crate) body_complete: OnceLock<SystemTime>,
+}
+
+impl Timings {
+	pub(crate) fn new(
+		request_start: SystemTime,
+		connect: Option<(SystemTime, SystemTime)>,
+		empty: bool,
+	) -> Self {
+		let headers_complete = SystemTime::now();
+		Self {
+			request_start,
+			connect,
+			headers_complete,
+			first_byte: OnceLock::new(),
+			body_complete: if empty {
+				OnceLock::from(headers_complete)
+			} else {
+				OnceLock::new()
+			},
+		}
+	}
+}
+
+/// Custom to Fáith.
+///
+/// Wall-clock times over the course of a request, in milliseconds since the Unix epoch (like
+/// `Date.now()`, but with sub-millisecond precision):
+///
+/// - `requestStart`: when the request was started, before connecting or looking in the cache.
+/// - `connectStart`: when a new connection was started for this request.
+/// - `connectEnd`: when that connection was established. This covers DNS resolution, the TCP
+///   connection, going through the proxy (including its `CONNECT` exchange), and the TLS handshake.
+///   Both are only set for the first request on a new HTTP/1.1 or HTTP/2 connection; they're not set
+///   for requests on reused connections, nor for HTTP/3.
+/// - `headersComplete`: when the response headers were received.
+/// - `firstByte`: when the first chunk of the response body was received. This is only known once
+///   the body is being read, and is never set for responses without a body.
+/// - `bodyComplete`: when the response body was fully received. This is only known once the body has
+///   been read to the end; for responses without a body, it's the same as `headersComplete`.
+///
+/// The time between `requestStart` and `headersComplete` is mostly the server's latency (plus
+/// connection setup), while the time between `headersComplete` and `bodyComplete` is mostly the
+/// payload transfer time.
+#[napi(object)]
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseTimestamps {
+	pub request_start: f64,
+	pub connect_start: Option<f64>,
+	pub connect_end: Option<f64>,
+	pub headers_complete: f64,
+	pub first_byte: Option<f64>,
+	pub body_complete: Option<f64>,
+}
+
+pub(crate) fn epoch_millis(time: SystemTime) -> f64 {
+	time.duration_since(UNIX_EPOCH)
+		.map_or(0.0, |duration| duration.as_secs_f64() * 1000.0)
+}
+
 /// Custom to Fáith.
 ///
 /// The `peer` read-only property of the `Response` interface contains an object with information about
 /// the remote peer that sent this response:
 ///
-/// - `address`: The IP address and port of the peer, if available.
+/// - `address`: The IP address and port of the peer, if available. When going through a proxy, this
+///   is the proxy's address.
 /// - `certificate`: When connected over HTTPS, this is the DER-encoded leaf certificate of the peer.
+/// - `proxy`: When the request went through a proxy, its URL (`url`), whether the connection was
+///   tunneled through it with `CONNECT` (`tunnel`), and if so, the status the proxy answered the
+///   `CONNECT` with (`connectStatus`). A tunnel is only established on a `200` answer; any other is
+///   an error: `ProxyAuth` for `407`, `Network` otherwise.
+/// - `tls`: When connected over HTTPS by an agent with its own TLS configuration (with
+///   `tls.verifyProc` or `tls.requireOcspStapling`), whether the server stapled an OCSP response and
+///   what it said. See `PeerTls`.
 #[derive(Debug)]
 pub struct PeerInformation {
 	pub address: Option<SocketAddr>,
 	pub certificate: Option<Vec<u8>>,
+	pub(crate) proxy: Option<ProxyDecision>,
+	pub(crate) tls: Option<PeerTls>,
+}
+
+/// Custom to Fáith.
+///
+/// A snapshot of a response, from `response.toJSON()`, for logging or persisting it. It only has
+/// JSON-compatible values: binary data is base64-encoded.
+///
+/// - `status`, `statusText`, `ok`, `url`, `redirected`, `version`, `timestamps`: as on the response.
+/// - `correlationId`: the request's `correlationId`, if it had one.
+/// - `headers`: the response headers, as `[name, value]` pairs.
+/// - `peer`: as on the response, with the `certificate` base64-encoded.
+/// - `body`: the body, base64-encoded, if it has already been read in full with `bytes()`,
+///   `text()`, `json()` and co. Otherwise it's omitted, as reading it here would use it up.
+#[napi(object)]
+pub struct ResponseSnapshot {
+	pub status: u16,
+	pub status_text: String,
+	pub ok: bool,
+	pub url: String,
+	pub redirected: bool,
+	pub version: String,
+	pub headers: Vec<(String, String)>,
+	pub peer: PeerSnapshot,
+	pub timestamps: ResponseTimestamps,
+	pub correlation_id: Option<String>,
+	pub body: Option<String>,
+}
+
+/// The `peer` of a `ResponseSnapshot`.
+#[napi(object)]
+pub struct PeerSnapshot {
+	pub address: Option<String>,
+	/// The DER-encoded leaf certificate of the peer, base64-encoded.
+	pub certificate: Option<String>,
+	pub proxy: Option<PeerProxySnapshot>,
+	pub tls: Option<PeerTls>,
+}
+
+/// The `peer.proxy` of a `ResponseSnapshot`.
+#[napi(object)]
+pub struct PeerProxySnapshot {
+	pub url: String,
+	pub tunnel: bool,
+	pub connect_status: Option<u16>,
+}
+
+/// Custom to Fáith.
+///
+/// The HTTP version of a response, in structured form:
+///
+/// - `major`: the major version number, e.g. `2` for HTTP/2.
+/// - `minor`: the minor version number, e.g. `1` for HTTP/1.1.
+/// - `alpn`: the ALPN protocol identifier for this version, e.g. `http/1.1`, `h2`, or `h3`. This is
+///   set regardless of whether the version was actually negotiated via ALPN.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct HttpVersion {
+	pub major: u8,
+	pub minor: u8,
+	pub alpn: String,
+}
+
+/// Custom to Fáith.
+///
+/// Which Alt-Svc cache entry drove the decision to try HTTP/3 for a request:
+///
+/// - `advertised`: the server advertised HTTP/3 in an `Alt-Svc` header, or the origin was given in
+///   `http3.hints`, but it hasn't been used successfully yet.
+/// - `confirmed`: a previous request to the origin succeeded over HTTP/3.
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AltSvcSource {
+	#[napi(value = "advertised")]
+	Advertised,
+	#[napi(value = "confirmed")]
+	Confirmed,
+}
+
+/// Custom to Fáith.
+///
+/// What the agent's HTTP/3 upgrade did for a request:
+///
+/// - `entry`: the Alt-Svc cache entry found for the origin, if any. See `AltSvcSource`.
+/// - `attempted`: whether the request was first tried over HTTP/3. It isn't when there's no entry,
+///   when HTTP/3 recently failed for the origin, or when the request body is a stream, as it can't be
+///   sent twice.
+/// - `recentlyFailed`: whether HTTP/3 failed for the origin within `http3.upgradeFailedTtl`.
+/// - `fellBack`: whether the HTTP/3 attempt failed, and the request was sent again over HTTP/2 or
+///   HTTP/1.1.
+/// - `fallbackAfter`: when `fellBack`, how long the HTTP/3 attempt took to fail, in milliseconds.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct AltSvcDecision {
+	pub entry: Option<AltSvcSource>,
+	pub attempted: bool,
+	pub recently_failed: bool,
+	pub fell_back: bool,
+	pub fallback_after: Option<f64>,
+}
+
+/// Custom to Fáith.
+///
+/// How the body of a response is being read, from `response.bodyStats()`:
+///
+/// - `chunksDelivered`: how many chunks have been read, summed over all consumers.
+/// - `bytesDelivered`: how many bytes have been read, summed over all consumers.
+/// - `consumers`: how many readers of the body are currently open: `body` streams, `chunks()`
+///   iterators, and reads in progress from `bytes()` and co, for this response and its clones.
+/// - `bufferedBytes`: how many bytes of the body are held in memory. Once the body starts being
+///   read, everything read from the network is kept so it can also be read by clones, until the
+///   response and all its clones are dropped or the body is discarded. This is what grows when a
+///   clone is never read.
+#[napi(object)]
+#[derive(Debug, Clone, Copy)]
+pub struct BodyStats {
+	pub chunks_delivered: i64,
+	pub bytes_delivered: i64,
+	pub consumers: u32,
+	pub buffered_bytes: i64,
 }
 
 #[derive(Debug, Default)]
@@ -75,8 +355,8 @@ impl FaithResponse {
 	/// The `headers` read-only property of the `Response` interface contains the `Headers` object
 	/// associated with the response.
 	///
-	/// Note that Fáith does not provide a custom `Headers` class; instead the Web API `Headers` structure
-	/// is used directly and constructed by Fáith when needed.
+	/// Note that this uses the Web API `Headers` structure, constructed by Fáith when needed. See
+	/// `faithHeaders` for Fáith's own `Headers`-like class.
 	///
 	/// This is a function as an internal implementation detail and the wrapper makes it a property.
 	#[napi]
@@ -92,6 +372,18 @@ impl FaithResponse {
 			.collect()
 	}
 
+	/// Custom to Fáith.
+	///
+	/// The `faithHeaders` read-only property of the `Response` interface contains a `FaithHeaders`
+	/// object with the headers of the response. Unlike `headers`, this preserves header values which
+	/// are not valid UTF-8 (they are decoded lossily), and does not go through the Web API `Headers`.
+	///
+	/// Each access returns a new copy; modifying it does not affect the response.
+	#[napi(getter)]
+	pub fn faith_headers(&self) -> FaithHeaders {
+		FaithHeaders::from_map(self.headers.clone())
+	}
+
 	/// The `ok` read-only property of the `Response` interface contains a boolean stating whether the
 	/// response was successful (status in the range 200-299) or not.
 	#[napi(getter)]
@@ -103,7 +395,10 @@ impl FaithResponse {
 	///
 	/// The `peer` read-only property of the `Response` interface contains an object with information about
 	/// the remote peer that sent this response:
-	#[napi(getter, ts_return_type = "{ address?: string; certificate?: Buffer }")]
+	#[napi(
+		getter,
+		ts_return_type = "{ address?: string; certificate?: Buffer; proxy?: { url: string; tunnel: boolean; connectStatus?: number }; tls?: PeerTls }"
+	)]
 	pub fn peer<'env>(&self, env: &'env Env) -> Result<Object<'env>, napi::Error> {
 		let mut obj = Object::new(env)?;
 		obj.set("address", self.peer.address.map(|addr| addr.to_string()))?;
@@ -114,6 +409,16 @@ impl FaithResponse {
 				.as_deref()
 				.map(|cert| Buffer::from(cert)),
 		)?;
+		if let Some(proxy) = &self.peer.proxy {
+			let mut info = Object::new(env)?;
+			info.set("url", proxy.url.as_str())?;
+			info.set("tunnel", proxy.tunnel)?;
+			info.set("connectStatus", proxy.tunnel.then_some(200))?;
+			obj.set("proxy", info)?;
+		}
+		if let Some(tls) = &self.peer.tls {
+			obj.set("tls", tls.clone())?;
+		}
 		Ok(obj)
 	}
 
@@ -127,34 +432,84 @@ impl FaithResponse {
 		self.redirected
 	}
 
+	/// The `history` read-only property of the `Response` interface contains the redirects which
+	/// were followed to get this response, in order, or is empty if the request wasn't redirected.
+	/// Each entry has the `url` which answered with a redirect, its `status`, and its `location`.
+	///
+	/// The headers of the redirect responses are not available, as the underlying client doesn't
+	/// expose them. Responses from the cache have an empty history.
+	///
+	/// This is custom to Fáith.
+	#[napi(getter)]
+	pub fn history(&self) -> Vec<RedirectSnapshot> {
+		self.history.to_vec()
+	}
+
 	/// The `status` read-only property of the `Response` interface contains the HTTP status codes of the
 	/// response. For example, 200 for success, 404 if the resource could not be found.
 	///
 	/// A value is `0` is returned for a response whose `type` is `opaque`, `opaqueredirect`, or `error`.
 	#[napi(getter)]
 	pub fn status(&self) -> u16 {
-		self.status_code.as_u16()
+		if self.opaque_redirect {
+			0
+		} else {
+			self.status_code.as_u16()
+		}
 	}
 
 	/// The `statusText` read-only property of the `Response` interface contains the status message
 	/// corresponding to the HTTP status code in `Response.status`. For example, this would be `OK` for a
 	/// status code `200`, `Continue` for `100`, `Not Found` for `404`.
 	///
-	/// In HTTP/1, servers can send custom status text. This is returned here. In HTTP/2 and HTTP/3, custom
-	/// status text is not supported at all, and the `statusText` property is either empty or simulated
-	/// from well-known status codes.
+	/// In HTTP/1, servers can send custom status text. This is returned here, unless the agent has
+	/// `http1.canonicalStatusText`. In HTTP/2 and HTTP/3, custom status text is not supported at all,
+	/// and the `statusText` property is either empty or simulated from well-known status codes.
+	#[napi(getter)]
+	pub fn status_text(&self) -> String {
+		if self.opaque_redirect {
+			return String::new();
+		}
+
+		match &self.reason_phrase {
+			// reason phrases may have obs-text bytes, which map to the same code points (as ByteString)
+			Some(reason) if !self.canonical_status_text => {
+				reason.iter().copied().map(char::from).collect()
+			}
+			_ => self
+				.status_code
+				.canonical_reason()
+				.unwrap_or_default()
+				.into(),
+		}
+	}
+
+	/// Custom to Fáith.
+	///
+	/// The `reasonPhrase` read-only property contains the raw bytes of the reason phrase from the
+	/// HTTP/1 status line, e.g. `OK` in `HTTP/1.1 200 OK`, even when `statusText` is canonical.
+	///
+	/// This is `null` for HTTP/2 and HTTP/3, which have no reason phrases, and for responses from
+	/// the cache.
 	#[napi(getter)]
-	pub fn status_text(&self) -> &'static str {
-		self.status_code.canonical_reason().unwrap_or_default()
+	pub fn reason_phrase(&self) -> Option<Buffer> {
+		self.reason_phrase
+			.as_ref()
+			.map(|reason| reason.to_vec().into())
 	}
 
 	/// The `type` read-only property of the `Response` interface contains the type of the response. The
 	/// type determines whether scripts are able to access the response body and headers.
 	///
-	/// In Fáith, this is always set to `basic`.
+	/// In Fáith, this is set to `basic`, or to `opaqueredirect` for redirects returned by a `strict`
+	/// agent with `redirect: "manual"`.
 	#[napi(getter, js_name = "type")]
 	pub fn typ(&self) -> &'static str {
-		"basic"
+		if self.opaque_redirect {
+			"opaqueredirect"
+		} else {
+			"basic"
+		}
 	}
 
 	/// The `url` read-only property of the `Response` interface contains the URL of the response. The
@@ -173,6 +528,49 @@ impl FaithResponse {
 		format!("{:?}", self.version)
 	}
 
+	/// The `httpVersion` read-only property of the `Response` interface contains the HTTP version of
+	/// the response in structured form, which is easier to check programmatically than `version`.
+	///
+	/// This is custom to Fáith.
+	#[napi(getter)]
+	pub fn http_version(&self) -> HttpVersion {
+		let (major, minor, alpn) = version_parts(self.version);
+		HttpVersion {
+			major,
+			minor,
+			alpn: alpn.into(),
+		}
+	}
+
+	/// The `altSvc` read-only property of the `Response` interface contains what the agent's HTTP/3
+	/// upgrade did for this request. See `AltSvcDecision`.
+	///
+	/// This is `null` when the upgrade wasn't considered: when it's disabled (`http3.upgradeEnabled`),
+	/// when the request pinned its `version`, when the response came from the cache, or when Fáith was
+	/// built without HTTP/3 support.
+	///
+	/// This is custom to Fáith.
+	#[napi(getter)]
+	pub fn alt_svc(&self) -> Option<AltSvcDecision> {
+		self.alt_svc.clone()
+	}
+
+	/// The `timestamps` read-only property of the `Response` interface contains wall-clock times over
+	/// the course of the request. See `ResponseTimestamps`.
+	///
+	/// This is custom to Fáith.
+	#[napi(getter)]
+	pub fn timestamps(&self) -> ResponseTimestamps {
+		ResponseTimestamps {
+			request_start: epoch_millis(self.timings.request_start),
+			connect_start: self.timings.connect.map(|(start, _)| epoch_millis(start)),
+			connect_end: self.timings.connect.map(|(_, end)| epoch_millis(end)),
+			headers_complete: epoch_millis(self.timings.headers_complete),
+			first_byte: self.timings.first_byte.get().copied().map(epoch_millis),
+			body_complete: self.timings.body_complete.get().copied().map(epoch_millis),
+		}
+	}
+
 	/// The `bodyUsed` read-only property of the `Response` interface is a boolean value that indicates
 	/// whether the body has been read yet.
 	///
@@ -184,9 +582,35 @@ impl FaithResponse {
 		self.disturbed.load(Ordering::SeqCst)
 	}
 
+	/// Custom to Fáith.
+	///
+	/// The `correlationId` given in the request's options, or by `withCorrelationId()` when using
+	/// the wrapper, or `null`. Errors of the request also have it as their `correlationId`.
+	#[napi(getter)]
+	pub fn correlation_id(&self) -> Option<String> {
+		self.correlation_id.as_deref().map(Into::into)
+	}
+
+	/// Custom to Fáith.
+	///
+	/// Returns a filename to save the response's body as: the `filename*` or `filename` parameter of
+	/// the `Content-Disposition` header, decoding RFC 8187 (formerly RFC 5987) encoded values, or else
+	/// the last segment of the URL's path. Returns `null` if there's neither.
+	///
+	/// The filename is sanitized so it can be used as is on any common filesystem: it can't contain
+	/// path separators, control characters, or characters Windows doesn't allow (which become `_`),
+	/// doesn't start with a dot, isn't a name Windows reserves like `CON`, and is at most 255 bytes.
+	/// It should still be saved in a directory chosen by the application, not one from the response.
+	#[napi]
+	pub fn suggested_filename(&self) -> Option<String> {
+		suggested_filename(&self.headers, &self.url)
+	}
+
 	/// The `body` read-only property of the `Response` interface is a `ReadableStream` of the body
-	/// contents, or `null` for any actual HTTP response that has no body, such as `HEAD` requests and
-	/// `204 No Content` responses.
+	/// contents, or `null` for any actual HTTP response that has no body: responses to `HEAD` requests,
+	/// and those with a null body status (`101`, `103`, `204`, `205`, and `304`). The `Content-Length`
+	/// header of a `HEAD` response is kept, so the size of the resource can still be read, except when
+	/// the response is compressed, as decompression removes it.
 	///
 	/// Note that browsers currently do not return `null` for those responses, but the spec requires
 	/// it. Fáith chooses to respect the spec rather than the browsers in this case.
@@ -218,11 +642,12 @@ impl FaithResponse {
 		let stream = self
 			.ensure_stream(&mut body, self.body.drained.clone())
 			.map_err(|e| e.into_napi())?;
+		let stream = rechunk(stream, self.chunking);
 
+		let checksum = self.checksum.clone();
 		let stream = napi::bindgen_prelude::ReadableStream::create_with_stream_bytes(
 			&env,
-			stream
-				.map_err(|err| FaithError::new(FaithErrorKind::BodyStream, Some(err)).into_napi()),
+			stream.map_err(|err| ChecksumCheck::stream_error(checksum.as_ref(), err).into_napi()),
 		)
 		.map_err(|e| {
 			napi::Error::from(
@@ -233,6 +658,69 @@ impl FaithResponse {
 		Ok(Some(stream))
 	}
 
+	/// Custom to Fáith. Returns an async iterator over the chunks of the body, as `Buffer`s:
+	///
+	/// ```javascript
+	/// for await (const chunk of response.chunks()) {
+	///   // ...
+	/// }
+	/// ```
+	///
+	/// This reads the body directly rather than going through a `ReadableStream` like `body` does,
+	/// which avoids its overhead. Like `body`, this marks the body as used, and the `integrity` is not
+	/// checked. The iterator is empty for responses without a body.
+	///
+	/// Note that this returns a `BodyChunks` as an implementation detail; the wrapper makes it an
+	/// async iterator.
+	#[napi]
+	pub fn chunks(&self) -> Result<BodyChunks, napi::Error> {
+		self.check_stream_disturbed().map_err(|e| e.into_napi())?;
+
+		let stream = match &self.body.body {
+			None => None,
+			Some(lock) => {
+				let mut body = lock.try_lock().map_err(|_| {
+					FaithError::from(FaithErrorKind::ResponseAlreadyDisturbed).into_napi()
+				})?;
+				let stream = self
+					.ensure_stream(&mut body, self.body.drained.clone())
+					.map_err(|e| e.into_napi())?;
+				Some(Arc::new(Mutex::new(rechunk(stream, self.chunking))))
+			}
+		};
+
+		Ok(BodyChunks {
+			body: self.body.clone(),
+			checksum: self.checksum.clone(),
+			stream,
+		})
+	}
+
+	/// Custom to Fáith. Returns statistics on how the body is being read, to help diagnose memory
+	/// growth when the body is shared between clones. See `BodyStats`.
+	///
+	/// This does not mark the body as used.
+	#[napi]
+	pub fn body_stats(&self) -> BodyStats {
+		let stats = &self.body.stats;
+		let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed) as i64;
+		let discarded = self.body.body.as_ref().is_none_or(|lock| {
+			lock.try_lock()
+				.is_ok_and(|body| matches!(*body, Body::Consumed))
+		});
+
+		BodyStats {
+			chunks_delivered: load(&stats.chunks_delivered),
+			bytes_delivered: load(&stats.bytes_delivered),
+			consumers: stats.consumers.load(Ordering::Relaxed) as u32,
+			buffered_bytes: if discarded {
+				0
+			} else {
+				load(&stats.buffered_bytes)
+			},
+		}
+	}
+
 	fn check_stream_disturbed(&self) -> Result<(), FaithError> {
 		if self.disturbed.swap(true, Ordering::SeqCst) {
 			Err(FaithErrorKind::ResponseAlreadyDisturbed.into())
@@ -241,17 +729,86 @@ impl FaithResponse {
 		}
 	}
 
+	/// Sets the request's correlation ID on an error from reading the body.
+	fn correlate(&self, err: FaithError) -> FaithError {
+		err.with_correlation_id(self.correlation_id.clone())
+	}
+
+	/// Reads the entire body and parses it as JSON.
+	pub(crate) async fn parse_json(&self) -> Result<Value, FaithError> {
+		let bytes = self.read_bytes().await?;
+		let value = serde_json::from_slice(&bytes).map_err(|e| {
+			self.correlate(FaithError::new(
+				FaithErrorKind::JsonParse,
+				Some(e.to_string()),
+			))
+		})?;
+		Ok(Value(value))
+	}
+
+	/// Reads the entire body.
+	pub(crate) async fn read_bytes(&self) -> Result<Buffer, FaithError> {
+		self.check_stream_disturbed()
+			.map_err(|err| self.correlate(err))?;
+		self.gather_contiguous()
+			.await
+			.map_err(|err| self.correlate(err))
+	}
+
+	/// Makes an `HttpStatus` error for a response with an error status, for `throwOnError`. The
+	/// start of the body is kept as the error's `bodyPreview`. With `json`, for the `json-api`
+	/// profile, the whole body is read as the error's `body`: as JSON if it parses, and as text
+	/// otherwise.
+	///
+	/// Failing to read the body doesn't fail the request with another error: the status is what
+	/// matters, so the body is left out instead.
+	pub(crate) async fn into_status_error(self, json: bool) -> FaithError {
+		let (body, preview) = if json {
+			match self.gather_contiguous().await {
+				Ok(bytes) if bytes.is_empty() => (Some(serde_json::Value::Null), None),
+				Ok(bytes) => (
+					Some(serde_json::from_slice(&bytes).unwrap_or_else(|_| {
+						serde_json::Value::String(String::from_utf8_lossy(&bytes).into_owned())
+					})),
+					Some(bytes[..bytes.len().min(ERROR_PREVIEW_SIZE)].to_vec()),
+				),
+				Err(_) => (Some(serde_json::Value::Null), None),
+			}
+		} else {
+			(None, self.gather_prefix(ERROR_PREVIEW_SIZE).await.ok())
+		};
+
+		FaithError::new(
+			FaithErrorKind::HttpStatus,
+			Some(format!(
+				"request failed with status {} {}",
+				self.status_code.as_u16(),
+				self.status_text()
+			)),
+		)
+		.with_response(ErrorResponse {
+			status: self.status_code.as_u16(),
+			status_text: self.status_text(),
+			headers: self.headers.clone(),
+			body,
+			body_preview: preview
+				.filter(|preview| !preview.is_empty())
+				.map(|preview| String::from_utf8_lossy(&preview).into_owned()),
+		})
+	}
+
 	/// Ensures the body is converted to a SharedStream, returning a clone of it.
 	///
-	/// This allows multiple consumers (original + clones) to independently read the body.
+	/// This allows multiple consumers (original + clones) to independently read the body. The clone
+	/// counts what it reads, for `bodyStats()`.
 	fn ensure_stream(
 		&self,
 		body: &mut Body,
 		drained_flag: Arc<AtomicBool>,
-	) -> Result<SharedStream<Pin<Box<DynStream>>>, FaithError> {
+	) -> Result<Pin<Box<DynStream>>, FaithError> {
 		match body {
 			Body::Consumed => Err(FaithErrorKind::ResponseAlreadyDisturbed.into()),
-			Body::Stream(stream) => Ok(stream.clone()),
+			Body::Stream(stream) => Ok(self.body.stats.track(stream.clone())),
 			lock @ Body::Inner(_) => {
 				// temporarily replace with Consumed until we can put in the Stream
 				let Body::Inner(inner) = replace(lock, Body::Consumed) else {
@@ -259,57 +816,141 @@ impl FaithResponse {
 					unsafe { unreachable_unchecked() }
 				};
 
-				// Track that we've started consuming a body
-				self.stats.bodies_started.fetch_add(1, Ordering::Relaxed);
-
-				let trailers_stream = self.trailers.clone();
-				let trailers_finish = self.trailers.clone();
-				let stats_finish = self.stats.clone();
-				let drained_finish = drained_flag.clone();
-				let stream = SharedStream::new(Box::pin(
-					BodyStream::new(inner)
-						.then(move |frame| {
-							let trailers_lock = trailers_stream.clone();
-							async move {
-								match frame {
-									Err(err) => Some(Err(err.to_string())),
-									Ok(frame) => match frame.into_trailers() {
-										Ok(trailers) => {
-											let mut t = trailers_lock.write().await;
-											*t = Trailers::Some(trailers);
-											None
-										}
-										Err(frame) => Some(
-											frame
-												.into_data()
-												.map_err(|_| "unknown frame kind".to_string()),
-										),
-									},
-								}
-							}
-						})
-						.chain(stream::once(async move {
-							let mut t = trailers_finish.write().await;
-							if matches!(*t, Trailers::NotYet) {
-								*t = Trailers::None;
-							}
-							// Track that we've finished consuming a body
-							stats_finish.bodies_finished.fetch_add(1, Ordering::Relaxed);
-							// Mark body as drained so Drop doesn't try to drain again
-							drained_finish.store(true, Ordering::SeqCst);
-							None
-						}))
-						.filter_map(async |item| item),
-				) as Pin<Box<DynStream>>);
+				let stream = SharedStream::new(self.wrap_body(inner, drained_flag));
 
 				// the _ is the Consumed we put in there earlier
 				let _ = replace(lock, Body::Stream(stream.clone()));
 
-				Ok(stream)
+				Ok(self.body.stats.track(stream))
 			}
 		}
 	}
 
+	/// Wraps the network body into a stream of its data, which keeps the trailers, timings, and stats
+	/// up to date as it's read.
+	fn wrap_body(
+		&self,
+		inner: reqwest::Body,
+		drained_flag: Arc<AtomicBool>,
+	) -> Pin<Box<DynStream>> {
+		// Track that we've started consuming a body
+		self.stats.bodies_started.fetch_add(1, Ordering::Relaxed);
+
+		let trailers_stream = self.trailers.clone();
+		let trailers_finish = self.trailers.clone();
+		let stats_data = self.stats.clone();
+		let host_data = self.host_traffic.clone();
+		let stats_finish = self.stats.clone();
+		let timings_data = self.timings.clone();
+		let timings_finish = self.timings.clone();
+		let observer_data = self.observer.clone();
+		let observer_finish = self.observer.clone();
+		let resource_timing_data = self.resource_timing.clone();
+		let resource_timing_finish = self.resource_timing.clone();
+		let drained_finish = drained_flag.clone();
+		let verifier = self
+			.checksum
+			.as_ref()
+			.and_then(|check| check.verifier(&self.headers))
+			.map(|verifier| Arc::new(std::sync::Mutex::new(Some(verifier))));
+		let verifier_data = verifier.clone();
+		let conn_tracker = self.conn_tracker.clone();
+		let origin: Arc<str> = self.url.origin().ascii_serialization().into();
+		Box::pin(
+			BodyStream::new(inner)
+				.then(move |frame| {
+					let trailers_lock = trailers_stream.clone();
+					let stats = stats_data.clone();
+					let host = host_data.clone();
+					let timings = timings_data.clone();
+					let observer = observer_data.clone();
+					let resource_timing = resource_timing_data.clone();
+					let verifier = verifier_data.clone();
+					let conn_tracker = conn_tracker.clone();
+					let origin = origin.clone();
+					async move {
+						match frame {
+							Err(err) => {
+								if is_keepalive_timeout(&err) {
+									conn_tracker.unhealthy(&origin);
+								}
+								let err = err.to_string();
+								if let Some(observer) = &observer {
+									observer.error(&FaithError::new(
+										FaithErrorKind::BodyStream,
+										Some(&err),
+									));
+								}
+								Some(Err(err))
+							}
+							Ok(frame) => match frame.into_trailers() {
+								Ok(trailers) => {
+									let mut t = trailers_lock.write().await;
+									*t = Trailers::Some(trailers);
+									None
+								}
+								Err(frame) => Some(
+									frame
+										.into_data()
+										.inspect(|data| {
+											if timings.first_byte.set(SystemTime::now()).is_ok()
+												&& let Some(observer) = &observer
+											{
+												observer.emit(RequestEventType::FirstByte);
+											}
+											stats
+												.bytes_received
+												.fetch_add(data.len() as u64, Ordering::Relaxed);
+											host.bytes_received
+												.fetch_add(data.len() as u64, Ordering::Relaxed);
+											if let Some(resource_timing) = &resource_timing {
+												resource_timing.received(data.len());
+											}
+											if let Some(verifier) = &verifier
+												&& let Some(verifier) = verifier
+													.lock()
+													.unwrap_or_else(|err| err.into_inner())
+													.as_mut()
+											{
+												verifier.update(data);
+											}
+										})
+										.map_err(|_| "unknown frame kind".to_string()),
+								),
+							},
+						}
+					}
+				})
+				.chain(stream::once(async move {
+					let mut t = trailers_finish.write().await;
+					if matches!(*t, Trailers::NotYet) {
+						*t = Trailers::None;
+					}
+					// Track that we've finished consuming a body
+					timings_finish.body_complete.get_or_init(SystemTime::now);
+					if let Some(observer) = &observer_finish {
+						observer.emit(RequestEventType::BodyDone);
+					}
+					if let Some(resource_timing) = &resource_timing_finish {
+						resource_timing.finish(&timings_finish);
+					}
+					stats_finish.bodies_finished.fetch_add(1, Ordering::Relaxed);
+					// Mark body as drained so Drop doesn't try to drain again
+					drained_finish.store(true, Ordering::SeqCst);
+					verifier
+						.and_then(|verifier| {
+							verifier
+								.lock()
+								.unwrap_or_else(|err| err.into_inner())
+								.take()
+						})
+						.and_then(|verifier| verifier.finish().err())
+						.map(Err)
+				}))
+				.filter_map(async |item| item),
+		) as Pin<Box<DynStream>>
+	}
+
 	/// Underlying efficient response body fetcher.
 	///
 	/// Unlike bytes() and co, this grabs all the chunks of the response but doesn't
@@ -324,10 +965,18 @@ impl FaithResponse {
 		drop(body); // release lock before consuming stream
 
 		let mut chunks = Vec::new();
+		let mut size = 0;
 		futures::pin_mut!(stream);
 		while let Some(result) = stream.next().await {
 			let chunk =
-				result.map_err(|err| FaithError::new(FaithErrorKind::BodyStream, Some(err)))?;
+				result.map_err(|err| ChecksumCheck::stream_error(self.checksum.as_ref(), err))?;
+			size += chunk.len();
+			if let Some(limit) = self.body_limit.filter(|&limit| size > limit as usize) {
+				return Err(FaithError::new(
+					FaithErrorKind::ResponseTooLarge,
+					Some(format!("response body is over the limit of {limit} bytes")),
+				));
+			}
 			chunks.push(chunk);
 		}
 
@@ -337,6 +986,30 @@ impl FaithResponse {
 		Ok(Arc::from(chunks.into_boxed_slice()))
 	}
 
+	/// Reads the start of the body, up to `limit` bytes, leaving the rest unread.
+	async fn gather_prefix(&self, limit: usize) -> Result<Vec<u8>, FaithError> {
+		let Some(lock) = &self.body.body else {
+			return Ok(Vec::new());
+		};
+
+		let mut body = lock.lock().await;
+		let stream = self.ensure_stream(&mut body, self.body.drained.clone())?;
+		drop(body); // release lock before consuming stream
+
+		let mut prefix = Vec::new();
+		futures::pin_mut!(stream);
+		while prefix.len() < limit {
+			let Some(result) = stream.next().await else {
+				break;
+			};
+			let chunk =
+				result.map_err(|err| ChecksumCheck::stream_error(self.checksum.as_ref(), err))?;
+			prefix.extend_from_slice(&chunk[..chunk.len().min(limit - prefix.len())]);
+		}
+
+		Ok(prefix)
+	}
+
 	/// Discard the response body, releasing the connection back to the pool.
 	///
 	/// This is useful when you don't need the body but want to ensure the connection
@@ -373,7 +1046,7 @@ impl FaithResponse {
 		let body = self.gather().await?;
 		let length = body.iter().map(|chunk| chunk.len()).sum();
 		let mut bytes = Vec::with_capacity(length);
-		for chunk in body.into_iter() {
+		for chunk in body.iter() {
 			bytes.extend_from_slice(chunk);
 		}
 
@@ -381,6 +1054,7 @@ impl FaithResponse {
 			verify_integrity(&bytes, integrity)?;
 		}
 
+		let _ = self.buffered.set(body);
 		Ok(bytes.into())
 	}
 
@@ -391,10 +1065,7 @@ impl FaithResponse {
 	#[napi]
 	pub fn bytes(&self) -> Async<Buffer> {
 		let this = Clone::clone(&*self);
-		FaithAsyncResult::run(async move || {
-			this.check_stream_disturbed()?;
-			this.gather_contiguous().await
-		})
+		FaithAsyncResult::run(async move || this.read_bytes().await)
 	}
 
 	/// The `text()` method of the `Response` interface takes a `Response` stream and reads it to
@@ -404,10 +1075,13 @@ impl FaithResponse {
 	pub fn text(&self) -> Async<String> {
 		let this = Clone::clone(&*self);
 		FaithAsyncResult::run(async move || {
-			this.check_stream_disturbed()?;
-			let bytes = this.gather_contiguous().await?;
-			String::from_utf8(bytes.to_vec())
-				.map_err(|e| FaithError::new(FaithErrorKind::Utf8Parse, Some(e.to_string())).into())
+			let bytes = this.read_bytes().await?;
+			String::from_utf8(bytes.to_vec()).map_err(|e| {
+				this.correlate(FaithError::new(
+					FaithErrorKind::Utf8Parse,
+					Some(e.to_string()),
+				))
+			})
 		})
 	}
 
@@ -424,13 +1098,7 @@ impl FaithResponse {
 	#[napi]
 	pub fn json(&self) -> Async<Value> {
 		let this = Clone::clone(&*self);
-		FaithAsyncResult::run(async move || {
-			this.check_stream_disturbed()?;
-			let bytes = this.gather_contiguous().await?;
-			let value = serde_json::from_slice(&bytes)
-				.map_err(|e| FaithError::new(FaithErrorKind::JsonParse, Some(e.to_string())))?;
-			Ok(Value(value))
-		})
+		FaithAsyncResult::run(async move || this.parse_json().await)
 	}
 
 	/// The `trailers()` read-only property of the `Response` interface returns a promise that
@@ -474,6 +1142,8 @@ impl FaithResponse {
 	/// `clone()` throws an `Error` if the response body has already been used.
 	///
 	/// (In-spec, this should throw a `TypeError`, but for technical reasons this is not possible with Fáith.)
+	///
+	/// The body is kept in memory until every clone has read it, unless the agent has `spill` set.
 	#[napi]
 	pub fn clone(&self, env: Env) -> Result<Self, napi::Error> {
 		if self.disturbed.load(Ordering::SeqCst) {
@@ -482,9 +1152,186 @@ impl FaithResponse {
 				.into());
 		}
 
+		let body = match self.spooled() {
+			Some(spool) => BodyHolder::new(
+				Some(Arc::new(Mutex::new(Body::Stream(SharedStream::new(
+					spool.reader(),
+				))))),
+				self.body.version,
+			),
+			None => self.body.clone(),
+		};
+
 		Ok(Self {
+			body,
+			buffered: Default::default(),
 			disturbed: Arc::new(AtomicBool::new(false)),
 			..Clone::clone(self)
 		})
 	}
+
+	/// Returns the spool for the body if the agent has `spill` set, moving the body into one if it
+	/// hasn't started being read.
+	fn spooled(&self) -> Option<Arc<Spool>> {
+		if let Some(spool) = self.spool.get() {
+			return Some(spool.clone());
+		}
+
+		let options = self.spill.as_ref()?;
+		let mut body = self.body.body.as_ref()?.try_lock().ok()?;
+		if !matches!(*body, Body::Inner(_)) {
+			return None;
+		}
+
+		let Body::Inner(inner) = replace(&mut *body, Body::Consumed) else {
+			// SAFETY: we've just checked for this exact thing
+			unsafe { unreachable_unchecked() }
+		};
+		let spool = Arc::new(Spool::new(
+			self.wrap_body(inner, self.body.drained.clone()),
+			options,
+		));
+		*body = Body::Stream(SharedStream::new(spool.reader()));
+		Some(self.spool.get_or_init(|| spool).clone())
+	}
+
+	/// Custom to Fáith. Returns a snapshot of the response, without reading its body. See
+	/// `ResponseSnapshot`.
+	///
+	/// This is called by `JSON.stringify()`.
+	#[napi(js_name = "toJSON")]
+	pub fn to_json(&self) -> ResponseSnapshot {
+		ResponseSnapshot {
+			status: self.status(),
+			status_text: self.status_text(),
+			ok: self.ok(),
+			url: self.url(),
+			redirected: self.redirected(),
+			version: self.version(),
+			headers: self.headers(),
+			peer: PeerSnapshot {
+				address: self.peer.address.map(|addr| addr.to_string()),
+				certificate: self
+					.peer
+					.certificate
+					.as_ref()
+					.map(|cert| BASE64_STANDARD.encode(cert)),
+				proxy: self.peer.proxy.as_ref().map(|proxy| PeerProxySnapshot {
+					url: proxy.url.to_string(),
+					tunnel: proxy.tunnel,
+					connect_status: proxy.tunnel.then_some(200),
+				}),
+				tls: self.peer.tls.clone(),
+			},
+			timestamps: self.timestamps(),
+			correlation_id: self.correlation_id(),
+			body: self.buffered.get().map(|body| {
+				let mut engine = base64::write::EncoderStringWriter::new(&BASE64_STANDARD);
+				for chunk in body.iter() {
+					let _ = engine.write_all(chunk);
+				}
+				engine.into_inner()
+			}),
+		}
+	}
+
+	/// Custom to Fáith. Splits the response into `count` responses (default 2), which each read the
+	/// same body independently, so that one can be hashed or saved while another is parsed. This
+	/// response's body is then used.
+	///
+	/// Unlike `clone()`, which keeps the body in memory until every clone has read it, the responses
+	/// from `tee()` only buffer up to 16 chunks ahead of the slowest of them. This means they must be
+	/// read concurrently: reading one to the end before starting another will stall once it gets
+	/// that far ahead. Call `discard()` on, or drop, those you don't need.
+	#[napi]
+	pub fn tee(&self, env: Env, count: Option<u32>) -> Result<Vec<Self>, napi::Error> {
+		if self.disturbed.swap(true, Ordering::SeqCst) {
+			return Err(FaithError::from(FaithErrorKind::ResponseAlreadyDisturbed)
+				.into_js_error(&env)
+				.into());
+		}
+
+		let count = count.unwrap_or(2) as usize;
+		let Some(lock) = &self.body.body else {
+			return Ok((0..count)
+				.map(|_| Self {
+					disturbed: Arc::new(AtomicBool::new(false)),
+					..Clone::clone(self)
+				})
+				.collect());
+		};
+
+		let source = {
+			let mut body = lock.try_lock().map_err(|_| {
+				FaithError::from(FaithErrorKind::ResponseAlreadyDisturbed).into_js_error(&env)
+			})?;
+			self.ensure_stream(&mut body, self.body.drained.clone())
+				.map_err(|err| err.into_js_error(&env))?
+		};
+
+		let (senders, receivers): (Vec<_>, Vec<_>) =
+			(0..count).map(|_| mpsc::channel(TEE_BUFFER)).unzip();
+		let drained = self.body.drained.clone();
+		runtime::handle()
+			.map_err(|err| err.into_js_error(&env))?
+			.spawn(async move {
+				futures::pin_mut!(source);
+				while let Some(chunk) = source.next().await {
+					let mut open = false;
+					for sender in &senders {
+						open |= sender.send(chunk.clone()).await.is_ok();
+					}
+					if !open {
+						return;
+					}
+				}
+				drained.store(true, Ordering::SeqCst);
+			});
+
+		Ok(receivers
+			.into_iter()
+			.map(|receiver| {
+				let stream = Box::pin(ReceiverStream::new(receiver)) as Pin<Box<DynStream>>;
+				Self {
+					body: BodyHolder::new(
+						Some(Arc::new(Mutex::new(Body::Stream(SharedStream::new(
+							stream,
+						))))),
+						self.body.version,
+					),
+					buffered: Default::default(),
+					disturbed: Arc::new(AtomicBool::new(false)),
+					..Clone::clone(self)
+				}
+			})
+			.collect())
+	}
+
+	/// Custom to Fáith. Gives up this response, so that it can be read in another thread, and returns
+	/// a handle to it. Post the handle to a worker with `postMessage()`, and call
+	/// `receiveResponse()` with it there to get the response back, with its body still unread. The
+	/// body is then read in the worker straight from Fáith, rather than being copied over chunk by
+	/// chunk. This response's body is then used.
+	///
+	/// A handle which is never received keeps the response, and its connection, until the process
+	/// exits: call `discard()` on the received response if its body isn't needed.
+	#[napi]
+	pub fn transfer(&self, env: Env) -> Result<TransferredResponse, napi::Error> {
+		if self.disturbed.swap(true, Ordering::SeqCst) {
+			return Err(FaithError::from(FaithErrorKind::ResponseAlreadyDisturbed)
+				.into_js_error(&env)
+				.into());
+		}
+
+		Ok(transfer::give(Self {
+			disturbed: Arc::new(AtomicBool::new(false)),
+			..Clone::clone(self)
+		}))
+	}
 }
+
+/// How many chunks the responses from `tee()` can get ahead of the slowest one.
+const TEE_BUFFER: usize = 16;
+
+/// How much of the body of a response with an error status is kept in `HttpStatus` errors.
+const ERROR_PREVIEW_SIZE: usize = 1024;
@@ -0,0 +1,408 @@
+use std::{
+	future::Future,
+	mem::replace,
+	sync::{
+		Arc, Mutex, RwLock,
+		atomic::{AtomicBool, AtomicUsize, Ordering},
+	},
+	time::Duration,
+};
+
+use napi::{Env, bindgen_prelude::PromiseRaw};
+use napi_derive::napi;
+use tokio::{
+	runtime::{Builder, Handle, Runtime},
+	sync::Notify,
+	time::timeout,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+	error::{FaithError, FaithErrorKind},
+	instance::Instance,
+};
+
+enum State {
+	NotStarted,
+	Running(Runtime),
+	ShutDown,
+}
+
+static STATE: RwLock<State> = RwLock::new(State::NotStarted);
+static OPTIONS: Mutex<Option<RuntimeOptions>> = Mutex::new(None);
+
+/// How many environments are using the runtime, which is stopped once none are.
+///
+/// When locked together with `STATE`, this is locked first.
+static ENVS: Mutex<usize> = Mutex::new(0);
+
+tokio::task_local! {
+	/// The scope of the task being run.
+	static SCOPE: Arc<Scope>;
+}
+
+/// How long aborted tasks, and then the runtime itself, are given to stop during shutdown.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(1);
+
+/// Settings for Fáith's runtime, for `configureRuntime()`.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeOptions {
+	/// Maximum number of threads for blocking operations, such as reading and writing the disk cache
+	/// and system DNS resolution. These are started as needed and stop after being idle for a while.
+	///
+	/// Default: 512.
+	pub max_blocking_threads: Option<u32>,
+	/// Prefix of the names of the runtime's threads, which are suffixed with a sequence number. This
+	/// shows up in debuggers and profilers.
+	///
+	/// Default: `faith-worker`.
+	pub thread_name_prefix: Option<String>,
+	/// Number of worker threads, which drive all requests.
+	///
+	/// Default: the number of CPU cores, or the `TOKIO_WORKER_THREADS` environment variable.
+	pub worker_threads: Option<u32>,
+}
+
+/// Settings for `faithShutdown()`.
+#[napi(object)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShutdownOptions {
+	/// How long to wait for in-flight requests and body reads to complete before aborting them, in
+	/// milliseconds.
+	///
+	/// Default: 5000.
+	pub drain_ms: Option<u32>,
+}
+
+/// The requests of one JS environment: the main thread, or a worker thread, each of which loads the
+/// addon separately. `faithShutdown()` and the environment exiting only stop the environment's own
+/// requests, and the runtime they share is stopped once no environment is using it.
+#[derive(Debug)]
+pub(crate) struct Scope {
+	/// Set by `faithShutdown()`, after which no new requests are accepted.
+	shutting_down: AtomicBool,
+	/// Cancelled when the shutdown drain deadline is reached, to abort the remaining tasks.
+	cancel: CancellationToken,
+	in_flight: AtomicUsize,
+	drained: Notify,
+}
+
+impl Scope {
+	/// Starts a scope for an environment, which holds on to the runtime until it's shut down.
+	pub(crate) fn new() -> Arc<Self> {
+		let mut envs = ENVS.lock().unwrap_or_else(|err| err.into_inner());
+		*envs += 1;
+
+		// a new environment can start the runtime again once all others have shut it down
+		let mut state = STATE.write().unwrap_or_else(|err| err.into_inner());
+		if matches!(*state, State::ShutDown) {
+			*state = State::NotStarted;
+		}
+
+		Arc::new(Self {
+			shutting_down: AtomicBool::new(false),
+			cancel: CancellationToken::new(),
+			in_flight: AtomicUsize::new(0),
+			drained: Notify::new(),
+		})
+	}
+
+	/// Runs a future in the scope, so that the requests it makes are rejected once it's shut down,
+	/// and the tasks it spawns are tracked in it.
+	pub(crate) async fn run<F: Future>(self: Arc<Self>, future: F) -> F::Output {
+		SCOPE.scope(self, future).await
+	}
+
+	/// Returns an error if new requests are no longer accepted, because of `faithShutdown()`.
+	fn check_accepting(&self) -> Result<(), FaithError> {
+		if self.shutting_down.load(Ordering::SeqCst) {
+			Err(FaithError::new(
+				FaithErrorKind::RuntimeThread,
+				Some("not accepting new requests: faithShutdown() has been called"),
+			))
+		} else {
+			Ok(())
+		}
+	}
+
+	async fn drained(&self) {
+		loop {
+			let notified = self.drained.notified();
+			if self.in_flight.load(Ordering::SeqCst) == 0 {
+				return;
+			}
+			notified.await;
+		}
+	}
+
+	/// Stops accepting new requests. Returns false if that was already done.
+	fn stop(&self) -> bool {
+		!self.shutting_down.swap(true, Ordering::SeqCst)
+	}
+
+	/// Gives the requests in flight `drain` to complete and aborts the rest, then lets go of the
+	/// runtime. This blocks, so it can't be called from within the runtime.
+	fn shutdown(&self, drain: Duration) {
+		let handle = match &*STATE.read().unwrap_or_else(|err| err.into_inner()) {
+			State::Running(runtime) => Some(runtime.handle().clone()),
+			_ => None,
+		};
+
+		if let Some(handle) = handle {
+			handle.block_on(async {
+				if timeout(drain, self.drained()).await.is_err() {
+					self.cancel.cancel();
+					let _ = timeout(SHUTDOWN_GRACE, self.drained()).await;
+				}
+			});
+		}
+
+		let mut envs = ENVS.lock().unwrap_or_else(|err| err.into_inner());
+		*envs = envs.saturating_sub(1);
+		if *envs == 0 {
+			let state = replace(
+				&mut *STATE.write().unwrap_or_else(|err| err.into_inner()),
+				State::ShutDown,
+			);
+			if let State::Running(runtime) = state {
+				runtime.shutdown_timeout(SHUTDOWN_GRACE);
+			}
+		}
+	}
+
+	/// Shuts the scope down when its environment exits, aborting its requests still in flight,
+	/// unless `faithShutdown()` already did.
+	pub(crate) fn close(&self) {
+		if self.stop() {
+			self.shutdown(Duration::ZERO);
+		}
+	}
+}
+
+fn build(options: RuntimeOptions) -> Result<Runtime, FaithError> {
+	let prefix = options
+		.thread_name_prefix
+		.unwrap_or_else(|| "faith-worker".into());
+	let counter = AtomicUsize::new(0);
+
+	let mut builder = Builder::new_multi_thread();
+	builder
+		.enable_all()
+		.thread_name_fn(move || format!("{prefix}-{}", counter.fetch_add(1, Ordering::Relaxed)));
+	if let Some(threads) = options.worker_threads {
+		builder.worker_threads(threads as usize);
+	}
+	if let Some(threads) = options.max_blocking_threads {
+		builder.max_blocking_threads(threads as usize);
+	}
+
+	builder
+		.build()
+		.map_err(|err| FaithError::new(FaithErrorKind::RuntimeThread, Some(err.to_string())))
+}
+
+/// Returns a handle to Fáith's runtime, starting it on first use.
+///
+/// This is a multi-threaded Tokio runtime owned by the addon and shared by all the environments it's
+/// loaded into, which lives until they have all called `faithShutdown()` or exited. All requests
+/// and body reads are driven on it, and their promises are resolved from it, so that no libuv
+/// threadpool thread is blocked for the duration of a request. It is built with the options given
+/// to `configureRuntime()`, if any.
+pub(crate) fn handle() -> Result<Handle, FaithError> {
+	if let State::Running(runtime) = &*STATE.read().unwrap_or_else(|err| err.into_inner()) {
+		return Ok(runtime.handle().clone());
+	}
+
+	let mut state = STATE.write().unwrap_or_else(|err| err.into_inner());
+	match &*state {
+		State::Running(runtime) => Ok(runtime.handle().clone()),
+		State::ShutDown => Err(FaithError::new(
+			FaithErrorKind::RuntimeThread,
+			Some("the runtime has been shut down by faithShutdown()"),
+		)),
+		State::NotStarted => {
+			let options = OPTIONS
+				.lock()
+				.unwrap_or_else(|err| err.into_inner())
+				.clone()
+				.unwrap_or_default();
+			let runtime = build(options)?;
+			let handle = runtime.handle().clone();
+			*state = State::Running(runtime);
+			Ok(handle)
+		}
+	}
+}
+
+/// Returns an error if new requests are no longer accepted, because `faithShutdown()` was called in
+/// the environment the current task was started from.
+pub(crate) fn check_accepting() -> Result<(), FaithError> {
+	SCOPE
+		.try_with(|scope| scope.check_accepting())
+		.unwrap_or(Ok(()))
+}
+
+/// Marks a task as in flight in its scope until dropped, for shutdown to wait on.
+struct InFlight(Arc<Scope>);
+
+impl InFlight {
+	fn start(scope: Arc<Scope>) -> Self {
+		scope.in_flight.fetch_add(1, Ordering::SeqCst);
+		Self(scope)
+	}
+}
+
+impl Drop for InFlight {
+	fn drop(&mut self) {
+		if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+			self.0.drained.notify_waiters();
+		}
+	}
+}
+
+/// Runs a task on the runtime in the environment's scope, then passes its result to `then`.
+///
+/// The task is tracked so that shutdown can wait for it, and it is aborted if shutdown's drain
+/// deadline is reached. If the runtime can't be started or has been shut down, `then` is called
+/// immediately with the error.
+pub(crate) fn spawn_for<T: Send + 'static>(
+	env: &Env,
+	task: impl Future<Output = Result<T, FaithError>> + Send + 'static,
+	then: impl FnOnce(Result<T, FaithError>) + Send + 'static,
+) {
+	match Instance::get(env) {
+		Ok(instance) => spawn_in(Some(instance.scope.clone()), task, then),
+		Err(err) => then(Err(err)),
+	}
+}
+
+/// Runs a task on the runtime in the current task's scope, if any, then passes its result to
+/// `then`, as with `spawn_for()`.
+pub(crate) fn spawn<T: Send + 'static>(
+	task: impl Future<Output = Result<T, FaithError>> + Send + 'static,
+	then: impl FnOnce(Result<T, FaithError>) + Send + 'static,
+) {
+	spawn_in(SCOPE.try_with(Arc::clone).ok(), task, then);
+}
+
+fn spawn_in<T: Send + 'static>(
+	scope: Option<Arc<Scope>>,
+	task: impl Future<Output = Result<T, FaithError>> + Send + 'static,
+	then: impl FnOnce(Result<T, FaithError>) + Send + 'static,
+) {
+	let handle = match handle() {
+		Ok(handle) => handle,
+		Err(err) => return then(Err(err)),
+	};
+	let Some(scope) = scope else {
+		handle.spawn(async move { then(task.await) });
+		return;
+	};
+
+	let in_flight = InFlight::start(scope.clone());
+	handle.spawn(async move {
+		let cancel = scope.cancel.clone();
+		let result = tokio::select! {
+			result = scope.clone().run(task) => result,
+			_ = cancel.cancelled() => Err(FaithError::new(
+				FaithErrorKind::Aborted,
+				Some("aborted by faithShutdown()"),
+			)
+			.with_cause("faithShutdown")),
+		};
+		then(result);
+		drop(in_flight);
+	});
+}
+
+/// Custom to Fáith.
+///
+/// Configures Fáith's runtime, which is started on first use: this must be called before any agent is
+/// created or request is made, including with the global agent, and throws otherwise. Use this to
+/// shrink the runtime in constrained environments such as serverless functions or Electron, or to
+/// grow it for heavy workloads.
+///
+/// Throws a `Config` error if the runtime has already started, or if any of the thread counts are
+/// zero.
+#[napi]
+pub fn configure_runtime(env: Env, options: RuntimeOptions) -> Result<(), napi::Error> {
+	let check = || {
+		if !matches!(
+			*STATE.read().unwrap_or_else(|err| err.into_inner()),
+			State::NotStarted
+		) {
+			return Err(FaithError::new(
+				FaithErrorKind::Config,
+				Some("the runtime has already started: configureRuntime() must be called first"),
+			));
+		}
+
+		if options.worker_threads == Some(0) || options.max_blocking_threads == Some(0) {
+			return Err(FaithError::new(
+				FaithErrorKind::Config,
+				Some("runtime thread counts must be at least 1"),
+			));
+		}
+
+		Ok(())
+	};
+	check().map_err(|err| napi::Error::from(err.into_js_error(&env)))?;
+
+	*OPTIONS.lock().unwrap_or_else(|err| err.into_inner()) = Some(options);
+	Ok(())
+}
+
+/// Custom to Fáith.
+///
+/// Shuts Fáith down in the current thread, so the addon can be unloaded cleanly, e.g. at the end of
+/// a test suite or before a worker thread exits:
+///
+/// 1. new requests are rejected with a `RuntimeThread` error;
+/// 2. in-flight requests and body reads are given `drainMs` to complete;
+/// 3. those still pending are then aborted with an `Aborted` error;
+/// 4. once every thread which loaded Fáith has shut it down or exited, the runtime is stopped,
+///    which closes all connections and ends background tasks.
+///
+/// The returned promise resolves once that's done. Fáith can't be used again afterwards in this
+/// thread, but other threads are unaffected. Calling this more than once resolves immediately.
+///
+/// A worker thread which exits without calling this has its requests still in flight aborted.
+#[napi(ts_return_type = "Promise<void>")]
+pub fn faith_shutdown(
+	env: &Env,
+	options: Option<ShutdownOptions>,
+) -> Result<PromiseRaw<'_, ()>, napi::Error> {
+	let drain = Duration::from_millis(
+		options
+			.and_then(|options| options.drain_ms)
+			.unwrap_or(5000)
+			.into(),
+	);
+
+	let scope = Instance::get(env)
+		.map_err(|err| napi::Error::from(err.into_js_error(env)))?
+		.scope
+		.clone();
+	let (deferred, promise) = env.create_deferred()?;
+	if !scope.stop() {
+		deferred.resolve(|_| Ok(()));
+		return Ok(promise);
+	}
+
+	// the runtime can't be stopped from within itself, so this is done from a separate thread
+	std::thread::Builder::new()
+		.name("faith-shutdown".into())
+		.spawn(move || {
+			scope.shutdown(drain);
+			deferred.resolve(|_| Ok(()));
+		})
+		.map_err(|err| {
+			napi::Error::from(
+				FaithError::new(FaithErrorKind::RuntimeThread, Some(err.to_string()))
+					.into_js_error(env),
+			)
+		})?;
+
+	Ok(promise)
+}
@@ -0,0 +1,173 @@
+use std::{
+	future::Future,
+	net::SocketAddr,
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+use http::Extensions;
+use hyper_util::client::legacy::connect::{Connection, HttpInfo};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{
+	agent::AgentSocketOptions,
+	error::{FaithError, FaithErrorKind},
+};
+
+/// Sets the agent's `socket` options on each new TCP connection, as its connector doesn't expose
+/// the sockets it creates.
+///
+/// The options are set once the connection is established, before any request is sent on it. The
+/// socket is found among the process's open file descriptors by its local and peer addresses, which
+/// together are unique to it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SocketOptionsLayer {
+	tos: Option<u8>,
+	#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+	mark: Option<u32>,
+}
+
+impl SocketOptionsLayer {
+	/// Returns `None` if no option is set.
+	pub(crate) fn new(options: &AgentSocketOptions) -> Result<Option<Self>, FaithError> {
+		let tos = options
+			.tos
+			.map(|tos| {
+				u8::try_from(tos).map_err(|_| {
+					FaithError::new(
+						FaithErrorKind::Config,
+						Some(format!("socket.tos must be between 0 and 255, got {tos}")),
+					)
+				})
+			})
+			.transpose()?;
+
+		if cfg!(not(unix)) && tos.is_some() {
+			return Err(FaithError::new(
+				FaithErrorKind::Config,
+				Some("socket.tos is not supported on this platform"),
+			));
+		}
+
+		if cfg!(not(target_os = "linux")) && options.mark.is_some() {
+			return Err(FaithError::new(
+				FaithErrorKind::Config,
+				Some("socket.mark is only supported on Linux"),
+			));
+		}
+
+		Ok((tos.is_some() || options.mark.is_some()).then_some(Self {
+			tos,
+			mark: options.mark,
+		}))
+	}
+}
+
+impl<S> Layer<S> for SocketOptionsLayer {
+	type Service = SocketOptionsService<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		SocketOptionsService {
+			inner,
+			options: *self,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SocketOptionsService<S> {
+	inner: S,
+	options: SocketOptionsLayer,
+}
+
+impl<S, R> Service<R> for SocketOptionsService<S>
+where
+	S: Service<R>,
+	S::Response: Connection,
+	S::Error: From<std::io::Error>,
+	S::Future: Send + 'static,
+{
+	type Response = S::Response;
+	type Error = S::Error;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, request: R) -> Self::Future {
+		let connecting = self.inner.call(request);
+		let options = self.options;
+		Box::pin(async move {
+			let conn = connecting.await?;
+
+			let mut extras = Extensions::new();
+			conn.connected().get_extras(&mut extras);
+			if let Some(info) = extras.get::<HttpInfo>() {
+				options.apply(info.local_addr(), info.remote_addr())?;
+			}
+
+			Ok(conn)
+		})
+	}
+}
+
+impl SocketOptionsLayer {
+	#[cfg(unix)]
+	fn apply(&self, local: SocketAddr, remote: SocketAddr) -> std::io::Result<()> {
+		use std::os::fd::BorrowedFd;
+
+		use socket2::SockRef;
+
+		let dir = if cfg!(target_os = "linux") {
+			"/proc/self/fd"
+		} else {
+			"/dev/fd"
+		};
+
+		for entry in std::fs::read_dir(dir)?.flatten() {
+			let Some(fd) = entry
+				.file_name()
+				.to_str()
+				.and_then(|name| name.parse().ok())
+			else {
+				continue;
+			};
+
+			// SAFETY: the descriptor is only used for the duration of this iteration. If it was closed
+			// since it was listed, the calls below fail; if it was reused, its addresses won't match.
+			let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+			let socket = SockRef::from(&fd);
+			let addr = |addr: std::io::Result<socket2::SockAddr>| addr.ok()?.as_socket();
+			if addr(socket.local_addr()) != Some(local) || addr(socket.peer_addr()) != Some(remote)
+			{
+				continue;
+			}
+
+			if let Some(tos) = self.tos {
+				if local.is_ipv4() {
+					socket.set_tos_v4(tos.into())?;
+				} else {
+					socket.set_tclass_v6(tos.into())?;
+				}
+			}
+
+			#[cfg(target_os = "linux")]
+			if let Some(mark) = self.mark {
+				socket.set_mark(mark)?;
+			}
+
+			return Ok(());
+		}
+
+		Err(std::io::Error::other(format!(
+			"could not find the socket for {local} -> {remote} to set its options"
+		)))
+	}
+
+	#[cfg(not(unix))]
+	fn apply(&self, _local: SocketAddr, _remote: SocketAddr) -> std::io::Result<()> {
+		Ok(())
+	}
+}
@@ -0,0 +1,200 @@
+use std::{
+	fmt::Debug,
+	io::SeekFrom,
+	path::{Path, PathBuf},
+	pin::Pin,
+	sync::{
+		Arc,
+		atomic::{AtomicU64, Ordering},
+	},
+};
+
+use bytes::{Bytes, BytesMut};
+use futures::{StreamExt, stream};
+use tokio::{
+	fs::{File, OpenOptions},
+	io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+	sync::Mutex,
+};
+
+use crate::{agent::AgentSpillOptions, body::DynStream};
+
+/// How much is read from the temporary file at once.
+const READ_SIZE: usize = 64 * 1024;
+
+/// Used to give each temporary file a unique name within the process.
+static SPOOL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A response body shared between clones, for the agent's `spill` option.
+///
+/// The body is read from the network once, and kept so each clone can read it from the start at its
+/// own pace: its first `threshold` bytes in memory, and the rest in a temporary file. The file is
+/// deleted when the spool is dropped, which is when all the clones reading from it are.
+pub(crate) struct Spool {
+	state: Mutex<SpoolState>,
+	threshold: u64,
+	dir: PathBuf,
+}
+
+struct SpoolState {
+	source: Pin<Box<DynStream>>,
+	/// The chunks kept in memory, with the offset each starts at.
+	memory: Vec<(u64, Bytes)>,
+	memory_len: u64,
+	file: Option<(File, PathBuf)>,
+	file_len: u64,
+	end: Option<Result<(), String>>,
+}
+
+impl Spool {
+	pub(crate) fn new(source: Pin<Box<DynStream>>, options: &AgentSpillOptions) -> Self {
+		Self {
+			state: Mutex::new(SpoolState {
+				source,
+				memory: Vec::new(),
+				memory_len: 0,
+				file: None,
+				file_len: 0,
+				end: None,
+			}),
+			threshold: options.threshold.unwrap_or(8 * 1024 * 1024).into(),
+			dir: options
+				.dir
+				.as_ref()
+				.map(PathBuf::from)
+				.unwrap_or_else(std::env::temp_dir),
+		}
+	}
+
+	/// Returns a stream of the whole body, from the start.
+	pub(crate) fn reader(self: &Arc<Self>) -> Pin<Box<DynStream>> {
+		Box::pin(stream::unfold(Some((self.clone(), 0)), async |reader| {
+			let (spool, position) = reader?;
+			match spool.read(position).await {
+				Some(Ok(chunk)) => {
+					let next = position + chunk.len() as u64;
+					Some((Ok(chunk), Some((spool, next))))
+				}
+				Some(Err(err)) => Some((Err(err), None)),
+				None => None,
+			}
+		}))
+	}
+
+	/// Reads the next chunk at `position`, pulling it from the network if no clone has yet.
+	async fn read(&self, position: u64) -> Option<Result<Bytes, String>> {
+		let mut state = self.state.lock().await;
+		loop {
+			if position < state.memory_len {
+				let index = state
+					.memory
+					.partition_point(|(start, _)| *start <= position)
+					- 1;
+				let (start, chunk) = &state.memory[index];
+				return Some(Ok(chunk.slice((position - start) as usize..)));
+			}
+
+			if position < state.memory_len + state.file_len {
+				return Some(state.read_file(position).await);
+			}
+
+			match &state.end {
+				Some(Ok(())) => return None,
+				Some(Err(err)) => return Some(Err(err.clone())),
+				None => {}
+			}
+
+			match state.source.next().await {
+				Some(Ok(chunk)) => {
+					if let Err(err) = state.append(chunk, self).await {
+						state.end = Some(Err(err));
+					}
+				}
+				Some(Err(err)) => state.end = Some(Err(err)),
+				None => state.end = Some(Ok(())),
+			}
+		}
+	}
+}
+
+impl Debug for Spool {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Spool")
+			.field("threshold", &self.threshold)
+			.field("dir", &self.dir)
+			.finish_non_exhaustive()
+	}
+}
+
+impl SpoolState {
+	async fn append(&mut self, chunk: Bytes, spool: &Spool) -> Result<(), String> {
+		if self.file.is_none() && self.memory_len + chunk.len() as u64 <= spool.threshold {
+			let length = chunk.len() as u64;
+			self.memory.push((self.memory_len, chunk));
+			self.memory_len += length;
+			return Ok(());
+		}
+
+		let (file, _) = match &mut self.file {
+			Some(file) => file,
+			None => self
+				.file
+				.insert(create_file(&spool.dir).await.map_err(|err| {
+					format!(
+						"failed to create a spill file in {}: {err}",
+						spool.dir.display()
+					)
+				})?),
+		};
+		file.seek(SeekFrom::End(0))
+			.await
+			.map_err(|err| err.to_string())?;
+		file.write_all(&chunk)
+			.await
+			.map_err(|err| err.to_string())?;
+		file.flush().await.map_err(|err| err.to_string())?;
+		self.file_len += chunk.len() as u64;
+		Ok(())
+	}
+
+	async fn read_file(&mut self, position: u64) -> Result<Bytes, String> {
+		let offset = position - self.memory_len;
+		let length = (self.file_len - offset).min(READ_SIZE as u64) as usize;
+		let Some((file, _)) = &mut self.file else {
+			return Err("spill file is missing".into());
+		};
+
+		file.seek(SeekFrom::Start(offset))
+			.await
+			.map_err(|err| err.to_string())?;
+		let mut buffer = BytesMut::zeroed(length);
+		file.read_exact(&mut buffer)
+			.await
+			.map_err(|err| err.to_string())?;
+		Ok(buffer.freeze())
+	}
+}
+
+impl Drop for SpoolState {
+	fn drop(&mut self) {
+		if let Some((file, path)) = self.file.take() {
+			drop(file);
+			let _ = std::fs::remove_file(path);
+		}
+	}
+}
+
+async fn create_file(dir: &Path) -> std::io::Result<(File, PathBuf)> {
+	let path = dir.join(format!(
+		"faith-spill-{}-{}",
+		std::process::id(),
+		SPOOL_COUNTER.fetch_add(1, Ordering::Relaxed)
+	));
+	let file = OpenOptions::new()
+		.read(true)
+		.write(true)
+		.create_new(true)
+		.open(&path)
+		.await?;
+	Ok((file, path))
+}
@@ -38,17 +38,47 @@ impl StreamBodySender {
 	}
 }
 
+/// The default for the agent's `limits.replayBuffer`.
+pub(crate) const DEFAULT_REPLAY_BUFFER: u32 = 64 * 1024;
+
 /// Internal receiver that can be converted into a stream for reqwest
 pub struct StreamBodyReceiver {
 	rx: mpsc::Receiver<Bytes>,
 }
 
 impl StreamBodyReceiver {
-	/// Convert this receiver into a Stream suitable for reqwest::Body
+	/// Reads the whole body if it's at most `limit` bytes, so it can be sent as a buffered body,
+	/// which reqwest can send again. Otherwise, returns the chunks read so far with the receiver.
+	pub async fn read_ahead(
+		mut self,
+		limit: usize,
+	) -> std::result::Result<Bytes, (Vec<Bytes>, Self)> {
+		let mut chunks = Vec::new();
+		if limit == 0 {
+			return Err((chunks, self));
+		}
+
+		let mut size = 0;
+		while size <= limit {
+			let Some(bytes) = self.rx.recv().await else {
+				return Ok(Bytes::from(chunks.concat()));
+			};
+			size += bytes.len();
+			chunks.push(bytes);
+		}
+		Err((chunks, self))
+	}
+
+	/// Convert this receiver into a Stream suitable for reqwest::Body, starting with the chunks
+	/// already read by `read_ahead()`.
 	pub fn into_stream(
 		self,
+		read: Vec<Bytes>,
 	) -> impl futures::Stream<Item = std::result::Result<Bytes, std::io::Error>> + Send {
 		async_stream::stream! {
+			for bytes in read {
+				yield std::result::Result::<Bytes, std::io::Error>::Ok(bytes);
+			}
 			let mut rx = self.rx;
 			while let Some(bytes) = rx.recv().await {
 				yield std::result::Result::<Bytes, std::io::Error>::Ok(bytes);
@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use napi::{Env, bindgen_prelude::Buffer};
+use napi_derive::napi;
+use tokio::time::timeout;
+
+use crate::{
+	agent::Agent,
+	error::{FaithError, FaithErrorKind},
+	fetch::fetch,
+	instance::Instance,
+	options::{FaithOptions, FaithOptionsAndBody},
+	runtime, tls,
+};
+
+/// How long `faithFetchSync()` blocks for at most, without a `timeout`.
+const SYNC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How large a body `faithFetchSync()` reads, without the agent's `limits.responseBody`.
+const SYNC_BODY_LIMIT: u32 = 16 * 1024 * 1024;
+
+/// Custom to Fáith.
+///
+/// The response from `faithFetchSync()`, with its body read in full. The properties are as on
+/// `Response`.
+#[napi(object)]
+pub struct FaithSyncResponse {
+	pub status: u16,
+	pub status_text: String,
+	pub ok: bool,
+	pub url: String,
+	pub redirected: bool,
+	pub headers: Vec<(String, String)>,
+	pub body: Buffer,
+}
+
+/// Custom to Fáith.
+///
+/// Fetches a resource and reads its body in full, blocking the thread until it's done, for short
+/// CLI tools and config loaders which run before the event loop matters. Takes the same options as
+/// `faithFetch()`, except that streaming bodies and `signal` aren't supported.
+///
+/// The `timeout` option (or `timeouts.total`) is a hard limit on the whole request including
+/// reading the body, and defaults to 30 seconds: it fails with a `Timeout` error once it's over.
+/// The body is limited to the agent's `limits.responseBody`, or 16 MiB if it has none, and fails
+/// with a `ResponseTooLarge` error if it's larger. HTTP error statuses don't throw, unless the
+/// agent or the request has `throwOnError`.
+///
+/// Callbacks which run on the JS thread, like the `observe` option and the agent's cookie filter,
+/// can't run while it's blocked: they run once it returns, so cookies subject to a filter are only
+/// stored then.
+#[napi]
+pub fn faith_fetch_sync(
+	env: Env,
+	url: String,
+	options: Option<FaithOptionsAndBody>,
+) -> Result<FaithSyncResponse, napi::Error> {
+	let (options, agent, body) = FaithOptions::extract(options.unwrap_or_default());
+	let (limit, cause) = options.total_timeout().unwrap_or((SYNC_TIMEOUT, "timeout"));
+	let agent = agent.map_or_else(|| Agent::global(&env), Ok);
+	let scope = Instance::get(&env).map(|instance| instance.scope.clone());
+
+	let run = async move {
+		runtime::check_accepting()?;
+		let agent = agent?;
+		let body_limit = agent.limits.response_body.unwrap_or(SYNC_BODY_LIMIT);
+		let mut response = fetch(url, options, agent, body, None, None).await?;
+		response.body_limit = Some(body_limit);
+		let body = response.read_bytes().await?;
+		Ok(FaithSyncResponse {
+			status: response.status(),
+			status_text: response.status_text(),
+			ok: response.ok(),
+			url: response.url(),
+			redirected: response.redirected(),
+			headers: response.headers(),
+			body,
+		})
+	};
+	// the JS thread is blocked, so the agent's certificate verify proc can't run
+	let run = tls::blocking(run);
+
+	runtime::handle()
+		.and_then(|handle| {
+			handle.block_on(scope?.run(async {
+				timeout(limit, run).await.unwrap_or_else(|_| {
+					Err(FaithError::new(
+						FaithErrorKind::Timeout,
+						Some(format!(
+							"faithFetchSync() took longer than {}ms",
+							limit.as_millis()
+						)),
+					)
+					.with_cause(cause))
+				})
+			}))
+		})
+		.map_err(|err| err.into_js_error(&env).into())
+}
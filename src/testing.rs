@@ -0,0 +1,423 @@
+use std::{
+	convert::Infallible,
+	io,
+	net::{Ipv4Addr, SocketAddr},
+	pin::Pin,
+	result::Result,
+	sync::{
+		Arc, Mutex,
+		atomic::{AtomicU32, Ordering},
+	},
+	time::Duration,
+};
+
+use bytes::Bytes;
+use futures::Stream;
+use http::{
+	HeaderMap, HeaderName, HeaderValue, Method, Request, Response, StatusCode,
+	header::{CONTENT_LENGTH, TRANSFER_ENCODING},
+	request::Parts,
+};
+use http_body_util::{BodyExt, StreamBody};
+use hyper::{
+	body::{Frame, Incoming},
+	service::service_fn,
+};
+use hyper_util::{
+	rt::{TokioExecutor, TokioIo},
+	server::conn::auto,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+	async_task::{Async, FaithAsyncResult},
+	error::{FaithError, FaithErrorKind},
+	options::FaithOptions,
+	runtime,
+	version::version_parts,
+};
+
+/// A route of a `TestServer`, for `server.route()`.
+#[napi(object)]
+pub struct TestRoute {
+	/// The request method to match, case-insensitively.
+	///
+	/// Default: any method.
+	pub method: Option<String>,
+	/// The request path to match exactly, without the query string.
+	pub path: String,
+	/// The response status.
+	///
+	/// Default: 200.
+	pub status: Option<u16>,
+	/// The response headers. `Content-Length` is set to the length of the body unless given here,
+	/// or unless `Transfer-Encoding` is.
+	pub headers: Option<Vec<(String, String)>>,
+	/// The response body.
+	///
+	/// Default: empty.
+	pub body: Option<Either3<String, Buffer, Uint8Array>>,
+	/// Send the body in chunks of this many bytes.
+	///
+	/// Default: all at once.
+	pub chunk_size: Option<u32>,
+	/// Wait this many milliseconds before sending each chunk of the body.
+	///
+	/// Default: 0.
+	pub chunk_delay: Option<u32>,
+	/// Wait this many milliseconds before sending the response headers.
+	///
+	/// Default: 0.
+	pub delay: Option<u32>,
+	/// Break off the response after this many bytes of the body: the HTTP/1 connection is closed,
+	/// or the HTTP/2 stream is reset. As `Content-Length` is still that of the whole body, the client
+	/// sees a truncated body.
+	///
+	/// Default: the whole body is sent.
+	pub disconnect_after: Option<u32>,
+	/// Only answer this many requests, after which the route is skipped.
+	///
+	/// Default: unlimited.
+	pub times: Option<u32>,
+}
+
+/// A request received by a `TestServer`, from `server.requests()`.
+#[napi(object)]
+pub struct TestRequest {
+	pub method: String,
+	/// The path and query string.
+	pub path: String,
+	/// The ALPN identifier of the HTTP version, e.g. `http/1.1` or `h2`.
+	pub version: String,
+	pub headers: Vec<(String, String)>,
+	pub body: Buffer,
+}
+
+struct Route {
+	method: Option<Method>,
+	path: String,
+	status: StatusCode,
+	headers: HeaderMap,
+	body: Bytes,
+	chunk_size: usize,
+	chunk_delay: Duration,
+	delay: Duration,
+	disconnect_after: Option<usize>,
+	remaining: Option<AtomicU32>,
+}
+
+impl Route {
+	fn new(route: TestRoute) -> Result<Self, FaithError> {
+		let method = route
+			.method
+			.map(|method| Method::from_bytes(method.to_uppercase().as_bytes()))
+			.transpose()
+			.map_err(|_| FaithErrorKind::InvalidMethod)?;
+		let status = StatusCode::from_u16(route.status.unwrap_or(200)).map_err(|err| {
+			FaithError::new(
+				FaithErrorKind::Config,
+				Some(format!("invalid status: {err}")),
+			)
+		})?;
+
+		let mut headers = HeaderMap::new();
+		for (name, value) in route.headers.into_iter().flatten() {
+			let (Ok(name), Ok(value)) = (
+				HeaderName::from_bytes(name.as_bytes()),
+				HeaderValue::from_str(&value),
+			) else {
+				return Err(FaithError::new(
+					FaithErrorKind::InvalidHeader,
+					Some(format!("invalid header: {name}")),
+				));
+			};
+			headers.append(name, value);
+		}
+
+		let body = route.body.map(FaithOptions::body).unwrap_or_default();
+		if !headers.contains_key(CONTENT_LENGTH) && !headers.contains_key(TRANSFER_ENCODING) {
+			headers.insert(CONTENT_LENGTH, body.len().into());
+		}
+
+		let millis = |millis: Option<u32>| Duration::from_millis(millis.unwrap_or(0).into());
+		Ok(Self {
+			method,
+			path: route.path,
+			status,
+			headers,
+			chunk_size: route
+				.chunk_size
+				.map_or(body.len(), |size| size as usize)
+				.max(1),
+			body,
+			chunk_delay: millis(route.chunk_delay),
+			delay: millis(route.delay),
+			disconnect_after: route.disconnect_after.map(|bytes| bytes as usize),
+			remaining: route.times.map(AtomicU32::new),
+		})
+	}
+
+	/// Whether the route answers the request, counting it towards `times` if so.
+	fn answers(&self, method: &Method, path: &str) -> bool {
+		self.method.as_ref().is_none_or(|m| m == method)
+			&& self.path == path
+			&& self.remaining.as_ref().is_none_or(|remaining| {
+				remaining
+					.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+					.is_ok()
+			})
+	}
+
+	fn respond(&self) -> Response<TestBody> {
+		let body = self.body.clone();
+		let limit = self
+			.disconnect_after
+			.map_or(body.len(), |cut| cut.min(body.len()));
+		let disconnect = self.disconnect_after.is_some();
+		let chunk_size = self.chunk_size;
+		let chunk_delay = self.chunk_delay;
+
+		let frames = async_stream::stream! {
+			let mut sent = 0;
+			while sent < limit {
+				if !chunk_delay.is_zero() {
+					tokio::time::sleep(chunk_delay).await;
+				}
+				let end = (sent + chunk_size).min(limit);
+				yield Ok(Frame::data(body.slice(sent..end)));
+				sent = end;
+			}
+			if disconnect {
+				yield Err(io::Error::new(
+					io::ErrorKind::ConnectionReset,
+					"disconnected by the test server",
+				));
+			}
+		};
+
+		let mut response = Response::new(StreamBody::new(Box::pin(frames) as FrameStream));
+		*response.status_mut() = self.status;
+		*response.headers_mut() = self.headers.clone();
+		response
+	}
+}
+
+type FrameStream = Pin<Box<dyn Stream<Item = io::Result<Frame<Bytes>>> + Send>>;
+type TestBody = StreamBody<FrameStream>;
+
+struct RecordedRequest {
+	method: String,
+	path: String,
+	version: &'static str,
+	headers: Vec<(String, String)>,
+	body: Bytes,
+}
+
+#[derive(Default)]
+struct ServerState {
+	routes: Mutex<Vec<Arc<Route>>>,
+	requests: Mutex<Vec<RecordedRequest>>,
+}
+
+impl ServerState {
+	fn record(&self, parts: &Parts, body: Bytes) {
+		let request = RecordedRequest {
+			method: parts.method.to_string(),
+			path: parts
+				.uri
+				.path_and_query()
+				.map_or_else(|| parts.uri.path().into(), ToString::to_string),
+			version: version_parts(parts.version).2,
+			headers: parts
+				.headers
+				.iter()
+				.map(|(name, value)| {
+					(
+						name.to_string(),
+						String::from_utf8_lossy(value.as_bytes()).into_owned(),
+					)
+				})
+				.collect(),
+			body,
+		};
+		self.requests
+			.lock()
+			.unwrap_or_else(|err| err.into_inner())
+			.push(request);
+	}
+
+	/// The most recently added route which answers the request.
+	fn route(&self, method: &Method, path: &str) -> Option<Arc<Route>> {
+		self.routes
+			.lock()
+			.unwrap_or_else(|err| err.into_inner())
+			.iter()
+			.rev()
+			.find(|route| route.answers(method, path))
+			.cloned()
+	}
+}
+
+async fn handle(
+	state: Arc<ServerState>,
+	request: Request<Incoming>,
+) -> Result<Response<TestBody>, Infallible> {
+	let (parts, body) = request.into_parts();
+	let body = body
+		.collect()
+		.await
+		.map(|body| body.to_bytes())
+		.unwrap_or_default();
+	state.record(&parts, body);
+
+	let Some(route) = state.route(&parts.method, parts.uri.path()) else {
+		let mut response = Response::new(StreamBody::new(
+			Box::pin(futures::stream::empty()) as FrameStream
+		));
+		*response.status_mut() = StatusCode::NOT_FOUND;
+		response.headers_mut().insert(CONTENT_LENGTH, 0.into());
+		return Ok(response);
+	};
+
+	if !route.delay.is_zero() {
+		tokio::time::sleep(route.delay).await;
+	}
+	Ok(route.respond())
+}
+
+async fn serve(listener: TcpListener, state: Arc<ServerState>, shutdown: CancellationToken) {
+	loop {
+		let stream = tokio::select! {
+			accepted = listener.accept() => match accepted {
+				Ok((stream, _)) => stream,
+				Err(_) => continue,
+			},
+			_ = shutdown.cancelled() => return,
+		};
+
+		let state = state.clone();
+		let shutdown = shutdown.clone();
+		tokio::spawn(async move {
+			let service = service_fn(move |request| handle(state.clone(), request));
+			let builder = auto::Builder::new(TokioExecutor::new());
+			tokio::select! {
+				_ = builder.serve_connection(TokioIo::new(stream), service) => {}
+				_ = shutdown.cancelled() => {}
+			}
+		});
+	}
+}
+
+/// Custom to Fáith, from `faith/testing`.
+///
+/// An in-process HTTP server for tests, listening on a random port on `127.0.0.1`. It speaks
+/// HTTP/1.1, and HTTP/2 with prior knowledge (`version: "h2"`), without TLS.
+///
+/// Requests are answered by the most recently added route which matches them, or with a `404`.
+/// Every request is recorded, with its body, whether a route answered it or not.
+#[napi]
+pub struct TestServer {
+	addr: SocketAddr,
+	state: Arc<ServerState>,
+	shutdown: CancellationToken,
+}
+
+#[napi]
+impl TestServer {
+	/// The server's base URL, e.g. `http://127.0.0.1:12345`.
+	#[napi(getter)]
+	pub fn url(&self) -> String {
+		format!("http://{}", self.addr)
+	}
+
+	#[napi(getter)]
+	pub fn port(&self) -> u16 {
+		self.addr.port()
+	}
+
+	/// Adds a route. Throws if the method, status, or a header is invalid.
+	#[napi]
+	pub fn route(&self, env: Env, route: TestRoute) -> Result<(), napi::Error> {
+		let route = Route::new(route).map_err(|err| napi::Error::from(err.into_js_error(&env)))?;
+		self.state
+			.routes
+			.lock()
+			.unwrap_or_else(|err| err.into_inner())
+			.push(Arc::new(route));
+		Ok(())
+	}
+
+	/// The requests received so far, in order.
+	#[napi]
+	pub fn requests(&self) -> Vec<TestRequest> {
+		self.state
+			.requests
+			.lock()
+			.unwrap_or_else(|err| err.into_inner())
+			.iter()
+			.map(|request| TestRequest {
+				method: request.method.clone(),
+				path: request.path.clone(),
+				version: request.version.into(),
+				headers: request.headers.clone(),
+				body: request.body.to_vec().into(),
+			})
+			.collect()
+	}
+
+	/// Removes all routes and forgets the requests received so far.
+	#[napi]
+	pub fn reset(&self) {
+		self.state
+			.routes
+			.lock()
+			.unwrap_or_else(|err| err.into_inner())
+			.clear();
+		self.state
+			.requests
+			.lock()
+			.unwrap_or_else(|err| err.into_inner())
+			.clear();
+	}
+
+	/// Stops the server, closing its connections. This also happens when it's garbage collected.
+	#[napi]
+	pub fn close(&self) {
+		self.shutdown.cancel();
+	}
+}
+
+impl Drop for TestServer {
+	fn drop(&mut self) {
+		self.shutdown.cancel();
+	}
+}
+
+/// Custom to Fáith, from `faith/testing`.
+///
+/// Starts a `TestServer`.
+#[napi(ts_return_type = "Async<TestServer>")]
+pub fn start_test_server() -> Async<TestServer> {
+	FaithAsyncResult::run(async move || {
+		let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+			.await
+			.map_err(|err| FaithError::new(FaithErrorKind::Network, Some(err.to_string())))?;
+		let addr = listener
+			.local_addr()
+			.map_err(|err| FaithError::new(FaithErrorKind::Network, Some(err.to_string())))?;
+
+		let state = Arc::new(ServerState::default());
+		let shutdown = CancellationToken::new();
+		// Not spawned with `runtime::spawn()`, so that the server doesn't hold up shutdown
+		runtime::handle()?.spawn(serve(listener, state.clone(), shutdown.clone()));
+
+		Ok(TestServer {
+			addr,
+			state,
+			shutdown,
+		})
+	})
+}
@@ -0,0 +1,232 @@
+use std::{
+	future::{Future, pending},
+	pin::Pin,
+	sync::{
+		Arc,
+		atomic::{AtomicU64, AtomicUsize, Ordering},
+	},
+	task::{Context, Poll, ready},
+	time::Duration,
+};
+
+use futures::{Stream, StreamExt, stream};
+use http_body_util::{BodyStream, StreamBody};
+use tokio::time::{Instant, Sleep, sleep, sleep_until};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{
+	error::{FaithError, FaithErrorKind},
+	options::Timeout,
+};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+tokio::task_local! {
+	/// The connect timeout of the current request, if it has its own, and its progress.
+	static REQUEST: (Option<Timeout>, Arc<Progress>);
+}
+
+/// Makes the request use its own connect timeout, if it has one, for new connections made for it,
+/// and note their progress.
+pub(crate) async fn with_request<F: Future>(
+	connect: Option<Timeout>,
+	progress: Arc<Progress>,
+	request: F,
+) -> F::Output {
+	REQUEST.scope((connect, progress), request).await
+}
+
+/// How a request is getting on before its response arrives: when it last connected or sent part of
+/// its body, and whether it's connecting now, so that the read timeout only counts idle time.
+#[derive(Debug)]
+pub(crate) struct Progress {
+	start: Instant,
+	/// Milliseconds since `start`.
+	last: AtomicU64,
+	connecting: AtomicUsize,
+}
+
+impl Progress {
+	pub(crate) fn new() -> Arc<Self> {
+		Arc::new(Self {
+			start: Instant::now(),
+			last: AtomicU64::new(0),
+			connecting: AtomicUsize::new(0),
+		})
+	}
+
+	pub(crate) fn mark(&self) {
+		let elapsed = self.start.elapsed().as_millis() as u64;
+		self.last.store(elapsed, Ordering::Relaxed);
+	}
+
+	fn last(&self) -> Instant {
+		self.start + Duration::from_millis(self.last.load(Ordering::Relaxed))
+	}
+}
+
+/// Counts a connection being made for the request, until it's dropped.
+struct Connecting<'a>(&'a Progress);
+
+impl<'a> Connecting<'a> {
+	fn new(progress: &'a Progress) -> Self {
+		progress.connecting.fetch_add(1, Ordering::Relaxed);
+		Self(progress)
+	}
+}
+
+impl Drop for Connecting<'_> {
+	fn drop(&mut self) {
+		self.0.connecting.fetch_sub(1, Ordering::Relaxed);
+		self.0.mark();
+	}
+}
+
+/// Notes the progress of the request as the body stream is sent.
+pub(crate) fn progressing<S: Stream>(
+	body: S,
+	progress: Arc<Progress>,
+) -> impl Stream<Item = S::Item> {
+	body.inspect(move |_| progress.mark())
+}
+
+/// Resolves with the read timeout, if there is one, once the request has made no progress for
+/// that long.
+pub(crate) async fn idle(timeout: Option<Timeout>, progress: &Progress) -> Timeout {
+	let Some((dur, cause)) = timeout else {
+		return pending().await;
+	};
+
+	loop {
+		sleep_until(progress.last() + dur).await;
+		if progress.connecting.load(Ordering::Relaxed) > 0 {
+			progress.mark();
+		} else if progress.last() + dur <= Instant::now() {
+			return (dur, cause);
+		}
+	}
+}
+
+fn timed_out(message: String, cause: &'static str) -> FaithError {
+	FaithError::new(FaithErrorKind::Timeout, Some(message)).with_cause(cause)
+}
+
+/// Times out new connections, with the connect timeout of the request they're made for, or else
+/// the agent's, and notes the progress of that request.
+///
+/// This is done here rather than by the client so that requests can have their own: connections
+/// made in the background (e.g. when a pooled one became free first) use the agent's.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ConnectTimeoutLayer {
+	default: Option<Timeout>,
+}
+
+impl ConnectTimeoutLayer {
+	pub(crate) fn new(default: Option<Timeout>) -> Self {
+		Self { default }
+	}
+}
+
+impl<S> Layer<S> for ConnectTimeoutLayer {
+	type Service = ConnectTimeoutService<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		ConnectTimeoutService {
+			inner,
+			default: self.default,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ConnectTimeoutService<S> {
+	inner: S,
+	default: Option<Timeout>,
+}
+
+impl<S, R> Service<R> for ConnectTimeoutService<S>
+where
+	S: Service<R>,
+	S::Error: From<FaithError>,
+	S::Future: Send + 'static,
+{
+	type Response = S::Response;
+	type Error = S::Error;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, request: R) -> Self::Future {
+		let (timeout, progress) = REQUEST
+			.try_with(|(timeout, progress)| (*timeout, Some(progress.clone())))
+			.unwrap_or_default();
+		let timeout = timeout.or(self.default);
+		let connecting = self.inner.call(request);
+		Box::pin(async move {
+			let _connecting = progress.as_deref().map(Connecting::new);
+			let Some((dur, cause)) = timeout else {
+				return connecting.await;
+			};
+
+			tokio::time::timeout(dur, connecting)
+				.await
+				.unwrap_or_else(|_| {
+					Err(timed_out(
+						format!("connecting took longer than {}ms", dur.as_millis()),
+						cause,
+					)
+					.into())
+				})
+		})
+	}
+}
+
+/// Times out reading a response body: when no data arrives for the `read` timeout, and when it
+/// isn't read in full by the `body` timeout, which starts now.
+pub(crate) fn body(
+	body: reqwest::Body,
+	read: Option<Timeout>,
+	whole: Option<Timeout>,
+) -> reqwest::Body {
+	if read.is_none() && whole.is_none() {
+		return body;
+	}
+
+	let mut frames = BodyStream::new(body);
+	let mut whole = whole.map(|(dur, cause)| (Box::pin(sleep(dur)), dur, cause));
+	let mut idle: Option<Pin<Box<Sleep>>> = None;
+	let mut done = false;
+	let frames = stream::poll_fn(move |cx| {
+		if done {
+			return Poll::Ready(None);
+		}
+
+		if let Some((deadline, dur, cause)) = &mut whole
+			&& deadline.as_mut().poll(cx).is_ready()
+		{
+			done = true;
+			let message = format!("reading the body took longer than {}ms", dur.as_millis());
+			return Poll::Ready(Some(Err(BoxError::from(timed_out(message, *cause)))));
+		}
+
+		if let Some((dur, cause)) = read {
+			let idle = idle.get_or_insert_with(|| Box::pin(sleep(dur)));
+			if idle.as_mut().poll(cx).is_ready() {
+				done = true;
+				let message = format!("no data received for {}ms", dur.as_millis());
+				return Poll::Ready(Some(Err(BoxError::from(timed_out(message, cause)))));
+			}
+		}
+
+		let frame = ready!(frames.poll_next_unpin(cx));
+		if let (Some(idle), Some((dur, _))) = (&mut idle, read) {
+			idle.as_mut().reset(Instant::now() + dur);
+		}
+		Poll::Ready(frame.map(|frame| frame.map_err(BoxError::from)))
+	});
+
+	reqwest::Body::wrap(StreamBody::new(frames))
+}
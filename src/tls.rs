@@ -0,0 +1,631 @@
+use std::{
+	fmt::Debug,
+	future::Future,
+	sync::{Arc, RwLock},
+};
+
+#[cfg(feature = "ech")]
+use base64::prelude::*;
+use napi::{
+	Either, Status,
+	bindgen_prelude::{Buffer, Either3, Function, Promise},
+	threadsafe_function::ThreadsafeFunction,
+};
+use napi_derive::napi;
+use reqwest::Url;
+use rustls::{
+	CertificateError, ClientConfig, DigitallySignedStruct, KeyLogFile, OtherError, RootCertStore,
+	SignatureScheme,
+	client::{
+		Resumption, WebPkiServerVerifier,
+		danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+	},
+	crypto::{CryptoProvider, WebPkiSupportedAlgorithms, ring},
+	pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime, pem::PemObject},
+};
+#[cfg(feature = "ech")]
+use rustls::{
+	client::{EchConfig, EchMode},
+	crypto::aws_lc_rs::hpke::ALL_SUPPORTED_SUITES,
+	pki_types::EchConfigListBytes,
+};
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+#[cfg(feature = "ech")]
+use crate::ech::EchLookup;
+use crate::{
+	agent::{AgentEchOptions, AgentOptions, AgentTlsOptions},
+	error::{FaithError, FaithErrorKind},
+	ocsp::{self, OcspStatus},
+	tls_sessions::{self, DEFAULT_SESSION_CACHE, Sessions},
+};
+
+/// ALPN protocols for connections which may use HTTP/2 or HTTP/1.1, preferring HTTP/2.
+const ALPN_ANY: &[&[u8]] = &[b"h2", b"http/1.1"];
+/// ALPN protocols for connections pinned to HTTP/1.1.
+pub(crate) const ALPN_HTTP1: &[&[u8]] = &[b"http/1.1"];
+/// ALPN protocols for connections pinned to HTTP/2.
+pub(crate) const ALPN_HTTP2: &[&[u8]] = &[b"h2"];
+
+/// ALPN protocols for the agent's connections which aren't pinned to a protocol.
+pub(crate) fn default_alpn(options: &AgentOptions) -> &'static [&'static [u8]] {
+	let http1_only = options
+		.http1
+		.as_ref()
+		.is_some_and(|http1| http1.only.unwrap_or(false) || http1.version10.unwrap_or(false));
+	if http1_only { ALPN_HTTP1 } else { ALPN_ANY }
+}
+
+/// Custom to Fáith.
+///
+/// The cryptography library used for TLS:
+///
+/// - `ring`: [ring], the default.
+/// - `aws-lc-rs`: [AWS-LC], with the `aws-lc-rs` feature.
+/// - `fips`: AWS-LC in its FIPS 140-3 validated mode, with the `fips` feature. This is the default
+///   in builds with it.
+///
+/// [ring]: https://github.com/briansmith/ring
+/// [AWS-LC]: https://github.com/aws/aws-lc-rs
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsCryptoProvider {
+	#[napi(value = "ring")]
+	Ring,
+	#[napi(value = "aws-lc-rs")]
+	AwsLcRs,
+	#[napi(value = "fips")]
+	Fips,
+}
+
+impl Default for TlsCryptoProvider {
+	fn default() -> Self {
+		if cfg!(feature = "fips") {
+			Self::Fips
+		} else {
+			Self::Ring
+		}
+	}
+}
+
+impl TlsCryptoProvider {
+	/// The providers available in this build.
+	pub(crate) fn available() -> Vec<Self> {
+		let mut providers = vec![Self::Ring];
+		if cfg!(feature = "aws-lc-rs") {
+			providers.push(Self::AwsLcRs);
+		}
+		if cfg!(feature = "fips") {
+			providers.push(Self::Fips);
+		}
+		providers
+	}
+
+	fn provider(self) -> Result<CryptoProvider, FaithError> {
+		let unavailable = |name: &str| {
+			Err(FaithError::new(
+				FaithErrorKind::Config,
+				Some(format!(
+					"the {name} crypto provider is not enabled in this build"
+				)),
+			))
+		};
+		match self {
+			Self::Ring => Ok(ring::default_provider()),
+			#[cfg(feature = "aws-lc-rs")]
+			Self::AwsLcRs => Ok(rustls::crypto::aws_lc_rs::default_provider()),
+			#[cfg(not(feature = "aws-lc-rs"))]
+			Self::AwsLcRs => unavailable("aws-lc-rs"),
+			#[cfg(feature = "fips")]
+			Self::Fips => Ok(rustls::crypto::default_fips_provider()),
+			#[cfg(not(feature = "fips"))]
+			Self::Fips => unavailable("fips"),
+		}
+	}
+}
+
+/// The verify proc's answer to trust the certificate.
+const ACCEPT: i32 = 0;
+/// The verify proc's answer to reject the certificate.
+const REJECT: i32 = -2;
+/// The verify proc's answer to use the agent's own verification.
+const USE_VERIFICATION_RESULT: i32 = -3;
+
+/// Custom to Fáith.
+///
+/// What is known of the TLS connection a response came on, for agents with their own TLS
+/// configuration (with `tls.verifyProc` or `tls.requireOcspStapling`):
+///
+/// - `ocspStapled`: whether the server stapled an OCSP response to its certificate.
+/// - `ocspStatus`: the certificate's status in the stapled response, if it could be read. See
+///   `OcspStatus`. The response's signature isn't checked for this, but is with
+///   `tls.requireOcspStapling`.
+/// - `echAccepted`: whether the connection was made with Encrypted Client Hello, with `tls.ech`.
+/// - `resumed`: whether the connection resumed an earlier TLS session, instead of making a full
+///   handshake. The server doesn't send its certificate again then, so nothing is stapled.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct PeerTls {
+	pub ocsp_stapled: bool,
+	pub ocsp_status: Option<OcspStatus>,
+	pub ech_accepted: bool,
+	pub resumed: bool,
+}
+
+/// A server certificate to verify, given to the `agent.setCertificateVerifyProc()` callback.
+#[napi(object)]
+pub struct CertificateVerifyRequest {
+	/// The name the certificate is verified for: the URL's host, or `tls.serverName`.
+	pub hostname: String,
+	/// The server's certificate, DER-encoded.
+	pub certificate: Buffer,
+	/// The intermediate certificates sent by the server, DER-encoded, in the order it sent them.
+	pub chain: Vec<Buffer>,
+	/// The OCSP response stapled by the server, DER-encoded, if it sent one. It isn't checked by the
+	/// agent's own verification.
+	pub ocsp_response: Option<Buffer>,
+	/// Whether the agent's own verification trusts the certificate.
+	pub verified: bool,
+	/// `OK` if the agent's own verification trusts the certificate, or else why it doesn't.
+	pub verification_result: String,
+}
+
+/// What the verify proc answers: a number as with Electron, or whether to trust the certificate.
+pub(crate) type VerifyProcAnswer = Either3<i32, bool, Promise<Either<i32, bool>>>;
+
+pub(crate) type VerifyProcCallback = ThreadsafeFunction<
+	CertificateVerifyRequest,
+	VerifyProcAnswer,
+	CertificateVerifyRequest,
+	Status,
+	false,
+	true,
+>;
+
+#[derive(Default)]
+struct VerifyProc(RwLock<Option<Arc<VerifyProcCallback>>>);
+
+impl Debug for VerifyProc {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let set = self.0.read().map(|cb| cb.is_some()).unwrap_or_default();
+		f.debug_tuple("VerifyProc").field(&set).finish()
+	}
+}
+
+tokio::task_local! {
+	/// Set while a request blocks the JS thread, which the verify proc would need to run.
+	static BLOCKING: ();
+}
+
+/// Runs a request while blocking the JS thread, as `faithFetchSync()` does.
+pub(crate) async fn blocking<F: Future>(request: F) -> F::Output {
+	BLOCKING.scope((), request).await
+}
+
+/// Whether the current request blocks the JS thread, so that callbacks can't be waited on.
+pub(crate) fn is_blocking() -> bool {
+	BLOCKING.try_with(|_| ()).is_ok()
+}
+
+impl VerifyProc {
+	/// Asks the callback, if one is set, what to make of a certificate. The TLS handshake can't wait
+	/// for an answer asynchronously, so this blocks the connection's thread until there is one.
+	fn decide(
+		&self,
+		request: impl FnOnce() -> CertificateVerifyRequest,
+	) -> Option<Result<i32, FaithError>> {
+		let callback = self
+			.0
+			.read()
+			.unwrap_or_else(|err| err.into_inner())
+			.clone()?;
+
+		let failed = |message: String| {
+			Some(Err(FaithError::new(
+				FaithErrorKind::CertificateInvalid,
+				Some(message),
+			)))
+		};
+		if is_blocking() {
+			return failed("the certificate verify proc can't run during faithFetchSync()".into());
+		}
+		let Some(handle) = Handle::try_current()
+			.ok()
+			.filter(|handle| handle.runtime_flavor() == RuntimeFlavor::MultiThread)
+		else {
+			return failed("the certificate verify proc can't run on this thread".into());
+		};
+
+		let request = request();
+		let answer = tokio::task::block_in_place(|| {
+			handle.block_on(async {
+				let answer = match callback.call_async(request).await? {
+					Either3::A(code) => Either::A(code),
+					Either3::B(trusted) => Either::B(trusted),
+					Either3::C(promise) => promise.await?,
+				};
+				Ok(match answer {
+					Either::A(code) => code,
+					Either::B(true) => ACCEPT,
+					Either::B(false) => REJECT,
+				})
+			})
+		});
+		match answer {
+			Ok(answer) => Some(Ok(answer)),
+			Err(err) => failed(format!("certificate verify proc failed: {}", err.reason)),
+		}
+	}
+}
+
+/// Verifies certificates as usual, then lets the app's verify proc overrule the result.
+///
+/// It also notes what the server stapled, for responses on the connection to report. With
+/// `tls.requireOcspStapling`, certificates without a stapled OCSP response saying they're good,
+/// which is signed by their issuer and current, are rejected, before the verify proc is asked.
+///
+/// Configurations using ECH have their own verifier, which notes that ECH was accepted: a server
+/// which rejects it is still verified, but for the name in its ECH configuration, and the handshake
+/// then fails regardless.
+#[derive(Debug, Clone)]
+struct AgentVerifier {
+	inner: Arc<WebPkiServerVerifier>,
+	proc: Arc<VerifyProc>,
+	require_stapling: bool,
+	/// The crypto provider's signature algorithms, to verify stapled OCSP responses with.
+	algorithms: WebPkiSupportedAlgorithms,
+	ech: bool,
+}
+
+impl ServerCertVerifier for AgentVerifier {
+	fn verify_server_cert(
+		&self,
+		end_entity: &CertificateDer<'_>,
+		intermediates: &[CertificateDer<'_>],
+		server_name: &ServerName<'_>,
+		ocsp_response: &[u8],
+		now: UnixTime,
+	) -> Result<ServerCertVerified, rustls::Error> {
+		let verified = self.inner.verify_server_cert(
+			end_entity,
+			intermediates,
+			server_name,
+			ocsp_response,
+			now,
+		);
+
+		tls_sessions::verified(PeerTls {
+			ocsp_stapled: !ocsp_response.is_empty(),
+			ocsp_status: ocsp::status(ocsp_response, end_entity),
+			ech_accepted: self.ech,
+			resumed: false,
+		});
+		let stapling = if self.require_stapling {
+			let intermediates: Vec<&[u8]> =
+				intermediates.iter().map(|cert| cert.as_ref()).collect();
+			ocsp::check(
+				ocsp_response,
+				end_entity,
+				&intermediates,
+				self.algorithms.all,
+				now,
+			)
+		} else {
+			Ok(())
+		};
+		if let Err(message) = stapling {
+			return Err(rustls::Error::InvalidCertificate(CertificateError::Other(
+				OtherError(Arc::new(FaithError::new(
+					FaithErrorKind::CertificateInvalid,
+					Some(message.into()),
+				))),
+			)));
+		}
+
+		let answer = self.proc.decide(|| CertificateVerifyRequest {
+			hostname: server_name.to_str().into_owned(),
+			certificate: end_entity.to_vec().into(),
+			chain: intermediates
+				.iter()
+				.map(|cert| cert.to_vec().into())
+				.collect(),
+			ocsp_response: (!ocsp_response.is_empty()).then(|| ocsp_response.to_vec().into()),
+			verified: verified.is_ok(),
+			verification_result: match &verified {
+				Ok(_) => "OK".into(),
+				Err(err) => err.to_string(),
+			},
+		});
+		match answer {
+			None | Some(Ok(USE_VERIFICATION_RESULT)) => verified,
+			Some(Ok(ACCEPT)) => Ok(ServerCertVerified::assertion()),
+			Some(Ok(_)) => Err(rustls::Error::InvalidCertificate(
+				CertificateError::ApplicationVerificationFailure,
+			)),
+			Some(Err(err)) => Err(rustls::Error::InvalidCertificate(CertificateError::Other(
+				OtherError(Arc::new(err)),
+			))),
+		}
+	}
+
+	fn verify_tls12_signature(
+		&self,
+		message: &[u8],
+		cert: &CertificateDer<'_>,
+		dss: &DigitallySignedStruct,
+	) -> Result<HandshakeSignatureValid, rustls::Error> {
+		self.inner.verify_tls12_signature(message, cert, dss)
+	}
+
+	fn verify_tls13_signature(
+		&self,
+		message: &[u8],
+		cert: &CertificateDer<'_>,
+		dss: &DigitallySignedStruct,
+	) -> Result<HandshakeSignatureValid, rustls::Error> {
+		self.inner.verify_tls13_signature(message, cert, dss)
+	}
+
+	fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+		self.inner.supported_verify_schemes()
+	}
+}
+
+/// Rebuilds a configuration to use ECH, with the configurations in an `ECHConfigList`.
+#[cfg(feature = "ech")]
+fn with_ech(
+	base: &ClientConfig,
+	verifier: &AgentVerifier,
+	config_list: &[u8],
+) -> Result<ClientConfig, FaithError> {
+	let invalid = |err: rustls::Error| {
+		FaithError::new(
+			FaithErrorKind::Config,
+			Some(format!("invalid ECH configuration: {err}")),
+		)
+	};
+	let ech = EchConfig::new(
+		EchConfigListBytes::from(config_list.to_vec()),
+		ALL_SUPPORTED_SUITES,
+	)
+	.map_err(invalid)?;
+
+	let mut config = ClientConfig::builder_with_provider(base.crypto_provider().clone())
+		.with_ech(EchMode::from(ech))
+		.map_err(invalid)?
+		.dangerous()
+		.with_custom_certificate_verifier(Arc::new(AgentVerifier {
+			ech: true,
+			..verifier.clone()
+		}))
+		.with_no_client_auth();
+	config.client_auth_cert_resolver = base.client_auth_cert_resolver.clone();
+	config.key_log = base.key_log.clone();
+	config.enable_early_data = base.enable_early_data;
+	config.resumption = base.resumption.clone();
+	Ok(config)
+}
+
+/// The agent's own TLS configuration, for what the HTTP client's can't do: with `tls.verifyProc`,
+/// `tls.requireOcspStapling`, `tls.ech`, or `tls.sessionCache`, or a `tls.cryptoProvider` other
+/// than ring, which is the only one the client has.
+///
+/// This reproduces the client's defaults from the agent options: the bundled and system roots, the
+/// `identity`, `earlyData`, and `SSLKEYLOGFILE`. The client doesn't change a preconfigured TLS
+/// setup, so ALPN is set per client, for the protocols it may use.
+///
+/// When ECH configurations are looked up for each server, connections with ECH need a
+/// configuration of their own, which the pinned clients are built with.
+#[derive(Debug)]
+pub(crate) struct AgentTls {
+	config: ClientConfig,
+	verifier: AgentVerifier,
+	sessions: Option<Arc<Sessions>>,
+	#[cfg(feature = "ech")]
+	ech: Option<EchLookup>,
+	/// Whether the configuration has ECH, with `tls.ech.configList`.
+	ech_config: bool,
+	/// Whether the agent has `tls.verifyProc`, so a verify proc may be set.
+	pub(crate) verify_proc: bool,
+}
+
+impl AgentTls {
+	/// Returns `None` if the agent doesn't need its own TLS configuration.
+	pub(crate) fn new(options: &AgentOptions) -> Result<Option<Self>, FaithError> {
+		let default = AgentTlsOptions::default();
+		let tls = options.tls.as_ref().unwrap_or(&default);
+		let ech = match &tls.ech {
+			None | Some(Either::A(false)) => None,
+			Some(Either::A(true)) => Some(AgentEchOptions::default()),
+			Some(Either::B(ech)) => Some(ech.clone()),
+		};
+		let provider = tls.crypto_provider.unwrap_or_default();
+		if !tls.verify_proc.unwrap_or(false)
+			&& !tls.require_ocsp_stapling.unwrap_or(false)
+			&& ech.is_none()
+			&& tls.session_cache.is_none()
+			&& provider == TlsCryptoProvider::Ring
+		{
+			return Ok(None);
+		}
+		#[cfg(not(feature = "ech"))]
+		if ech.is_some() {
+			return Err(FaithError::new(
+				FaithErrorKind::Config,
+				Some("ECH support is not enabled in this build"),
+			));
+		}
+
+		let provider = Arc::new(provider.provider()?);
+
+		let mut roots = RootCertStore::empty();
+		roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+		// system stores often have certificates which can't be parsed, which are skipped
+		roots.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+		let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+			.build()
+			.map_err(|err| FaithError::new(FaithErrorKind::Config, Some(err.to_string())))?;
+
+		let verifier = AgentVerifier {
+			inner,
+			proc: Default::default(),
+			require_stapling: tls.require_ocsp_stapling.unwrap_or(false),
+			algorithms: provider.signature_verification_algorithms,
+			ech: false,
+		};
+		let builder = ClientConfig::builder_with_provider(provider)
+			.with_safe_default_protocol_versions()
+			.map_err(|err| FaithError::new(FaithErrorKind::Config, Some(err.to_string())))?
+			.dangerous()
+			.with_custom_certificate_verifier(Arc::new(verifier.clone()));
+
+		let mut config = match &tls.identity {
+			None => builder.with_no_client_auth(),
+			Some(identity) => {
+				let pem = match identity {
+					Either::A(buf) => buf.as_ref(),
+					Either::B(string) => string.as_bytes(),
+				};
+				let pem_error = |err: rustls::pki_types::pem::Error| {
+					FaithError::new(FaithErrorKind::PemParse, Some(err.to_string()))
+				};
+				let certs = CertificateDer::pem_slice_iter(pem)
+					.collect::<Result<Vec<_>, _>>()
+					.map_err(pem_error)?;
+				let key = PrivateKeyDer::from_pem_slice(pem).map_err(pem_error)?;
+				builder.with_client_auth_cert(certs, key).map_err(|err| {
+					FaithError::new(FaithErrorKind::PemParse, Some(err.to_string()))
+				})?
+			}
+		};
+		config.key_log = Arc::new(KeyLogFile::new());
+		config.enable_early_data = tls.early_data.unwrap_or(false);
+
+		let sessions = match tls.session_cache {
+			Some(0) => None,
+			size => Some(Arc::new(Sessions::new(
+				size.map_or(DEFAULT_SESSION_CACHE, |size| size as usize),
+			))),
+		};
+		config.resumption = match &sessions {
+			Some(sessions) => Resumption::store(sessions.clone()),
+			None => Resumption::disabled(),
+		};
+
+		// a configuration list given upfront is used for every connection, otherwise they're looked
+		// up for each server
+		let ech_config = matches!(
+			ech,
+			Some(AgentEchOptions {
+				config_list: Some(_),
+				..
+			})
+		);
+		#[cfg(feature = "ech")]
+		let ech = match ech {
+			None => None,
+			Some(AgentEchOptions {
+				config_list: Some(list),
+				..
+			}) => {
+				let list = match list {
+					Either::A(buf) => buf.to_vec(),
+					Either::B(string) => BASE64_STANDARD.decode(string).map_err(|err| {
+						FaithError::new(
+							FaithErrorKind::Config,
+							Some(format!("tls.ech.configList: {err}")),
+						)
+					})?,
+				};
+				config = with_ech(&config, &verifier, &list)?;
+				None
+			}
+			Some(AgentEchOptions { doh, .. }) => Some(EchLookup::new(doh.unwrap_or_default())),
+		};
+
+		Ok(Some(Self {
+			config,
+			verify_proc: tls.verify_proc.unwrap_or(false),
+			verifier,
+			sessions,
+			#[cfg(feature = "ech")]
+			ech,
+			ech_config,
+		}))
+	}
+
+	/// The configuration for a client, offering these protocols with ALPN.
+	pub(crate) fn client_config(&self, alpn: &[&[u8]]) -> ClientConfig {
+		let mut config = self.config.clone();
+		config.alpn_protocols = alpn.iter().map(|protocol| protocol.to_vec()).collect();
+		config
+	}
+
+	/// The configuration for a client connecting with ECH, offering these protocols with ALPN.
+	pub(crate) fn client_config_with_ech(
+		&self,
+		alpn: &[&[u8]],
+		config_list: &[u8],
+	) -> Result<ClientConfig, FaithError> {
+		#[cfg(feature = "ech")]
+		let mut config = with_ech(&self.config, &self.verifier, config_list)?;
+		#[cfg(not(feature = "ech"))]
+		let mut config = {
+			// no configurations are looked up without ECH support
+			let _ = config_list;
+			self.config.clone()
+		};
+		config.alpn_protocols = alpn.iter().map(|protocol| protocol.to_vec()).collect();
+		Ok(config)
+	}
+
+	/// The ECH configurations to connect to the URL's host with, when they're looked up for each
+	/// server. Connections with the agent's configuration otherwise.
+	pub(crate) async fn ech_config_list(&self, url: &Url) -> Option<Arc<[u8]>> {
+		#[cfg(feature = "ech")]
+		if let Some(lookup) = &self.ech
+			&& url.scheme() == "https"
+			&& let Some(host) = url.domain()
+		{
+			return lookup.config_list(host).await;
+		}
+		#[cfg(not(feature = "ech"))]
+		let _ = url;
+		None
+	}
+
+	/// Whether connections are made with ECH, with the agent's configuration.
+	pub(crate) fn ech(&self) -> bool {
+		self.ech_config
+	}
+
+	/// Forgets the TLS sessions kept to resume them.
+	pub(crate) fn flush_sessions(&self) {
+		if let Some(sessions) = &self.sessions {
+			sessions.flush();
+		}
+	}
+
+	/// Sets the callback which decides whether to trust servers' certificates, or removes it.
+	pub(crate) fn set_verify_proc(
+		&self,
+		proc: Option<Function<CertificateVerifyRequest, VerifyProcAnswer>>,
+	) -> napi::Result<()> {
+		let tsfn = proc
+			.map(|proc| {
+				proc.build_threadsafe_function()
+					.callee_handled::<false>()
+					.weak::<true>()
+					.build()
+					.map(Arc::new)
+			})
+			.transpose()?;
+		*self
+			.verifier
+			.proc
+			.0
+			.write()
+			.unwrap_or_else(|err| err.into_inner()) = tsfn;
+		Ok(())
+	}
+}
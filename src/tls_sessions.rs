@@ -0,0 +1,200 @@
+use std::{
+	future::Future,
+	net::SocketAddr,
+	pin::Pin,
+	sync::{Arc, LazyLock, Mutex, RwLock},
+	task::{Context, Poll},
+	time::Duration,
+};
+
+use http::Extensions;
+use hyper_util::client::legacy::connect::{Connection, HttpInfo};
+use moka::sync::Cache;
+use reqwest::tls::TlsInfo;
+use rustls::{
+	NamedGroup,
+	client::{
+		ClientSessionMemoryCache, ClientSessionStore, Tls12ClientSessionValue,
+		Tls13ClientSessionValue,
+	},
+	pki_types::ServerName,
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::tls::PeerTls;
+
+/// How many sessions are kept for resumption by default, as rustls does.
+pub(crate) const DEFAULT_SESSION_CACHE: usize = 256;
+
+/// The TLS sessions an agent keeps to resume them, which can be flushed.
+///
+/// This is rustls' in-memory cache, swapped for an empty one when flushed.
+#[derive(Debug)]
+pub(crate) struct Sessions {
+	size: usize,
+	cache: RwLock<Arc<ClientSessionMemoryCache>>,
+}
+
+impl Sessions {
+	pub(crate) fn new(size: usize) -> Self {
+		Self {
+			size,
+			cache: RwLock::new(Arc::new(ClientSessionMemoryCache::new(size))),
+		}
+	}
+
+	/// Forgets all sessions, so that new connections make a full handshake.
+	pub(crate) fn flush(&self) {
+		*self.cache.write().unwrap_or_else(|err| err.into_inner()) =
+			Arc::new(ClientSessionMemoryCache::new(self.size));
+	}
+
+	fn cache(&self) -> Arc<ClientSessionMemoryCache> {
+		self.cache
+			.read()
+			.unwrap_or_else(|err| err.into_inner())
+			.clone()
+	}
+}
+
+impl ClientSessionStore for Sessions {
+	fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+		self.cache().set_kx_hint(server_name, group);
+	}
+
+	fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+		self.cache().kx_hint(server_name)
+	}
+
+	fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+		self.cache().set_tls12_session(server_name, value);
+	}
+
+	fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+		self.cache().tls12_session(server_name)
+	}
+
+	fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+		self.cache().remove_tls12_session(server_name);
+	}
+
+	fn insert_tls13_ticket(
+		&self,
+		server_name: ServerName<'static>,
+		value: Tls13ClientSessionValue,
+	) {
+		self.cache().insert_tls13_ticket(server_name, value);
+	}
+
+	fn take_tls13_ticket(
+		&self,
+		server_name: &ServerName<'static>,
+	) -> Option<Tls13ClientSessionValue> {
+		self.cache().take_tls13_ticket(server_name)
+	}
+}
+
+tokio::task_local! {
+	/// Set by the certificate verifier while connecting, which it's only asked on full handshakes.
+	static HANDSHAKE: Arc<Mutex<Option<PeerTls>>>;
+}
+
+/// Notes what the certificate verifier found of the connection being made, which also means that
+/// it isn't resumed.
+pub(crate) fn verified(peer: PeerTls) {
+	let _ = HANDSHAKE.try_with(|handshake| {
+		*handshake.lock().unwrap_or_else(|err| err.into_inner()) = Some(peer);
+	});
+}
+
+/// What is known of TLS connections, by their local and peer addresses. Unlike the connect timings,
+/// these are asked for every response on the connection, so they're kept while it's used.
+static PEERS: LazyLock<Cache<(SocketAddr, SocketAddr), PeerTls>> = LazyLock::new(|| {
+	Cache::builder()
+		.max_capacity(10_000)
+		.time_to_idle(Duration::from_secs(3600))
+		.build()
+});
+
+/// What is known of the TLS connection between these addresses.
+pub(crate) fn peer(info: &HttpInfo) -> Option<PeerTls> {
+	PEERS.get(&(info.local_addr(), info.remote_addr()))
+}
+
+/// Records what is known of each new TLS connection, for agents with their own TLS configuration:
+/// what the certificate verifier found, and whether the connection resumed a session, which rustls
+/// doesn't say, but only full handshakes verify the server's certificate.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PeerTlsLayer {
+	/// Whether the client connects with ECH, which resumed connections can't tell otherwise.
+	ech: bool,
+}
+
+impl PeerTlsLayer {
+	pub(crate) fn new(ech: bool) -> Self {
+		Self { ech }
+	}
+}
+
+impl<S> Layer<S> for PeerTlsLayer {
+	type Service = PeerTlsService<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		PeerTlsService {
+			inner,
+			ech: self.ech,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PeerTlsService<S> {
+	inner: S,
+	ech: bool,
+}
+
+impl<S, R> Service<R> for PeerTlsService<S>
+where
+	S: Service<R>,
+	S::Response: Connection,
+	S::Future: Send + 'static,
+{
+	type Response = S::Response;
+	type Error = S::Error;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, request: R) -> Self::Future {
+		let handshake = Arc::new(Mutex::new(None));
+		let connecting = HANDSHAKE.scope(handshake.clone(), self.inner.call(request));
+		let ech = self.ech;
+		Box::pin(async move {
+			let conn = connecting.await?;
+
+			let mut extras = Extensions::new();
+			conn.connected().get_extras(&mut extras);
+			if extras.get::<TlsInfo>().is_some()
+				&& let Some(info) = extras.get::<HttpInfo>()
+			{
+				let verified = handshake
+					.lock()
+					.unwrap_or_else(|err| err.into_inner())
+					.take();
+				// the server doesn't send its certificate again to resume, so nothing is stapled
+				let peer = verified.unwrap_or(PeerTls {
+					ocsp_stapled: false,
+					ocsp_status: None,
+					ech_accepted: ech,
+					resumed: true,
+				});
+				PEERS.insert((info.local_addr(), info.remote_addr()), peer);
+			}
+
+			Ok(conn)
+		})
+	}
+}
@@ -0,0 +1,74 @@
+use std::{
+	collections::HashMap,
+	sync::{
+		LazyLock, Mutex,
+		atomic::{AtomicU64, Ordering},
+	},
+};
+
+use napi::Env;
+use napi_derive::napi;
+
+use crate::{
+	error::{FaithError, FaithErrorKind},
+	response::FaithResponse,
+};
+
+/// Custom to Fáith.
+///
+/// A handle to a response given up with `response.transfer()`, to be passed to another thread with
+/// `postMessage()` and turned back into a response there with `receiveResponse()`. It's a plain
+/// object, so it's cheap to copy between threads, while the body stays in Fáith.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct TransferredResponse {
+	pub id: String,
+}
+
+/// Responses given up by one thread and not yet received by another.
+///
+/// The addon is loaded once per process, so this is shared by the main thread and every worker.
+static TRANSFERRED: LazyLock<Mutex<HashMap<String, FaithResponse>>> =
+	LazyLock::new(Default::default);
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Keeps the response until it's received, and returns its handle.
+pub(crate) fn give(response: FaithResponse) -> TransferredResponse {
+	let id = format!("faith-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+	TRANSFERRED
+		.lock()
+		.unwrap_or_else(|err| err.into_inner())
+		.insert(id.clone(), response);
+	TransferredResponse { id }
+}
+
+/// Custom to Fáith.
+///
+/// Takes a response given up with `response.transfer()`, typically in a worker thread the handle
+/// was posted to, so that its body can be read there without copying the chunks between threads.
+/// Each handle can only be received once.
+///
+/// The response's body keeps being read from the network by Fáith's own threads. Its request's
+/// `observe` callback, and the performance timeline entry, stay with the thread which made the
+/// request.
+///
+/// Throws a `ResponseAlreadyDisturbed` error if the handle was already received.
+#[napi]
+pub fn receive_response(
+	env: Env,
+	handle: TransferredResponse,
+) -> Result<FaithResponse, napi::Error> {
+	TRANSFERRED
+		.lock()
+		.unwrap_or_else(|err| err.into_inner())
+		.remove(&handle.id)
+		.ok_or_else(|| {
+			FaithError::new(
+				FaithErrorKind::ResponseAlreadyDisturbed,
+				Some("this transferred response was already received"),
+			)
+			.into_js_error(&env)
+			.into()
+		})
+}
@@ -0,0 +1,235 @@
+use std::{io::SeekFrom, result::Result, time::Duration};
+
+use bytes::Bytes;
+use futures::{StreamExt, TryStreamExt, stream};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use reqwest::{StatusCode, Url, header::ETAG};
+use tokio::{
+	fs::File,
+	io::{AsyncReadExt, AsyncSeekExt},
+};
+
+use crate::{
+	agent::Agent,
+	async_task::{Async, FaithAsyncResult},
+	error::{FaithError, FaithErrorKind},
+	fetch::{abort_receiver, fetch},
+	options::FaithOptions,
+};
+
+/// Settings for `faithUpload()`.
+#[napi(object)]
+#[derive(Default)]
+pub struct FaithUploadOptions {
+	/// The agent to upload with. Defaults to the global agent.
+	pub agent: Option<Reference<Agent>>,
+	/// Maximum number of parts uploaded at once. Parts are started in order as others complete.
+	///
+	/// Default: 4.
+	pub concurrency: Option<u32>,
+	/// Headers sent with every part, in addition to the agent's default headers.
+	pub headers_per_part: Option<Vec<(String, Option<String>)>>,
+	/// The method used for each part.
+	///
+	/// Default: `PUT`.
+	pub method: Option<String>,
+	/// The size of each part in bytes; the last part has the remainder. S3 requires parts of at
+	/// least 5 MiB, except for the last.
+	///
+	/// Default: 8 MiB.
+	pub part_size: Option<u32>,
+	/// How many times a part is retried after a network error, a timeout, or a `408`, `429`, or
+	/// `5xx` response, with exponential backoff from 250 milliseconds.
+	///
+	/// Default: 3.
+	pub retries: Option<u32>,
+}
+
+/// A part of an upload, from `faithUpload()`.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct FaithUploadPart {
+	/// The part's number, from 1.
+	pub part_number: u32,
+	/// Where the part starts in the file.
+	pub offset: i64,
+	/// The part's size in bytes.
+	pub size: u32,
+	/// The status of the response to the part's last attempt.
+	pub status: u16,
+	/// The `ETag` of the response, which S3 needs to complete the upload.
+	pub etag: Option<String>,
+	/// How many requests were made for the part, including retries.
+	pub attempts: u32,
+}
+
+/// Custom to Fáith.
+///
+/// Uploads a file in parts, for multipart upload endpoints like S3's `UploadPart`. The file is read
+/// a part at a time, so only `concurrency` parts are in memory at once.
+///
+/// Each part is sent to `url` with a `partNumber` query parameter (from 1) and a `Content-Range`
+/// header giving its place in the file. Parts with a `2xx` response are done; others are retried as
+/// set by `retries`. Starting and completing the upload, e.g. with S3's `CreateMultipartUpload` and
+/// `CompleteMultipartUpload`, is left to the caller: the parts' `etag`s are returned for this.
+///
+/// Resolves to the parts in order. Rejects with the first error, cancelling the parts in flight: an
+/// `Upload` error if the file can't be read or if a part fails after its retries, or the error of a
+/// part's last attempt if it couldn't be sent.
+///
+/// The `signal` aborts the whole upload.
+#[napi(ts_return_type = "Async<Array<FaithUploadPart>>")]
+pub fn faith_upload(
+	env: Env,
+	url: String,
+	file_path: String,
+	options: Option<FaithUploadOptions>,
+	signal: Option<AbortSignal>,
+) -> Async<Vec<FaithUploadPart>> {
+	let options = options.unwrap_or_default();
+	let agent = options
+		.agent
+		.as_deref()
+		.map(Agent::clone)
+		.map_or_else(|| Agent::global(&env), Ok);
+	let concurrency = options.concurrency.unwrap_or(4).max(1) as usize;
+	let part_size = u64::from(options.part_size.unwrap_or(8 * 1024 * 1024).max(1));
+	let retries = options.retries.unwrap_or(3);
+	let template = FaithOptions {
+		headers: options.headers_per_part,
+		method: Some(options.method.unwrap_or_else(|| "PUT".into())),
+		..Default::default()
+	};
+
+	let abort = abort_receiver(signal.as_ref());
+	FaithAsyncResult::run(async move || {
+		let agent = agent?;
+		let url = Url::parse(&url)
+			.map_err(|err| FaithError::new(FaithErrorKind::InvalidUrl, Some(err.to_string())))?;
+
+		let total = tokio::fs::metadata(&file_path)
+			.await
+			.map_err(|err| upload_error(format!("can't read {file_path}: {err}")))?
+			.len();
+		let parts = total.div_ceil(part_size).max(1);
+
+		let upload = stream::iter(1..=parts)
+			.map(|number| {
+				let offset = (number - 1) * part_size;
+				let size = part_size.min(total - offset);
+				upload_part(
+					&agent, &url, &file_path, &template, number, offset, size, total, retries,
+				)
+			})
+			.buffered(concurrency)
+			.try_collect::<Vec<_>>();
+
+		match abort {
+			Some(mut abort) => tokio::select! {
+				parts = upload => parts,
+				_ = abort.recv() => Err(FaithError::from(FaithErrorKind::Aborted).with_cause("signal")),
+			},
+			None => upload.await,
+		}
+	})
+}
+
+fn upload_error(message: String) -> FaithError {
+	FaithError::new(FaithErrorKind::Upload, Some(message))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn upload_part(
+	agent: &Agent,
+	url: &Url,
+	file_path: &str,
+	template: &FaithOptions,
+	number: u64,
+	offset: u64,
+	size: u64,
+	total: u64,
+	retries: u32,
+) -> Result<FaithUploadPart, FaithError> {
+	let body = read_part(file_path, offset, size)
+		.await
+		.map_err(|err| upload_error(format!("can't read part {number} of {file_path}: {err}")))?;
+
+	let mut url = url.clone();
+	url.query_pairs_mut()
+		.append_pair("partNumber", &number.to_string());
+
+	let mut options = template.clone();
+	if size > 0 {
+		options.headers.get_or_insert_default().push((
+			"content-range".into(),
+			Some(format!("bytes {offset}-{}/{total}", offset + size - 1)),
+		));
+	}
+
+	let mut attempts = 0;
+	loop {
+		attempts += 1;
+		let retry = attempts <= retries;
+		let backoff = Duration::from_millis(250 << (attempts - 1).min(6));
+
+		match fetch(
+			url.to_string(),
+			options.clone(),
+			agent.clone(),
+			Some(body.clone()),
+			None,
+			None,
+		)
+		.await
+		{
+			Ok(response) if response.status_code.is_success() => {
+				return Ok(FaithUploadPart {
+					part_number: number as u32,
+					offset: offset as i64,
+					size: size as u32,
+					status: response.status_code.as_u16(),
+					etag: response
+						.headers
+						.get(ETAG)
+						.and_then(|etag| etag.to_str().ok())
+						.map(Into::into),
+					attempts,
+				});
+			}
+			Ok(response) if retry && is_retryable(response.status_code) => {}
+			Ok(response) => {
+				return Err(upload_error(format!(
+					"part {number} failed with status {} after {attempts} attempts",
+					response.status_code.as_u16()
+				)));
+			}
+			Err(err)
+				if retry
+					&& matches!(
+						err.kind,
+						FaithErrorKind::Dns
+							| FaithErrorKind::Network
+							| FaithErrorKind::Proxy
+							| FaithErrorKind::Timeout
+					) => {}
+			Err(err) => return Err(err),
+		}
+
+		agent.clock.sleep(backoff).await;
+	}
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+	status == StatusCode::REQUEST_TIMEOUT
+		|| status == StatusCode::TOO_MANY_REQUESTS
+		|| status.is_server_error()
+}
+
+async fn read_part(file_path: &str, offset: u64, size: u64) -> std::io::Result<Bytes> {
+	let mut file = File::open(file_path).await?;
+	file.seek(SeekFrom::Start(offset)).await?;
+	let mut buf = vec![0; size as usize];
+	file.read_exact(&mut buf).await?;
+	Ok(buf.into())
+}
@@ -0,0 +1,13 @@
+use http::Version;
+
+/// Returns the major and minor version numbers, and the ALPN protocol identifier, of an HTTP version.
+pub(crate) fn version_parts(version: Version) -> (u8, u8, &'static str) {
+	match version {
+		Version::HTTP_09 => (0, 9, "http/0.9"),
+		Version::HTTP_10 => (1, 0, "http/1.0"),
+		Version::HTTP_11 => (1, 1, "http/1.1"),
+		Version::HTTP_2 => (2, 0, "h2"),
+		Version::HTTP_3 => (3, 0, "h3"),
+		_ => (0, 0, ""),
+	}
+}
@@ -189,3 +189,42 @@ test("signal: abort does not prevent reading completed response", async (t) => {
     t.fail(`Unexpected error: ${error.message}`);
   }
 });
+
+test("signal: rejection carries the abort reason", async (t) => {
+  t.plan(4);
+
+  const controller = new AbortController();
+  const reason = new Error("user navigated away");
+  setTimeout(() => controller.abort(reason), 200);
+
+  try {
+    await fetch(url("/delay/2"), { signal: controller.signal });
+    t.fail("Should have been aborted");
+  } catch (error) {
+    t.equal(error.name, "AbortError", "should reject with AbortError");
+    t.equal(error.code, "Aborted", "should have the Aborted code");
+    t.ok(
+      error.message.includes("user navigated away"),
+      "message should be the reason's",
+    );
+    t.equal(error.cause, reason, "cause should be the reason");
+  }
+});
+
+test("signal: non-error reason before request starts", async (t) => {
+  t.plan(2);
+
+  const controller = new AbortController();
+  controller.abort("cancelled by user");
+
+  try {
+    await fetch(url("/get"), { signal: controller.signal });
+    t.fail("Should have been aborted");
+  } catch (error) {
+    t.ok(
+      error.message.includes("cancelled by user"),
+      "message should be the reason",
+    );
+    t.equal(error.cause, "cancelled by user", "cause should be the reason");
+  }
+});
@@ -0,0 +1,50 @@
+const { url } = require("./helpers.js");
+const test = require("tape");
+const { Agent, fetch, fetchAll } = require("../wrapper.js");
+
+test("abortAll: aborts every request in flight on the agent", async (t) => {
+  t.plan(4);
+
+  const agent = new Agent();
+  const pending = [
+    fetch(url("/delay/2"), { agent }),
+    fetch(url("/delay/2"), { agent }),
+  ];
+  setTimeout(() => agent.abortAll("shutting down"), 200);
+
+  const results = await Promise.allSettled(pending);
+  for (const result of results) {
+    t.equal(result.reason?.name, "AbortError", "should reject with AbortError");
+    t.ok(
+      result.reason?.message.includes("shutting down"),
+      "error message should be the reason",
+    );
+  }
+});
+
+test("abortAll: aborts requests in a batch", async (t) => {
+  t.plan(1);
+
+  const agent = new Agent();
+  const pending = fetchAll([{ url: url("/delay/2"), options: { agent } }]);
+  setTimeout(() => agent.abortAll(), 200);
+
+  const [result] = await pending;
+  t.equal(result.reason?.code, "Aborted", "should reject with Aborted");
+});
+
+test("abortAll: does not affect other agents or later requests", async (t) => {
+  t.plan(3);
+
+  const agent = new Agent();
+  const other = new Agent();
+  const pending = fetch(url("/delay/1"), { agent: other });
+  agent.abortAll();
+
+  const response = await pending;
+  t.equal(response.status, 200, "other agent's request should complete");
+
+  const later = await fetch(url("/get"), { agent });
+  t.equal(later.status, 200, "requests after abortAll should work");
+  t.equal(agent.stats().aborted, 0, "nothing should have been aborted");
+});
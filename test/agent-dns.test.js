@@ -1,5 +1,6 @@
+const http = require("node:http");
 const test = require("tape");
-const { fetch: faithFetch, Agent } = require("../wrapper.js");
+const { fetch: faithFetch, Agent, ERROR_CODES } = require("../wrapper.js");
 const { url, port } = require("./helpers.js");
 
 test("Agent with dns.system option enabled", async (t) => {
@@ -573,3 +574,34 @@ test("Agent dns with peer information", async (t) => {
 	t.ok(response.peer, "Should have peer information");
 	t.ok(response.peer.address, "Should have peer address");
 });
+
+test("Agent with dns.order prefers an address family", async (t) => {
+	const server = http.createServer((_req, res) => res.end("ok"));
+	await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+	try {
+		const agent = new Agent({
+			dns: { system: true, order: { family: "ipv4", avoid: ["::1"] } },
+		});
+
+		const response = await faithFetch(
+			`http://localhost:${server.address().port}/`,
+			{ agent },
+		);
+		t.equal(await response.text(), "ok", "Should connect over IPv4");
+		t.match(response.peer.address, /^127\.0\.0\.1:/, "Peer is the IPv4 address");
+	} finally {
+		await new Promise((resolve) => server.close(resolve));
+	}
+	t.end();
+});
+
+test("Agent with invalid dns.order range throws", (t) => {
+	t.plan(1);
+
+	try {
+		new Agent({ dns: { order: { prefer: ["10.0.0.0/33"] } } });
+		t.fail("Should have thrown");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.Config, "Config error");
+	}
+});
@@ -611,3 +611,115 @@ test("Agent with many headers", async (t) => {
 		);
 	}
 });
+
+test("Request-level headers replace all values of an agent header", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent({
+		headers: [
+			{ name: "X-Multi", value: "agent-one" },
+			{ name: "X-Multi", value: "agent-two" },
+		],
+	});
+
+	const response = await faithFetch(url("/headers"), {
+		agent,
+		headers: { "x-multi": "request-value" },
+	});
+
+	const data = await response.json();
+	const value = data.headers["X-Multi"];
+	t.deepEqual(
+		Array.isArray(value) ? value : [value],
+		["request-value"],
+		"Only the request-level value should be sent",
+	);
+});
+
+test("Request-level null removes an agent header", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({
+		headers: [
+			{ name: "X-Removed", value: "agent-value" },
+			{ name: "X-Kept", value: "agent-value" },
+		],
+	});
+
+	const response = await faithFetch(url("/headers"), {
+		agent,
+		headers: { "X-Removed": null },
+	});
+
+	const data = await response.json();
+	t.notOk(data.headers["X-Removed"], "Removed header should not be sent");
+	t.equal(
+		getHeader(data.headers, "X-Kept"),
+		"agent-value",
+		"Other agent headers should still be sent",
+	);
+});
+
+test("Request-level null removes the User-Agent", async (t) => {
+	t.plan(1);
+
+	const response = await faithFetch(url("/headers"), {
+		headers: { "User-Agent": null },
+	});
+
+	const data = await response.json();
+	t.notOk(data.headers["User-Agent"], "User-Agent should not be sent");
+});
+
+test("Request-level sensitiveHeaders are still sent", async (t) => {
+	t.plan(2);
+
+	const response = await faithFetch(url("/headers"), {
+		headers: { Authorization: "Bearer request-token" },
+		sensitiveHeaders: ["authorization"],
+	});
+	t.ok(response.ok, "Should successfully fetch");
+
+	const data = await response.json();
+	t.equal(
+		getHeader(data.headers, "Authorization"),
+		"Bearer request-token",
+		"Sensitive header should be sent",
+	);
+});
+
+test("Request-level sensitiveHeaders rejects invalid names", async (t) => {
+	t.plan(1);
+
+	try {
+		await faithFetch(url("/headers"), { sensitiveHeaders: ["Invalid Name"] });
+		t.fail("Should have thrown");
+	} catch (err) {
+		t.equal(err.code, "InvalidHeader", "Should throw an InvalidHeader error");
+	}
+});
+
+test("Request-level Host header overrides the URL host", async (t) => {
+	t.plan(2);
+
+	const response = await faithFetch(url("/headers"), {
+		agent: new Agent({ unsafe: true }),
+		headers: { Host: "virtual.example.test" },
+	});
+	t.ok(response.ok, "Should successfully fetch");
+
+	const data = await response.json();
+	t.equal(
+		getHeader(data.headers, "Host"),
+		"virtual.example.test",
+		"Host header should be sent as set",
+	);
+});
+
+test("Agent with tls.serverName does not affect plain HTTP", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent({ tls: { serverName: "virtual.example.test" } });
+	const response = await faithFetch(url("/get"), { agent });
+	t.ok(response.ok, "Should successfully fetch");
+});
@@ -0,0 +1,122 @@
+const test = require("tape");
+const net = require("node:net");
+const { fetch: faithFetch, Agent } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("Agent with http1.only uses HTTP/1.1", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ http1: { only: true } });
+	const response = await faithFetch(url("/get"), { agent });
+
+	t.ok(response.ok, "Should successfully fetch");
+	t.equal(response.version, "HTTP/1.1", "Version should be HTTP/1.1");
+});
+
+test("Agent with http1.version10 sends requests", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ http1: { version10: true } });
+	const response = await faithFetch(url("/get"), { agent });
+
+	t.ok(response.ok, "Should successfully fetch over HTTP/1.0");
+	const data = await response.json();
+	t.ok(data.url, "Should receive a full body");
+});
+
+test("Agent with http1.version10 sends buffered bodies", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ http1: { version10: true } });
+	const response = await faithFetch(url("/post"), {
+		agent,
+		method: "POST",
+		body: "hello",
+	});
+
+	t.ok(response.ok, "Should successfully POST over HTTP/1.0");
+	const data = await response.json();
+	t.equal(data.data, "hello", "Body should be sent");
+});
+
+test("Agent with http1.version10 rejects streaming bodies", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent({ http1: { version10: true } });
+	const body = new ReadableStream({
+		start(controller) {
+			controller.enqueue(new TextEncoder().encode("hello"));
+			controller.close();
+		},
+	});
+
+	try {
+		await faithFetch(url("/post"), {
+			agent,
+			method: "POST",
+			body,
+			duplex: "half",
+		});
+		t.fail("Should have thrown");
+	} catch (err) {
+		t.equal(err.code, "Config", "Should throw a Config error");
+	}
+});
+
+test("Agent with http1.lenient works with conformant servers", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent({ http1: { lenient: true } });
+	const response = await faithFetch(url("/get"), { agent });
+
+	t.ok(response.ok, "Should successfully fetch");
+});
+
+test("Agent with http1.version10 sends an HTTP/1.0 request line and Connection: close", async (t) => {
+	t.plan(3);
+
+	let head = "";
+	const server = net.createServer((socket) => {
+		socket.on("data", (chunk) => {
+			head += chunk.toString("latin1");
+			if (head.includes("\r\n\r\n")) {
+				socket.end("HTTP/1.0 200 OK\r\nContent-Length: 2\r\n\r\nok");
+			}
+		});
+	});
+	await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+
+	try {
+		const agent = new Agent({ http1: { version10: true } });
+		const response = await faithFetch(`http://127.0.0.1:${server.address().port}/old`, { agent });
+		t.equal(await response.text(), "ok", "Should read the response");
+
+		const [requestLine, ...headers] = head.split("\r\n\r\n")[0].split("\r\n");
+		t.equal(requestLine, "GET /old HTTP/1.0", "Should send an HTTP/1.0 request line");
+		t.ok(
+			headers.some((header) => header.toLowerCase() === "connection: close"),
+			"Should send Connection: close",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("Agent with http1.lenient still rejects HTTP/0.9 responses", async (t) => {
+	t.plan(1);
+
+	const server = net.createServer((socket) => {
+		socket.once("data", () => socket.end("no status line here"));
+	});
+	await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+
+	try {
+		const agent = new Agent({ http1: { lenient: true } });
+		await faithFetch(`http://127.0.0.1:${server.address().port}/`, { agent });
+		t.fail("Should have thrown");
+	} catch (err) {
+		t.ok(err.code, "Should throw a Fáith error");
+	} finally {
+		server.close();
+	}
+});
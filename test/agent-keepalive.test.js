@@ -0,0 +1,39 @@
+const test = require("tape");
+const { Agent, fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+test("keepalive: answered pings keep the connection", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/slow", body: "abcd", chunkSize: 1, chunkDelay: 700 });
+
+		const agent = new Agent({ keepalive: { interval: 1, timeout: 1 } });
+		let unhealthy = 0;
+		agent.onConnectionUnhealthy(() => {
+			unhealthy += 1;
+		});
+
+		const response = await fetch(`${server.url}/slow`, { agent, version: "h2" });
+		t.equal(await response.text(), "abcd", "Body arrives while pings are sent");
+		t.equal(unhealthy, 0, "No connection is reported unhealthy");
+
+		agent.onConnectionUnhealthy(null);
+		const again = await fetch(`${server.url}/slow`, { agent, version: "h2" });
+		t.equal(await again.text(), "abcd", "The connection is still usable");
+	} finally {
+		server.close();
+	}
+});
+
+test("keepalive: interval must be at least 1 second", (t) => {
+	t.throws(
+		() => new Agent({ keepalive: { interval: 0 } }),
+		/keepalive.interval/,
+		"Throws for a zero interval",
+	);
+	t.doesNotThrow(
+		() => new Agent({ keepalive: { timeout: 5, whileIdle: false } }),
+		"Other settings without an interval are accepted",
+	);
+	t.end();
+});
@@ -0,0 +1,71 @@
+const test = require("tape");
+const { fetch, Agent } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+async function tooLarge(t, promise, message) {
+	try {
+		await promise;
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, "RequestTooLarge", message);
+	}
+}
+
+test("limits.requestBody: rejects larger bodies", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ limits: { requestBody: 16 } });
+	await tooLarge(
+		t,
+		fetch(url("/post"), { agent, method: "POST", body: "x".repeat(17) }),
+		"should reject a body over the limit",
+	);
+
+	const response = await fetch(url("/post"), {
+		agent,
+		method: "POST",
+		body: "x".repeat(16),
+	});
+	t.equal(response.status, 200, "should send a body at the limit");
+});
+
+test("limits.requestHeaders: rejects larger headers", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ limits: { requestHeaders: 1024 } });
+	await tooLarge(
+		t,
+		fetch(url("/get"), { agent, headers: { "X-Large": "x".repeat(1024) } }),
+		"should reject headers over the limit",
+	);
+
+	const response = await fetch(url("/get"), {
+		agent,
+		headers: { "X-Small": "x" },
+	});
+	t.equal(response.status, 200, "should send headers under the limit");
+});
+
+test("limits.responseBody: rejects larger declared bodies", async (t) => {
+	const agent = new Agent({ limits: { responseBody: 1024 } });
+	try {
+		await fetch(url("/bytes/1025"), { agent });
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, "ResponseTooLarge", "should reject a Content-Length over the limit");
+	}
+
+	const response = await fetch(url("/bytes/1024"), { agent });
+	t.equal((await response.bytes()).length, 1024, "should read a body at the limit");
+});
+
+test("limits.responseBody: fails reads of larger undeclared bodies", async (t) => {
+	const agent = new Agent({ limits: { responseBody: 1024 } });
+	const response = await fetch(url("/stream-bytes/2048?chunk_size=256"), { agent });
+	try {
+		await response.bytes();
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, "ResponseTooLarge", "should fail once the body goes over");
+	}
+});
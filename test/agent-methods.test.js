@@ -0,0 +1,90 @@
+const test = require("tape");
+const { Agent, Response } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+// Helper to get header value (handles both string and array)
+function getHeader(headers, name) {
+	const value = headers[name];
+	return Array.isArray(value) ? value[0] : value;
+}
+
+test("agent.get() makes a GET request", async (t) => {
+	t.plan(3);
+
+	const agent = new Agent();
+	const response = await agent.get(url("/get"), {
+		headers: { "X-Test": "get" },
+	});
+	t.ok(response instanceof Response, "should return a wrapped Response");
+	t.equal(response.status, 200, "should return status 200");
+	const data = await response.json();
+	t.equal(getHeader(data.headers, "X-Test"), "get", "should send the options headers");
+});
+
+test("agent.head() makes a HEAD request", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent();
+	const response = await agent.head(url("/get"));
+	t.equal(response.status, 200, "should return status 200");
+	t.equal(await response.text(), "", "should have no body");
+});
+
+test("agent.delete() makes a DELETE request", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent();
+	const response = await agent.delete(url("/delete"));
+	t.equal(response.status, 200, "should return status 200");
+});
+
+test("agent.post() sends the body argument", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent();
+	const response = await agent.post(url("/post"), "hello", {
+		body: "ignored",
+		method: "GET",
+	});
+	t.equal(response.status, 200, "should use POST regardless of options.method");
+	const data = await response.json();
+	t.equal(data.data, "hello", "body argument should take precedence");
+});
+
+test("agent.put() and agent.patch() send bodies", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent();
+	const put = await (await agent.put(url("/put"), "put-body")).json();
+	t.equal(put.data, "put-body", "put should send the body");
+	const patch = await (await agent.patch(url("/patch"), "patch-body")).json();
+	t.equal(patch.data, "patch-body", "patch should send the body");
+});
+
+test("agent.json() fetches and parses in one go", async (t) => {
+	t.plan(3);
+
+	const agent = new Agent();
+	const data = await agent.json(url("/get"), {
+		headers: { "X-Test": "json" },
+	});
+	t.equal(typeof data, "object", "should return parsed JSON");
+	t.equal(getHeader(data.headers, "X-Test"), "json", "should send the options headers");
+	t.equal(
+		getHeader(data.headers, "Accept"),
+		"application/json",
+		"should send Accept: application/json",
+	);
+});
+
+test("agent.json() rejects on invalid JSON", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent();
+	try {
+		await agent.json(url("/html"));
+		t.fail("should have rejected");
+	} catch (error) {
+		t.equal(error.code, "JsonParse", "should reject with JsonParse");
+	}
+});
@@ -0,0 +1,49 @@
+const test = require("tape");
+const { fetch: faithFetch, Agent } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+// Time to fetch a cached response while another request holds the only per-host slot
+async function cachedFetchTime(middleware) {
+	const agent = new Agent({
+		cache: { store: "memory" },
+		middleware,
+		pool: { maxPerHost: 1 },
+	});
+	const testUrl = url("/cache/60");
+	await (await faithFetch(testUrl, { agent })).text();
+
+	const slow = faithFetch(url("/delay/1"), { agent });
+	await new Promise((resolve) => setTimeout(resolve, 100));
+
+	const start = Date.now();
+	await (await faithFetch(testUrl, { agent })).text();
+	const elapsed = Date.now() - start;
+
+	await (await slow).text();
+	return elapsed;
+}
+
+test("middleware: cached responses skip the host limit by default", async (t) => {
+	t.plan(1);
+
+	const elapsed = await cachedFetchTime(undefined);
+	t.ok(elapsed < 500, "cached response should not wait for the slot");
+});
+
+test("middleware: host-limit outermost applies to cached responses", async (t) => {
+	t.plan(1);
+
+	const elapsed = await cachedFetchTime(["host-limit", "cache"]);
+	t.ok(elapsed >= 500, "cached response should wait for the slot");
+});
+
+test("middleware: listing a stage twice throws", (t) => {
+	t.plan(1);
+
+	try {
+		new Agent({ middleware: ["cache", "cache"] });
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, "Config", "should throw a Config error");
+	}
+});
@@ -0,0 +1,323 @@
+const { execFile } = require("node:child_process");
+const http = require("node:http");
+const path = require("node:path");
+const { promisify } = require("node:util");
+const test = require("tape");
+const { fetch, Agent, ERROR_CODES } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+const BASIC = `Basic ${Buffer.from("user:pass").toString("base64")}`;
+
+// A forward proxy which answers every request itself, recording what it was asked for. With
+// `auth`, it requires the user:pass credentials. It refuses to open tunnels, answering `CONNECT`
+// with `connectStatus`.
+async function startProxy({ auth = false, connectStatus = 403 } = {}) {
+	const requests = [];
+	const server = http.createServer((req, res) => {
+		requests.push({ url: req.url, auth: req.headers["proxy-authorization"] });
+		if (auth && req.headers["proxy-authorization"] !== BASIC) {
+			res.writeHead(407, { "Proxy-Authenticate": 'Basic realm="test"' });
+			res.end();
+			return;
+		}
+		res.end("proxied");
+	});
+	server.on("connect", (req, socket) => {
+		requests.push({ url: req.url, auth: req.headers["proxy-authorization"] });
+		socket.end(`HTTP/1.1 ${connectStatus} ${http.STATUS_CODES[connectStatus]}\r\n\r\n`);
+	});
+	await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+	return {
+		url: `http://127.0.0.1:${server.address().port}`,
+		requests,
+		close: () => new Promise((resolve) => server.close(resolve)),
+	};
+}
+
+test("proxy: sends http requests through the given proxy", async (t) => {
+	const proxy = await startProxy();
+	try {
+		const agent = new Agent({ proxy: { http: proxy.url } });
+		const response = await fetch("http://example.invalid/path", { agent });
+
+		t.equal(await response.text(), "proxied", "Response is from the proxy");
+		t.equal(proxy.requests.length, 1, "Proxy received one request");
+		t.equal(
+			proxy.requests[0].url,
+			"http://example.invalid/path",
+			"Proxy was asked for the absolute URL",
+		);
+	} finally {
+		await proxy.close();
+	}
+	t.end();
+});
+
+test("proxy: url applies to all requests", async (t) => {
+	const proxy = await startProxy();
+	try {
+		const agent = new Agent({ proxy: { url: proxy.url } });
+		const response = await fetch("http://example.invalid/", { agent });
+
+		t.equal(await response.text(), "proxied", "Response is from the proxy");
+	} finally {
+		await proxy.close();
+	}
+	t.end();
+});
+
+test("proxy: sends credentials in the proxy URL", async (t) => {
+	const proxy = await startProxy();
+	try {
+		const withAuth = proxy.url.replace("http://", "http://user:pass@");
+		const agent = new Agent({ proxy: { http: withAuth } });
+		await (await fetch("http://example.invalid/", { agent })).text();
+
+		t.equal(proxy.requests[0].auth, BASIC, "Proxy-Authorization is sent");
+	} finally {
+		await proxy.close();
+	}
+	t.end();
+});
+
+test("proxy: noProxy hosts are requested directly", async (t) => {
+	const proxy = await startProxy();
+	try {
+		const host = new URL(url("/")).hostname;
+		const agent = new Agent({ proxy: { url: proxy.url, noProxy: [host] } });
+		const response = await fetch(url("/get"), { agent });
+		await response.json();
+
+		t.ok(response.ok, "Request succeeds");
+		t.equal(proxy.requests.length, 0, "Proxy was not used");
+	} finally {
+		await proxy.close();
+	}
+	t.end();
+});
+
+test("proxy: is reported on the response", async (t) => {
+	const proxy = await startProxy();
+	try {
+		const agent = new Agent({ proxy: { http: proxy.url } });
+		const response = await fetch("http://example.invalid/", { agent });
+		await response.text();
+
+		t.deepEqual(
+			response.peer.proxy,
+			{ url: `${proxy.url}/`, tunnel: false, connectStatus: undefined },
+			"Proxy is reported, without a tunnel",
+		);
+
+		const direct = await fetch(url("/get"), { agent: new Agent() });
+		await direct.discard();
+		t.equal(direct.peer.proxy, undefined, "No proxy for direct requests");
+	} finally {
+		await proxy.close();
+	}
+	t.end();
+});
+
+test("proxy: connection setup is timed", async (t) => {
+	const proxy = await startProxy();
+	try {
+		const agent = new Agent({ proxy: { http: proxy.url } });
+		const first = await fetch("http://example.invalid/", { agent });
+		await first.text();
+		const { requestStart, connectStart, connectEnd, headersComplete } =
+			first.timestamps;
+
+		t.equal(typeof connectStart, "number", "connectStart is set");
+		t.equal(typeof connectEnd, "number", "connectEnd is set");
+		t.ok(requestStart <= connectStart, "Connecting starts after the request");
+		t.ok(connectStart <= connectEnd, "Connecting ends after it starts");
+		t.ok(connectEnd <= headersComplete, "Headers come after connecting");
+
+		const second = await fetch("http://example.invalid/", { agent });
+		await second.text();
+		t.equal(
+			second.timestamps.connectStart,
+			undefined,
+			"Not set on a reused connection",
+		);
+	} finally {
+		await proxy.close();
+	}
+	t.end();
+});
+
+test("proxy: refused tunnels are errors", async (t) => {
+	const proxy = await startProxy();
+	try {
+		const agent = new Agent({ proxy: { url: proxy.url } });
+		await fetch("https://example.invalid/", { agent }).then(
+			() => t.fail("Request should fail"),
+			(err) => t.equal(err.code, ERROR_CODES.Proxy, "Proxy error"),
+		);
+		t.equal(
+			proxy.requests[0]?.url,
+			"example.invalid:443",
+			"Proxy was asked to CONNECT",
+		);
+	} finally {
+		await proxy.close();
+	}
+
+	const authProxy = await startProxy({ connectStatus: 407 });
+	try {
+		const agent = new Agent({ proxy: { url: authProxy.url } });
+		await fetch("https://example.invalid/", { agent }).then(
+			() => t.fail("Request should fail"),
+			(err) => t.equal(err.code, ERROR_CODES.ProxyAuth, "ProxyAuth error"),
+		);
+	} finally {
+		await authProxy.close();
+	}
+	t.end();
+});
+
+test("proxy: none and system are accepted", async (t) => {
+	for (const mode of ["none", "system"]) {
+		const agent = new Agent({ proxy: mode });
+		const response = await fetch(url("/get"), { agent });
+		await response.discard();
+		t.ok(response.ok, `${mode}: request succeeds`);
+	}
+	t.end();
+});
+
+test("proxy: invalid options are a Config error", async (t) => {
+	t.throws(
+		() => new Agent({ proxy: {} }),
+		(err) => err.code === "Config",
+		"Empty proxy object is rejected",
+	);
+	t.throws(
+		() => new Agent({ proxy: { url: "not a url" } }),
+		(err) => err.code === "Config",
+		"Invalid proxy URL is rejected",
+	);
+	t.end();
+});
+
+test("proxyAuth: is sent upfront to proxies given in proxy", async (t) => {
+	const proxy = await startProxy({ auth: true });
+	try {
+		const agent = new Agent({
+			proxy: { http: proxy.url },
+			proxyAuth: { username: "user", password: "pass" },
+		});
+		const response = await fetch("http://example.invalid/", { agent });
+
+		t.equal(await response.text(), "proxied", "Request is authenticated");
+		t.equal(proxy.requests.length, 1, "No challenge round trip");
+	} finally {
+		await proxy.close();
+	}
+	t.end();
+});
+
+test("proxyAuth: rejected credentials are a ProxyAuth error", async (t) => {
+	const proxy = await startProxy({ auth: true });
+	try {
+		const agent = new Agent({
+			proxy: { http: proxy.url },
+			proxyAuth: { username: "user", password: "wrong" },
+		});
+		await fetch("http://example.invalid/", { agent });
+		t.fail("Should have thrown");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.ProxyAuth, "Should be a ProxyAuth error");
+	} finally {
+		await proxy.close();
+	}
+	t.end();
+});
+
+test("proxyAuth: without it, 407 responses are returned", async (t) => {
+	const proxy = await startProxy({ auth: true });
+	try {
+		const agent = new Agent({ proxy: { http: proxy.url } });
+		const response = await fetch("http://example.invalid/", { agent });
+		await response.discard();
+
+		t.equal(response.status, 407, "Response is the proxy's 407");
+	} finally {
+		await proxy.close();
+	}
+	t.end();
+});
+
+test("proxyAuth: answers the system proxy's challenge", async (t) => {
+	const proxy = await startProxy({ auth: true });
+	try {
+		// run in a separate process, as the system proxy is read from the environment once
+		const script = `
+			const { Agent, fetch } = require(${JSON.stringify(path.join(__dirname, "../wrapper.js"))});
+			const agent = new Agent({ proxyAuth: { username: "user", password: "pass" } });
+			fetch("http://example.invalid/", { agent })
+				.then((res) => res.text())
+				.then((text) => process.stdout.write(text));
+		`;
+		const { stdout } = await promisify(execFile)(
+			process.execPath,
+			["-e", script],
+			{ encoding: "utf8", env: { ...process.env, HTTP_PROXY: proxy.url } },
+		);
+
+		t.equal(stdout, "proxied", "Request is authenticated");
+		t.equal(proxy.requests.length, 2, "Request was retried after a 407");
+		t.notOk(proxy.requests[0].auth, "First attempt has no credentials");
+	} finally {
+		await proxy.close();
+	}
+	t.end();
+});
+
+test("proxy: resolver decides the proxy for each request", async (t) => {
+	const proxy = await startProxy();
+	try {
+		const host = proxy.url.slice("http://".length);
+		const agent = new Agent({ proxy: "resolver" });
+		const asked = [];
+		agent.setProxyResolver(async (url) => {
+			asked.push(url);
+			return url.startsWith(proxy.url) ? "DIRECT" : `SOCKS5 ${host}; PROXY ${host}`;
+		});
+
+		const response = await fetch("http://example.invalid/path", { agent });
+		t.equal(await response.text(), "proxied", "Response is from the proxy");
+		t.equal(proxy.requests.at(-1).url, "http://example.invalid/path", "Absolute URL");
+		t.equal(response.peer.proxy?.url, `${proxy.url}/`, "peer.proxy");
+		t.deepEqual(asked, ["http://example.invalid/path"], "Resolver is given the URL");
+
+		const direct = await fetch(`${proxy.url}/direct`, { agent });
+		await direct.text();
+		t.equal(proxy.requests.at(-1).url, "/direct", "DIRECT connects directly");
+		t.equal(direct.peer.proxy, undefined, "No peer.proxy for direct requests");
+
+		agent.setProxyResolver(() => proxy.url);
+		await (await fetch("http://example.invalid/url", { agent })).text();
+		t.equal(proxy.requests.at(-1).url, "http://example.invalid/url", "Proxy URLs");
+
+		agent.setProxyResolver(() => Promise.reject(new Error("no proxy for you")));
+		try {
+			await fetch("http://example.invalid/", { agent });
+			t.fail("should reject");
+		} catch (error) {
+			t.equal(error.code, ERROR_CODES.Proxy, "Resolver errors are Proxy errors");
+		}
+	} finally {
+		await proxy.close();
+	}
+	t.end();
+});
+
+test("proxy: setProxyResolver() needs proxy: resolver", (t) => {
+	t.throws(
+		() => new Agent().setProxyResolver(() => "DIRECT"),
+		/proxy: "resolver"/,
+		"Throws a Config error",
+	);
+	t.end();
+});
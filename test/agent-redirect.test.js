@@ -554,3 +554,24 @@ test("Agent redirect with different response types", async (t) => {
 		"Non-redirect should not be redirected",
 	);
 });
+
+test("Agent with redirect: 'error' rejects with the Redirect code", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent({ redirect: "error" });
+	try {
+		await faithFetch(url("/redirect/1"), { agent });
+		t.fail("Should throw error on redirect");
+	} catch (err) {
+		t.equal(err.code, "Redirect", "Should have the Redirect code");
+	}
+});
+
+test("Agent with tls.noDowngrade follows plain-text redirects", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ tls: { noDowngrade: true } });
+	const response = await faithFetch(url("/redirect/2"), { agent });
+	t.ok(response.ok, "Should follow http to http redirects");
+	t.ok(response.redirected, "Should have been redirected");
+});
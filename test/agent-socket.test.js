@@ -0,0 +1,41 @@
+const test = require("tape");
+const { fetch, Agent, ERROR_CODES } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test(
+	"socket.tos: requests work with a type of service",
+	{ skip: process.platform === "win32" },
+	async (t) => {
+		t.plan(1);
+
+		const agent = new Agent({ socket: { tos: 184 } });
+		const response = await fetch(url("/get"), { agent });
+		t.equal(response.status, 200, "should make the request");
+	},
+);
+
+test("socket.tos: out of range values are rejected", (t) => {
+	t.plan(1);
+
+	try {
+		new Agent({ socket: { tos: 256 } });
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.Config, "should be a Config error");
+	}
+});
+
+test(
+	"socket.mark: is rejected outside of Linux",
+	{ skip: process.platform === "linux" },
+	(t) => {
+		t.plan(1);
+
+		try {
+			new Agent({ socket: { mark: 1 } });
+			t.fail("should have thrown");
+		} catch (error) {
+			t.equal(error.code, ERROR_CODES.Config, "should be a Config error");
+		}
+	},
+);
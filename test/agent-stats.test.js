@@ -606,3 +606,69 @@ test("Agent stats() parallel requests with some failures", async (t) => {
     "responsesReceived should be less than requestsSent with failures",
   );
 });
+
+test("Agent stats() counts responses by status class", async (t) => {
+  t.plan(3);
+
+  const agent = new Agent();
+  await faithFetch(url("/status/200"), { agent });
+  await faithFetch(url("/status/404"), { agent });
+  await faithFetch(url("/status/500"), { agent });
+
+  const stats = agent.stats();
+  t.equal(stats.responses2xx, 1, "responses2xx should be 1");
+  t.equal(stats.responses4xx, 1, "responses4xx should be 1");
+  t.equal(stats.responses5xx, 1, "responses5xx should be 1");
+});
+
+test("Agent stats() counts redirects followed", async (t) => {
+  t.plan(2);
+
+  const agent = new Agent();
+  await faithFetch(url("/redirect/2"), { agent });
+
+  const stats = agent.stats();
+  t.equal(stats.redirects, 2, "redirects should be 2");
+  t.equal(stats.responses3xx, 0, "followed redirects are not responses");
+});
+
+test("Agent stats() counts bytes sent and received", async (t) => {
+  t.plan(2);
+
+  const agent = new Agent();
+  const response = await faithFetch(url("/post"), {
+    agent,
+    method: "POST",
+    body: "hello world",
+  });
+  const text = await response.text();
+
+  const stats = agent.stats();
+  t.equal(stats.bytesSent, 11, "bytesSent should be the body length");
+  t.equal(
+    stats.bytesReceived,
+    Buffer.byteLength(text),
+    "bytesReceived should be the response body length",
+  );
+});
+
+test("Agent stats() counts timeouts and aborts", async (t) => {
+  t.plan(4);
+
+  const agent = new Agent();
+  await faithFetch(url("/delay/2"), { agent, timeout: 100 }).catch(() => {});
+
+  const controller = new AbortController();
+  const pending = faithFetch(url("/delay/2"), {
+    agent,
+    signal: controller.signal,
+  }).catch(() => {});
+  setTimeout(() => controller.abort(), 50);
+  await pending;
+
+  const stats = agent.stats();
+  t.equal(stats.timeouts, 1, "timeouts should be 1");
+  t.equal(stats.aborted, 1, "aborted should be 1");
+  t.equal(stats.requestsSent, 2, "requestsSent should be 2");
+  t.equal(stats.responsesReceived, 0, "responsesReceived should be 0");
+});
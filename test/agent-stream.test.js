@@ -0,0 +1,80 @@
+const test = require("tape");
+const { setTimeout: sleep } = require("node:timers/promises");
+const { Agent, ERROR_CODES, fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+async function chunkSizes(response) {
+	const sizes = [];
+	for await (const chunk of response.chunks()) {
+		sizes.push(chunk.length);
+	}
+	return sizes;
+}
+
+test("agent.stream: maxChunkBytes splits chunks", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/", body: Buffer.alloc(10_000, "a") });
+
+		const agent = new Agent({ stream: { maxChunkBytes: 1000 } });
+		const sizes = await chunkSizes(await fetch(`${server.url}/`, { agent }));
+		t.ok(
+			sizes.every((size) => size <= 1000),
+			"No chunk over the limit",
+		);
+		t.equal(
+			sizes.reduce((a, b) => a + b, 0),
+			10_000,
+			"Whole body",
+		);
+
+		const response = await fetch(`${server.url}/`, { agent });
+		let length = 0;
+		for await (const chunk of response.body) {
+			t.ok(chunk.length <= 1000, "Also for the body stream");
+			length += chunk.length;
+			if (length >= 2000) break;
+		}
+	} finally {
+		server.close();
+	}
+});
+
+test("agent.stream: coalesceSmallChunks merges received chunks", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/",
+			body: Buffer.alloc(50, "a"),
+			chunkSize: 10,
+			chunkDelay: 10,
+		});
+
+		const agent = new Agent({ stream: { coalesceSmallChunks: true } });
+		const response = await fetch(`${server.url}/`, { agent });
+		await sleep(300);
+		t.deepEqual(await chunkSizes(response), [50], "One chunk once all is received");
+
+		const split = new Agent({
+			stream: { coalesceSmallChunks: true, maxChunkBytes: 20 },
+		});
+		const again = await fetch(`${server.url}/`, { agent: split });
+		await sleep(300);
+		t.deepEqual(await chunkSizes(again), [20, 20, 10], "Then split");
+
+		const text = await (await fetch(`${server.url}/`, { agent })).text();
+		t.equal(text.length, 50, "Reading in full is unaffected");
+	} finally {
+		server.close();
+	}
+});
+
+test("agent.stream: maxChunkBytes can't be 0", (t) => {
+	t.plan(1);
+	try {
+		new Agent({ stream: { maxChunkBytes: 0 } });
+		t.fail("should throw");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.Config, "Config error");
+	}
+});
@@ -0,0 +1,93 @@
+const test = require("tape");
+const { fetch: faithFetch, Agent } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+function withCredentials(path) {
+	const parsed = new URL(url(path));
+	parsed.username = "user";
+	parsed.password = "pass";
+	return parsed.toString();
+}
+
+async function rejection(t, promise, message) {
+	try {
+		await promise;
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, "InvalidUrl", message);
+	}
+}
+
+test("url: embedded credentials are used by default", async (t) => {
+	t.plan(1);
+
+	const response = await faithFetch(withCredentials("/basic-auth/user/pass"));
+	t.equal(response.status, 200, "should authenticate with the credentials");
+});
+
+test("url: embedded credentials can be stripped", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent({ url: { credentials: "strip" } });
+	const response = await faithFetch(withCredentials("/basic-auth/user/pass"), {
+		agent,
+	});
+	t.equal(response.status, 401, "should not send the credentials");
+});
+
+test("url: embedded credentials can be rejected", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ url: { credentials: "reject" } });
+	await rejection(
+		t,
+		faithFetch(withCredentials("/get"), { agent }),
+		"should reject URLs with credentials",
+	);
+
+	const response = await faithFetch(url("/get"), { agent });
+	t.equal(response.status, 200, "should allow URLs without credentials");
+});
+
+test("url: internationalized domain names can be rejected", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ url: { idn: "reject" } });
+	await rejection(
+		t,
+		faithFetch("http://bücher.invalid/", { agent }),
+		"should reject unicode domains",
+	);
+	await rejection(
+		t,
+		faithFetch("http://xn--bcher-kva.invalid/", { agent }),
+		"should reject punycode domains",
+	);
+});
+
+test("url: unencoded characters are encoded by default", async (t) => {
+	t.plan(1);
+
+	const response = await faithFetch(url("/anything/a b"));
+	const data = await response.json();
+	t.ok(data.url.endsWith("/anything/a%20b"), "should percent-encode spaces");
+});
+
+test("url: unencoded characters can be rejected", async (t) => {
+	t.plan(3);
+
+	const agent = new Agent({ url: { strictEncoding: true } });
+	await rejection(
+		t,
+		faithFetch(url("/anything/a b"), { agent }),
+		"should reject spaces",
+	);
+	await rejection(
+		t,
+		faithFetch(url("/anything/é"), { agent }),
+		"should reject non-ASCII characters",
+	);
+
+	const response = await faithFetch(url("/anything/a%20b"), { agent });
+	t.equal(response.status, 200, "should allow percent-encoded URLs");
+});
@@ -193,3 +193,27 @@ test("Agent in options doesn't interfere with custom User-Agent header", async (
 		"Custom User-Agent should override agent default",
 	);
 });
+
+test("Agent with upgradeInsecureRequests sends http requests over https", async (t) => {
+	t.plan(1);
+
+	// the test server only speaks plain-text HTTP, so an upgraded request fails
+	const agent = new Agent({ upgradeInsecureRequests: true });
+	try {
+		await faithFetch(url("/get"), { agent });
+		t.fail("Should have been upgraded to https");
+	} catch (error) {
+		t.equal(error.code, "TlsHandshake", "Should fail to connect over https");
+	}
+});
+
+test("Agent with upgradeInsecureRequests skips excluded hosts", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({
+		upgradeInsecureRequests: { exclude: [new URL(url("/")).hostname] },
+	});
+	const response = await faithFetch(url("/get"), { agent });
+	t.equal(response.status, 200, "Should not upgrade excluded hosts");
+	t.ok(response.url.startsWith("http:"), "URL should stay plain-text");
+});
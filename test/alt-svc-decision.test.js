@@ -0,0 +1,43 @@
+const test = require("tape");
+const { fetch: faithFetch, Agent } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("altSvc: reports no attempt without an Alt-Svc entry", async (t) => {
+	const agent = new Agent();
+	const response = await faithFetch(url("/get"), { agent });
+	await response.discard();
+
+	t.ok(response.altSvc, "altSvc should be set");
+	t.equal(response.altSvc.attempted, false, "HTTP/3 was not attempted");
+	t.equal(response.altSvc.fellBack, false, "Did not fall back");
+	t.equal(response.altSvc.recentlyFailed, false, "No recent failure");
+	t.notOk(response.altSvc.entry, "No cache entry");
+	t.notOk(response.altSvc.fallbackAfter, "No fallback duration");
+	t.end();
+});
+
+test("altSvc: is null when the upgrade is disabled", async (t) => {
+	const agent = new Agent({ http3: { upgradeEnabled: false } });
+	const response = await faithFetch(url("/get"), { agent });
+	await response.discard();
+
+	t.equal(response.altSvc, null, "altSvc should be null");
+	t.end();
+});
+
+test("altSvc: is null when the version is pinned", async (t) => {
+	const response = await faithFetch(url("/get"), { version: "http/1.1" });
+	await response.discard();
+
+	t.equal(response.altSvc, null, "altSvc should be null");
+	t.end();
+});
+
+test("altSvc: is kept on clones", async (t) => {
+	const response = await faithFetch(url("/get"));
+	const clone = response.clone();
+	await Promise.all([response.discard(), clone.discard()]);
+
+	t.deepEqual(clone.altSvc, response.altSvc, "Clone has the same decision");
+	t.end();
+});
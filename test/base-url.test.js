@@ -0,0 +1,96 @@
+const test = require("tape");
+const { Agent, ERROR_CODES, fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+async function rejection(promise) {
+	try {
+		await promise;
+	} catch (error) {
+		return error;
+	}
+	throw new Error("expected a rejection");
+}
+
+test("baseUrl: relative URLs are appended to the base path", async (t) => {
+	const server = await startServer();
+	try {
+		const agent = new Agent({ baseUrl: `${server.url}/v1` });
+		for (const path of ["users", "/users", "//users"]) {
+			const response = await agent.fetch(path);
+			t.equal(response.url, `${server.url}/v1/users`, `${path} resolves under /v1`);
+		}
+
+		const slashed = new Agent({ baseUrl: `${server.url}/v1/` });
+		t.equal(
+			(await slashed.get("/users?page=2")).url,
+			`${server.url}/v1/users?page=2`,
+			"Trailing slash on the base isn't doubled",
+		);
+		t.equal(
+			(await slashed.fetch("?page=3")).url,
+			`${server.url}/v1/?page=3`,
+			"Query-only URLs are appended as they are",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("baseUrl: absolute URLs are used as they are", async (t) => {
+	const server = await startServer();
+	try {
+		const agent = new Agent({ baseUrl: "https://api.example.invalid/v1" });
+		const response = await fetch(`${server.url}/direct`, { agent });
+		t.equal(response.url, `${server.url}/direct`, "Absolute URL");
+	} finally {
+		server.close();
+	}
+});
+
+test("baseUrl: relative URLs without a base are rejected", async (t) => {
+	const error = await rejection(fetch("/users"));
+	t.equal(error.code, ERROR_CODES.InvalidUrl, "InvalidUrl");
+});
+
+test("baseUrl: invalid base URLs throw", (t) => {
+	for (const baseUrl of ["not a url", "/v1", "ftp://example.com/"]) {
+		try {
+			new Agent({ baseUrl });
+			t.fail(`${baseUrl} should throw`);
+		} catch (error) {
+			t.equal(error.code, ERROR_CODES.Config, `${baseUrl} is a Config error`);
+		}
+	}
+	t.end();
+});
+
+test("params: placeholders are filled and encoded", async (t) => {
+	const server = await startServer();
+	try {
+		const agent = new Agent({ baseUrl: server.url });
+		await agent.fetch("/users/{id}/files/{name}", {
+			params: { id: 42, name: "a b/../c?d" },
+		});
+		t.equal(
+			server.requests()[0].path,
+			"/users/42/files/a%20b%2F..%2Fc%3Fd",
+			"Values are a single encoded segment",
+		);
+
+		await fetch(`${server.url}/{id}`, { params: { id: "x" } });
+		t.equal(server.requests()[1].path, "/x", "Works without a base URL");
+
+		await fetch(`${server.url}/{id}`);
+		t.equal(server.requests()[2].path, "/%7Bid%7D", "Placeholders are left alone without params");
+	} finally {
+		server.close();
+	}
+});
+
+test("params: missing values are rejected", async (t) => {
+	const error = await rejection(fetch("http://127.0.0.1/{id}", { params: { other: 1 } }));
+	t.equal(error.code, ERROR_CODES.InvalidUrl, "Missing parameter");
+
+	const unclosed = await rejection(fetch("http://127.0.0.1/{id", { params: { id: 1 } }));
+	t.equal(unclosed.code, ERROR_CODES.InvalidUrl, "Unclosed placeholder");
+});
@@ -0,0 +1,91 @@
+const test = require("tape");
+const { fetch, Agent } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+const agent = new Agent({ blockPrivateNetworks: true });
+
+async function forbidden(t, promise, message) {
+	try {
+		await promise;
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, "ForbiddenAddress", message);
+	}
+}
+
+test("blockPrivateNetworks: rejects private IP addresses", async (t) => {
+	t.plan(6);
+
+	for (const host of [
+		"127.0.0.1",
+		"10.1.2.3",
+		"169.254.169.254",
+		"[::1]",
+		"[fd00:ec2::254]",
+		"[::ffff:192.168.0.1]",
+	]) {
+		await forbidden(
+			t,
+			fetch(`http://${host}:1/`, { agent }),
+			`${host} should be forbidden`,
+		);
+	}
+});
+
+test("blockPrivateNetworks: rejects domains resolving to private addresses", async (t) => {
+	t.plan(2);
+
+	await forbidden(t, fetch("http://localhost:1/", { agent }), "localhost");
+
+	try {
+		await fetch(url("/get"), { agent });
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.name, "NetworkError", "should be a NetworkError");
+	}
+});
+
+test("blockPrivateNetworks: applies to connectTo", async (t) => {
+	t.plan(1);
+
+	await forbidden(
+		t,
+		fetch("http://example.com/", { agent, connectTo: "127.0.0.1:1" }),
+		"connectTo should be checked",
+	);
+});
+
+test("blockPrivateNetworks: applies to tls.serverName routes", async (t) => {
+	t.plan(1);
+
+	const named = new Agent({
+		blockPrivateNetworks: true,
+		tls: { serverName: "virtual.example.test" },
+	});
+	await forbidden(
+		t,
+		fetch("https://localhost:1/", { agent: named }),
+		"the URL's host should be resolved with the agent's resolver",
+	);
+});
+
+test("blockPrivateNetworks: dns overrides to private addresses throw", (t) => {
+	t.plan(1);
+
+	try {
+		new Agent({
+			blockPrivateNetworks: true,
+			dns: { overrides: [{ domain: "example.com", addresses: ["10.0.0.1"] }] },
+		});
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, "Config", "should throw a Config error");
+	}
+});
+
+test("blockPrivateNetworks: private networks are allowed by default", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/get"));
+	t.equal(response.status, 200, "should fetch from the local server");
+});
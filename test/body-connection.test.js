@@ -244,6 +244,30 @@ test("204 No Content response does not start a body", async (t) => {
 	t.equal(stats.bodiesFinished, 0, "bodiesFinished should be 0 for 204");
 });
 
+test("null body statuses have a null body", async (t) => {
+	t.plan(6);
+
+	const agent = new Agent();
+
+	for (const status of [204, 205, 304]) {
+		const response = await fetch(url(`/status/${status}`), { agent });
+		t.equal(response.status, status, `should be a ${status}`);
+		t.equal(response.body, null, `${status} should have a null body`);
+	}
+});
+
+test("HEAD response keeps its Content-Length", async (t) => {
+	t.plan(2);
+
+	const response = await fetch(url("/bytes/64"), { method: "HEAD" });
+	t.equal(response.body, null, "HEAD response should have null body");
+	t.equal(
+		response.headers.get("content-length"),
+		"64",
+		"Content-Length should be the size of the resource",
+	);
+});
+
 test("parallel requests with full consumption balance bodies", async (t) => {
 	t.plan(2);
 
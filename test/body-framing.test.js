@@ -0,0 +1,98 @@
+const test = require("tape");
+const { fetch, Agent, ERROR_CODES } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+const agent = new Agent({ unsafe: true });
+
+async function rejects(t, options, message) {
+	try {
+		await fetch(url("/post"), { agent, method: "POST", ...options });
+		t.fail(`${message}: should have thrown`);
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.InvalidHeader, message);
+	}
+}
+
+test("forceChunked: sends a buffered body chunked", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/post"), {
+		method: "POST",
+		body: "chunked hello",
+		forceChunked: true,
+	});
+	const data = await response.json();
+	t.equal(data.data, "chunked hello", "should send the whole body");
+});
+
+test("forceChunked: throws with http1.version10", async (t) => {
+	t.plan(1);
+
+	try {
+		await fetch(url("/post"), {
+			agent: new Agent({ http1: { version10: true } }),
+			method: "POST",
+			body: "hello",
+			forceChunked: true,
+		});
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.Config, "should be a Config error");
+	}
+});
+
+test("framing: a matching Content-Length is accepted", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/post"), {
+		agent,
+		method: "POST",
+		body: "hello",
+		headers: { "Content-Length": "5" },
+	});
+	t.equal((await response.json()).data, "hello", "should send the body");
+});
+
+test("framing: conflicting headers are rejected", async (t) => {
+	t.plan(4);
+
+	await rejects(
+		t,
+		{ body: "hello", headers: { "Content-Length": "3" } },
+		"mismatched Content-Length",
+	);
+	await rejects(
+		t,
+		{ body: "hello", headers: { "Transfer-Encoding": "chunked" } },
+		"Transfer-Encoding with a sized body",
+	);
+	await rejects(
+		t,
+		{
+			body: "hello",
+			forceChunked: true,
+			headers: { "Content-Length": "5" },
+		},
+		"Content-Length with a chunked body",
+	);
+	await rejects(
+		t,
+		{
+			body: "hello",
+			forceChunked: true,
+			headers: { "Content-Length": "5", "Transfer-Encoding": "chunked" },
+		},
+		"both Content-Length and Transfer-Encoding",
+	);
+});
+
+test("framing: forbidden headers are dropped without unsafe", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/post"), {
+		method: "POST",
+		body: "hello",
+		headers: { "Content-Length": "3" },
+	});
+	t.equal((await response.json()).data, "hello", "should send the body");
+});
@@ -0,0 +1,65 @@
+const test = require("tape");
+const { fetch } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("bodyStats: nothing is counted before the body is read", async (t) => {
+	const response = await fetch(url("/bytes/1024"));
+	const stats = response.bodyStats();
+
+	t.equal(stats.chunksDelivered, 0, "No chunks delivered");
+	t.equal(stats.bytesDelivered, 0, "No bytes delivered");
+	t.equal(stats.consumers, 0, "No consumers");
+	t.equal(stats.bufferedBytes, 0, "Nothing buffered");
+	t.equal(response.bodyUsed, false, "Body is not marked as used");
+
+	await response.discard();
+	t.end();
+});
+
+test("bodyStats: counts bytes read and buffered", async (t) => {
+	const response = await fetch(url("/bytes/1024"));
+	await response.bytes();
+	const stats = response.bodyStats();
+
+	t.ok(stats.chunksDelivered >= 1, "At least one chunk delivered");
+	t.equal(stats.bytesDelivered, 1024, "All bytes delivered");
+	t.equal(stats.consumers, 0, "No consumers left once read");
+	t.equal(stats.bufferedBytes, 1024, "Body is kept in memory");
+	t.end();
+});
+
+test("bodyStats: sums reads across clones", async (t) => {
+	const response = await fetch(url("/bytes/1024"));
+	const clone = response.clone();
+	await response.bytes();
+
+	t.equal(response.bodyStats().bytesDelivered, 1024, "Original read");
+	t.equal(clone.bodyStats().bufferedBytes, 1024, "Clone shares the buffer");
+
+	await clone.bytes();
+	const stats = response.bodyStats();
+	t.equal(stats.bytesDelivered, 2048, "Both reads are counted");
+	t.equal(stats.bufferedBytes, 1024, "Body is buffered once");
+	t.end();
+});
+
+test("bodyStats: counts open readers as consumers", async (t) => {
+	const response = await fetch(url("/bytes/1024"));
+	const chunks = response.chunks();
+	await chunks.next();
+
+	t.equal(response.bodyStats().consumers, 1, "Iterator is a consumer");
+
+	for await (const _ of chunks) {
+	}
+	t.end();
+});
+
+test("bodyStats: is empty for responses without a body", async (t) => {
+	const response = await fetch(url("/get"), { method: "HEAD" });
+	const stats = response.bodyStats();
+
+	t.equal(stats.bytesDelivered, 0, "No bytes delivered");
+	t.equal(stats.bufferedBytes, 0, "Nothing buffered");
+	t.end();
+});
@@ -1,5 +1,6 @@
+const http = require("node:http");
 const test = require("tape");
-const { fetch: faithFetch, Agent } = require("../wrapper.js");
+const { fetch: faithFetch, Agent, ERROR_CODES } = require("../wrapper.js");
 const { url } = require("./helpers.js");
 const fs = require("fs");
 const path = require("path");
@@ -53,18 +54,47 @@ test("fetch with cache: 'only-if-cached' without cached entry", async (t) => {
 	});
 
 	try {
-		const response = await faithFetch(url("/get"), {
+		await faithFetch(url("/get"), {
 			cache: "only-if-cached",
 			agent,
 		});
-		t.pass(
-			"only-if-cached completed (may succeed or fail based on cache state)",
-		);
+		t.fail("Should have thrown");
 	} catch (err) {
-		t.pass("only-if-cached threw error when no cache entry available");
+		t.equal(err.code, ERROR_CODES.NotCached, "NotCached error");
 	}
 });
 
+test("fetch with cache: 'only-if-cached' with cached entry", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({
+		cache: { store: "memory" },
+	});
+
+	await (await faithFetch(url("/cache/60"), { agent })).text();
+	const response = await faithFetch(url("/cache/60"), {
+		cache: "only-if-cached",
+		agent,
+	});
+	t.equal(response.status, 200, "Cached response is returned");
+	t.ok(await response.json(), "With its body");
+});
+
+test("fetch with cache: 'only-if-cached' without cache", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent();
+	const before = agent.stats().requestsSent;
+
+	try {
+		await faithFetch(url("/get"), { cache: "only-if-cached", agent });
+		t.fail("Should have thrown");
+	} catch (err) {
+		t.equal(err.code, ERROR_CODES.NotCached, "NotCached error");
+	}
+	t.equal(agent.stats().requestsSent, before, "No request was made");
+});
+
 test("fetch with cache: 'ignore-rules'", async (t) => {
 	t.plan(2);
 
@@ -667,3 +697,96 @@ test("Agent without cache option", async (t) => {
 	t.ok(response.ok, "Should successfully fetch without cache option");
 	t.equal(response.status, 200, "Status should be 200");
 });
+
+// Counts requests, answering with a cacheable response which echoes the URL and Accept-Language.
+async function startCountingServer(vary) {
+	const server = http.createServer((req, res) => {
+		server.hits++;
+		res.setHeader("Cache-Control", "max-age=60");
+		if (vary) {
+			res.setHeader("Vary", vary);
+		}
+		res.end(`${req.url} ${req.headers["accept-language"] ?? ""}`);
+	});
+	server.hits = 0;
+	await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+	return {
+		server,
+		url: (path) => `http://127.0.0.1:${server.address().port}${path}`,
+		close: () => new Promise((resolve) => server.close(resolve)),
+	};
+}
+
+test("cache.key normalises the query", async (t) => {
+	const { server, url, close } = await startCountingServer();
+	try {
+		const agent = new Agent({
+			cache: {
+				store: "memory",
+				key: { sortQuery: true, ignoreQuery: ["utm_source"] },
+			},
+		});
+
+		await (await faithFetch(url("/q?b=2&a=1&utm_source=x"), { agent })).text();
+		const response = await faithFetch(url("/q?a=1&b=2"), { agent });
+		t.equal(await response.text(), "/q?b=2&a=1&utm_source=x ", "Served from the cache");
+		t.equal(server.hits, 1, "Only one request reached the server");
+	} finally {
+		await close();
+	}
+	t.end();
+});
+
+test("cache.key.headers keeps a response per header value", async (t) => {
+	const { server, url, close } = await startCountingServer("Accept-Language");
+	try {
+		const agent = new Agent({
+			cache: { store: "memory", key: { headers: ["Accept-Language"] } },
+		});
+		const get = async (lang) =>
+			(
+				await faithFetch(url("/lang"), {
+					agent,
+					headers: { "Accept-Language": lang },
+				})
+			).text();
+
+		await get("fr");
+		await get("en");
+		t.equal(await get("fr"), "/lang fr", "French response is still cached");
+		t.equal(await get("en"), "/lang en", "English response is still cached");
+		t.equal(server.hits, 2, "One request per language");
+	} finally {
+		await close();
+	}
+	t.end();
+});
+
+test("cache.key.ignoreVaryStar caches Vary: * responses", async (t) => {
+	const { server, url, close } = await startCountingServer("*");
+	try {
+		const agent = new Agent({
+			cache: { store: "memory", key: { ignoreVaryStar: true } },
+		});
+
+		await (await faithFetch(url("/star"), { agent })).text();
+		const response = await faithFetch(url("/star"), { agent });
+		await response.text();
+		t.equal(server.hits, 1, "Second response served from the cache");
+		t.equal(response.headers.get("vary"), null, "Vary: * is removed");
+	} finally {
+		await close();
+	}
+	t.end();
+});
+
+test("cache.key.headers with an invalid header name throws", (t) => {
+	t.plan(1);
+
+	try {
+		new Agent({ cache: { store: "memory", key: { headers: ["not valid"] } } });
+		t.fail("Should have thrown");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.InvalidHeader, "InvalidHeader error");
+	}
+});
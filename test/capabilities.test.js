@@ -0,0 +1,58 @@
+const test = require("tape");
+const { fetch, capabilities } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("capabilities() lists the build's features as booleans", (t) => {
+	const caps = capabilities();
+	for (const name of [
+		"brotli",
+		"cookies",
+		"deflate",
+		"ech",
+		"gzip",
+		"http3",
+		"socketMark",
+		"socketTos",
+		"systemProxy",
+		"testing",
+		"zstd",
+	]) {
+		t.equal(typeof caps[name], "boolean", `${name} should be a boolean`);
+	}
+	t.end();
+});
+
+test("capabilities() lists the crypto providers", (t) => {
+	const caps = capabilities();
+	t.ok(caps.cryptoProviders.includes("ring"), "ring is always available");
+	t.ok(
+		caps.cryptoProviders.includes(caps.cryptoProvider),
+		"the default provider is available",
+	);
+	t.equal(
+		caps.cryptoProvider,
+		caps.cryptoProviders.includes("fips") ? "fips" : "ring",
+		"fips is the default in builds with it",
+	);
+	t.end();
+});
+
+test("capabilities() matches the platform", (t) => {
+	const caps = capabilities();
+	t.equal(caps.socketMark, process.platform === "linux", "socketMark");
+	t.equal(caps.socketTos, process.platform !== "win32", "socketTos");
+	t.end();
+});
+
+test("capabilities().gzip matches decompression", async (t) => {
+	const response = await fetch(url("/gzip"));
+	const body = await response.text();
+	let decoded = true;
+	try {
+		JSON.parse(body);
+	} catch {
+		decoded = false;
+	}
+	t.equal(decoded, capabilities().gzip, "gzip body is decoded iff enabled");
+	t.end();
+});
@@ -0,0 +1,132 @@
+const { createHash } = require("node:crypto");
+const http = require("node:http");
+const { gzipSync } = require("node:zlib");
+const test = require("tape");
+const { fetch, ERROR_CODES } = require("../wrapper.js");
+
+const MD5 = "XrY7u+Ae7tCTyyK7j1rNww==";
+const CRC32C = "yZRlqg==";
+const WRONG_MD5 = "AAAAAAAAAAAAAAAAAAAAAA==";
+
+// Serves "hello world" with the checksum headers given in the query string.
+async function startServer() {
+	const server = http.createServer((req, res) => {
+		const { searchParams } = new URL(req.url, "http://localhost");
+		for (const [name, value] of searchParams) {
+			res.setHeader(name, value);
+		}
+		res.end("hello world");
+	});
+	await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+	return {
+		url: (headers) =>
+			`http://127.0.0.1:${server.address().port}/?${new URLSearchParams(headers)}`,
+		close: () => new Promise((resolve) => server.close(resolve)),
+	};
+}
+
+test("checksums: matching checksums pass", async (t) => {
+	const server = await startServer();
+	try {
+		const md5 = await fetch(server.url({ "Content-MD5": MD5 }), {
+			checksums: ["content-md5"],
+		});
+		t.equal(await md5.text(), "hello world", "Content-MD5 matches");
+
+		const crc = await fetch(server.url({ "x-amz-checksum-crc32c": CRC32C }), {
+			checksums: ["crc32c"],
+		});
+		t.equal(await crc.text(), "hello world", "x-amz-checksum-crc32c matches");
+
+		const goog = await fetch(
+			server.url({ "x-goog-hash": `crc32c=${CRC32C},md5=${MD5}` }),
+			{ checksums: ["content-md5", "crc32c"] },
+		);
+		t.equal(await goog.text(), "hello world", "x-goog-hash matches");
+	} finally {
+		await server.close();
+	}
+	t.end();
+});
+
+test("checksums: a mismatch is an IntegrityMismatch error", async (t) => {
+	const server = await startServer();
+	try {
+		const response = await fetch(server.url({ "Content-MD5": WRONG_MD5 }), {
+			checksums: ["content-md5"],
+		});
+		try {
+			await response.text();
+			t.fail("Should have thrown");
+		} catch (error) {
+			t.equal(error.code, ERROR_CODES.IntegrityMismatch, "IntegrityMismatch error");
+		}
+
+		const chunked = await fetch(server.url({ "Content-MD5": WRONG_MD5 }), {
+			checksums: ["content-md5"],
+		});
+		try {
+			for await (const _ of chunked.chunks()) {
+			}
+			t.fail("Should have thrown");
+		} catch (error) {
+			t.equal(error.code, ERROR_CODES.IntegrityMismatch, "Also when reading chunks");
+		}
+	} finally {
+		await server.close();
+	}
+	t.end();
+});
+
+test("checksums: are only checked when asked for", async (t) => {
+	const server = await startServer();
+	try {
+		const unchecked = await fetch(server.url({ "Content-MD5": WRONG_MD5 }));
+		t.equal(await unchecked.text(), "hello world", "Not checked without the option");
+
+		const other = await fetch(server.url({ "Content-MD5": WRONG_MD5 }), {
+			checksums: ["crc32c"],
+		});
+		t.equal(await other.text(), "hello world", "Not checked for other algorithms");
+	} finally {
+		await server.close();
+	}
+	t.end();
+});
+
+test("checksums: are of the compressed body, which is still decompressed", async (t) => {
+	const gzipped = gzipSync("hello world");
+	const md5 = createHash("md5").update(gzipped).digest("base64");
+	const server = http.createServer((req, res) => {
+		const { searchParams } = new URL(req.url, "http://localhost");
+		res.setHeader("Content-Encoding", "gzip");
+		res.setHeader("Content-MD5", searchParams.get("md5"));
+		res.end(gzipped);
+	});
+	await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+	const base = `http://127.0.0.1:${server.address().port}/`;
+	try {
+		const response = await fetch(`${base}?md5=${encodeURIComponent(md5)}`, {
+			checksums: ["content-md5"],
+		});
+		t.equal(response.headers.get("content-encoding"), null, "Content-Encoding is removed");
+		t.equal(await response.text(), "hello world", "Body is decompressed");
+
+		const wrong = await fetch(`${base}?md5=${encodeURIComponent(MD5)}`, {
+			checksums: ["content-md5"],
+		});
+		try {
+			await wrong.text();
+			t.fail("Should have thrown");
+		} catch (error) {
+			t.equal(
+				error.code,
+				ERROR_CODES.IntegrityMismatch,
+				"The decompressed body's checksum doesn't match",
+			);
+		}
+	} finally {
+		await new Promise((resolve) => server.close(resolve));
+	}
+	t.end();
+});
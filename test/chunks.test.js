@@ -0,0 +1,58 @@
+const test = require("tape");
+const { fetch, ERROR_CODES } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("chunks: iterates over the whole body", async (t) => {
+	t.plan(3);
+
+	const response = await fetch(url("/bytes/20000"));
+	const chunks = [];
+	for await (const chunk of response.chunks()) {
+		chunks.push(chunk);
+	}
+
+	t.ok(
+		chunks.every((chunk) => Buffer.isBuffer(chunk)),
+		"should be Buffers",
+	);
+	t.equal(Buffer.concat(chunks).length, 20000, "should read the whole body");
+	t.ok(response.bodyUsed, "should mark the body as used");
+});
+
+test("chunks: is empty for responses without a body", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/status/204"));
+	const chunks = [];
+	for await (const chunk of response.chunks()) {
+		chunks.push(chunk);
+	}
+	t.equal(chunks.length, 0, "should have no chunks");
+});
+
+test("chunks: throws if the body was already used", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/get"));
+	await response.text();
+	try {
+		response.chunks();
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(
+			error.code,
+			ERROR_CODES.ResponseAlreadyDisturbed,
+			"should be ResponseAlreadyDisturbed",
+		);
+	}
+});
+
+test("chunks: stopping early leaves the rest unread", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/stream-bytes/50000?chunk_size=1000"));
+	for await (const chunk of response.chunks()) {
+		t.ok(chunk.length > 0, "should get a first chunk");
+		break;
+	}
+});
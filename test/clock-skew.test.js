@@ -0,0 +1,74 @@
+const test = require("tape");
+const { Agent } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+const HOUR = 3_600_000;
+
+function near(t, actual, expected, message) {
+	t.ok(Math.abs(actual - expected) <= 2000, `${message} (${actual} ≈ ${expected})`);
+}
+
+test("clockSkew: null unless enabled and sampled", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/" });
+
+		const disabled = new Agent();
+		await (await disabled.get(`${server.url}/`)).text();
+		t.equal(disabled.clockSkew(), null, "Disabled by default");
+
+		const agent = new Agent({ clockSkew: true });
+		t.equal(agent.clockSkew(), null, "No samples yet");
+		await (await agent.get(`${server.url}/`)).text();
+		const estimate = agent.clockSkew();
+		t.equal(estimate.samples, 1, "One sample");
+		near(t, estimate.offset, 0, "In sync with the local server");
+	} finally {
+		server.close();
+	}
+});
+
+test("clockSkew: servers ahead of the local clock", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/ahead",
+			headers: { Date: new Date(Date.now() + HOUR).toUTCString() },
+		});
+
+		const agent = new Agent({ clockSkew: true });
+		await (await agent.get(`${server.url}/ahead`)).text();
+		near(t, agent.clockSkew().offset, HOUR, "An hour ahead");
+	} finally {
+		server.close();
+	}
+});
+
+test("clockSkew: follows the mock clock", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/" });
+
+		const agent = new Agent({ clockSkew: true, mockClock: true });
+		agent.advanceClock(HOUR);
+		await (await agent.get(`${server.url}/`)).text();
+		near(t, agent.clockSkew().offset, -HOUR, "Servers are an hour behind");
+	} finally {
+		server.close();
+	}
+});
+
+test("clockSkew: responses from the cache are not sampled", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/cached", headers: { "Cache-Control": "max-age=60" }, body: "cached" });
+
+		const agent = new Agent({ clockSkew: true, cache: { store: "memory" } });
+		await (await agent.get(`${server.url}/cached`)).text();
+		await (await agent.get(`${server.url}/cached`)).text();
+		t.equal(server.requests().length, 1, "Second response is from the cache");
+		t.equal(agent.clockSkew().samples, 1, "Only sampled once");
+	} finally {
+		server.close();
+	}
+});
@@ -114,3 +114,43 @@ test("connections tracks multiple connections", async (t) => {
 
 	t.end();
 });
+
+test("onConnect is called for new connections", async (t) => {
+	const agent = new Agent();
+	const events = [];
+	agent.onConnect((event) => events.push(event));
+
+	const r1 = await fetch(`${HTTPBIN_URL}/get`, { agent });
+	await r1.text();
+	const r2 = await fetch(`${HTTPBIN_URL}/get`, { agent });
+	await r2.text();
+	await new Promise((resolve) => setTimeout(resolve, 50));
+
+	t.equal(events.length, 1, "called once for a reused connection");
+	const event = events[0];
+	t.equal(event.origin, new URL(HTTPBIN_URL).origin, "has the origin");
+	t.equal(event.protocol, "http/1.1", "has the protocol");
+	t.ok(event.localAddress, "has local address");
+	t.ok(typeof event.remotePort === "number", "has remote port");
+	t.equal(event.reuseCount, 0, "new connections have not been reused");
+	t.end();
+});
+
+test("onConnectionClosed is called when connections go idle", async (t) => {
+	const agent = new Agent({ pool: { idleTimeout: 1 } });
+	const closed = [];
+	agent.onConnectionClosed((event) => closed.push(event));
+
+	const r1 = await fetch(`${HTTPBIN_URL}/get`, { agent });
+	await r1.text();
+	const r2 = await fetch(`${HTTPBIN_URL}/get`, { agent });
+	await r2.text();
+
+	await new Promise((resolve) => setTimeout(resolve, 2500));
+
+	t.equal(closed.length, 1, "called once");
+	t.equal(closed[0].reuseCount, 1, "has the final reuse count");
+
+	agent.onConnectionClosed(null);
+	t.end();
+});
@@ -0,0 +1,62 @@
+const test = require("tape");
+const { fetch: faithFetch, Agent } = require("../wrapper.js");
+const { url, port } = require("./helpers.js");
+
+test("connectTo connects to the given address", async (t) => {
+	t.plan(3);
+
+	const testUrl = url("/headers").replace("localhost", "canary.tld");
+	const response = await faithFetch(testUrl, {
+		connectTo: `127.0.0.1:${port()}`,
+	});
+	t.ok(response.ok, "Should connect to the given address");
+
+	const data = await response.json();
+	const host = Array.isArray(data.headers.Host)
+		? data.headers.Host[0]
+		: data.headers.Host;
+	t.ok(host.startsWith("canary.tld"), "Host header should be the URL's host");
+	t.equal(
+		new URL(response.url).hostname,
+		"canary.tld",
+		"Response URL should be the requested URL",
+	);
+});
+
+test("connectTo works with an agent and a version pin", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent();
+	const testUrl = url("/get").replace("localhost", "canary.tld");
+	const response = await faithFetch(testUrl, {
+		agent,
+		connectTo: "127.0.0.1",
+		version: "http/1.1",
+	});
+	t.ok(response.ok, "Should connect to the given address");
+	t.equal(response.version, "HTTP/1.1", "Version should be HTTP/1.1");
+});
+
+test("connectTo rejects invalid addresses", async (t) => {
+	t.plan(1);
+
+	try {
+		await faithFetch(url("/get"), { connectTo: "not an address" });
+		t.fail("Should have thrown");
+	} catch (err) {
+		t.equal(err.code, "AddressParse", "Should throw an AddressParse error");
+	}
+});
+
+test("connectTo rejects URLs with an IP address host", async (t) => {
+	t.plan(1);
+
+	try {
+		await faithFetch(`http://127.0.0.1:${port()}/get`, {
+			connectTo: "127.0.0.1",
+		});
+		t.fail("Should have thrown");
+	} catch (err) {
+		t.equal(err.code, "Config", "Should throw a Config error");
+	}
+});
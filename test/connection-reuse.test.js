@@ -890,3 +890,61 @@ test("auto-drain works with multiple dropped responses", async (t) => {
 		await tracker.close();
 	}
 });
+
+test("pool.maxConnectionAge rotates connections", async (t) => {
+	t.plan(3);
+
+	const tracker = createConnectionTracker();
+	await tracker.listen();
+
+	try {
+		const agent = new Agent({ pool: { maxConnectionAge: 1 } });
+
+		const r1 = await fetch(tracker.url("/get"), { agent });
+		await r1.text();
+
+		const r2 = await fetch(tracker.url("/get"), { agent });
+		await r2.text();
+
+		t.equal(
+			tracker.stats().totalConnections,
+			1,
+			"should reuse the connection before the max age",
+		);
+
+		await new Promise((resolve) => setTimeout(resolve, 1500));
+
+		const r3 = await fetch(tracker.url("/get"), { agent });
+		await r3.text();
+
+		const stats = tracker.stats();
+		t.equal(stats.totalConnections, 2, "should open a new connection after the max age");
+		t.equal(stats.totalRequests, 3, "should have made 3 requests");
+	} finally {
+		await tracker.close();
+	}
+});
+
+test("pool.maxPerHost queues requests over the limit", async (t) => {
+	t.plan(2);
+
+	const tracker = createConnectionTracker();
+	await tracker.listen();
+
+	try {
+		const agent = new Agent({ pool: { maxPerHost: 1 } });
+
+		await Promise.all(
+			[1, 2, 3].map(async () => {
+				const response = await fetch(tracker.url("/get"), { agent });
+				await response.text();
+			}),
+		);
+
+		const stats = tracker.stats();
+		t.equal(stats.totalConnections, 1, "should only open a single connection");
+		t.equal(stats.totalRequests, 3, "should have made 3 requests");
+	} finally {
+		await tracker.close();
+	}
+});
@@ -0,0 +1,87 @@
+const test = require("tape");
+const { setTimeout: sleep } = require("node:timers/promises");
+const { Agent, ERROR_CODES } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+test("onCookieSet: called for cookies set by responses", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/account/login",
+			headers: { "Set-Cookie": "session=abc123; HttpOnly; SameSite=Lax; Max-Age=3600" },
+		});
+		server.route({ path: "/theme", headers: { "Set-Cookie": "theme=dark; Path=/" } });
+
+		const agent = new Agent({ cookies: true });
+		const events = [];
+		agent.onCookieSet((event) => events.push(event));
+
+		const before = Date.now();
+		await (await agent.get(`${server.url}/account/login`)).text();
+		await (await agent.get(`${server.url}/theme`)).text();
+		await sleep(50);
+
+		t.equal(events.length, 2, "One event per cookie");
+		const [session, theme] = events;
+		t.equal(session.url, `${server.url}/account/login`, "Source URL");
+		t.equal(session.name, "session", "Name");
+		t.equal(session.value, "abc123", "Value");
+		t.equal(session.path, "/account", "Default path");
+		t.equal(session.domain, undefined, "Host-only");
+		t.ok(session.httpOnly, "HttpOnly");
+		t.notOk(session.secure, "Not Secure");
+		t.equal(session.sameSite, "Lax", "SameSite");
+		t.ok(session.expires >= before + 3_599_000, "Expiry from Max-Age");
+		t.notOk(session.removed, "Stored");
+
+		t.equal(theme.path, "/", "Path attribute");
+		t.equal(theme.expires, undefined, "Session cookie");
+	} finally {
+		server.close();
+	}
+});
+
+test("onCookieSet: removals, rejections, and unsetting the callback", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/set", headers: { "Set-Cookie": "id=1; Path=/" } });
+		server.route({ path: "/clear", headers: { "Set-Cookie": "id=; Path=/; Max-Age=0" } });
+		server.route({ path: "/other", headers: { "Set-Cookie": "id=2; Domain=example.com" } });
+
+		const agent = new Agent({ cookies: true });
+		const events = [];
+		agent.onCookieSet((event) => events.push(event));
+
+		await (await agent.get(`${server.url}/set`)).text();
+		await (await agent.get(`${server.url}/clear`)).text();
+		await (await agent.get(`${server.url}/other`)).text();
+		await sleep(50);
+
+		t.deepEqual(
+			events.map(({ name, removed }) => [name, removed]),
+			[
+				["id", false],
+				["id", true],
+			],
+			"Removal reported, other domain skipped",
+		);
+		t.equal(agent.getCookie(`${server.url}/`), null, "Removed from the jar");
+
+		agent.onCookieSet(null);
+		await (await agent.get(`${server.url}/set`)).text();
+		await sleep(50);
+		t.equal(events.length, 2, "No more events");
+	} finally {
+		server.close();
+	}
+});
+
+test("onCookieSet: needs cookies", (t) => {
+	try {
+		new Agent().onCookieSet(() => {});
+		t.fail("should throw");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.Config, "Config error");
+	}
+	t.end();
+});
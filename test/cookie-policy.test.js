@@ -0,0 +1,159 @@
+const test = require("tape");
+const { setTimeout: sleep } = require("node:timers/promises");
+const { Agent, ERROR_CODES } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+function hostsAgent(server, hosts, options = {}) {
+	const { port } = new URL(server.url);
+	return new Agent({
+		cookies: true,
+		dns: {
+			overrides: hosts.map((domain) => ({ domain, addresses: [`127.0.0.1:${port}`] })),
+		},
+		...options,
+	});
+}
+
+function header(request, name) {
+	return request.headers.find(([key]) => key === name)?.[1];
+}
+
+test("cookiePolicy: blockThirdParty", async (t) => {
+	const server = await startServer();
+	const { port } = new URL(server.url);
+	const at = (host) => `http://${host}:${port}/`;
+	try {
+		server.route({ path: "/", headers: { "Set-Cookie": "id=1" } });
+
+		const agent = hostsAgent(server, ["www.example.com", "tracker.test"], {
+			cookiePolicy: { blockThirdParty: true, topLevelSite: "https://example.com" },
+		});
+		await (await agent.get(at("www.example.com"))).text();
+		await (await agent.get(at("tracker.test"))).text();
+		t.equal(agent.getCookie(at("www.example.com")), "id=1", "Same-site cookie stored");
+		t.equal(agent.getCookie(at("tracker.test")), null, "Third-party cookie not stored");
+
+		agent.addCookie(at("tracker.test"), "id=2");
+		await (await agent.get(at("tracker.test"))).text();
+		await (await agent.get(at("tracker.test"), { topLevelSite: "tracker.test" })).text();
+		const [, , third, fourth] = server.requests();
+		t.equal(header(third, "cookie"), undefined, "Not sent cross-site");
+		t.equal(header(fourth, "cookie"), "id=2", "Sent with a per-request top-level site");
+	} finally {
+		server.close();
+	}
+});
+
+test("cookiePolicy: SameSite cookies stay same-site", async (t) => {
+	const server = await startServer();
+	const { port } = new URL(server.url);
+	const at = (host) => `http://${host}:${port}/`;
+	try {
+		server.route({ path: "/lax", headers: { "Set-Cookie": "lax=1; SameSite=Lax" } });
+		server.route({ path: "/none", headers: { "Set-Cookie": "none=1" } });
+		server.route({ path: "/" });
+
+		const agent = hostsAgent(server, ["tracker.test"], {
+			cookiePolicy: { topLevelSite: "example.com" },
+		});
+		await (await agent.get(`${at("tracker.test")}lax`)).text();
+		await (await agent.get(`${at("tracker.test")}none`)).text();
+		t.equal(agent.getCookie(at("tracker.test")), "none=1", "Cross-site responses can't set Lax");
+
+		await (await agent.get(`${at("tracker.test")}lax`, { topLevelSite: "tracker.test" })).text();
+		await (await agent.get(at("tracker.test"))).text();
+		await (await agent.get(at("tracker.test"), { topLevelSite: "tracker.test" })).text();
+		const requests = server.requests();
+		t.equal(header(requests[3], "cookie"), "none=1", "Lax cookie not sent cross-site");
+		t.deepEqual(
+			header(requests[4], "cookie").split("; ").sort(),
+			["lax=1", "none=1"],
+			"Sent same-site",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("cookiePolicy: secureOnly", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/plain", headers: { "Set-Cookie": "plain=1" } });
+		server.route({ path: "/secure", headers: { "Set-Cookie": "secure=1; Secure" } });
+
+		const agent = new Agent({ cookies: true, cookiePolicy: { secureOnly: true } });
+		await (await agent.get(`${server.url}/plain`)).text();
+		await (await agent.get(`${server.url}/secure`)).text();
+		t.equal(agent.getCookie(`${server.url}/`), null, "Nothing stored over http");
+	} finally {
+		server.close();
+	}
+});
+
+test("setCookieFilter: vetoes cookies", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/session", headers: { "Set-Cookie": "session=abc; Path=/" } });
+		server.route({ path: "/tracking", headers: { "Set-Cookie": "_ga=GA1.2; Path=/" } });
+
+		const agent = new Agent({ cookies: true });
+		const seen = [];
+		agent.setCookieFilter((cookie) => {
+			seen.push(cookie.name);
+			return !cookie.name.startsWith("_ga");
+		});
+		await (await agent.get(`${server.url}/session`)).text();
+		await (await agent.get(`${server.url}/tracking`)).text();
+		await sleep(50);
+
+		t.deepEqual(seen, ["session", "_ga"], "Called for each cookie");
+		t.equal(agent.getCookie(`${server.url}/`), "session=abc", "Vetoed cookie not stored");
+
+		agent.setCookieFilter(null);
+		await (await agent.get(`${server.url}/tracking`)).text();
+		t.deepEqual(
+			agent.getCookie(`${server.url}/`).split("; ").sort(),
+			["_ga=GA1.2", "session=abc"],
+			"Filter removed",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("setCookieFilter: allowed cookies are sent on redirect", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/login",
+			status: 302,
+			headers: { "Set-Cookie": "session=abc; Path=/", Location: "/home" },
+		});
+		server.route({ path: "/home", body: "home" });
+
+		const agent = new Agent({ cookies: true });
+		agent.setCookieFilter(() => true);
+		const response = await agent.get(`${server.url}/login`);
+		await response.text();
+
+		t.equal(response.status, 200, "Redirect followed");
+		t.equal(
+			agent.getCookie(`${server.url}/`),
+			"session=abc",
+			"Stored by the time the request resolves",
+		);
+		t.equal(header(server.requests()[1], "cookie"), "session=abc", "Sent on redirect");
+	} finally {
+		server.close();
+	}
+});
+
+test("setCookieFilter: needs cookies", (t) => {
+	try {
+		new Agent().setCookieFilter(() => true);
+		t.fail("should throw");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.Config, "Config error");
+	}
+	t.end();
+});
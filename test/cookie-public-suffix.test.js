@@ -0,0 +1,74 @@
+const test = require("tape");
+const { Agent } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+function hostsAgent(server, hosts, options = {}) {
+	const { port } = new URL(server.url);
+	return new Agent({
+		cookies: true,
+		dns: {
+			overrides: hosts.map((domain) => ({ domain, addresses: [`127.0.0.1:${port}`] })),
+		},
+		...options,
+	});
+}
+
+test("cookies: can't be set for a public suffix", async (t) => {
+	const server = await startServer();
+	const { port } = new URL(server.url);
+	const at = (host) => `http://${host}:${port}/`;
+	try {
+		server.route({ path: "/suffix", headers: { "Set-Cookie": "fixed=1; Domain=co.uk" } });
+		server.route({ path: "/site", headers: { "Set-Cookie": "site=1; Domain=example.co.uk" } });
+
+		const agent = hostsAgent(server, ["shop.example.co.uk", "other.co.uk"]);
+		await (await agent.get(`${at("shop.example.co.uk")}suffix`)).text();
+		await (await agent.get(`${at("shop.example.co.uk")}site`)).text();
+
+		t.equal(agent.getCookie(at("other.co.uk")), null, "Not sent to other sites under co.uk");
+		t.equal(agent.getCookie(at("shop.example.co.uk")), "site=1", "Registrable domain is fine");
+
+		agent.addCookie(at("shop.example.co.uk"), "added=1; Domain=co.uk");
+		t.equal(agent.getCookie(at("other.co.uk")), null, "addCookie() is checked too");
+	} finally {
+		server.close();
+	}
+});
+
+test("cookies: a public suffix host keeps its cookies to itself", async (t) => {
+	const server = await startServer();
+	const { port } = new URL(server.url);
+	const at = (host) => `http://${host}:${port}/`;
+	try {
+		server.route({ path: "/", headers: { "Set-Cookie": "own=1; Domain=github.io" } });
+
+		const agent = hostsAgent(server, ["github.io", "user.github.io"]);
+		await (await agent.get(at("github.io"))).text();
+		t.equal(agent.getCookie(at("github.io")), "own=1", "Host-only cookie");
+		t.equal(agent.getCookie(at("user.github.io")), null, "Not sent to subdomains");
+	} finally {
+		server.close();
+	}
+});
+
+test("cookies: publicSuffixList replaces the bundled list", async (t) => {
+	const server = await startServer();
+	const { port } = new URL(server.url);
+	const at = (host) => `http://${host}:${port}/`;
+	try {
+		server.route({ path: "/", headers: { "Set-Cookie": "shared=1; Domain=example.test" } });
+		const hosts = ["a.example.test", "b.example.test"];
+
+		const bundled = hostsAgent(server, hosts);
+		await (await bundled.get(at("a.example.test"))).text();
+		t.equal(bundled.getCookie(at("b.example.test")), "shared=1", "Not a suffix by default");
+
+		const custom = hostsAgent(server, hosts, {
+			publicSuffixList: "// ===BEGIN PRIVATE DOMAINS===\nexample.test\n// ===END PRIVATE DOMAINS===\n",
+		});
+		await (await custom.get(at("a.example.test"))).text();
+		t.equal(custom.getCookie(at("b.example.test")), null, "A suffix in the custom list");
+	} finally {
+		server.close();
+	}
+});
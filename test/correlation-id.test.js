@@ -0,0 +1,80 @@
+const test = require("tape");
+const { Agent, ERROR_CODES, fetch, withCorrelationId } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+async function rejection(promise) {
+	try {
+		await promise;
+	} catch (error) {
+		return error;
+	}
+	throw new Error("expected a rejection");
+}
+
+test("correlationId: kept on the response", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/" });
+		const response = await fetch(`${server.url}/`, { correlationId: "req-1" });
+		t.equal(response.correlationId, "req-1", "Response property");
+		t.equal(response.toJSON().correlationId, "req-1", "In the snapshot");
+
+		t.equal((await fetch(`${server.url}/`)).correlationId, null, "null without one");
+	} finally {
+		server.close();
+	}
+});
+
+test("correlationId: set on errors", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/error", status: 500 });
+		server.route({ path: "/text", body: "not json" });
+		server.route({ path: "/slow", delay: 500 });
+
+		const invalid = await rejection(fetch("not a url", { correlationId: "req-2" }));
+		t.equal(invalid.code, ERROR_CODES.InvalidUrl, "InvalidUrl");
+		t.equal(invalid.correlationId, "req-2", "Validation errors");
+
+		const status = await rejection(
+			fetch(`${server.url}/error`, { correlationId: "req-3", throwOnError: true }),
+		);
+		t.equal(status.correlationId, "req-3", "HttpStatus errors");
+
+		const response = await fetch(`${server.url}/text`, { correlationId: "req-4" });
+		const parse = await rejection(response.json());
+		t.equal(parse.code, ERROR_CODES.JsonParse, "JsonParse");
+		t.equal(parse.correlationId, "req-4", "Body errors");
+
+		const controller = new AbortController();
+		setTimeout(() => controller.abort(), 50);
+		const aborted = await rejection(
+			fetch(`${server.url}/slow`, { correlationId: "req-5", signal: controller.signal }),
+		);
+		t.equal(aborted.name, "AbortError", "AbortError");
+		t.equal(aborted.correlationId, "req-5", "Kept on abort errors");
+	} finally {
+		server.close();
+	}
+});
+
+test("correlationId: withCorrelationId() applies to requests in its scope", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/" });
+		const agent = new Agent();
+
+		const ids = await withCorrelationId("outer", async () => {
+			const first = await agent.get(`${server.url}/`);
+			await new Promise((resolve) => setTimeout(resolve, 10));
+			const second = await fetch(`${server.url}/`);
+			const inner = await withCorrelationId("inner", () => fetch(`${server.url}/`));
+			const explicit = await fetch(`${server.url}/`, { correlationId: "explicit" });
+			return [first, second, inner, explicit].map((response) => response.correlationId);
+		});
+		t.deepEqual(ids, ["outer", "outer", "inner", "explicit"], "Scoped IDs");
+		t.equal((await fetch(`${server.url}/`)).correlationId, null, "Not outside the scope");
+	} finally {
+		server.close();
+	}
+});
@@ -1,6 +1,9 @@
 const { url } = require("./helpers.js");
 const test = require("tape");
-const { fetch } = require("../wrapper.js");
+const { Agent, fetch } = require("../wrapper.js");
+
+// Cookie is a forbidden request header, so sending it needs an unsafe agent
+const agent = new Agent({ unsafe: true });
 
 // Helper to get cookies from response (go-httpbin returns cookies at root level)
 function getCookies(data) {
@@ -95,6 +98,7 @@ test("credentials: 'omit' filters Cookie header from request", async (t) => {
 
 	try {
 		const response = await fetch(url("/cookies"), {
+			agent,
 			credentials: "omit",
 			headers: {
 				Cookie: "test=value; session=abc123; token=xyz",
@@ -118,6 +122,7 @@ test("credentials: 'include' preserves Cookie header in request", async (t) => {
 
 	try {
 		const response = await fetch(url("/cookies"), {
+			agent,
 			credentials: "include",
 			headers: {
 				Cookie: "test=value; another=data",
@@ -138,6 +143,7 @@ test("credentials: default (include) preserves Cookie header", async (t) => {
 
 	try {
 		const response = await fetch(url("/cookies"), {
+			agent,
 			headers: {
 				Cookie: "defaulttest=defaultvalue",
 			},
@@ -252,6 +258,7 @@ test("credentials: 'omit' with POST request", async (t) => {
 	try {
 		const response = await fetch(url("/post"), {
 			method: "POST",
+			agent,
 			credentials: "omit",
 			headers: {
 				"Content-Type": "application/json",
@@ -274,6 +281,7 @@ test("credentials: 'omit' is case-insensitive for Cookie header", async (t) => {
 
 	try {
 		const response = await fetch(url("/cookies"), {
+			agent,
 			credentials: "omit",
 			headers: {
 				COOKIE: "uppercase=test",
@@ -305,7 +313,7 @@ test("credentials: works with Request object", async (t) => {
 			},
 		});
 
-		const response = await fetch(request);
+		const response = await fetch(request, { agent });
 		const data = await response.json();
 		const cookies = getCookies(data);
 		t.equal(
@@ -330,6 +338,7 @@ test("credentials: options override Request object", async (t) => {
 		});
 
 		const response = await fetch(request, {
+			agent,
 			credentials: "include",
 		});
 
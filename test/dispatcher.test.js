@@ -0,0 +1,183 @@
+const test = require("tape");
+const { Agent, FaithDispatcher } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+/**
+ * Dispatch a request with a legacy undici handler, recording the calls made to it
+ * @param {FaithDispatcher} dispatcher
+ * @param {object} options
+ * @param {{ pause?: boolean }} [behaviour]
+ * @returns {Promise<{ calls: string[], status?: number, headers?: Buffer[], body: string, error?: any }>}
+ */
+function dispatchLegacy(dispatcher, options, behaviour) {
+	return new Promise((resolve) => {
+		const result = { calls: [], body: "" };
+		dispatcher.dispatch(options, {
+			onConnect() {
+				result.calls.push("connect");
+			},
+			onHeaders(status, headers, resume) {
+				result.calls.push("headers");
+				result.status = status;
+				result.headers = headers;
+				if (behaviour?.pause) {
+					setTimeout(() => {
+						result.calls.push("resume");
+						resume();
+					}, 50);
+					return false;
+				}
+				return true;
+			},
+			onData(chunk) {
+				result.calls.push("data");
+				result.body += chunk.toString();
+				return true;
+			},
+			onComplete() {
+				result.calls.push("complete");
+				resolve(result);
+			},
+			onError(error) {
+				result.calls.push("error");
+				result.error = error;
+				resolve(result);
+			},
+		});
+	});
+}
+
+test("FaithDispatcher: Node's fetch through the agent", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/",
+			headers: { "Content-Type": "text/plain" },
+			body: "hello",
+		});
+		server.route({ path: "/echo", method: "POST", body: "posted" });
+
+		const agent = new Agent({
+			headers: [{ name: "X-Agent", value: "dispatcher" }],
+		});
+		const dispatcher = new FaithDispatcher({ agent });
+
+		const response = await fetch(`${server.url}/?a=1`, { dispatcher });
+		t.equal(response.status, 200, "Status");
+		t.equal(response.headers.get("content-type"), "text/plain", "Headers");
+		t.equal(await response.text(), "hello", "Body");
+		const request = server.requests().at(-1);
+		t.equal(request.path, "/?a=1", "Path and query");
+		t.equal(
+			Object.fromEntries(request.headers)["x-agent"],
+			"dispatcher",
+			"Agent's default headers",
+		);
+		t.equal(agent.stats().requestsSent, 1, "Sent by the agent");
+
+		const posted = await fetch(`${server.url}/echo`, {
+			dispatcher,
+			method: "POST",
+			body: "request body",
+		});
+		t.equal(await posted.text(), "posted", "POST response");
+		t.equal(server.requests().at(-1).body, "request body", "Request body");
+
+		await dispatcher.close();
+		t.ok(dispatcher.closed, "Closed");
+	} finally {
+		server.close();
+	}
+});
+
+test("FaithDispatcher: legacy handlers", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/",
+			headers: { "X-Test": "yes" },
+			body: "abc",
+		});
+
+		const dispatcher = new FaithDispatcher();
+		const result = await dispatchLegacy(
+			dispatcher,
+			{ origin: server.url, path: "/", method: "GET", headers: ["X-Flat", "1"] },
+			{ pause: true },
+		);
+		t.equal(result.status, 200, "Status");
+		t.equal(result.body, "abc", "Body");
+		t.deepEqual(
+			[result.calls[0], result.calls[1], result.calls[2], result.calls.at(-1)],
+			["connect", "headers", "resume", "complete"],
+			"Paused until resumed",
+		);
+		const headers = result.headers.map((item) => item.toString());
+		t.equal(headers[headers.indexOf("x-test") + 1], "yes", "Raw headers");
+		t.equal(
+			Object.fromEntries(server.requests().at(-1).headers)["x-flat"],
+			"1",
+			"Flat request headers",
+		);
+
+		await dispatcher.close();
+		const closed = await dispatchLegacy(dispatcher, {
+			origin: server.url,
+			path: "/",
+			method: "GET",
+		});
+		t.deepEqual(closed.calls, ["connect", "error"], "Fails once closed");
+	} finally {
+		server.close();
+	}
+});
+
+test("FaithDispatcher: newer handlers and destroy()", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/", body: "abc" });
+		server.route({ path: "/slow", delay: 2000 });
+
+		const dispatcher = new FaithDispatcher();
+		const result = await new Promise((resolve) => {
+			const result = { body: "" };
+			dispatcher.dispatch(
+				{ origin: new URL(server.url), path: "/", method: "GET" },
+				{
+					onRequestStart(controller) {
+						result.controller = typeof controller.abort;
+					},
+					onResponseStart(_controller, status, headers) {
+						result.status = status;
+						result.headers = headers;
+					},
+					onResponseData(_controller, chunk) {
+						result.body += chunk.toString();
+					},
+					onResponseEnd() {
+						resolve(result);
+					},
+					onResponseError(_controller, error) {
+						resolve({ error });
+					},
+				},
+			);
+		});
+		t.equal(result.controller, "function", "Controller");
+		t.equal(result.status, 200, "Status");
+		t.equal(typeof result.headers, "object", "Headers as an object");
+		t.equal(result.body, "abc", "Body");
+
+		const pending = dispatchLegacy(dispatcher, {
+			origin: server.url,
+			path: "/slow",
+			method: "GET",
+		});
+		setTimeout(() => dispatcher.destroy(new Error("going away")), 50);
+		const destroyed = await pending;
+		t.equal(destroyed.error?.message, "going away", "Aborted with the error");
+		t.ok(dispatcher.destroyed, "Destroyed");
+	} finally {
+		server.close();
+	}
+});
@@ -0,0 +1,96 @@
+const test = require("tape");
+const {
+	ERROR_CODES,
+	FaithAbortError,
+	FaithError,
+	FaithHttpError,
+	FaithNetworkError,
+	FaithSyntaxError,
+	FaithTimeoutError,
+	FaithTypeError,
+	fetch,
+} = require("../wrapper.js");
+const native = require("../index.js");
+const { url } = require("./helpers.js");
+
+async function rejection(promise) {
+	try {
+		await promise;
+	} catch (error) {
+		return error;
+	}
+	throw new Error("expected a rejection");
+}
+
+test("error classes are exported by the addon and the wrapper", (t) => {
+	for (const name of [
+		"FaithAbortError",
+		"FaithError",
+		"FaithNetworkError",
+		"FaithSyntaxError",
+		"FaithTimeoutError",
+		"FaithTypeError",
+	]) {
+		t.equal(typeof native[name], "function", `index.js exports ${name}`);
+	}
+	t.equal(FaithError, native.FaithError, "wrapper re-exports the same classes");
+	t.ok(FaithAbortError.prototype instanceof FaithError, "FaithAbortError extends FaithError");
+	t.ok(FaithHttpError.prototype instanceof FaithError, "FaithHttpError extends FaithError");
+	t.ok(FaithTypeError.prototype instanceof TypeError, "FaithTypeError extends TypeError");
+	t.ok(FaithSyntaxError.prototype instanceof SyntaxError, "FaithSyntaxError extends SyntaxError");
+	t.end();
+});
+
+test("error classes keep fetch's names and take a code", (t) => {
+	const error = new FaithTimeoutError("slow", { code: ERROR_CODES.Timeout, cause: "test" });
+	t.equal(error.name, "TimeoutError", "name");
+	t.equal(error.code, "Timeout", "code");
+	t.equal(error.cause, "test", "cause");
+	t.ok(error instanceof FaithError, "instanceof FaithError");
+	t.end();
+});
+
+test("invalid methods throw a FaithTypeError", async (t) => {
+	const error = await rejection(fetch(url("/get"), { method: "INV@LID-METHOD!" }));
+	t.ok(error instanceof FaithTypeError, "instanceof FaithTypeError");
+	t.ok(error instanceof TypeError, "still a TypeError");
+	t.equal(error.code, ERROR_CODES.InvalidMethod, "code");
+});
+
+test("timeouts throw a FaithTimeoutError", async (t) => {
+	const error = await rejection(fetch(url("/delay/2"), { timeout: 200 }));
+	t.ok(error instanceof FaithTimeoutError, "instanceof FaithTimeoutError");
+	t.equal(error.name, "TimeoutError", "name");
+	t.equal(error.code, ERROR_CODES.Timeout, "code");
+});
+
+test("aborts throw a FaithAbortError", async (t) => {
+	const controller = new AbortController();
+	const promise = fetch(url("/delay/2"), { signal: controller.signal });
+	controller.abort();
+	const error = await rejection(promise);
+	t.ok(error instanceof FaithAbortError, "instanceof FaithAbortError");
+	t.equal(error.name, "AbortError", "name");
+	t.equal(error.code, ERROR_CODES.Aborted, "code");
+});
+
+test("connection failures throw a FaithNetworkError", async (t) => {
+	const error = await rejection(fetch("http://127.0.0.1:1/"));
+	t.ok(error instanceof FaithNetworkError, "instanceof FaithNetworkError");
+	t.equal(error.name, "NetworkError", "name");
+	t.equal(error.code, ERROR_CODES.Network, "code");
+});
+
+test("invalid JSON throws a FaithSyntaxError", async (t) => {
+	const response = await fetch(url("/html"));
+	const error = await rejection(response.json());
+	t.ok(error instanceof FaithSyntaxError, "instanceof FaithSyntaxError");
+	t.ok(error instanceof SyntaxError, "still a SyntaxError");
+	t.equal(error.code, ERROR_CODES.JsonParse, "code");
+});
+
+test("unresolvable hosts throw a FaithNetworkError with the Dns code", async (t) => {
+	const error = await rejection(fetch("http://example.invalid/"));
+	t.ok(error instanceof FaithNetworkError, "instanceof FaithNetworkError");
+	t.equal(error.code, ERROR_CODES.Dns, "code");
+});
@@ -4,7 +4,7 @@ const path = require("path");
 const native = require("../index.js");
 
 test("wrapper.d.ts ERROR_CODES definition is in sync", (t) => {
-  const nativeErrorCodes = native.errorCodes();
+  const nativeErrorCodes = native.errorCodes().map(({ code }) => code);
 
   // Read the wrapper.d.ts file
   const dtsPath = path.join(__dirname, "..", "wrapper.d.ts");
@@ -62,3 +62,27 @@ test("wrapper.d.ts ERROR_CODES definition is in sync", (t) => {
 
   t.end();
 });
+
+test("errorCodes() describes each code", (t) => {
+  const codes = native.errorCodes();
+  t.ok(codes.length > 0, "should list codes");
+
+  for (const { code, jsErrorName, retriable, defaultMessage } of codes) {
+    t.equal(typeof code, "string", `${code} has a code`);
+    t.ok(
+      ["AbortError", "Error", "NetworkError", "SyntaxError", "TimeoutError", "TypeError"].includes(jsErrorName),
+      `${code} has a known jsErrorName: ${jsErrorName}`,
+    );
+    t.equal(typeof retriable, "boolean", `${code} says whether it's retriable`);
+    t.ok(defaultMessage.length > 0, `${code} has a default message`);
+  }
+
+  const byCode = Object.fromEntries(codes.map((entry) => [entry.code, entry]));
+  t.equal(byCode.Timeout.jsErrorName, "TimeoutError", "Timeout is a TimeoutError");
+  t.ok(byCode.Timeout.retriable, "Timeout is retriable");
+  t.ok(byCode.Network.retriable, "Network is retriable");
+  t.notOk(byCode.InvalidUrl.retriable, "InvalidUrl is not retriable");
+  t.equal(byCode.InvalidUrl.defaultMessage, "invalid URL", "InvalidUrl's default message");
+
+  t.end();
+});
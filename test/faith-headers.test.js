@@ -0,0 +1,74 @@
+const test = require("tape");
+const { fetch: faithFetch, FaithHeaders } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("FaithHeaders matches names case-insensitively", async (t) => {
+	t.plan(3);
+
+	const headers = new FaithHeaders({ "Content-Type": "text/plain" });
+	t.ok(headers.has("content-type"), "has() should ignore case");
+	t.equal(headers.get("CONTENT-TYPE"), "text/plain", "get() should ignore case");
+	headers.delete("Content-type");
+	t.notOk(headers.has("content-type"), "delete() should ignore case");
+});
+
+test("FaithHeaders combines values except Set-Cookie", async (t) => {
+	t.plan(3);
+
+	const headers = new FaithHeaders([
+		["Accept", "text/html"],
+		["accept", "application/json"],
+		["Set-Cookie", "a=1"],
+		["Set-Cookie", "b=2"],
+	]);
+	t.equal(headers.get("accept"), "text/html, application/json");
+	t.deepEqual(headers.getSetCookie(), ["a=1", "b=2"]);
+	t.deepEqual(headers.entries(), [
+		["accept", "text/html, application/json"],
+		["set-cookie", "a=1"],
+		["set-cookie", "b=2"],
+	]);
+});
+
+test("FaithHeaders rejects invalid names with a TypeError", async (t) => {
+	t.plan(2);
+
+	const headers = new FaithHeaders();
+	try {
+		headers.append("Invalid Name", "value");
+		t.fail("Should have thrown");
+	} catch (err) {
+		t.equal(err.name, "TypeError", "Should throw a TypeError");
+		t.equal(err.code, "InvalidHeader", "Should have InvalidHeader code");
+	}
+});
+
+test("FaithHeaders can be used as request headers", async (t) => {
+	t.plan(2);
+
+	const headers = new FaithHeaders();
+	headers.append("X-Custom", "one");
+	headers.append("X-Custom", "two");
+	const response = await faithFetch(url("/headers"), { headers });
+	t.ok(response.ok, "Should successfully fetch");
+	const data = await response.json();
+	t.equal(data.headers["X-Custom"], "one, two", "Should send combined header");
+});
+
+test("response.faithHeaders returns a FaithHeaders copy", async (t) => {
+	t.plan(3);
+
+	const response = await faithFetch(url("/get"));
+	const headers = response.faithHeaders;
+	t.ok(headers instanceof FaithHeaders, "Should be a FaithHeaders");
+	t.equal(
+		headers.get("content-type"),
+		response.headers.get("content-type"),
+		"Should match the response headers",
+	);
+	headers.delete("content-type");
+	t.ok(
+		response.faithHeaders.has("content-type"),
+		"Modifying the copy should not affect the response",
+	);
+});
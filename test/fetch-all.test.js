@@ -0,0 +1,66 @@
+const test = require("tape");
+const { fetchAll, Agent, Response } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("fetchAll() settles all requests in order", async (t) => {
+	t.plan(6);
+
+	const results = await fetchAll([
+		url("/status/200"),
+		new URL(url("/status/404")),
+		{ url: url("/post"), options: { method: "POST", body: "hello" } },
+		"http://localhost:1",
+	]);
+
+	t.equal(results.length, 4, "should return one result per request");
+	t.equal(results[0].status, "fulfilled", "first should be fulfilled");
+	t.ok(results[0].value instanceof Response, "value should be a Response");
+	t.equal(results[1].value.status, 404, "second should be the 404");
+	t.equal(
+		(await results[2].value.json()).data,
+		"hello",
+		"third should have sent the body",
+	);
+	t.equal(results[3].status, "rejected", "fourth should be rejected");
+});
+
+test("fetchAll() respects concurrency", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent();
+	const start = Date.now();
+	const results = await fetchAll(
+		[1, 2, 3, 4].map(() => ({ url: url("/delay/1"), options: { agent } })),
+		{ concurrency: 2 },
+	);
+
+	t.ok(
+		results.every((result) => result.status === "fulfilled"),
+		"all should be fulfilled",
+	);
+	t.ok(Date.now() - start >= 2000, "should take at least two rounds");
+});
+
+test("fetchAll() with failFast rejects with the first error", async (t) => {
+	t.plan(1);
+
+	try {
+		await fetchAll([url("/get"), "http://localhost:1"], { failFast: true });
+		t.fail("should have rejected");
+	} catch (error) {
+		t.ok(error.code, "should reject with a Faith error");
+	}
+});
+
+test("fetchAll() can be aborted", async (t) => {
+	t.plan(1);
+
+	const controller = new AbortController();
+	setTimeout(() => controller.abort(), 100);
+	try {
+		await fetchAll([url("/delay/2")], { signal: controller.signal });
+		t.fail("should have rejected");
+	} catch (error) {
+		t.ok(error, "should reject when aborted");
+	}
+});
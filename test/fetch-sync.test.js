@@ -0,0 +1,76 @@
+const test = require("tape");
+const { Agent, ERROR_CODES, faithFetchSync } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+test("faithFetchSync: blocks until the body is read", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/config",
+			headers: { "Content-Type": "application/json" },
+			body: JSON.stringify({ debug: true }),
+		});
+		server.route({ path: "/echo", method: "POST", status: 201, body: "created" });
+
+		const response = faithFetchSync(`${server.url}/config`);
+		t.equal(response.status, 200, "Status");
+		t.ok(response.ok, "ok");
+		t.equal(response.headers.get("content-type"), "application/json", "Headers");
+		t.deepEqual(response.json(), { debug: true }, "JSON body");
+
+		const posted = faithFetchSync(new URL(`${server.url}/echo`), {
+			method: "POST",
+			body: new URLSearchParams({ a: "1" }),
+		});
+		t.equal(posted.status, 201, "Status of a POST");
+		t.equal(posted.text(), "created", "Text body");
+		const request = server.requests().at(-1);
+		t.equal(request.body, "a=1", "Body sent");
+		t.equal(
+			request.headers.find(([key]) => key === "content-type")?.[1],
+			"application/x-www-form-urlencoded;charset=UTF-8",
+			"Content-Type of the body",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("faithFetchSync: hard timeout and body limit", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/slow", delay: 2000 });
+		server.route({ path: "/large", body: Buffer.alloc(2048) });
+
+		try {
+			faithFetchSync(`${server.url}/slow`, { timeout: 100 });
+			t.fail("should throw");
+		} catch (error) {
+			t.equal(error.code, ERROR_CODES.Timeout, "Timed out");
+		}
+
+		const agent = new Agent({ limits: { responseBody: 1024 } });
+		try {
+			faithFetchSync(`${server.url}/large`, { agent });
+			t.fail("should throw");
+		} catch (error) {
+			t.equal(error.code, ERROR_CODES.ResponseTooLarge, "Body over the agent's limit");
+		}
+	} finally {
+		server.close();
+	}
+});
+
+test("faithFetchSync: unsupported options", (t) => {
+	t.plan(2);
+	t.throws(
+		() => faithFetchSync("http://127.0.0.1/", { body: new Blob(["hi"]), method: "POST" }),
+		TypeError,
+		"Blob bodies",
+	);
+	t.throws(
+		() => faithFetchSync("http://127.0.0.1/", { signal: new AbortController().signal }),
+		TypeError,
+		"signal",
+	);
+});
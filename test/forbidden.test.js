@@ -0,0 +1,68 @@
+const test = require("tape");
+const { fetch, Agent } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+function getHeader(headers, name) {
+	const value = headers[name];
+	return Array.isArray(value) ? value[0] : value;
+}
+
+test("forbidden: TRACE, TRACK, and CONNECT are rejected", async (t) => {
+	t.plan(3);
+
+	for (const method of ["TRACE", "track", "CONNECT"]) {
+		try {
+			await fetch(url("/anything"), { method });
+			t.fail("should have thrown");
+		} catch (error) {
+			t.equal(error.code, "InvalidMethod", `${method} should be rejected`);
+		}
+	}
+});
+
+test("forbidden: forbidden request headers are dropped", async (t) => {
+	t.plan(4);
+
+	const response = await fetch(url("/headers"), {
+		headers: {
+			Origin: "https://example.com",
+			"Proxy-Foo": "bar",
+			"Sec-Foo": "bar",
+			"X-HTTP-Method-Override": "TRACE",
+		},
+	});
+	const { headers } = await response.json();
+	t.notOk(getHeader(headers, "Origin"), "Origin should be dropped");
+	t.notOk(getHeader(headers, "Proxy-Foo"), "Proxy-* should be dropped");
+	t.notOk(getHeader(headers, "Sec-Foo"), "Sec-* should be dropped");
+	t.notOk(
+		getHeader(headers, "X-Http-Method-Override"),
+		"method override to a forbidden method should be dropped",
+	);
+});
+
+test("forbidden: allowed method overrides are kept", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/headers"), {
+		headers: { "X-HTTP-Method-Override": "PATCH" },
+	});
+	const { headers } = await response.json();
+	t.equal(getHeader(headers, "X-Http-Method-Override"), "PATCH");
+});
+
+test("forbidden: unsafe agents send anything", async (t) => {
+	t.plan(3);
+
+	const agent = new Agent({ unsafe: true });
+	const response = await fetch(url("/headers"), {
+		agent,
+		headers: { Origin: "https://example.com", "Sec-Foo": "bar" },
+	});
+	const { headers } = await response.json();
+	t.equal(getHeader(headers, "Origin"), "https://example.com");
+	t.equal(getHeader(headers, "Sec-Foo"), "bar");
+
+	const trace = await fetch(url("/anything"), { agent, method: "TRACE" });
+	t.ok(trace.status, "TRACE should be sent");
+});
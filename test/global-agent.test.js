@@ -0,0 +1,99 @@
+const test = require("tape");
+const path = require("node:path");
+const { once } = require("node:events");
+const { Worker } = require("node:worker_threads");
+const {
+	fetch: faithFetch,
+	Agent,
+	getGlobalAgent,
+	setGlobalAgent,
+} = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+const wrapper = path.join(__dirname, "..", "wrapper.js");
+
+// Helper to get header value (handles both string and array)
+function getHeader(headers, name) {
+	const value = headers[name];
+	return Array.isArray(value) ? value[0] : value;
+}
+
+test("fetch() without an agent uses the global agent", async (t) => {
+	t.plan(2);
+
+	const agent = getGlobalAgent();
+	t.ok(agent instanceof Agent, "getGlobalAgent() should return an Agent");
+
+	const before = agent.stats().requestsSent;
+	await faithFetch(url("/get"));
+	t.equal(
+		agent.stats().requestsSent,
+		before + 1,
+		"request should be counted in the global agent's stats",
+	);
+});
+
+test("setGlobalAgent() replaces the global agent", async (t) => {
+	t.plan(3);
+
+	const previous = getGlobalAgent();
+	const agent = new Agent({
+		headers: [{ name: "X-Global-Agent", value: "yes" }],
+	});
+	setGlobalAgent(agent);
+
+	try {
+		const response = await faithFetch(url("/headers"));
+		const data = await response.json();
+		t.equal(
+			getHeader(data.headers, "X-Global-Agent"),
+			"yes",
+			"request should use the new global agent's headers",
+		);
+		t.equal(agent.stats().requestsSent, 1, "new agent should count the request");
+
+		await faithFetch(url("/get"), { agent: previous });
+		t.equal(
+			agent.stats().requestsSent,
+			1,
+			"an explicit agent should take precedence over the global agent",
+		);
+	} finally {
+		setGlobalAgent(previous);
+	}
+});
+
+test("each worker thread has its own global agent", async (t) => {
+	t.plan(2);
+
+	const previous = getGlobalAgent();
+	const agent = new Agent({
+		headers: [{ name: "X-Global-Agent", value: "main" }],
+	});
+	setGlobalAgent(agent);
+
+	try {
+		const worker = new Worker(
+			`
+			const { parentPort, workerData } = require("node:worker_threads");
+			const { fetch } = require(workerData.wrapper);
+			fetch(workerData.url)
+				.then((response) => response.json())
+				.then((data) => parentPort.postMessage(data.headers["X-Global-Agent"] ?? null));
+			`,
+			{ eval: true, workerData: { wrapper, url: url("/headers") } },
+		);
+		const [header] = await once(worker, "message").finally(() => worker.terminate());
+		t.equal(header, null, "the worker should use its own global agent");
+
+		const response = await faithFetch(url("/headers"));
+		const data = await response.json();
+		t.equal(
+			getHeader(data.headers, "X-Global-Agent"),
+			"main",
+			"the main thread should keep its global agent",
+		);
+	} finally {
+		setGlobalAgent(previous);
+	}
+});
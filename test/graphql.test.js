@@ -0,0 +1,136 @@
+const test = require("tape");
+const { createHash } = require("node:crypto");
+const { Agent, ERROR_CODES, FaithError } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+async function rejection(promise) {
+	try {
+		await promise;
+	} catch (error) {
+		return error;
+	}
+	throw new Error("expected a rejection");
+}
+
+function header(request, name) {
+	return request.headers.find(([key]) => key === name)?.[1];
+}
+
+const JSON_HEADERS = { "Content-Type": "application/graphql-response+json" };
+
+test("graphql: posts the envelope and resolves to the result", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			method: "POST",
+			path: "/graphql",
+			headers: JSON_HEADERS,
+			body: JSON.stringify({ data: { user: { name: "Ada" } }, extensions: { cost: 1 } }),
+		});
+
+		const query = "query User($id: ID!) { user(id: $id) { name } }";
+		const result = await new Agent().graphql(`${server.url}/graphql`, {
+			query,
+			variables: { id: 1 },
+			operationName: "User",
+		});
+		t.deepEqual(result, { data: { user: { name: "Ada" } }, extensions: { cost: 1 } }, "Result");
+
+		const [request] = server.requests();
+		t.deepEqual(
+			JSON.parse(request.body),
+			{ query, variables: { id: 1 }, operationName: "User" },
+			"Envelope",
+		);
+		t.equal(header(request, "content-type"), "application/json", "Content-Type header");
+		t.equal(
+			header(request, "accept"),
+			"application/graphql-response+json, application/json",
+			"Accept header",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("graphql: errors in the result reject", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			method: "POST",
+			path: "/graphql",
+			headers: JSON_HEADERS,
+			body: JSON.stringify({
+				data: { user: null },
+				errors: [{ message: "not allowed", path: ["user"] }, { message: "also bad" }],
+			}),
+		});
+		server.route({ method: "POST", path: "/broken", status: 502, body: "bad gateway" });
+
+		const agent = new Agent();
+		const error = await rejection(agent.graphql(`${server.url}/graphql`, { query: "{ user }" }));
+		t.ok(error instanceof FaithError, "FaithError");
+		t.equal(error.code, ERROR_CODES.Graphql, "code");
+		t.ok(error.message.includes("not allowed (and 1 more errors)"), "message");
+		t.equal(error.errors.length, 2, "errors");
+		t.deepEqual(error.errors[0].path, ["user"], "error details");
+		t.deepEqual(error.data, { user: null }, "partial data");
+
+		const status = await rejection(agent.graphql(`${server.url}/broken`, { query: "{ user }" }));
+		t.equal(status.code, ERROR_CODES.HttpStatus, "Error statuses without a result");
+		t.equal(status.status, 502, "status");
+	} finally {
+		server.close();
+	}
+});
+
+test("graphql: persisted queries are sent in full when unknown", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			method: "POST",
+			path: "/graphql",
+			headers: JSON_HEADERS,
+			body: JSON.stringify({ data: { ok: true } }),
+		});
+		server.route({
+			method: "POST",
+			path: "/graphql",
+			headers: JSON_HEADERS,
+			body: JSON.stringify({
+				errors: [
+					{
+						message: "PersistedQueryNotFound",
+						extensions: { code: "PERSISTED_QUERY_NOT_FOUND" },
+					},
+				],
+			}),
+			times: 1,
+		});
+
+		const query = "{ ok }";
+		const hash = createHash("sha256").update(query).digest("hex");
+		const agent = new Agent();
+		const result = await agent.graphql(`${server.url}/graphql`, { query, persisted: true });
+		t.deepEqual(result.data, { ok: true }, "Result");
+
+		const [first, second] = server.requests().map(({ body }) => JSON.parse(body));
+		const extensions = { persistedQuery: { version: 1, sha256Hash: hash } };
+		t.deepEqual(first, { extensions }, "Hash only");
+		t.deepEqual(second, { query, extensions }, "Then the full query");
+
+		await agent.graphql(`${server.url}/graphql`, { sha256Hash: hash, persisted: true });
+		t.equal(server.requests().length, 3, "Known hashes are sent once");
+	} finally {
+		server.close();
+	}
+});
+
+test("graphql: requests need a query", async (t) => {
+	const agent = new Agent();
+	const error = await rejection(agent.graphql("http://127.0.0.1/graphql", {}));
+	t.equal(error.code, ERROR_CODES.Config, "Without a query");
+
+	const persisted = await rejection(agent.graphql("http://127.0.0.1/graphql", { persisted: true }));
+	t.equal(persisted.code, ERROR_CODES.Config, "Persisted without a query or hash");
+});
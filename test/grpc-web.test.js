@@ -0,0 +1,117 @@
+const test = require("tape");
+const { Agent, ERROR_CODES, FaithSyntaxError } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+async function rejection(promise) {
+	try {
+		await promise;
+	} catch (error) {
+		return error;
+	}
+	throw new Error("expected a rejection");
+}
+
+function header(request, name) {
+	return request.headers.find(([key]) => key === name)?.[1];
+}
+
+function frame(flags, payload) {
+	const data = Buffer.from(payload);
+	const header = Buffer.alloc(5);
+	header.writeUInt8(flags, 0);
+	header.writeUInt32BE(data.length, 1);
+	return Buffer.concat([header, data]);
+}
+
+const GRPC_HEADERS = { "Content-Type": "application/grpc-web+proto", "X-Initial": "meta" };
+
+test("grpcWeb: frames the request and parses the response", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			method: "POST",
+			path: "/pkg.Service/Method",
+			headers: GRPC_HEADERS,
+			body: Buffer.concat([
+				frame(0, [8, 42]),
+				frame(0x80, "grpc-status: 0\r\ngrpc-message: \r\nx-trailing: done\r\n"),
+			]),
+		});
+
+		const response = await new Agent().grpcWeb(
+			`${server.url}/pkg.Service/Method`,
+			new Uint8Array([10, 3, 65, 100, 97]),
+			{ headers: { "X-Request-Meta": "yes" }, timeout: 1500 },
+		);
+		t.equal(response.status, 0, "OK status");
+		t.deepEqual([...response.message], [8, 42], "Response message");
+		t.equal(response.headers.get("x-initial"), "meta", "Initial metadata");
+		t.equal(response.trailers.get("x-trailing"), "done", "Trailing metadata");
+
+		const [request] = server.requests();
+		t.deepEqual([...request.body], [0, 0, 0, 0, 5, 10, 3, 65, 100, 97], "Framed request");
+		t.equal(header(request, "content-type"), "application/grpc-web+proto", "Content-Type");
+		t.equal(header(request, "x-grpc-web"), "1", "X-Grpc-Web");
+		t.equal(header(request, "x-request-meta"), "yes", "Request metadata");
+		t.equal(header(request, "grpc-timeout"), "1500m", "Deadline");
+	} finally {
+		server.close();
+	}
+});
+
+test("grpcWeb: error statuses resolve with the status", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			method: "POST",
+			path: "/trailers",
+			headers: GRPC_HEADERS,
+			body: frame(0x80, "grpc-status: 5\r\ngrpc-message: no%20such%20thing\r\n"),
+		});
+		server.route({
+			method: "POST",
+			path: "/trailers-only",
+			headers: { ...GRPC_HEADERS, "grpc-status": "16", "grpc-message": "who are you" },
+		});
+
+		const agent = new Agent();
+		const notFound = await agent.grpcWeb(`${server.url}/trailers`, Buffer.alloc(0));
+		t.equal(notFound.status, 5, "Status from the trailers");
+		t.equal(notFound.statusMessage, "no such thing", "Message is percent-decoded");
+		t.equal(notFound.message, null, "No message");
+
+		const unauthenticated = await agent.grpcWeb(`${server.url}/trailers-only`, null);
+		t.equal(unauthenticated.status, 16, "Status from the headers");
+		t.equal(unauthenticated.statusMessage, "who are you", "Message from the headers");
+	} finally {
+		server.close();
+	}
+});
+
+test("grpcWeb: malformed responses reject", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ method: "POST", path: "/html", body: "<html>" });
+		server.route({
+			method: "POST",
+			path: "/truncated",
+			headers: GRPC_HEADERS,
+			body: frame(0, [1, 2, 3]).subarray(0, 6),
+		});
+		server.route({ method: "POST", path: "/missing", status: 404 });
+
+		const agent = new Agent();
+		const html = await rejection(agent.grpcWeb(`${server.url}/html`, null));
+		t.ok(html instanceof FaithSyntaxError, "FaithSyntaxError");
+		t.equal(html.code, ERROR_CODES.GrpcWeb, "Not gRPC-web");
+
+		const truncated = await rejection(agent.grpcWeb(`${server.url}/truncated`, null));
+		t.equal(truncated.code, ERROR_CODES.GrpcWeb, "Truncated frame");
+
+		const missing = await rejection(agent.grpcWeb(`${server.url}/missing`, null));
+		t.equal(missing.code, ERROR_CODES.HttpStatus, "HTTP error status");
+		t.equal(missing.status, 404, "status");
+	} finally {
+		server.close();
+	}
+});
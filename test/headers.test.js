@@ -4,8 +4,8 @@ const { url } = require("./helpers.js");
  *
  * Tests that:
  * 1. Response.headers always returns a Headers object
- * 2. fetch() interface only supports Headers or plain objects for headers
- * 3. Arrays of pairs are not supported in fetch()
+ * 2. fetch() interface supports Headers, plain objects, and arrays of pairs for headers
+ * 3. Malformed pairs and other types are rejected
  */
 
 const test = require("tape");
@@ -142,43 +142,46 @@ test("fetch() rejects invalid header value", async (t) => {
 	}
 });
 
-test("fetch() rejects array of tuples for headers", async (t) => {
+test("fetch() accepts array of tuples for headers", async (t) => {
 	t.plan(2);
 
-	try {
-		await fetch(url("/get"), {
-			headers: [
-				["X-Test", "value1"],
-				["Accept", "application/json"],
-			],
-		});
+	const response = await fetch(url("/headers"), {
+		headers: [
+			["X-Test", "value1"],
+			["X-Number", 42],
+		],
+	});
+	const data = await response.json();
+	t.equal(getHeader(data.headers, "X-Test"), "value1", "should send pair");
+	t.equal(
+		getHeader(data.headers, "X-Number"),
+		"42",
+		"should stringify non-string values",
+	);
+});
 
-		t.fail("Should have thrown error for array headers");
-	} catch (error) {
-		t.ok(
-			error.message.includes(
-				"headers must be a Headers object or a plain object",
-			),
-			"should have correct error message",
-		);
-		t.equal(error.constructor.name, "TypeError", "should throw TypeError");
-	}
+test("fetch() accepts a Map for headers", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/headers"), {
+		headers: new Map([["X-Test", "from-map"]]),
+	});
+	const data = await response.json();
+	t.equal(getHeader(data.headers, "X-Test"), "from-map", "should send pair");
 });
 
-test("fetch() rejects nested array for headers", async (t) => {
+test("fetch() rejects malformed pairs for headers", async (t) => {
 	t.plan(2);
 
 	try {
 		await fetch(url("/get"), {
-			headers: [["X-Test", "value1"]], // Single element array
+			headers: [["X-Test"]], // Single element pair
 		});
 
-		t.fail("Should have thrown error for nested array headers");
+		t.fail("Should have thrown error for malformed pair");
 	} catch (error) {
 		t.ok(
-			error.message.includes(
-				"headers must be a Headers object or a plain object",
-			),
+			error.message.includes("exactly two items"),
 			"should have correct error message",
 		);
 		t.equal(error.constructor.name, "TypeError", "should throw TypeError");
@@ -197,7 +200,7 @@ test("fetch() rejects string for headers", async (t) => {
 	} catch (error) {
 		t.ok(
 			error.message.includes(
-				"headers must be a Headers object or a plain object",
+				"headers must be a Headers or FaithHeaders object, an array of [name, value] pairs, or a plain object",
 			),
 			"should have correct error message",
 		);
@@ -217,7 +220,7 @@ test("fetch() rejects number for headers", async (t) => {
 	} catch (error) {
 		t.ok(
 			error.message.includes(
-				"headers must be a Headers object or a plain object",
+				"headers must be a Headers or FaithHeaders object, an array of [name, value] pairs, or a plain object",
 			),
 			"should have correct error message",
 		);
@@ -0,0 +1,66 @@
+const test = require("tape");
+const { Agent, fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+test("history: redirects followed, in order", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/start", status: 301, headers: { Location: "/middle" } });
+		server.route({ path: "/middle", status: 307, headers: { Location: `${server.url}/end` } });
+		server.route({ path: "/end", body: "end" });
+
+		const response = await fetch(`${server.url}/start`);
+		t.equal(await response.text(), "end", "Followed");
+		t.deepEqual(
+			response.history,
+			[
+				{ url: `${server.url}/start`, status: 301, location: `${server.url}/middle` },
+				{ url: `${server.url}/middle`, status: 307, location: `${server.url}/end` },
+			],
+			"Both redirects",
+		);
+		t.deepEqual(response.clone().history, response.history, "Kept by clones");
+	} finally {
+		server.close();
+	}
+});
+
+test("history: empty without redirects followed", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/", body: "home" });
+		server.route({ path: "/moved", status: 302, headers: { Location: "/" } });
+
+		t.deepEqual((await fetch(`${server.url}/`)).history, [], "Not redirected");
+
+		const stopped = await fetch(`${server.url}/moved`, { agent: new Agent({ redirect: "stop" }) });
+		t.equal(stopped.status, 302, "Redirect returned");
+		t.deepEqual(stopped.history, [], "Not followed");
+	} finally {
+		server.close();
+	}
+});
+
+test("history: separate for concurrent requests", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/a", status: 302, headers: { Location: "/done" }, delay: 100 });
+		server.route({ path: "/b", status: 303, headers: { Location: "/done" } });
+		server.route({ path: "/done" });
+
+		const agent = new Agent();
+		const [a, b] = await Promise.all([agent.get(`${server.url}/a`), agent.get(`${server.url}/b`)]);
+		t.deepEqual(
+			a.history.map(({ url, status }) => [url, status]),
+			[[`${server.url}/a`, 302]],
+			"First request",
+		);
+		t.deepEqual(
+			b.history.map(({ url, status }) => [url, status]),
+			[[`${server.url}/b`, 303]],
+			"Second request",
+		);
+	} finally {
+		server.close();
+	}
+});
@@ -0,0 +1,63 @@
+const test = require("tape");
+const { fetch, Agent } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+const host = new URL(url("/")).hostname;
+
+async function forbidden(t, promise, message) {
+	try {
+		await promise;
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, "ForbiddenHost", message);
+	}
+}
+
+test("allowedHosts: allows matching hosts only", async (t) => {
+	t.plan(3);
+
+	const allowed = new Agent({ allowedHosts: ["example.com", host] });
+	const response = await fetch(url("/get"), { agent: allowed });
+	t.equal(response.status, 200, "should fetch from an allowed host");
+
+	const wildcard = new Agent({ allowedHosts: ["*"] });
+	const all = await fetch(url("/get"), { agent: wildcard });
+	t.equal(all.status, 200, "should match a wildcard");
+
+	const other = new Agent({ allowedHosts: ["*.example.com"] });
+	await forbidden(
+		t,
+		fetch(url("/get"), { agent: other }),
+		"should reject other hosts",
+	);
+});
+
+test("allowedHosts: applies to redirects", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent({ allowedHosts: [host] });
+	const target = encodeURIComponent("http://example.com/");
+	await forbidden(
+		t,
+		fetch(url(`/redirect-to?url=${target}`), { agent }),
+		"should reject redirects to other hosts",
+	);
+});
+
+test("blockedHosts: rejects matching hosts", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ blockedHosts: [host] });
+	await forbidden(
+		t,
+		fetch(url("/get"), { agent }),
+		"should reject a blocked host",
+	);
+
+	const both = new Agent({ allowedHosts: ["*"], blockedHosts: [host] });
+	await forbidden(
+		t,
+		fetch(url("/get"), { agent: both }),
+		"blockedHosts should take precedence",
+	);
+});
@@ -0,0 +1,121 @@
+const test = require("tape");
+const http = require("node:http");
+const { Agent, FaithHttpAgent } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+/**
+ * Make a request with http.request(), resolving with the response and its body
+ * @param {string} url
+ * @param {import("node:http").RequestOptions} options
+ * @param {(request: import("node:http").ClientRequest) => void} [send] - ends the request if not given
+ * @returns {Promise<{ response: import("node:http").IncomingMessage, body: string }>}
+ */
+function request(url, options, send) {
+	return new Promise((resolve, reject) => {
+		const req = http.request(url, options, (response) => {
+			let body = "";
+			response.setEncoding("utf8");
+			response.on("data", (chunk) => {
+				body += chunk;
+			});
+			response.on("end", () => resolve({ response, body }));
+			response.on("error", reject);
+		});
+		req.on("error", reject);
+		if (send) send(req);
+		else req.end();
+	});
+}
+
+test("FaithHttpAgent: http.request() through the agent", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/",
+			headers: { "Content-Type": "text/plain", "X-Test": "yes" },
+			body: "hello",
+		});
+		server.route({ path: "/echo", method: "POST", status: 201, body: "created" });
+		server.route({ path: "/empty", status: 204 });
+
+		const agent = new Agent({
+			headers: [{ name: "X-Agent", value: "http" }],
+		});
+		const httpAgent = new FaithHttpAgent({ agent });
+
+		const { response, body } = await request(`${server.url}/?a=1`, {
+			agent: httpAgent,
+			headers: { "X-Request": "1" },
+		});
+		t.equal(response.statusCode, 200, "Status");
+		t.equal(response.headers["x-test"], "yes", "Headers");
+		t.equal(body, "hello", "Body");
+		const sent = server.requests().at(-1);
+		t.equal(sent.path, "/?a=1", "Path and query");
+		const headers = Object.fromEntries(sent.headers);
+		t.equal(headers["x-request"], "1", "Request headers");
+		t.equal(headers["x-agent"], "http", "Agent's default headers");
+		t.equal(agent.stats().requestsSent, 1, "Sent by the agent");
+
+		const posted = await request(
+			`${server.url}/echo`,
+			{
+				agent: httpAgent,
+				method: "POST",
+				headers: { "Content-Length": 11 },
+			},
+			(req) => req.end("fixed body!"),
+		);
+		t.equal(posted.response.statusCode, 201, "Status of a POST");
+		t.equal(posted.body, "created", "Body of a POST");
+		t.equal(server.requests().at(-1).body, "fixed body!", "Content-Length body");
+
+		await request(
+			`${server.url}/echo`,
+			{ agent: httpAgent, method: "POST" },
+			(req) => {
+				req.write("chunked ");
+				setTimeout(() => req.end("body"), 20);
+			},
+		);
+		t.equal(server.requests().at(-1).body, "chunked body", "Chunked body");
+
+		const empty = await request(`${server.url}/empty`, { agent: httpAgent });
+		t.equal(empty.response.statusCode, 204, "No content");
+		t.equal(empty.body, "", "Without a body");
+
+		const head = await request(`${server.url}/`, {
+			agent: httpAgent,
+			method: "HEAD",
+		});
+		t.equal(head.response.statusCode, 200, "HEAD");
+		t.equal(head.body, "", "HEAD without a body");
+	} finally {
+		server.close();
+	}
+});
+
+test("FaithHttpAgent: errors and protocols", async (t) => {
+	const closed = await startServer();
+	const url = closed.url;
+	closed.close();
+
+	try {
+		await request(`${url}/`, { agent: new FaithHttpAgent() });
+		t.fail("should reject");
+	} catch (error) {
+		t.equal(error.name, "NetworkError", "Request errors are emitted");
+	}
+
+	t.equal(new FaithHttpAgent().protocol, "http:", "http: by default");
+	t.equal(
+		new FaithHttpAgent({ protocol: "https:" }).protocol,
+		"https:",
+		"https: for https.request()",
+	);
+	t.throws(
+		() => new FaithHttpAgent({ protocol: "ftp:" }),
+		TypeError,
+		"Other protocols",
+	);
+});
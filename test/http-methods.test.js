@@ -1,6 +1,6 @@
 const test = require("tape");
 const { compareResponses, hasNativeFetch, url } = require("./helpers.js");
-const { fetch, ERROR_CODES } = require("../wrapper.js");
+const { fetch, Agent, ERROR_CODES } = require("../wrapper.js");
 const native = require("../index.js");
 
 test("Compare different HTTP methods", { skip: !hasNativeFetch }, async (t) => {
@@ -36,3 +36,42 @@ test(
     }
   },
 );
+
+test("fetch rejects GET and HEAD requests with a body", async (t) => {
+  t.plan(4);
+
+  for (const method of ["GET", "HEAD"]) {
+    try {
+      await fetch(url("/anything"), { method, body: "" });
+      t.fail(`Should have thrown error for ${method} with a body`);
+    } catch (error) {
+      t.equal(error.code, ERROR_CODES.BodyNotAllowed, `${method} code`);
+      t.equal(error.name, "TypeError", `${method} should be a TypeError`);
+    }
+  }
+});
+
+test("allowGetBody lets GET requests have a body", async (t) => {
+  t.plan(2);
+
+  const agent = new Agent({ allowGetBody: true });
+  const response = await fetch(url("/anything"), {
+    agent,
+    body: '{"query":{}}',
+  });
+  const data = await response.json();
+  t.equal(data.method, "GET", "should be a GET request");
+  t.equal(data.data, '{"query":{}}', "should send the body");
+});
+
+test("allowGetBody still rejects HEAD requests with a body", async (t) => {
+  t.plan(1);
+
+  const agent = new Agent({ allowGetBody: true });
+  try {
+    await fetch(url("/anything"), { agent, method: "HEAD", body: "x" });
+    t.fail("Should have thrown error for HEAD with a body");
+  } catch (error) {
+    t.equal(error.code, ERROR_CODES.BodyNotAllowed, "should be BodyNotAllowed");
+  }
+});
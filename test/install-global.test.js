@@ -0,0 +1,64 @@
+const test = require("tape");
+const {
+	Agent,
+	installGlobal,
+	Response,
+	uninstallGlobal,
+} = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+test("installGlobal: global fetch goes through the agent", async (t) => {
+	const server = await startServer();
+	const original = globalThis.fetch;
+	try {
+		server.route({ path: "/", body: "hello" });
+
+		const agent = new Agent({
+			headers: [{ name: "X-Agent", value: "installed" }],
+		});
+		const uninstall = installGlobal({ agent });
+		t.notEqual(globalThis.fetch, original, "Replaced");
+		t.equal(globalThis.fetch.name, "fetch", "Named fetch");
+
+		const response = await globalThis.fetch(`${server.url}/`);
+		t.ok(response instanceof Response, "Fáith's Response");
+		t.ok(response.headers instanceof Headers, "Global Headers");
+		t.equal(await response.text(), "hello", "Body");
+		let headers = Object.fromEntries(server.requests().at(-1).headers);
+		t.equal(headers["x-agent"], "installed", "Agent's default headers");
+
+		await globalThis.fetch(
+			new Request(`${server.url}/`, {
+				headers: new Headers({ "X-Request": "global" }),
+			}),
+		);
+		headers = Object.fromEntries(server.requests().at(-1).headers);
+		t.equal(headers["x-request"], "global", "Global Request and Headers");
+
+		const other = new Agent();
+		await globalThis.fetch(`${server.url}/`, { agent: other });
+		t.equal(other.stats().requestsSent, 1, "Request's own agent");
+
+		uninstall();
+		t.equal(globalThis.fetch, original, "Restored");
+	} finally {
+		uninstallGlobal();
+		server.close();
+	}
+});
+
+test("installGlobal: installing again keeps the original", (t) => {
+	const original = globalThis.fetch;
+	try {
+		installGlobal();
+		installGlobal({ agent: new Agent() });
+		uninstallGlobal();
+		t.equal(globalThis.fetch, original, "Restored the original");
+
+		uninstallGlobal();
+		t.equal(globalThis.fetch, original, "Uninstalling again does nothing");
+	} finally {
+		uninstallGlobal();
+		t.end();
+	}
+});
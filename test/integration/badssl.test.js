@@ -1,5 +1,5 @@
 const test = require("tape");
-const { fetch: faithFetch } = require("../../wrapper.js");
+const { fetch: faithFetch, Agent, capabilities } = require("../../wrapper.js");
 
 test("badssl.com - valid certificate should succeed", async (t) => {
 	t.plan(2);
@@ -101,7 +101,7 @@ test("badssl.com - expired certificate should fail", async (t) => {
 		await faithFetch("https://expired.badssl.com/");
 		t.fail("Should throw for expired certificate");
 	} catch (err) {
-		t.pass("Should throw error for expired certificate");
+		t.equal(err.code, "CertificateInvalid", "Should throw CertificateInvalid for expired certificate");
 	}
 });
 
@@ -112,7 +112,7 @@ test("badssl.com - wrong host certificate should fail", async (t) => {
 		await faithFetch("https://wrong.host.badssl.com/");
 		t.fail("Should throw for wrong host certificate");
 	} catch (err) {
-		t.pass("Should throw error for wrong host certificate");
+		t.equal(err.code, "CertificateInvalid", "Should throw CertificateInvalid for wrong host certificate");
 	}
 });
 
@@ -123,7 +123,7 @@ test("badssl.com - self-signed certificate should fail", async (t) => {
 		await faithFetch("https://self-signed.badssl.com/");
 		t.fail("Should throw for self-signed certificate");
 	} catch (err) {
-		t.pass("Should throw error for self-signed certificate");
+		t.equal(err.code, "CertificateInvalid", "Should throw CertificateInvalid for self-signed certificate");
 	}
 });
 
@@ -134,7 +134,7 @@ test("badssl.com - untrusted root certificate should fail", async (t) => {
 		await faithFetch("https://untrusted-root.badssl.com/");
 		t.fail("Should throw for untrusted root certificate");
 	} catch (err) {
-		t.pass("Should throw error for untrusted root certificate");
+		t.equal(err.code, "CertificateInvalid", "Should throw CertificateInvalid for untrusted root certificate");
 	}
 });
 
@@ -256,3 +256,181 @@ test.skip("badssl.com - extended validation certificate should succeed", async (
 	t.ok(response.ok, "Should successfully fetch with EV certificate");
 	t.equal(response.status, 200, "Status should be 200");
 });
+
+test("badssl.com - tls.serverName validates against the given name", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ tls: { serverName: "badssl.com" } });
+	const response = await faithFetch("https://wrong.host.badssl.com/", { agent });
+	t.ok(response.ok, "Should validate the certificate for badssl.com");
+	t.equal(
+		new URL(response.url).host,
+		"wrong.host.badssl.com",
+		"Response URL should be the requested URL",
+	);
+});
+
+test("badssl.com - certificate verify proc can trust a self-signed certificate", async (t) => {
+	t.plan(5);
+
+	const agent = new Agent({ tls: { verifyProc: true } });
+	const requests = [];
+	agent.setCertificateVerifyProc(async (request) => {
+		requests.push(request);
+		return request.hostname === "self-signed.badssl.com" ? 0 : -3;
+	});
+
+	const response = await faithFetch("https://self-signed.badssl.com/", { agent });
+	t.ok(response.ok, "Should trust the certificate");
+	t.equal(requests[0].hostname, "self-signed.badssl.com", "Should be given the hostname");
+	t.ok(Buffer.isBuffer(requests[0].certificate), "Should be given the certificate");
+	t.notEqual(requests[0].verificationResult, "OK", "Should be given the failed verification");
+
+	try {
+		await faithFetch("https://expired.badssl.com/", { agent });
+		t.fail("Should throw for expired certificate");
+	} catch (err) {
+		t.equal(err.code, "CertificateInvalid", "Should use the verification result with -3");
+	}
+});
+
+test("badssl.com - certificate verify proc can reject a valid certificate", async (t) => {
+	t.plan(3);
+
+	const agent = new Agent({ tls: { verifyProc: true } });
+	const results = [];
+	agent.setCertificateVerifyProc((request) => {
+		results.push(request.verificationResult);
+		return -2;
+	});
+
+	try {
+		await faithFetch("https://badssl.com/", { agent });
+		t.fail("Should throw for rejected certificate");
+	} catch (err) {
+		t.equal(err.code, "CertificateInvalid", "Should throw CertificateInvalid");
+	}
+	t.equal(results[0], "OK", "Should have been verified");
+
+	t.throws(
+		() => new Agent().setCertificateVerifyProc(() => 0),
+		/tls.verifyProc/,
+		"Needs tls.verifyProc",
+	);
+});
+
+test("badssl.com - certificate verify proc for trust on first use", async (t) => {
+	t.plan(5);
+
+	// trusts the first certificate seen for each host, and only that one after
+	const known = new Map([["expired.badssl.com", Buffer.from("not the certificate")]]);
+	const requests = [];
+	const agent = new Agent({
+		tls: { verifyProc: true },
+		pool: { maxIdlePerHost: 0 },
+	});
+	agent.setCertificateVerifyProc((request) => {
+		requests.push(request);
+		if (!known.has(request.hostname)) known.set(request.hostname, request.certificate);
+		return known.get(request.hostname).equals(request.certificate);
+	});
+
+	await (await faithFetch("https://self-signed.badssl.com/", { agent })).text();
+	const response = await faithFetch("https://self-signed.badssl.com/", { agent });
+	t.ok(response.ok, "Should trust the same certificate again");
+	t.equal(requests.length, 2, "Should be called for each new connection");
+	t.equal(requests[0].verified, false, "Should say the agent doesn't trust it");
+	t.ok(
+		requests[0].ocspResponse === undefined || Buffer.isBuffer(requests[0].ocspResponse),
+		"Should be given the stapled OCSP response if any",
+	);
+
+	try {
+		await faithFetch("https://expired.badssl.com/", { agent });
+		t.fail("Should throw for another certificate");
+	} catch (err) {
+		t.equal(err.code, "CertificateInvalid", "Should reject a changed certificate");
+	}
+});
+
+test("badssl.com - peer.tls reports OCSP stapling", async (t) => {
+	t.plan(5);
+
+	const plain = await faithFetch("https://badssl.com/");
+	t.equal(plain.peer.tls, undefined, "Should not be known with the client's TLS");
+
+	const agent = new Agent({ tls: { verifyProc: true } });
+	const response = await faithFetch("https://badssl.com/", { agent });
+	t.equal(typeof response.peer.tls?.ocspStapled, "boolean", "Should say if a response was stapled");
+	t.ok(
+		[undefined, "good", "revoked", "unknown"].includes(response.peer.tls.ocspStatus),
+		"Should give the status if stapled",
+	);
+	t.deepEqual(response.toJSON().peer.tls, response.peer.tls, "Should be in the snapshot");
+
+	t.throws(
+		() => new Agent({ tls: { requireOcspStapling: true } }).setCertificateVerifyProc(() => 0),
+		/tls.verifyProc/,
+		"Doesn't allow a verify proc without tls.verifyProc",
+	);
+});
+
+test("badssl.com - requireOcspStapling fails closed", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent({ tls: { requireOcspStapling: true } });
+	try {
+		await faithFetch("https://self-signed.badssl.com/", { agent });
+		t.fail("Should throw without a good stapled OCSP response");
+	} catch (err) {
+		t.equal(err.code, "CertificateInvalid", "Should throw CertificateInvalid");
+	}
+});
+
+test("badssl.com - tls.cryptoProvider picks the crypto library", async (t) => {
+	const { cryptoProviders } = capabilities();
+	t.plan(cryptoProviders.length + 1);
+
+	for (const cryptoProvider of cryptoProviders) {
+		const agent = new Agent({ tls: { cryptoProvider } });
+		const response = await faithFetch("https://badssl.com/", { agent });
+		t.ok(response.ok, `Should connect with ${cryptoProvider}`);
+	}
+
+	const missing = ["aws-lc-rs", "fips"].find((name) => !cryptoProviders.includes(name));
+	if (missing) {
+		t.throws(
+			() => new Agent({ tls: { cryptoProvider: missing } }),
+			/not enabled in this build/,
+			"Should throw for a provider which isn't built",
+		);
+	} else {
+		t.pass("All providers are built");
+	}
+});
+
+test("badssl.com - tls.sessionCache resumes sessions until flushed", async (t) => {
+	t.plan(6);
+
+	const agent = new Agent({ tls: { sessionCache: 16 }, pool: { maxIdlePerHost: 0 } });
+	const first = await faithFetch("https://badssl.com/", { agent });
+	t.equal(first.peer.tls?.resumed, false, "Should make a full handshake first");
+	const second = await faithFetch("https://badssl.com/", { agent });
+	t.equal(second.peer.tls?.resumed, true, "Should resume the session on a new connection");
+	t.equal(second.peer.tls?.ocspStapled, false, "Should report what this connection stapled");
+
+	agent.flushTlsSessions();
+	const third = await faithFetch("https://badssl.com/", { agent });
+	t.equal(third.peer.tls?.resumed, false, "Should make a full handshake after a flush");
+
+	const disabled = new Agent({ tls: { sessionCache: 0 }, pool: { maxIdlePerHost: 0 } });
+	await faithFetch("https://badssl.com/", { agent: disabled });
+	const again = await faithFetch("https://badssl.com/", { agent: disabled });
+	t.equal(again.peer.tls?.resumed, false, "Should not resume with the cache disabled");
+
+	t.throws(
+		() => new Agent().flushTlsSessions(),
+		/own TLS configuration/,
+		"Doesn't flush without the agent's own TLS configuration",
+	);
+});
@@ -0,0 +1,26 @@
+const test = require("tape");
+const { fetch: faithFetch, Agent } = require("../../wrapper.js");
+
+test("blockPrivateNetworks - refuses redirects to private addresses", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent({ blockPrivateNetworks: true });
+	try {
+		await faithFetch(
+			"https://httpbin.org/redirect-to?url=" +
+				encodeURIComponent("http://169.254.169.254/latest/meta-data/"),
+			{ agent },
+		);
+		t.fail("Should refuse the redirect");
+	} catch (err) {
+		t.equal(err.code, "ForbiddenAddress", "Should be a ForbiddenAddress");
+	}
+});
+
+test("blockPrivateNetworks - public addresses are allowed", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent({ blockPrivateNetworks: true });
+	const response = await faithFetch("https://httpbin.org/get", { agent });
+	t.equal(response.status, 200, "Should fetch from a public host");
+});
@@ -0,0 +1,26 @@
+const test = require("tape");
+const { fetch: faithFetch, Agent } = require("../../wrapper.js");
+
+const downgrade =
+	"https://httpbin.org/redirect-to?url=" +
+	encodeURIComponent("http://httpbin.org/get");
+
+test("tls.noDowngrade - refuses redirects from https to http", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ tls: { noDowngrade: true } });
+	try {
+		await faithFetch(downgrade, { agent });
+		t.fail("Should refuse the downgrade");
+	} catch (err) {
+		t.equal(err.code, "RedirectDowngrade", "Should be a RedirectDowngrade");
+		t.equal(err.name, "NetworkError", "Should be a NetworkError");
+	}
+});
+
+test("tls.noDowngrade - downgrades are followed by default", async (t) => {
+	t.plan(1);
+
+	const response = await faithFetch(downgrade);
+	t.equal(response.url, "http://httpbin.org/get", "Should follow the redirect");
+});
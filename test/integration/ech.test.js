@@ -0,0 +1,34 @@
+const test = require("tape");
+const { fetch: faithFetch, Agent, capabilities } = require("../../wrapper.js");
+
+const skip = !capabilities().ech;
+
+test("tls.ech - hides the server name from servers publishing ECH", { skip }, async (t) => {
+	t.plan(3);
+
+	const agent = new Agent({ tls: { ech: true } });
+	const response = await faithFetch("https://crypto.cloudflare.com/cdn-cgi/trace", {
+		agent,
+	});
+	const trace = await response.text();
+	t.ok(trace.includes("sni=encrypted"), "Should have sent the server name encrypted");
+	t.equal(response.peer.tls.echAccepted, true, "Should say ECH was accepted");
+
+	const plain = await faithFetch("https://badssl.com/", { agent });
+	t.equal(plain.peer.tls.echAccepted, false, "Should connect without ECH to other servers");
+});
+
+test("tls.ech - checks the configuration list", { skip }, async (t) => {
+	t.plan(2);
+
+	t.throws(
+		() => new Agent({ tls: { ech: { configList: Buffer.from("not a config") } } }),
+		/ECH/,
+		"Should throw on an invalid configList",
+	);
+	t.throws(
+		() => new Agent({ tls: { ech: { configList: "not base64!" } } }),
+		/configList/,
+		"Should throw on invalid base64",
+	);
+});
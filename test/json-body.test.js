@@ -0,0 +1,55 @@
+const test = require("tape");
+const { fetch, Agent } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("json: sends the value as a JSON body", async (t) => {
+	t.plan(2);
+
+	const value = { name: "faith", list: [1, 2.5, "three", null, true] };
+	const response = await fetch(url("/post"), { method: "POST", json: value });
+	const data = await response.json();
+	t.deepEqual(data.json, value, "should send the value as JSON");
+	t.equal(
+		data.headers["Content-Type"][0],
+		"application/json",
+		"should set the Content-Type",
+	);
+});
+
+test("json: keeps a given Content-Type", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/post"), {
+		method: "POST",
+		headers: { "Content-Type": "application/vnd.api+json" },
+		json: [],
+	});
+	const data = await response.json();
+	t.equal(
+		data.headers["Content-Type"][0],
+		"application/vnd.api+json",
+		"should not replace the Content-Type",
+	);
+});
+
+test("json: works with agent methods", async (t) => {
+	t.plan(1);
+
+	const agent = new Agent();
+	const response = await agent.put(url("/put"), undefined, {
+		json: { ok: true },
+	});
+	const data = await response.json();
+	t.deepEqual(data.json, { ok: true }, "should send the value as JSON");
+});
+
+test("json: can't be used with body", async (t) => {
+	t.plan(1);
+
+	try {
+		await fetch(url("/post"), { method: "POST", body: "{}", json: {} });
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, "Config", "should throw a Config error");
+	}
+});
@@ -0,0 +1,68 @@
+const http = require("node:http");
+const test = require("tape");
+const { fetch, Agent, ERROR_CODES } = require("../wrapper.js");
+
+// Counts requests, answering with a response cacheable for a minute which sets a cookie for as long.
+async function startServer() {
+	const server = http.createServer((_req, res) => {
+		server.hits++;
+		res.setHeader("Cache-Control", "max-age=60");
+		res.setHeader("Set-Cookie", "session=abc; Max-Age=60");
+		res.end("ok");
+	});
+	server.hits = 0;
+	await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+	return {
+		server,
+		url: `http://127.0.0.1:${server.address().port}/`,
+		close: () => new Promise((resolve) => server.close(resolve)),
+	};
+}
+
+test("mockClock: cached responses expire when the clock is advanced", async (t) => {
+	const { server, url, close } = await startServer();
+	try {
+		const agent = new Agent({ mockClock: true, cache: { store: "memory" } });
+
+		await (await fetch(url, { agent })).text();
+		await (await fetch(url, { agent })).text();
+		t.equal(server.hits, 1, "Second response is from the cache");
+
+		agent.advanceClock(61_000);
+		await (await fetch(url, { agent })).text();
+		t.equal(server.hits, 2, "Response expired after advancing the clock");
+	} finally {
+		await close();
+	}
+	t.end();
+});
+
+test("mockClock: cookies expire when the clock is advanced", async (t) => {
+	const { url, close } = await startServer();
+	try {
+		const agent = new Agent({ mockClock: true, cookies: true });
+
+		await (await fetch(url, { agent })).text();
+		t.equal(agent.getCookie(url), "session=abc", "Cookie is set");
+
+		agent.advanceClock(30_000);
+		t.equal(agent.getCookie(url), "session=abc", "Cookie is still there halfway");
+
+		agent.advanceClock(31_000);
+		t.equal(agent.getCookie(url), null, "Cookie expired after advancing the clock");
+	} finally {
+		await close();
+	}
+	t.end();
+});
+
+test("mockClock: advanceClock() needs a mock clock", (t) => {
+	t.plan(1);
+
+	try {
+		new Agent().advanceClock(1000);
+		t.fail("Should have thrown");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.Config, "Config error");
+	}
+});
@@ -0,0 +1,45 @@
+const test = require("tape");
+const { Agent, fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+test("notifyNetworkChange: new requests use new connections", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/", body: "ok" });
+
+		const agent = new Agent();
+		const ports = [];
+		agent.onConnect((event) => ports.push(event.localPort));
+
+		await (await fetch(server.url, { agent })).text();
+		await (await fetch(server.url, { agent })).text();
+		await new Promise((resolve) => setTimeout(resolve, 50));
+		t.equal(ports.length, 1, "Reuses the connection before the change");
+
+		agent.notifyNetworkChange();
+		await (await fetch(server.url, { agent })).text();
+		await new Promise((resolve) => setTimeout(resolve, 50));
+		t.equal(ports.length, 2, "Makes a new connection after the change");
+		t.notEqual(ports[0], ports[1], "From another local port");
+
+		agent.notifyNetworkChange();
+		await (await fetch(server.url, { agent })).text();
+		await new Promise((resolve) => setTimeout(resolve, 50));
+		t.equal(ports.length, 3, "And again after another change");
+	} finally {
+		server.close();
+	}
+});
+
+test("detectNetworkChanges: watches the system on supported platforms", (t) => {
+	if (["linux", "darwin", "win32"].includes(process.platform)) {
+		t.doesNotThrow(() => new Agent({ detectNetworkChanges: true }), "Starts watching");
+	} else {
+		t.throws(
+			() => new Agent({ detectNetworkChanges: true }),
+			/detectNetworkChanges/,
+			"Throws on unsupported platforms",
+		);
+	}
+	t.end();
+});
@@ -0,0 +1,96 @@
+const test = require("tape");
+const { setTimeout: sleep } = require("node:timers/promises");
+const { Agent, ERROR_CODES, fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+test("observe: events in order", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/", body: "hello" });
+
+		const agent = new Agent();
+		const events = [];
+		const before = Date.now();
+		const response = await fetch(`${server.url}/`, {
+			agent,
+			observe: (event) => events.push(event),
+		});
+		await response.text();
+		await sleep(50);
+
+		t.deepEqual(
+			events.map(({ type }) => type),
+			["queued", "connectStart", "connectEnd", "headersDone", "firstByte", "bodyDone"],
+			"Lifecycle of a request on a new connection",
+		);
+		const { host, port } = new URL(server.url);
+		t.deepEqual(events[2].addresses, [`${host}:${port}`], "Address connected to");
+		t.equal(events[3].status, 200, "Status with the headers");
+		t.ok(
+			events.every(({ time }, i) => time >= before && (i === 0 || time >= events[i - 1].time)),
+			"Times in order",
+		);
+
+		const reused = [];
+		await (await fetch(`${server.url}/`, { agent, observe: (event) => reused.push(event) })).text();
+		await sleep(50);
+		t.deepEqual(
+			reused.map(({ type }) => type),
+			["queued", "headersDone", "firstByte", "bodyDone"],
+			"No connection events on a reused connection",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("observe: streamed bodies and empty responses", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/", status: 204 });
+
+		const events = [];
+		await fetch(`${server.url}/`, {
+			method: "POST",
+			body: "hello",
+			uploadRateLimit: 1_000_000,
+			observe: (event) => events.push(event.type),
+		});
+		await sleep(50);
+		t.deepEqual(
+			events.filter((type) => !type.startsWith("connect")),
+			["queued", "requestSent", "headersDone", "bodyDone"],
+			"Body sent, and no body to read",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("observe: errors", async (t) => {
+	const events = [];
+	try {
+		await fetch("http://127.0.0.1:1/", { observe: (event) => events.push(event) });
+		t.fail("should throw");
+	} catch (error) {
+		await sleep(50);
+		const last = events.at(-1);
+		t.equal(events[0].type, "queued", "Queued first");
+		t.equal(last.type, "error", "Error last");
+		t.equal(last.code, error.code, "Error code");
+		t.equal(typeof last.message, "string", "Error message");
+	}
+
+	const invalid = [];
+	try {
+		await fetch("not a url", { observe: (event) => invalid.push(event) });
+		t.fail("should throw");
+	} catch (error) {
+		await sleep(50);
+		t.deepEqual(
+			invalid.map(({ type, code }) => [type, code]),
+			[["error", ERROR_CODES.InvalidUrl]],
+			"Errors before the request is queued",
+		);
+	}
+});
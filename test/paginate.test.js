@@ -0,0 +1,104 @@
+const test = require("tape");
+const { Agent, ERROR_CODES } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+async function collect(pages) {
+	const bodies = [];
+	for await (const page of pages) {
+		bodies.push(await page.text());
+	}
+	return bodies;
+}
+
+test("paginate: follows Link rel=next headers", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/items/1",
+			headers: { Link: '</items/2>; rel="next", </items/3>; rel="last"' },
+			body: "one",
+		});
+		server.route({
+			path: "/items/2",
+			headers: { Link: `<${server.url}/items/3>; rel="prev next"` },
+			body: "two",
+		});
+		server.route({ path: "/items/3", headers: { Link: '</items/2>; rel="prev"' }, body: "three" });
+
+		const agent = new Agent();
+		t.deepEqual(
+			await collect(agent.paginate(`${server.url}/items/1`)),
+			["one", "two", "three"],
+			"All pages, in order",
+		);
+		t.deepEqual(
+			await collect(agent.paginate(`${server.url}/items/1`, null, { maxPages: 2 })),
+			["one", "two"],
+			"maxPages stops early",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("paginate: nextFrom extracts the next URL from the body", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/a", body: JSON.stringify({ items: [1, 2], next: "/b" }) });
+		server.route({ path: "/b", body: JSON.stringify({ items: [3], next: null }) });
+
+		const items = [];
+		const pages = new Agent().paginate(`${server.url}/a`, null, {
+			nextFrom: async (response) => (await response.json()).next,
+		});
+		for await (const page of pages) {
+			items.push(...(await page.json()).items);
+		}
+		t.deepEqual(items, [1, 2, 3], "Items from every page");
+	} finally {
+		server.close();
+	}
+});
+
+test("paginate: rate-limited pages are retried", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/page", body: "ok" });
+		server.route({
+			path: "/page",
+			status: 429,
+			headers: { "Retry-After": "0" },
+			times: 2,
+		});
+
+		const agent = new Agent();
+		t.deepEqual(await collect(agent.paginate(`${server.url}/page`)), ["ok"], "Succeeds");
+		t.equal(server.requests().length, 3, "After two retries");
+
+		server.reset();
+		server.route({ path: "/page", status: 503, times: 2 });
+		const pages = agent.paginate(`${server.url}/page`, null, { retries: 1, retryDelay: 10 });
+		const statuses = [];
+		for await (const page of pages) statuses.push(page.status);
+		t.deepEqual(statuses, [503], "Gives up after the retries");
+	} finally {
+		server.close();
+	}
+});
+
+test("paginate: errors which aren't retriable are thrown", async (t) => {
+	const agent = new Agent();
+	try {
+		await collect(agent.paginate("not a url"));
+		t.fail("should throw");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.InvalidUrl, "InvalidUrl");
+	}
+
+	try {
+		await agent.paginate("http://127.0.0.1/", null, { nextFrom: "body" }).next();
+		t.fail("should throw");
+	} catch (error) {
+		t.ok(error instanceof TypeError, "Invalid nextFrom");
+	}
+});
@@ -0,0 +1,77 @@
+const test = require("tape");
+const { Agent, ERROR_CODES } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+async function rejection(promise) {
+	try {
+		await promise;
+	} catch (error) {
+		return error;
+	}
+	throw new Error("expected a rejection");
+}
+
+test("prefetch: the response is stored in the cache", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/next", headers: { "Cache-Control": "max-age=60" }, body: "next" });
+		const agent = new Agent({ cache: { store: "memory" } });
+
+		t.equal(await agent.prefetch(`${server.url}/next`), undefined, "Resolves without a response");
+		t.equal(server.requests().length, 1, "Fetched");
+
+		const response = await agent.get(`${server.url}/next`);
+		t.equal(await response.text(), "next", "Body");
+		t.equal(server.requests().length, 1, "Served from the cache");
+	} finally {
+		server.close();
+	}
+});
+
+test("prefetch: identical prefetches in flight are deduplicated", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/slow",
+			headers: { "Cache-Control": "max-age=60" },
+			body: "slow",
+			delay: 200,
+		});
+		const agent = new Agent({ baseUrl: server.url, cache: { store: "memory" } });
+
+		await Promise.all([
+			agent.prefetch(`${server.url}/slow`),
+			agent.prefetch("/slow"),
+			agent.prefetch(new URL(`${server.url}/slow`)),
+		]);
+		t.equal(server.requests().length, 1, "One request");
+	} finally {
+		server.close();
+	}
+});
+
+test("prefetch: concurrency is limited", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/a", body: "a", delay: 200 });
+		server.route({ path: "/b", body: "b", delay: 200 });
+		server.route({ path: "/c", body: "c", delay: 200 });
+		const agent = new Agent({ cache: { store: "memory", prefetchConcurrency: 1 } });
+
+		const start = Date.now();
+		await Promise.all(["/a", "/b", "/c"].map((path) => agent.prefetch(`${server.url}${path}`)));
+		t.ok(Date.now() - start >= 550, "One at a time");
+		t.equal(server.requests().length, 3, "Every URL is fetched");
+	} finally {
+		server.close();
+	}
+});
+
+test("prefetch: errors reject", async (t) => {
+	const cached = new Agent({ cache: { store: "memory" } });
+	const invalid = await rejection(cached.prefetch("not a url"));
+	t.equal(invalid.code, ERROR_CODES.InvalidUrl, "Invalid URL");
+
+	const uncached = await rejection(new Agent().prefetch("http://127.0.0.1/"));
+	t.equal(uncached.code, ERROR_CODES.Config, "Needs a cache store");
+});
@@ -0,0 +1,78 @@
+const test = require("tape");
+const { setTimeout: sleep } = require("node:timers/promises");
+const { Agent, ERROR_CODES } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+async function requested(server, path) {
+	for (let i = 0; i < 100; i++) {
+		if (server.requests().some((request) => request.path === path)) {
+			return true;
+		}
+		await sleep(20);
+	}
+	return false;
+}
+
+const CACHEABLE = { "Cache-Control": "max-age=60" };
+
+test("cache.preload: linked resources are fetched into the cache", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/page",
+			headers: {
+				Link: '</style.css>; rel=preload; as=style, </next>; rel="next", </app.js>; rel="preload modulepreload"',
+			},
+			body: "page",
+		});
+		server.route({ path: "/style.css", headers: CACHEABLE, body: "body {}" });
+		server.route({ path: "/app.js", headers: CACHEABLE, body: "run()" });
+
+		const agent = new Agent({ cache: { store: "memory", preload: true } });
+		await (await agent.get(`${server.url}/page`)).text();
+		t.ok(await requested(server, "/style.css"), "style.css is preloaded");
+		t.ok(await requested(server, "/app.js"), "app.js is preloaded");
+		t.notOk(server.requests().some(({ path }) => path === "/next"), "Other links are ignored");
+		// the preloads are stored once their bodies have been read
+		await sleep(100);
+
+		const count = server.requests().length;
+		t.equal(await (await agent.get(`${server.url}/style.css`)).text(), "body {}", "Body");
+		t.equal(server.requests().length, count, "Served from the cache");
+	} finally {
+		server.close();
+	}
+});
+
+test("cache.preload: other origins are skipped unless allowed", async (t) => {
+	const server = await startServer();
+	const other = await startServer();
+	try {
+		server.route({ path: "/page", headers: { Link: `<${other.url}/font.woff2>; rel=preload` } });
+		other.route({ path: "/font.woff2", headers: CACHEABLE, body: "font" });
+
+		const agent = new Agent({ cache: { store: "memory", preload: { concurrency: 1 } } });
+		await (await agent.get(`${server.url}/page`)).text();
+		await sleep(200);
+		t.equal(other.requests().length, 0, "Not preloaded");
+
+		const crossOrigin = new Agent({
+			cache: { store: "memory", preload: { crossOrigin: true } },
+		});
+		await (await crossOrigin.get(`${server.url}/page`)).text();
+		t.ok(await requested(other, "/font.woff2"), "Preloaded with crossOrigin");
+	} finally {
+		server.close();
+		other.close();
+	}
+});
+
+test("cache.preload: needs a cache store", (t) => {
+	try {
+		new Agent({ cache: { preload: true } });
+		t.fail("should throw");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.Config, "Config error");
+	}
+	t.end();
+});
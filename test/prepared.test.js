@@ -0,0 +1,101 @@
+const test = require("tape");
+const { Agent, ERROR_CODES, PreparedRequest, Response } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+test("prepare: sends the same request many times", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/poll", body: "ok" });
+		const agent = new Agent();
+		const request = agent.prepare(`${server.url}/poll`, {
+			headers: { "X-Poller": "yes" },
+		});
+		t.ok(request instanceof PreparedRequest, "Returns a PreparedRequest");
+		t.equal(request.method, "GET", "Method defaults to GET");
+		t.equal(request.url, `${server.url}/poll`, "Parsed URL");
+
+		for (let i = 0; i < 3; i++) {
+			const response = await request.send();
+			t.ok(response instanceof Response, "Resolves to a wrapped Response");
+			t.equal(await response.text(), "ok", "Body");
+		}
+
+		const requests = server.requests();
+		t.equal(requests.length, 3, "Sent three times");
+		t.ok(
+			requests.every(({ headers }) =>
+				headers.some(([name, value]) => name === "x-poller" && value === "yes"),
+			),
+			"Every send has the prepared headers",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("prepare: only the body varies between sends", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ method: "POST", path: "/ingest", status: 204 });
+		const agent = new Agent();
+		const request = agent.prepare(`${server.url}/ingest`, {
+			method: "post",
+			body: "default",
+		});
+		t.equal(request.method, "POST", "Method is normalised");
+
+		await request.send();
+		await request.send("one");
+		await request.send(new TextEncoder().encode("two"));
+
+		t.deepEqual(
+			server.requests().map(({ body }) => body.toString()),
+			["default", "one", "two"],
+			"Bodies",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("prepare: validation errors throw synchronously", (t) => {
+	const agent = new Agent();
+	try {
+		agent.prepare("not a url");
+		t.fail("Invalid URL should throw");
+	} catch (err) {
+		t.equal(err.code, ERROR_CODES.InvalidUrl, "Invalid URL");
+	}
+	try {
+		agent.prepare("http://127.0.0.1/", { method: "INV@LID" });
+		t.fail("Invalid method should throw");
+	} catch (err) {
+		t.equal(err.code, ERROR_CODES.InvalidMethod, "Invalid method");
+	}
+	try {
+		agent.prepare("http://127.0.0.1/", { headers: { "bad header": "x" } });
+		t.fail("Invalid header should throw");
+	} catch (err) {
+		t.equal(err.code, ERROR_CODES.InvalidHeader, "Invalid header");
+	}
+	t.end();
+});
+
+test("prepare: sends can be aborted", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/slow", delay: 2000 });
+		const request = new Agent().prepare(`${server.url}/slow`);
+		const controller = new AbortController();
+		const promise = request.send(null, { signal: controller.signal });
+		controller.abort();
+		try {
+			await promise;
+			t.fail("Send should be aborted");
+		} catch (err) {
+			t.equal(err.name, "AbortError", "AbortError");
+		}
+	} finally {
+		server.close();
+	}
+});
@@ -0,0 +1,89 @@
+const test = require("tape");
+const { Agent, AgentProfile, ERROR_CODES, FaithError, fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+async function rejection(promise) {
+	try {
+		await promise;
+	} catch (error) {
+		return error;
+	}
+	throw new Error("expected a rejection");
+}
+
+function header(request, name) {
+	return request.headers.find(([key]) => key === name)?.[1];
+}
+
+test("profile: json-api sends Accept and JSON bodies", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ method: "POST", path: "/items", status: 201 });
+		const agent = new Agent({ profile: "json-api" });
+		t.equal(agent.profile, AgentProfile.JsonApi, "profile getter");
+
+		const response = await agent.post(`${server.url}/items`, { name: "one", tags: [1, 2] });
+		t.equal(response.status, 201, "status");
+		await fetch(`${server.url}/items`, { agent, method: "POST", body: [1, 2, 3] });
+		await fetch(`${server.url}/items`, { agent, method: "POST", body: "raw" });
+
+		const [object, array, raw] = server.requests();
+		t.equal(header(object, "accept"), "application/json", "Accept header");
+		t.equal(header(object, "content-type"), "application/json", "Content-Type header");
+		t.deepEqual(JSON.parse(object.body), { name: "one", tags: [1, 2] }, "Object body");
+		t.deepEqual(JSON.parse(array.body), [1, 2, 3], "Array body");
+		t.equal(raw.body.toString(), "raw", "String bodies are sent as they are");
+	} finally {
+		server.close();
+	}
+});
+
+test("profile: json-api Accept can be overridden", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/" });
+		const agent = new Agent({ profile: "json-api" });
+		await agent.get(`${server.url}/`, { headers: { Accept: "text/csv" } });
+		t.equal(header(server.requests()[0], "accept"), "text/csv", "Request header wins");
+	} finally {
+		server.close();
+	}
+});
+
+test("profile: json-api rejects error statuses with the payload", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/missing",
+			status: 404,
+			headers: { "Content-Type": "application/json" },
+			body: JSON.stringify({ error: "not found" }),
+		});
+		server.route({ path: "/broken", status: 500, body: "oops" });
+		server.route({ path: "/empty", status: 503 });
+		const agent = new Agent({ profile: "json-api" });
+
+		const error = await rejection(agent.get(`${server.url}/missing`));
+		t.ok(error instanceof FaithError, "FaithError");
+		t.equal(error.code, ERROR_CODES.HttpStatus, "code");
+		t.equal(error.status, 404, "status");
+		t.deepEqual(error.body, { error: "not found" }, "JSON payload");
+
+		t.equal((await rejection(agent.get(`${server.url}/broken`))).body, "oops", "Text payload");
+		t.equal((await rejection(agent.get(`${server.url}/empty`))).body, null, "Empty payload");
+	} finally {
+		server.close();
+	}
+});
+
+test("profile: without a profile, error statuses resolve", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/missing", status: 404 });
+		const response = await fetch(`${server.url}/missing`);
+		t.equal(response.status, 404, "status");
+		t.notEqual(header(server.requests()[0], "accept"), "application/json", "no JSON Accept");
+	} finally {
+		server.close();
+	}
+});
@@ -0,0 +1,96 @@
+const test = require("tape");
+const { Agent, ERROR_CODES, fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+function streamOf(...chunks) {
+	return new ReadableStream({
+		start(controller) {
+			for (const chunk of chunks) controller.enqueue(new TextEncoder().encode(chunk));
+			controller.close();
+		},
+	});
+}
+
+test("replayBuffer: small streamed bodies follow 307 redirects", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/old", status: 307, headers: { Location: "/new" } });
+		server.route({ path: "/new", body: "moved" });
+
+		const response = await fetch(`${server.url}/old`, {
+			method: "POST",
+			body: streamOf("hello, ", "world"),
+			duplex: "half",
+		});
+		t.equal(response.status, 200, "Followed");
+		t.equal(await response.text(), "moved", "Redirected response");
+		t.deepEqual(
+			server.requests().map(({ path, body }) => [path, body]),
+			[
+				["/old", "hello, world"],
+				["/new", "hello, world"],
+			],
+			"Body sent both times",
+		);
+	} finally {
+		server.close();
+	}
+});
+
+test("replayBuffer: larger streamed bodies aren't sent again", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/old", status: 308, headers: { Location: "/new" } });
+		server.route({ path: "/new" });
+
+		for (const replayBuffer of [4, 0]) {
+			const agent = new Agent({ limits: { replayBuffer } });
+			try {
+				await fetch(`${server.url}/old`, {
+					method: "POST",
+					body: streamOf("hello, ", "world"),
+					duplex: "half",
+					agent,
+				});
+				t.fail("should throw");
+			} catch (error) {
+				t.equal(error.code, ERROR_CODES.BodyNotReplayable, `Error with ${replayBuffer}`);
+			}
+		}
+		t.deepEqual(
+			server.requests().map(({ path }) => path),
+			["/old", "/old"],
+			"Not redirected",
+		);
+
+		const agent = new Agent({ limits: { replayBuffer: 4 } });
+		const response = await fetch(`${server.url}/new`, {
+			method: "POST",
+			body: streamOf("hello, ", "world"),
+			duplex: "half",
+			agent,
+		});
+		t.equal(response.status, 200, "Streamed without redirects");
+		t.equal(server.requests().at(-1).body, "hello, world", "Whole body");
+	} finally {
+		server.close();
+	}
+});
+
+test("replayBuffer: other redirect modes are left alone", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/old", status: 307, headers: { Location: "/new" } });
+
+		const agent = new Agent({ redirect: "stop", limits: { replayBuffer: 0 } });
+		const response = await fetch(`${server.url}/old`, {
+			method: "POST",
+			body: streamOf("hello"),
+			duplex: "half",
+			agent,
+		});
+		t.equal(response.status, 307, "Redirect returned");
+	} finally {
+		server.close();
+	}
+});
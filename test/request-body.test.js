@@ -0,0 +1,65 @@
+const test = require("tape");
+const { fetch } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+const encoder = new TextEncoder();
+
+async function echo(body, headers) {
+	const response = await fetch(url("/post"), { method: "POST", body, headers });
+	return response.json();
+}
+
+test("body: sends a Uint8Array subarray from its offset", async (t) => {
+	t.plan(1);
+
+	const bytes = encoder.encode("skip-hello-skip").subarray(5, 10);
+	t.equal((await echo(bytes)).data, "hello", "should send only the view");
+});
+
+test("body: sends a DataView from its offset", async (t) => {
+	t.plan(1);
+
+	const { buffer } = encoder.encode("xxworldxx");
+	const view = new DataView(buffer, 2, 5);
+	t.equal((await echo(view)).data, "world", "should send only the view");
+});
+
+test("body: sends other typed array kinds as their bytes", async (t) => {
+	t.plan(1);
+
+	const { buffer } = encoder.encode("..abcdef..");
+	const view = new Uint16Array(buffer, 2, 3);
+	t.equal((await echo(view)).data, "abcdef", "should send the view's bytes");
+});
+
+test("body: sends an ArrayBuffer", async (t) => {
+	t.plan(1);
+
+	const { buffer } = encoder.encode("whole");
+	t.equal((await echo(buffer)).data, "whole", "should send the buffer");
+});
+
+test("body: sends a Blob with its type as Content-Type", async (t) => {
+	t.plan(2);
+
+	const blob = new Blob(["<p>hi</p>"], { type: "text/html" });
+	const data = await echo(blob);
+	t.equal(data.data, "<p>hi</p>", "should send the blob");
+	t.equal(
+		data.headers["Content-Type"][0],
+		"text/html",
+		"should use the blob's type",
+	);
+});
+
+test("body: a given Content-Type overrides the Blob type", async (t) => {
+	t.plan(1);
+
+	const blob = new Blob(["hi"], { type: "text/html" });
+	const data = await echo(blob, { "Content-Type": "text/plain" });
+	t.equal(
+		data.headers["Content-Type"][0],
+		"text/plain",
+		"should keep the given Content-Type",
+	);
+});
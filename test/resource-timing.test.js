@@ -0,0 +1,75 @@
+const test = require("tape");
+const { setTimeout: sleep } = require("node:timers/promises");
+const { Agent, fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+function entriesFor(url) {
+	return performance.getEntriesByType("resource").filter(({ name }) => name === url);
+}
+
+test("resourceTiming: entries in the performance timeline", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/data", body: "hello" });
+		server.route({ path: "/old", status: 302, headers: { Location: "/data" } });
+		server.route({ path: "/empty", status: 204 });
+
+		const agent = new Agent();
+		const url = `${server.url}/data`;
+		const response = await fetch(url, { agent });
+		await sleep(50);
+		t.equal(entriesFor(url).length, 0, "No entry before the body is read");
+
+		await response.text();
+		await sleep(50);
+		const [entry] = entriesFor(url);
+		t.ok(entry, "Entry once the body is read");
+		t.equal(entry.initiatorType, "fetch", "initiatorType");
+		t.equal(entry.encodedBodySize, 5, "Body size");
+		t.ok(entry.connectEnd >= entry.connectStart, "New connection");
+		t.ok(entry.responseStart >= entry.requestStart, "Response after request");
+		t.ok(entry.responseEnd >= entry.responseStart, "Body after headers");
+
+		await (await fetch(`${server.url}/old`, { agent })).text();
+		await fetch(`${server.url}/empty`, { agent });
+		await agent.json(url);
+		await sleep(50);
+		t.equal(entriesFor(`${server.url}/old`).length, 1, "Named after the requested URL");
+		t.equal(entriesFor(`${server.url}/empty`).length, 1, "Entry for an empty body");
+		t.equal(entriesFor(url).length, 2, "Entry for agent.json()");
+	} finally {
+		server.close();
+	}
+});
+
+test("resourceTiming: can be turned off", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/", body: "hello" });
+
+		const agent = new Agent({ resourceTiming: false });
+		await (await fetch(`${server.url}/`, { agent })).text();
+		await sleep(50);
+		t.equal(entriesFor(`${server.url}/`).length, 0, "No entry");
+	} finally {
+		server.close();
+	}
+});
+
+test("resourceTiming: entries for the agent's shorthands", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/get", body: "hello" });
+		server.route({ path: "/post", method: "POST", body: "posted" });
+
+		const agent = new Agent();
+		await (await agent.get(`${server.url}/get`)).text();
+		await (await agent.post(`${server.url}/post`, "data")).text();
+		await (await agent.fetch(`${server.url}/get`)).text();
+		await sleep(50);
+		t.equal(entriesFor(`${server.url}/get`).length, 2, "Entries for agent.get() and agent.fetch()");
+		t.equal(entriesFor(`${server.url}/post`).length, 1, "Entry for agent.post()");
+	} finally {
+		server.close();
+	}
+});
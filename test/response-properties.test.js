@@ -27,3 +27,36 @@ test("Test response properties", async (t) => {
     "body should be an object (property)",
   );
 });
+
+test("Test response httpVersion property", async (t) => {
+  t.plan(4);
+
+  const response = await faithFetch(url("/get"));
+  const { major, minor, alpn } = response.httpVersion;
+
+  t.equal(major, 1, "major should be 1 over plaintext");
+  t.equal(minor, 1, "minor should be 1 over plaintext");
+  t.equal(alpn, "http/1.1", "alpn should be http/1.1");
+  t.equal(response.version, "HTTP/1.1", "version string should be kept");
+});
+
+test("Test response timestamps property", async (t) => {
+  t.plan(6);
+
+  const before = Date.now();
+  const response = await faithFetch(url("/delay/1"));
+  const headers = response.timestamps;
+
+  t.ok(headers.requestStart >= before - 1, "requestStart should be first");
+  t.ok(
+    headers.headersComplete - headers.requestStart >= 900,
+    "headersComplete should include the server's delay",
+  );
+  t.equal(headers.firstByte, undefined, "firstByte is unknown at first");
+  t.equal(headers.bodyComplete, undefined, "bodyComplete is unknown at first");
+
+  await response.text();
+  const { firstByte, bodyComplete } = response.timestamps;
+  t.ok(firstByte >= headers.headersComplete, "firstByte is after headers");
+  t.ok(bodyComplete >= firstByte, "bodyComplete is after firstByte");
+});
@@ -0,0 +1,57 @@
+const { execFileSync } = require("node:child_process");
+const path = require("node:path");
+const test = require("tape");
+const { fetch: faithFetch, Agent, configureRuntime } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("concurrency is not capped by the libuv threadpool", async (t) => {
+	t.plan(2);
+
+	// the libuv threadpool has 4 threads by default, so with one blocked per request this would take
+	// at least three rounds of one second
+	const agent = new Agent();
+	const start = Date.now();
+	const responses = await Promise.all(
+		Array.from({ length: 12 }, () => faithFetch(url("/delay/1"), { agent })),
+	);
+
+	t.ok(
+		responses.every((response) => response.ok),
+		"all requests should succeed",
+	);
+	t.ok(
+		Date.now() - start < 2500,
+		"requests should all be in flight at the same time",
+	);
+});
+
+test("configureRuntime() throws once the runtime has started", async (t) => {
+	t.plan(2);
+
+	await faithFetch(url("/get"));
+	try {
+		configureRuntime({ workerThreads: 2 });
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(error.code, "Config", "should throw a Config error");
+		t.ok(
+			error.message.includes("already started"),
+			"should explain that the runtime has started",
+		);
+	}
+});
+
+test("configureRuntime() configures the runtime before first use", (t) => {
+	t.plan(1);
+
+	// run in a separate process, as the runtime in this one has already started
+	const script = `
+		const { configureRuntime, fetch } = require(${JSON.stringify(path.join(__dirname, "../wrapper.js"))});
+		configureRuntime({ workerThreads: 1, maxBlockingThreads: 2, threadNamePrefix: "test" });
+		fetch(${JSON.stringify(url("/get"))}).then((res) => process.stdout.write(String(res.status)));
+	`;
+	const output = execFileSync(process.execPath, ["-e", script], {
+		encoding: "utf8",
+	});
+	t.equal(output, "200", "requests should work on the configured runtime");
+});
@@ -0,0 +1,99 @@
+const { execFileSync } = require("node:child_process");
+const path = require("node:path");
+const test = require("tape");
+const { url } = require("./helpers.js");
+
+// shutdown is final for the thread, so each test runs in a separate process
+function run(script) {
+	const output = execFileSync(
+		process.execPath,
+		[
+			"-e",
+			`
+				const faith = require(${JSON.stringify(path.join(__dirname, "../wrapper.js"))});
+				const url = (p) => ${JSON.stringify(url(""))} + p;
+				const results = [];
+				(async () => { ${script} })().then(() => process.stdout.write(JSON.stringify(results)));
+			`,
+		],
+		{ encoding: "utf8", timeout: 10000 },
+	);
+	return JSON.parse(output);
+}
+
+test("faithShutdown() waits for in-flight requests to complete", (t) => {
+	t.plan(1);
+
+	const results = run(`
+		const pending = faith.fetch(url("/delay/1")).then((res) => res.text()).then(() => "done");
+		await new Promise((resolve) => setTimeout(resolve, 100));
+		await faith.faithShutdown({ drainMs: 5000 });
+		results.push(await pending);
+	`);
+	t.deepEqual(results, ["done"], "request should complete during the drain");
+});
+
+test("faithShutdown() aborts requests still in flight after drainMs", (t) => {
+	t.plan(1);
+
+	const results = run(`
+		const pending = faith.fetch(url("/delay/5")).catch((err) => err.code);
+		await new Promise((resolve) => setTimeout(resolve, 100));
+		const start = Date.now();
+		await faith.faithShutdown({ drainMs: 200 });
+		results.push(await pending, Date.now() - start < 3000);
+	`);
+	t.deepEqual(
+		results,
+		["Aborted", true],
+		"request should be aborted once the deadline passes",
+	);
+});
+
+test("faithShutdown() rejects new requests", (t) => {
+	t.plan(1);
+
+	const results = run(`
+		await faith.fetch(url("/get"));
+		const shutdown = faith.faithShutdown();
+		results.push(await faith.fetch(url("/get")).catch((err) => err.code));
+		await shutdown;
+		results.push(await faith.fetch(url("/get")).catch((err) => err.code));
+		await faith.faithShutdown();
+	`);
+	t.deepEqual(
+		results,
+		["RuntimeThread", "RuntimeThread"],
+		"requests during and after shutdown should fail",
+	);
+});
+
+test("faithShutdown() in a worker doesn't affect other threads", (t) => {
+	t.plan(1);
+
+	const results = run(`
+		const { Worker } = require("node:worker_threads");
+		const { once } = require("node:events");
+		const worker = new Worker(
+			\`
+				const { parentPort } = require("node:worker_threads");
+				const faith = require(${JSON.stringify(path.join(__dirname, "../wrapper.js"))});
+				faith.fetch(${JSON.stringify(url("/get"))})
+					.then(() => faith.faithShutdown())
+					.then(() => faith.fetch(${JSON.stringify(url("/get"))}))
+					.catch((err) => err.code)
+					.then((code) => parentPort.postMessage(code));
+			\`,
+			{ eval: true },
+		);
+		const [code] = await once(worker, "message");
+		await worker.terminate();
+		results.push(code, (await faith.fetch(url("/get"))).status);
+		await faith.faithShutdown();
+	`);
+	t.deepEqual(
+		results,
+		["RuntimeThread", 200],
+		"the worker should be shut down and the main thread not",
+	);
+});
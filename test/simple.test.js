@@ -120,3 +120,29 @@ test("fetch() rejects invalid URL", async (t) => {
     );
   }
 });
+
+test("simple: fetch() accepts a URL object and null options", async (t) => {
+  t.plan(2);
+
+  const response = await fetch(new URL(url("/get")), null);
+  t.equal(response.status, 200, "should return status 200");
+  t.equal(response.url, url("/get"), "url should match the URL object");
+});
+
+test("simple: native faithFetch() works without options", async (t) => {
+  t.plan(1);
+
+  const response = await native.faithFetch(url("/get"));
+  t.equal(response.status, 200, "should return status 200");
+});
+
+test("fetch() rejects non-object options", async (t) => {
+  t.plan(1);
+
+  try {
+    await fetch(url("/get"), "GET");
+    t.fail("Should have thrown TypeError for string options");
+  } catch (error) {
+    t.equal(error.constructor.name, "TypeError", "should throw TypeError");
+  }
+});
@@ -0,0 +1,49 @@
+const test = require("tape");
+const { mkdtempSync, readdirSync, rmSync } = require("node:fs");
+const { tmpdir } = require("node:os");
+const { join } = require("node:path");
+const { fetch, Agent } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("spill: clones read the body back from a temporary file", async (t) => {
+	t.plan(3);
+
+	const dir = mkdtempSync(join(tmpdir(), "faith-spill-test-"));
+	try {
+		const agent = new Agent({ spill: { dir, threshold: 1024 } });
+		const response = await fetch(url("/bytes/100000?seed=7"), { agent });
+		const clone = response.clone();
+
+		const original = await response.bytes();
+		const files = readdirSync(dir);
+		t.equal(files.length, 1, "should write the rest of the body to a file");
+
+		const cloned = await clone.bytes();
+		t.equal(original.length, 100000, "should read the whole body");
+		t.ok(original.equals(cloned), "clone should read the same body");
+	} finally {
+		rmSync(dir, { recursive: true, force: true });
+	}
+});
+
+test("spill: bodies under the threshold stay in memory", async (t) => {
+	t.plan(2);
+
+	const dir = mkdtempSync(join(tmpdir(), "faith-spill-test-"));
+	try {
+		const agent = new Agent({ spill: { dir } });
+		const response = await fetch(url("/bytes/1000?seed=7"), { agent });
+		const clones = [response.clone(), response.clone()];
+
+		const original = await response.bytes();
+		t.equal(readdirSync(dir).length, 0, "should not write a file");
+
+		const cloned = await Promise.all(clones.map((clone) => clone.bytes()));
+		t.ok(
+			cloned.every((bytes) => original.equals(bytes)),
+			"clones should read the same body",
+		);
+	} finally {
+		rmSync(dir, { recursive: true, force: true });
+	}
+});
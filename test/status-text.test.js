@@ -0,0 +1,58 @@
+const http = require("node:http");
+const test = require("tape");
+const { fetch, Agent } = require("../wrapper.js");
+
+// Answers with the status message given in the `message` query parameter, if any.
+async function startServer() {
+	const server = http.createServer((req, res) => {
+		const message = new URL(req.url, "http://localhost").searchParams.get("message");
+		if (message !== null) res.statusMessage = message;
+		res.end("ok");
+	});
+	await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+	return {
+		url: `http://127.0.0.1:${server.address().port}/`,
+		close: () => new Promise((resolve) => server.close(resolve)),
+	};
+}
+
+test("statusText: custom reason phrases are kept", async (t) => {
+	const { url, close } = await startServer();
+	try {
+		const response = await fetch(`${url}?message=${encodeURIComponent("Très Bien")}`, {
+			version: "http/1.1",
+		});
+		t.equal(response.status, 200, "status");
+		t.equal(response.statusText, "Très Bien", "statusText is the reason phrase");
+		t.deepEqual(
+			response.reasonPhrase,
+			Buffer.from("Très Bien", "latin1"),
+			"reasonPhrase has the raw bytes",
+		);
+	} finally {
+		await close();
+	}
+});
+
+test("statusText: canonical reason phrases are exposed too", async (t) => {
+	const { url, close } = await startServer();
+	try {
+		const response = await fetch(url);
+		t.equal(response.statusText, "OK", "statusText");
+		t.deepEqual(response.reasonPhrase, Buffer.from("OK"), "reasonPhrase");
+	} finally {
+		await close();
+	}
+});
+
+test("statusText: http1.canonicalStatusText ignores the reason phrase", async (t) => {
+	const { url, close } = await startServer();
+	try {
+		const agent = new Agent({ http1: { canonicalStatusText: true } });
+		const response = await fetch(`${url}?message=Fine`, { agent });
+		t.equal(response.statusText, "OK", "statusText is canonical");
+		t.deepEqual(response.reasonPhrase, Buffer.from("Fine"), "reasonPhrase is still raw");
+	} finally {
+		await close();
+	}
+});
@@ -0,0 +1,45 @@
+const test = require("tape");
+const { fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+test("suggestedFilename: from Content-Disposition", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/plain",
+			headers: { "Content-Disposition": 'attachment; filename="report 2024.pdf"' },
+		});
+		server.route({
+			path: "/encoded",
+			headers: {
+				"Content-Disposition":
+					"attachment; filename=\"fallback.txt\"; filename*=UTF-8''%E2%82%AC%20rates.txt",
+			},
+		});
+		server.route({
+			path: "/unsafe",
+			headers: { "Content-Disposition": 'attachment; filename="../../.bashrc"' },
+		});
+
+		const name = async (path) => (await fetch(`${server.url}${path}`)).suggestedFilename();
+		t.equal(await name("/plain"), "report 2024.pdf", "Quoted filename");
+		t.equal(await name("/encoded"), "€ rates.txt", "filename* is preferred and decoded");
+		t.equal(await name("/unsafe"), "bashrc", "Sanitized");
+	} finally {
+		server.close();
+	}
+});
+
+test("suggestedFilename: falls back to the URL", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/files/my%20data.csv" });
+		server.route({ path: "/", headers: { "Content-Disposition": "inline" } });
+
+		const response = await fetch(`${server.url}/files/my%20data.csv?download=1`);
+		t.equal(response.suggestedFilename(), "my data.csv", "Last path segment");
+		t.equal((await fetch(`${server.url}/`)).suggestedFilename(), null, "null without a name");
+	} finally {
+		server.close();
+	}
+});
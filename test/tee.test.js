@@ -0,0 +1,71 @@
+const test = require("tape");
+const { createHash } = require("node:crypto");
+const { fetch, ERROR_CODES } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("tee: each response reads the whole body", async (t) => {
+	t.plan(4);
+
+	const response = await fetch(url("/stream-bytes/100000?seed=1"));
+	const [first, second] = response.tee();
+
+	const [hash, bytes] = await Promise.all([
+		(async () => {
+			const hash = createHash("sha256");
+			for await (const chunk of first.chunks()) {
+				hash.update(chunk);
+			}
+			return hash.digest("hex");
+		})(),
+		second.bytes(),
+	]);
+
+	t.equal(bytes.length, 100000, "should read the whole body");
+	t.equal(
+		hash,
+		createHash("sha256").update(bytes).digest("hex"),
+		"both should read the same body",
+	);
+	t.equal(first.status, response.status, "should keep the status");
+	t.ok(response.bodyUsed, "should use the original body");
+});
+
+test("tee: splits into the given number of responses", async (t) => {
+	t.plan(2);
+
+	const response = await fetch(url("/get"));
+	const responses = response.tee(3);
+	t.equal(responses.length, 3, "should return three responses");
+
+	const texts = await Promise.all(responses.map((r) => r.text()));
+	t.ok(
+		texts.every((text) => text === texts[0]),
+		"should all read the same body",
+	);
+});
+
+test("tee: discarded responses don't hold up the others", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/stream-bytes/200000?chunk_size=1000"));
+	const [kept, discarded] = response.tee();
+	const [bytes] = await Promise.all([kept.bytes(), discarded.discard()]);
+	t.equal(bytes.length, 200000, "should read the whole body");
+});
+
+test("tee: throws if the body was already used", async (t) => {
+	t.plan(1);
+
+	const response = await fetch(url("/get"));
+	await response.text();
+	try {
+		response.tee();
+		t.fail("should have thrown");
+	} catch (error) {
+		t.equal(
+			error.code,
+			ERROR_CODES.ResponseAlreadyDisturbed,
+			"should be ResponseAlreadyDisturbed",
+		);
+	}
+});
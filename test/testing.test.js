@@ -0,0 +1,137 @@
+const test = require("tape");
+const { fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+test("testing: routes answer requests, which are recorded", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			method: "post",
+			path: "/echo",
+			status: 201,
+			headers: { "X-Test": "yes" },
+			body: "created",
+		});
+
+		const response = await fetch(`${server.url}/echo?a=1`, {
+			method: "POST",
+			body: "hello",
+		});
+		t.equal(response.status, 201, "Route status");
+		t.equal(response.headers.get("x-test"), "yes", "Route header");
+		t.equal(await response.text(), "created", "Route body");
+
+		const [request] = server.requests();
+		t.equal(request.method, "POST", "Recorded method");
+		t.equal(request.path, "/echo?a=1", "Recorded path with query");
+		t.equal(request.version, "http/1.1", "Recorded version");
+		t.equal(request.body.toString(), "hello", "Recorded body");
+	} finally {
+		server.close();
+	}
+});
+
+test("testing: unmatched requests get a 404", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ method: "GET", path: "/only-get" });
+		t.equal((await fetch(`${server.url}/elsewhere`)).status, 404, "Unknown path");
+		t.equal(
+			(await fetch(`${server.url}/only-get`, { method: "DELETE" })).status,
+			404,
+			"Other method",
+		);
+		t.equal(server.requests().length, 2, "Unmatched requests are recorded");
+	} finally {
+		server.close();
+	}
+});
+
+test("testing: times limits a route, falling back to earlier ones", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/flaky", body: "ok" });
+		server.route({ path: "/flaky", status: 503, times: 2 });
+
+		const statuses = [];
+		for (let i = 0; i < 3; i++) {
+			statuses.push((await fetch(`${server.url}/flaky`)).status);
+		}
+		t.deepEqual(statuses, [503, 503, 200], "Fails twice, then succeeds");
+	} finally {
+		server.close();
+	}
+});
+
+test("testing: delay holds the response headers back", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/slow", delay: 200 });
+		const start = Date.now();
+		await fetch(`${server.url}/slow`);
+		t.ok(Date.now() - start >= 190, "Response took at least the delay");
+	} finally {
+		server.close();
+	}
+});
+
+test("testing: disconnectAfter breaks off the body", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/cut",
+			body: Buffer.alloc(1024, "x"),
+			chunkSize: 128,
+			disconnectAfter: 256,
+		});
+
+		const response = await fetch(`${server.url}/cut`);
+		t.equal(response.status, 200, "Headers arrive");
+		try {
+			await response.arrayBuffer();
+			t.fail("Reading the body should fail");
+		} catch (err) {
+			t.ok(err instanceof Error, `Body read fails: ${err.message}`);
+		}
+	} finally {
+		server.close();
+	}
+});
+
+test("testing: reset clears routes and requests", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/" });
+		await fetch(`${server.url}/`);
+		server.reset();
+		t.equal(server.requests().length, 0, "Requests are forgotten");
+		t.equal((await fetch(`${server.url}/`)).status, 404, "Routes are removed");
+	} finally {
+		server.close();
+	}
+});
+
+test("testing: serves HTTP/2 with prior knowledge", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/h2", body: "two" });
+		const response = await fetch(`${server.url}/h2`, { version: "h2" });
+		t.equal(await response.text(), "two", "Body over HTTP/2");
+		t.equal(server.requests()[0].version, "h2", "Recorded as HTTP/2");
+	} finally {
+		server.close();
+	}
+});
+
+test("testing: invalid routes throw", async (t) => {
+	const server = await startServer();
+	try {
+		t.throws(() => server.route({ path: "/", status: 1000 }), "Invalid status");
+		t.throws(
+			() => server.route({ path: "/", headers: { "bad header": "x" } }),
+			"Invalid header name",
+		);
+	} finally {
+		server.close();
+	}
+});
@@ -0,0 +1,85 @@
+const test = require("tape");
+const { Agent, ERROR_CODES, FaithHttpError, fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+async function rejection(promise) {
+	try {
+		await promise;
+	} catch (error) {
+		return error;
+	}
+	throw new Error("expected a rejection");
+}
+
+test("throwOnError: error statuses reject with an HttpError", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/teapot",
+			status: 418,
+			headers: { "X-Reason": "short and stout" },
+			body: "I'm a teapot",
+		});
+
+		const error = await rejection(fetch(`${server.url}/teapot`, { throwOnError: true }));
+		t.ok(error instanceof FaithHttpError, "instanceof FaithHttpError");
+		t.equal(error.name, "HttpError", "name");
+		t.equal(error.code, ERROR_CODES.HttpStatus, "code");
+		t.equal(error.status, 418, "status");
+		t.equal(error.statusText, "I'm a teapot", "statusText");
+		t.equal(error.headers.get("x-reason"), "short and stout", "headers");
+		t.equal(error.bodyPreview, "I'm a teapot", "bodyPreview");
+		t.equal(error.body, undefined, "no body without the json-api profile");
+	} finally {
+		server.close();
+	}
+});
+
+test("throwOnError: the body preview is size-limited", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/big", status: 500, body: "x".repeat(100_000) });
+		const error = await rejection(fetch(`${server.url}/big`, { throwOnError: true }));
+		t.equal(error.bodyPreview.length, 1024, "first KiB");
+
+		server.route({ path: "/empty", status: 500 });
+		const empty = await rejection(fetch(`${server.url}/empty`, { throwOnError: true }));
+		t.equal(empty.bodyPreview, null, "null when empty");
+	} finally {
+		server.close();
+	}
+});
+
+test("throwOnError: successful responses resolve", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/ok", body: "fine" });
+		const response = await fetch(`${server.url}/ok`, { throwOnError: true });
+		t.equal(await response.text(), "fine", "body");
+	} finally {
+		server.close();
+	}
+});
+
+test("throwOnError: agent default, overridable per request", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/missing", status: 404 });
+		const agent = new Agent({ throwOnError: true });
+
+		const error = await rejection(agent.get(`${server.url}/missing`));
+		t.equal(error.status, 404, "agent default rejects");
+
+		const response = await agent.get(`${server.url}/missing`, { throwOnError: false });
+		t.equal(response.status, 404, "request option resolves");
+
+		const profiled = new Agent({ profile: "json-api", throwOnError: false });
+		t.equal(
+			(await profiled.get(`${server.url}/missing`)).status,
+			404,
+			"json-api profile can opt out",
+		);
+	} finally {
+		server.close();
+	}
+});
@@ -1,6 +1,7 @@
 const { url } = require("./helpers.js");
+const net = require("node:net");
 const test = require("tape");
-const { fetch } = require("../wrapper.js");
+const { Agent, fetch } = require("../wrapper.js");
 
 test("timeout: request times out when too short", async (t) => {
   t.plan(2);
@@ -47,3 +48,155 @@ test("timeout: works with POST requests", async (t) => {
     t.fail(`Unexpected error: ${error.message}`);
   }
 });
+
+test("timeout: errors are TimeoutErrors saying which timeout fired", async (t) => {
+  t.plan(4);
+
+  try {
+    await fetch(url("/delay/2"), { timeout: 200 });
+    t.fail("Should have timed out");
+  } catch (error) {
+    t.equal(error.name, "TimeoutError", "should be a TimeoutError");
+    t.equal(error.cause, "timeout", "cause should be the request timeout");
+  }
+
+  const agent = new Agent({ timeout: { total: 200 } });
+  try {
+    await fetch(url("/delay/2"), { agent });
+    t.fail("Should have timed out");
+  } catch (error) {
+    t.equal(error.name, "TimeoutError", "should be a TimeoutError");
+    t.equal(
+      error.cause,
+      "agent.timeout.total",
+      "cause should be the agent's total timeout",
+    );
+  }
+});
+
+test("timeout: AbortSignal.timeout() gives a TimeoutError", async (t) => {
+  t.plan(2);
+
+  try {
+    await fetch(url("/delay/2"), { signal: AbortSignal.timeout(200) });
+    t.fail("Should have timed out");
+  } catch (error) {
+    t.equal(error.name, "TimeoutError", "should be a TimeoutError");
+    t.equal(error.code, "Timeout", "should have the Timeout code");
+  }
+});
+
+test("timeout: combined signals say which one fired", async (t) => {
+  t.plan(3);
+
+  const controller = new AbortController();
+  const signal = AbortSignal.any([controller.signal, AbortSignal.timeout(5000)]);
+  setTimeout(() => controller.abort("cancelled"), 200);
+
+  try {
+    await fetch(url("/delay/2"), { signal, timeout: 5000 });
+    t.fail("Should have been aborted");
+  } catch (error) {
+    t.equal(error.name, "AbortError", "should be an AbortError");
+    t.equal(error.code, "Aborted", "should have the Aborted code");
+    t.equal(error.cause, "cancelled", "cause should be the reason");
+  }
+});
+
+test("timeouts: a shorter read timeout replaces the agent's", async (t) => {
+  t.plan(2);
+
+  const agent = new Agent({ timeout: { read: 10000 } });
+  try {
+    await fetch(url("/delay/2"), { agent, timeouts: { read: 200 } });
+    t.fail("Should have timed out");
+  } catch (error) {
+    t.equal(error.code, "Timeout", "should be a timeout error");
+    t.equal(
+      error.cause,
+      "timeouts.read",
+      "cause should be the request's read timeout",
+    );
+  }
+});
+
+test("timeouts: a longer read timeout replaces the agent's", async (t) => {
+  t.plan(1);
+
+  const agent = new Agent({ timeout: { read: 200 } });
+  const response = await fetch(url("/delay/1"), {
+    agent,
+    timeouts: { read: 5000 },
+  });
+  t.equal(response.status, 200, "should complete successfully");
+});
+
+test("timeouts: total takes precedence over timeout", async (t) => {
+  t.plan(2);
+
+  try {
+    await fetch(url("/delay/2"), { timeout: 5000, timeouts: { total: 200 } });
+    t.fail("Should have timed out");
+  } catch (error) {
+    t.equal(error.code, "Timeout", "should be a timeout error");
+    t.equal(
+      error.cause,
+      "timeouts.total",
+      "cause should be the request's total timeout",
+    );
+  }
+});
+
+test("timeouts: connect times out stalled handshakes", async (t) => {
+  t.plan(2);
+
+  // accepts connections but never answers the TLS handshake
+  const server = net.createServer(() => {});
+  await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+  const { port } = server.address();
+
+  try {
+    await fetch(`https://127.0.0.1:${port}/`, {
+      timeouts: { connect: 200 },
+    });
+    t.fail("Should have timed out");
+  } catch (error) {
+    t.equal(error.code, "Timeout", "should be a timeout error");
+    t.equal(
+      error.cause,
+      "timeouts.connect",
+      "cause should be the request's connect timeout",
+    );
+  } finally {
+    server.close();
+  }
+});
+
+test("timeouts: body limits reading the whole body", async (t) => {
+  t.plan(2);
+
+  const response = await fetch(url("/drip?duration=2&numbytes=10&delay=0"), {
+    timeouts: { body: 300 },
+  });
+  t.equal(response.status, 200, "headers should arrive in time");
+
+  try {
+    await response.text();
+    t.fail("Should have timed out");
+  } catch (error) {
+    t.ok(error, "reading the body should fail");
+  }
+});
+
+test("timeouts: read resets as a rate-limited body is sent", async (t) => {
+  t.plan(1);
+
+  const agent = new Agent({ timeout: { read: 500 } });
+  const response = await fetch(url("/post"), {
+    agent,
+    method: "POST",
+    body: "x".repeat(2000),
+    uploadRateLimit: 1000,
+  });
+  t.equal(response.status, 200, "a slow upload shouldn't time out");
+});
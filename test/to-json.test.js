@@ -0,0 +1,34 @@
+const test = require("tape");
+const { fetch } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("toJSON: snapshots the response without reading the body", async (t) => {
+	const response = await fetch(url("/get"));
+	const snapshot = JSON.parse(JSON.stringify(response));
+
+	t.equal(snapshot.status, 200, "status");
+	t.equal(snapshot.statusText, "OK", "statusText");
+	t.equal(snapshot.ok, true, "ok");
+	t.equal(snapshot.url, url("/get"), "url");
+	t.equal(snapshot.redirected, false, "redirected");
+	t.equal(typeof snapshot.version, "string", "version");
+	t.ok(
+		snapshot.headers.some(([name]) => name === "content-type"),
+		"headers are [name, value] pairs",
+	);
+	t.equal(typeof snapshot.peer.address, "string", "peer.address");
+	t.equal(typeof snapshot.timestamps.requestStart, "number", "timestamps");
+	t.notOk("body" in snapshot, "body is omitted before it's read");
+	t.equal(response.bodyUsed, false, "body is not used");
+
+	const data = await response.json();
+	t.equal(data.url, url("/get"), "body can still be read");
+});
+
+test("toJSON: includes the body once it has been read", async (t) => {
+	const response = await fetch(url("/base64/aGVsbG8gd29ybGQ="));
+	t.equal(await response.text(), "hello world", "body");
+
+	const snapshot = response.toJSON();
+	t.equal(snapshot.body, Buffer.from("hello world").toString("base64"), "body is base64");
+});
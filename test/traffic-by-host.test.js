@@ -0,0 +1,47 @@
+const test = require("tape");
+const { Agent } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+test("trafficByHost: counted per host", async (t) => {
+	const server = await startServer();
+	const { port } = new URL(server.url);
+	const at = (host) => `http://${host}:${port}`;
+	try {
+		server.route({ path: "/upload", body: "ok" });
+		server.route({ path: "/asset", body: "0123456789" });
+		server.route({
+			path: "/moved",
+			status: 302,
+			headers: { Location: `${at("cdn.test")}/asset` },
+		});
+
+		const agent = new Agent({
+			dns: {
+				overrides: ["api.test", "cdn.test"].map((domain) => ({
+					domain,
+					addresses: [`127.0.0.1:${port}`],
+				})),
+			},
+		});
+		t.deepEqual(agent.trafficByHost(), {}, "Empty to start with");
+
+		await (await agent.post(`${at("api.test")}/upload`, "hello")).text();
+		await (await agent.get(`${at("cdn.test")}/asset`)).text();
+		await (await agent.post(`${at("api.test")}/moved`, "hi")).text();
+
+		t.deepEqual(
+			agent.trafficByHost(),
+			{
+				"api.test": { requests: 2, bytesSent: 7, bytesReceived: 2 },
+				"cdn.test": { requests: 1, bytesSent: 0, bytesReceived: 20 },
+			},
+			"Bytes received after a redirect count toward the final host",
+		);
+
+		const stats = agent.stats();
+		t.equal(stats.bytesSent, 7, "Same totals as stats()");
+		t.equal(stats.bytesReceived, 22, "Same totals as stats()");
+	} finally {
+		server.close();
+	}
+});
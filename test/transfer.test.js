@@ -0,0 +1,71 @@
+const test = require("tape");
+const path = require("node:path");
+const { once } = require("node:events");
+const { Worker } = require("node:worker_threads");
+const { ERROR_CODES, fetch, receiveResponse } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+const wrapper = path.join(__dirname, "..", "wrapper.js");
+
+function readInWorker(handle) {
+	const worker = new Worker(
+		`
+		const { parentPort, workerData } = require("node:worker_threads");
+		const { receiveResponse } = require(workerData.wrapper);
+		parentPort.once("message", async (handle) => {
+			const response = receiveResponse(handle);
+			parentPort.postMessage({ status: response.status, text: await response.text() });
+		});
+		`,
+		{ eval: true, workerData: { wrapper } },
+	);
+	worker.postMessage(handle);
+	return once(worker, "message").finally(() => worker.terminate());
+}
+
+test("transfer: body read in a worker", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/", body: "hello from the main thread" });
+
+		const response = await fetch(`${server.url}/`);
+		const handle = response.transfer();
+		t.equal(typeof handle.id, "string", "Plain handle");
+		t.ok(response.bodyUsed, "Body used once transferred");
+		try {
+			await response.text();
+			t.fail("should throw");
+		} catch (error) {
+			t.equal(error.code, ERROR_CODES.ResponseAlreadyDisturbed, "Can't read it here");
+		}
+
+		const [result] = await readInWorker(handle);
+		t.deepEqual(result, { status: 200, text: "hello from the main thread" }, "Read there");
+
+		try {
+			receiveResponse(handle);
+			t.fail("should throw");
+		} catch (error) {
+			t.equal(error.code, ERROR_CODES.ResponseAlreadyDisturbed, "Received only once");
+		}
+	} finally {
+		server.close();
+	}
+});
+
+test("transfer: not after the body is used", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({ path: "/", body: "hello" });
+
+		const response = await fetch(`${server.url}/`);
+		await response.text();
+		t.throws(() => response.transfer(), "Throws");
+
+		const other = await fetch(`${server.url}/`);
+		const received = receiveResponse(other.transfer());
+		t.equal(await received.text(), "hello", "Can be received in the same thread");
+	} finally {
+		server.close();
+	}
+});
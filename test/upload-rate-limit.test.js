@@ -0,0 +1,62 @@
+const { ReadableStream } = require("node:stream/web");
+const test = require("tape");
+const { fetch } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+function header(data, name) {
+	const value = data.headers[name];
+	return Array.isArray(value) ? value[0] : value;
+}
+
+test("uploadRateLimit: paces a buffered body", async (t) => {
+	const body = "x".repeat(2000);
+	const start = performance.now();
+	const response = await fetch(url("/post"), {
+		method: "POST",
+		body,
+		uploadRateLimit: 4000,
+	});
+	const data = await response.json();
+	const elapsed = performance.now() - start;
+
+	t.equal(data.data, body, "Body is sent whole");
+	t.equal(header(data, "Content-Length"), "2000", "Content-Length is kept");
+	t.ok(elapsed >= 350, `Took ${elapsed.toFixed(0)}ms, at least 2000 bytes at 4000/s`);
+	t.end();
+});
+
+test("uploadRateLimit: paces a streamed body", async (t) => {
+	const stream = new ReadableStream({
+		start(controller) {
+			controller.enqueue(new TextEncoder().encode("y".repeat(2000)));
+			controller.close();
+		},
+	});
+	const start = performance.now();
+	const response = await fetch(url("/post"), {
+		method: "POST",
+		body: stream,
+		duplex: "half",
+		uploadRateLimit: 4000,
+	});
+	const data = await response.json();
+	const elapsed = performance.now() - start;
+
+	t.equal(data.data, "y".repeat(2000), "Body is sent whole");
+	t.ok(elapsed >= 350, `Took ${elapsed.toFixed(0)}ms, at least 2000 bytes at 4000/s`);
+	t.end();
+});
+
+test("uploadRateLimit: zero is a Config error", async (t) => {
+	try {
+		await fetch(url("/post"), {
+			method: "POST",
+			body: "data",
+			uploadRateLimit: 0,
+		});
+		t.fail("Should have thrown");
+	} catch (error) {
+		t.equal(error.code, "Config", "Config error");
+	}
+	t.end();
+});
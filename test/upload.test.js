@@ -0,0 +1,129 @@
+const fs = require("node:fs/promises");
+const http = require("node:http");
+const os = require("node:os");
+const path = require("node:path");
+const test = require("tape");
+const { upload, Agent, ERROR_CODES } = require("../wrapper.js");
+
+// A multipart endpoint which stores parts by number, answering with an ETag. `statuses` are used
+// in turn for the first requests, before answering with 200.
+async function startServer({ statuses = [] } = {}) {
+	const parts = new Map();
+	const requests = [];
+	const server = http.createServer((req, res) => {
+		const chunks = [];
+		req.on("data", (chunk) => chunks.push(chunk));
+		req.on("end", () => {
+			const { searchParams } = new URL(req.url, "http://localhost");
+			const number = Number(searchParams.get("partNumber"));
+			requests.push({
+				method: req.method,
+				number,
+				range: req.headers["content-range"],
+				custom: req.headers["x-custom"],
+			});
+
+			const status = statuses.shift() ?? 200;
+			if (status === 200) {
+				parts.set(number, Buffer.concat(chunks));
+			}
+			res.writeHead(status, { ETag: `"part-${number}"` });
+			res.end();
+		});
+	});
+	await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+	return {
+		url: `http://127.0.0.1:${server.address().port}/bucket/key?uploadId=abc`,
+		parts,
+		requests,
+		close: () => new Promise((resolve) => server.close(resolve)),
+	};
+}
+
+async function tempFile(size) {
+	const dir = await fs.mkdtemp(path.join(os.tmpdir(), "faith-upload-"));
+	const file = path.join(dir, "data.bin");
+	const data = Buffer.alloc(size);
+	for (let i = 0; i < size; i++) {
+		data[i] = i % 251;
+	}
+	await fs.writeFile(file, data);
+	return { file, data, remove: () => fs.rm(dir, { recursive: true }) };
+}
+
+test("upload: sends the file in numbered parts", async (t) => {
+	const server = await startServer();
+	const { file, data, remove } = await tempFile(2500);
+	try {
+		const parts = await upload(server.url, file, {
+			partSize: 1000,
+			concurrency: 2,
+			headersPerPart: { "X-Custom": "yes" },
+		});
+
+		t.deepEqual(
+			parts.map((part) => [part.partNumber, part.offset, part.size, part.etag]),
+			[
+				[1, 0, 1000, '"part-1"'],
+				[2, 1000, 1000, '"part-2"'],
+				[3, 2000, 500, '"part-3"'],
+			],
+			"Parts are returned in order with their ETags",
+		);
+		t.ok(parts.every((part) => part.status === 200 && part.attempts === 1), "All succeeded at once");
+		t.ok(
+			Buffer.concat([1, 2, 3].map((n) => server.parts.get(n))).equals(data),
+			"Parts put together are the file",
+		);
+
+		const byNumber = Object.fromEntries(server.requests.map((r) => [r.number, r]));
+		t.equal(byNumber[3].range, "bytes 2000-2499/2500", "Content-Range is sent");
+		t.equal(byNumber[1].method, "PUT", "Parts are PUT");
+		t.equal(byNumber[2].custom, "yes", "headersPerPart are sent");
+	} finally {
+		await server.close();
+		await remove();
+	}
+	t.end();
+});
+
+test("upload: retries parts which fail", async (t) => {
+	const server = await startServer({ statuses: [503] });
+	const { file, remove } = await tempFile(100);
+	try {
+		const [part] = await upload(server.url, file, { agent: new Agent() });
+
+		t.equal(part.status, 200, "Part succeeded");
+		t.equal(part.attempts, 2, "After a retry");
+	} finally {
+		await server.close();
+		await remove();
+	}
+	t.end();
+});
+
+test("upload: refused parts are an Upload error", async (t) => {
+	const server = await startServer({ statuses: [503, 503, 403] });
+	const { file, remove } = await tempFile(100);
+	try {
+		await upload(server.url, file, { retries: 5 });
+		t.fail("Should have thrown");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.Upload, "Upload error");
+		t.equal(server.requests.length, 3, "Not retried after a 403");
+	} finally {
+		await server.close();
+		await remove();
+	}
+	t.end();
+});
+
+test("upload: missing files are an Upload error", async (t) => {
+	try {
+		await upload("http://127.0.0.1:1/", path.join(os.tmpdir(), "faith-missing-file"));
+		t.fail("Should have thrown");
+	} catch (error) {
+		t.equal(error.code, ERROR_CODES.Upload, "Upload error");
+	}
+	t.end();
+});
@@ -222,3 +222,56 @@ test("Agent userAgent with special characters", async (t) => {
 		"Special characters in userAgent should be preserved",
 	);
 });
+
+test("userAgent request option overrides the agent's user agent", async (t) => {
+	t.plan(1);
+
+	const response = await faithFetch(url("/headers"), {
+		userAgent: "PerRequest/1.0",
+	});
+	const data = await response.json();
+	t.equal(
+		getHeader(data.headers, "User-Agent"),
+		"PerRequest/1.0",
+		"User-Agent should be the request option",
+	);
+});
+
+test("User-Agent header takes precedence over the userAgent option", async (t) => {
+	t.plan(1);
+
+	const response = await faithFetch(url("/headers"), {
+		userAgent: "PerRequest/1.0",
+		headers: { "User-Agent": "FromHeaders/1.0" },
+	});
+	const data = await response.json();
+	t.equal(
+		getHeader(data.headers, "User-Agent"),
+		"FromHeaders/1.0",
+		"User-Agent should be the header",
+	);
+});
+
+test("userAgentSuffix is appended to the default user agent", async (t) => {
+	t.plan(2);
+
+	const agent = new Agent({ userAgentSuffix: "YourApp/1.2.3" });
+	const response = await faithFetch(url("/headers"), { agent });
+	const data = await response.json();
+	t.match(
+		getHeader(data.headers, "User-Agent"),
+		/^Faith\/\d+\.\d+\.\d+ reqwest\/\d+\.\d+\.\d+ YourApp\/1\.2\.3$/,
+		"User-Agent should keep the version info and end with the suffix",
+	);
+
+	const custom = await faithFetch(url("/headers"), {
+		agent,
+		userAgent: "PerRequest/1.0",
+	});
+	const customData = await custom.json();
+	t.equal(
+		getHeader(customData.headers, "User-Agent"),
+		"PerRequest/1.0 YourApp/1.2.3",
+		"suffix should be appended to the userAgent option",
+	);
+});
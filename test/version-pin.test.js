@@ -0,0 +1,37 @@
+const test = require("tape");
+const { fetch: faithFetch, Agent } = require("../wrapper.js");
+const { url } = require("./helpers.js");
+
+test("version: http/1.1 pins the request to HTTP/1.1", async (t) => {
+	t.plan(2);
+
+	const response = await faithFetch(url("/get"), { version: "http/1.1" });
+	t.ok(response.ok, "Should successfully fetch");
+	t.equal(response.version, "HTTP/1.1", "Version should be HTTP/1.1");
+});
+
+test("version: http/1.1 works with an agent", async (t) => {
+	t.plan(3);
+
+	const agent = new Agent();
+	const response = await faithFetch(url("/get"), {
+		agent,
+		version: "http/1.1",
+	});
+	t.ok(response.ok, "Should successfully fetch");
+	t.equal(response.version, "HTTP/1.1", "Version should be HTTP/1.1");
+
+	const unpinned = await faithFetch(url("/get"), { agent });
+	t.ok(unpinned.ok, "Unpinned requests on the same agent still work");
+});
+
+test("version: invalid values are rejected", async (t) => {
+	t.plan(1);
+
+	try {
+		await faithFetch(url("/get"), { version: "spdy" });
+		t.fail("Should have thrown");
+	} catch (err) {
+		t.pass("Should reject an unknown version");
+	}
+});
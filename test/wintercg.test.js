@@ -0,0 +1,254 @@
+// Conformance of strict agents with the WinterCG fetch spec, on a subset of the web-platform-tests
+// fetch suite (https://github.com/web-platform-tests/wpt/tree/master/fetch). Tests adapted from WPT
+// are named after their file; the others cover divergences which WPT can't observe from a browser.
+const test = require("tape");
+const { Agent, fetch } = require("../wrapper.js");
+const { startServer } = require("../testing.js");
+
+const agent = new Agent({ strict: true });
+const manual = new Agent({ strict: true, redirect: "manual" });
+
+/**
+ * Run a WPT-style test against a fresh server
+ * @param {string} file - the WPT file the test is adapted from
+ * @param {string} name
+ * @param {(t: import("tape").Test, server: import("../testing").TestServer) => Promise<void>} fn
+ */
+function wpt(file, name, fn) {
+	test(`wpt: ${file}: ${name}`, async (t) => {
+		const server = await startServer();
+		try {
+			await fn(t, server);
+		} finally {
+			server.close();
+		}
+	});
+}
+
+/**
+ * Assert that a promise rejects, returning the rejection
+ * @param {import("tape").Test} t
+ * @param {Promise<unknown>} promise
+ * @returns {Promise<any>}
+ */
+async function rejection(t, promise) {
+	try {
+		await promise;
+	} catch (error) {
+		return error;
+	}
+	t.fail("should reject");
+}
+
+test("strict: agent.strict", (t) => {
+	t.plan(2);
+	t.equal(agent.strict, true, "Set");
+	t.equal(new Agent().strict, false, "Off by default");
+});
+
+wpt(
+	"api/redirect/redirect-mode.any.js",
+	"manual redirects are opaque-redirect responses",
+	async (t, server) => {
+		for (const status of [301, 302, 303, 307, 308]) {
+			server.route({
+				path: `/redirect/${status}`,
+				status,
+				headers: { Location: "/target", "X-Extra": "1" },
+				body: "moved",
+			});
+		}
+		server.route({ path: "/target", body: "target" });
+
+		for (const status of [301, 302, 303, 307, 308]) {
+			const url = `${server.url}/redirect/${status}`;
+			const response = await fetch(url, { agent: manual });
+			t.equal(response.type, "opaqueredirect", `${status}: type`);
+			t.equal(response.status, 0, `${status}: status`);
+			t.equal(response.statusText, "", `${status}: statusText`);
+			t.equal(response.ok, false, `${status}: ok`);
+			t.equal([...response.headers].length, 0, `${status}: no headers`);
+			t.equal(response.body, null, `${status}: null body`);
+			t.equal(response.url, url, `${status}: url is the request's`);
+			t.equal(response.redirected, false, `${status}: not redirected`);
+		}
+
+		const followed = await fetch(`${server.url}/redirect/302`, { agent });
+		t.equal(followed.type, "basic", "Followed without redirect: manual");
+		t.equal(await followed.text(), "target", "Body of the target");
+	},
+);
+
+wpt(
+	"api/response/response-null-body.any.js",
+	"null body statuses and HEAD",
+	async (t, server) => {
+		server.route({ path: "/204", status: 204 });
+		server.route({ path: "/205", status: 205 });
+		server.route({ path: "/304", status: 304 });
+		server.route({ path: "/head", body: "not sent" });
+
+		for (const status of [204, 205, 304]) {
+			const response = await fetch(`${server.url}/${status}`, { agent });
+			t.equal(response.status, status, `${status}: status`);
+			t.equal(response.body, null, `${status}: null body`);
+		}
+
+		const head = await fetch(`${server.url}/head`, { agent, method: "HEAD" });
+		t.equal(head.body, null, "HEAD: null body");
+	},
+);
+
+test("strict: Set-Cookie and Set-Cookie2 are filtered", async (t) => {
+	const server = await startServer();
+	try {
+		server.route({
+			path: "/",
+			headers: {
+				"Set-Cookie": "a=1",
+				"Set-Cookie2": "b=2",
+				"X-Visible": "yes",
+			},
+		});
+
+		const response = await fetch(`${server.url}/`, { agent });
+		t.equal(response.headers.get("set-cookie"), null, "Set-Cookie");
+		t.equal(response.headers.get("set-cookie2"), null, "Set-Cookie2");
+		t.equal(response.headers.get("x-visible"), "yes", "Other headers");
+	} finally {
+		server.close();
+	}
+});
+
+wpt(
+	"api/headers/headers-record.any.js",
+	"record values are stringified",
+	async (t, server) => {
+		server.route({ path: "/" });
+
+		await fetch(`${server.url}/`, {
+			agent,
+			headers: { "X-Null": null, "X-Undefined": undefined, "X-Number": 1 },
+		});
+		const headers = Object.fromEntries(server.requests().at(-1).headers);
+		t.equal(headers["x-null"], "null", "null");
+		t.equal(headers["x-undefined"], "undefined", "undefined");
+		t.equal(headers["x-number"], "1", "Number");
+	},
+);
+
+wpt(
+	"api/basic/request-forbidden-headers.any.js",
+	"forbidden request headers are not sent",
+	async (t, server) => {
+		server.route({ path: "/" });
+
+		await fetch(`${server.url}/`, {
+			agent,
+			headers: { Cookie: "a=1", "Sec-Test": "1", "X-Allowed": "1" },
+		});
+		const headers = Object.fromEntries(server.requests().at(-1).headers);
+		t.equal(headers.cookie, undefined, "Cookie");
+		t.equal(headers["sec-test"], undefined, "Sec- prefix");
+		t.equal(headers["x-allowed"], "1", "Other headers");
+	},
+);
+
+wpt(
+	"api/basic/scheme-others.sub.any.js",
+	"unsupported schemes reject with a TypeError",
+	async (t) => {
+		for (const url of ["ftp://example.com/", "about:blank", "foo://bar"]) {
+			const error = await rejection(t, fetch(url, { agent }));
+			t.ok(error instanceof TypeError, url);
+		}
+	},
+);
+
+wpt(
+	"api/abort/general.any.js",
+	"aborted requests reject with the signal's reason",
+	async (t, server) => {
+		server.route({ path: "/slow", delay: 2000 });
+
+		const controller = new AbortController();
+		const pending = fetch(`${server.url}/slow`, {
+			agent,
+			signal: controller.signal,
+		});
+		setTimeout(() => controller.abort(), 50);
+		const error = await rejection(t, pending);
+		t.ok(error instanceof DOMException, "DOMException");
+		t.equal(error.name, "AbortError", "AbortError");
+
+		const reason = new Error("custom reason");
+		const custom = new AbortController();
+		custom.abort(reason);
+		t.equal(
+			await rejection(
+				t,
+				fetch(`${server.url}/slow`, { agent, signal: custom.signal }),
+			),
+			reason,
+			"Already aborted with a custom reason",
+		);
+
+		const later = new AbortController();
+		const laterPending = fetch(`${server.url}/slow`, {
+			agent,
+			signal: later.signal,
+		});
+		setTimeout(() => later.abort("string reason"), 50);
+		t.equal(
+			await rejection(t, laterPending),
+			"string reason",
+			"Aborted in flight with a custom reason",
+		);
+
+		const timeout = await rejection(
+			t,
+			fetch(`${server.url}/slow`, {
+				agent,
+				signal: AbortSignal.timeout(50),
+			}),
+		);
+		t.equal(timeout.name, "TimeoutError", "AbortSignal.timeout()");
+	},
+);
+
+test("strict: network errors, other aborts, and timeouts", async (t) => {
+	const closed = await startServer();
+	const url = closed.url;
+	closed.close();
+	const refused = await rejection(t, fetch(`${url}/`, { agent }));
+	t.ok(refused instanceof TypeError, "Network errors are TypeErrors");
+	t.equal(refused.cause?.name, "NetworkError", "Fáith error as the cause");
+
+	const server = await startServer();
+	try {
+		server.route({ path: "/slow", delay: 2000 });
+
+		const aborting = new Agent({ strict: true });
+		const pending = fetch(`${server.url}/slow`, { agent: aborting });
+		setTimeout(() => aborting.abortAll(), 50);
+		const error = await rejection(t, pending);
+		t.ok(error instanceof DOMException, "DOMException");
+		t.equal(error.name, "AbortError", "AbortError");
+		t.equal(error.cause?.code, "Aborted", "Fáith error as the cause");
+
+		const timeout = await rejection(
+			t,
+			fetch(`${server.url}/slow`, { agent, timeout: 50 }),
+		);
+		t.ok(timeout instanceof TypeError, "Timeouts are TypeErrors");
+		t.equal(timeout.cause?.code, "Timeout", "Fáith error as the cause");
+
+		const lax = await rejection(
+			t,
+			fetch(`${server.url}/slow`, { timeout: 50 }),
+		);
+		t.equal(lax.name, "TimeoutError", "Unchanged without strict");
+	} finally {
+		server.close();
+	}
+});
@@ -0,0 +1,37 @@
+import { TestRequest, TestRoute as NativeTestRoute } from "./index";
+export { TestRequest };
+
+/**
+ * A route of a `TestServer`, for `server.route()`. Headers may be given as an object, a `Headers`,
+ * or an array of pairs.
+ */
+export interface TestRoute extends Omit<NativeTestRoute, "headers"> {
+	headers?: Record<string, string> | Headers | Array<[string, string]>;
+}
+
+/**
+ * An in-process HTTP server for tests, listening on a random port on `127.0.0.1`. It speaks
+ * HTTP/1.1, and HTTP/2 with prior knowledge (`version: "h2"`), without TLS.
+ *
+ * Requests are answered by the most recently added route which matches them, or with a `404`.
+ * Every request is recorded, with its body, whether a route answered it or not.
+ */
+export declare class TestServer {
+	/** The server's base URL, e.g. `http://127.0.0.1:12345`. */
+	get url(): string;
+	get port(): number;
+	/** Adds a route. Throws if the method, status, or a header is invalid. */
+	route(route: TestRoute): this;
+	/** The requests received so far, in order. */
+	requests(): Array<TestRequest>;
+	/** Removes all routes and forgets the requests received so far. */
+	reset(): void;
+	/** Stops the server, closing its connections. This also happens when it's garbage collected. */
+	close(): void;
+}
+
+/**
+ * Starts a `TestServer`. Rejects if Fáith was built without the `testing` feature: see
+ * `capabilities().testing`.
+ */
+export declare function startServer(): Promise<TestServer>;
@@ -0,0 +1,61 @@
+/**
+ * Fáith test helpers, as `faith/testing`.
+ *
+ * This wraps the native `TestServer` so that routes can be given headers as an object, like in
+ * `fetch()` options. It's only available when Fáith is built with the `testing` feature, which is
+ * a default one, so builds without the default features may not have it: see
+ * `capabilities().testing`.
+ */
+
+const native = require("./index.js");
+
+function headerPairs(headers) {
+	if (headers === undefined || headers === null || Array.isArray(headers)) return headers;
+	if (typeof headers.entries === "function") return Array.from(headers.entries());
+	return Object.entries(headers).map(([name, value]) => [name, String(value)]);
+}
+
+class TestServer {
+	#native;
+
+	constructor(nativeServer) {
+		this.#native = nativeServer;
+	}
+
+	get url() {
+		return this.#native.url;
+	}
+
+	get port() {
+		return this.#native.port;
+	}
+
+	route(route) {
+		this.#native.route({ ...route, headers: headerPairs(route.headers) });
+		return this;
+	}
+
+	requests() {
+		return this.#native.requests();
+	}
+
+	reset() {
+		this.#native.reset();
+	}
+
+	close() {
+		this.#native.close();
+	}
+}
+
+async function startServer() {
+	if (typeof native.startTestServer !== "function") {
+		throw new Error("faith/testing is not available: Fáith was built without the `testing` feature");
+	}
+	return new TestServer(await native.startTestServer());
+}
+
+module.exports = {
+	startServer,
+	TestServer,
+};
@@ -1,25 +1,111 @@
-import { Agent } from "./index";
+import {
+	Agent,
+	AltSvcDecision,
+	BodyStats,
+	FaithHeaders,
+	FaithUploadOptions,
+	FaithUploadPart,
+	GraphqlRequest,
+	HttpVersion,
+	RedirectSnapshot,
+	RequestEvent,
+	RequestTimeouts,
+	ResponseSnapshot,
+	ResponseTimestamps,
+	TransferredResponse,
+} from "./index";
+import { EventEmitter } from "node:events";
+import {
+	Agent as HttpAgent,
+	AgentOptions as HttpAgentOptions,
+} from "node:http";
 export {
 	Agent,
+	AgentCacheKeyOptions,
 	AgentCacheOptions,
+	AgentCachePreloadOptions,
+	AgentCookiePolicy,
 	AgentDnsOptions,
+	AgentDnsOrderOptions,
+	AgentEchOptions,
+	AgentHttp1Options,
 	AgentHttp3Options,
+	AgentKeepaliveOptions,
+	AgentLimitsOptions,
 	AgentPoolOptions,
+	AgentProfile,
+	AgentProxyAuth,
+	AgentProxyOptions,
+	AgentSocketOptions,
+	AgentSpillOptions,
+	AgentStreamOptions,
 	AgentTimeoutOptions,
 	AgentTlsOptions,
+	AgentUpgradeInsecureOptions,
+	AgentUrlOptions,
 	AgentOptions,
 	AgentStats,
+	AltSvcDecision,
+	AltSvcSource,
+	BodyStats,
 	CacheMode,
 	CacheStore,
+	Capabilities,
+	capabilities,
+	CertificateVerifyRequest,
+	ChecksumAlgorithm,
+	configureRuntime,
+	ConnectionEvent,
+	CookieEvent,
 	CredentialsOption as Credentials,
+	DnsFamily,
 	DnsOverride,
 	DuplexOption as Duplex,
+	EchDohProvider,
+	errorCodes,
+	FaithAbortError,
+	FaithError,
+	FaithErrorCode,
+	FaithErrorOptions,
+	FaithHeaders,
+	FaithHttpError,
+	FaithNetworkError,
+	faithShutdown,
+	FaithSyntaxError,
+	FaithTimeoutError,
+	FaithTypeError,
+	FaithUploadPart,
+	getGlobalAgent,
+	GraphqlRequest,
 	Header,
+	HostTraffic,
 	Http3Congestion,
+	HttpVersion,
+	MiddlewareStage,
+	OcspStatus,
+	PeerProxySnapshot,
+	PeerSnapshot,
+	PeerTls,
+	PreparedRequest,
+	ProxyMode,
 	Redirect,
+	RedirectSnapshot,
+	RequestEvent,
+	RequestEventType,
+	RequestTimeouts,
+	ResponseSnapshot,
+	ResponseTimestamps,
+	RuntimeOptions,
 	FAITH_VERSION,
 	REQWEST_VERSION,
+	setGlobalAgent,
+	ShutdownOptions,
+	TlsCryptoProvider,
+	TransferredResponse,
+	UrlCredentials,
+	UrlIdn,
 	USER_AGENT,
+	VersionOption as Version,
 } from "./index";
 
 // NOTE: This must be kept in sync with FaithErrorKind in src/error.rs
@@ -27,8 +113,18 @@ export {
 export const ERROR_CODES: {
 	readonly Aborted: "Aborted";
 	readonly AddressParse: "AddressParse";
+	readonly BodyNotAllowed: "BodyNotAllowed";
+	readonly BodyNotReplayable: "BodyNotReplayable";
 	readonly BodyStream: "BodyStream";
+	readonly Cache: "Cache";
+	readonly CertificateInvalid: "CertificateInvalid";
 	readonly Config: "Config";
+	readonly Dns: "Dns";
+	readonly ForbiddenAddress: "ForbiddenAddress";
+	readonly ForbiddenHost: "ForbiddenHost";
+	readonly Graphql: "Graphql";
+	readonly GrpcWeb: "GrpcWeb";
+	readonly HttpStatus: "HttpStatus";
 	readonly IntegrityMismatch: "IntegrityMismatch";
 	readonly InvalidHeader: "InvalidHeader";
 	readonly InvalidIntegrity: "InvalidIntegrity";
@@ -36,12 +132,20 @@ export const ERROR_CODES: {
 	readonly InvalidUrl: "InvalidUrl";
 	readonly JsonParse: "JsonParse";
 	readonly Network: "Network";
+	readonly NotCached: "NotCached";
 	readonly PemParse: "PemParse";
+	readonly Proxy: "Proxy";
+	readonly ProxyAuth: "ProxyAuth";
 	readonly Redirect: "Redirect";
+	readonly RedirectDowngrade: "RedirectDowngrade";
+	readonly RequestTooLarge: "RequestTooLarge";
 	readonly ResponseAlreadyDisturbed: "ResponseAlreadyDisturbed";
 	readonly ResponseBodyNotAvailable: "ResponseBodyNotAvailable";
+	readonly ResponseTooLarge: "ResponseTooLarge";
 	readonly RuntimeThread: "RuntimeThread";
 	readonly Timeout: "Timeout";
+	readonly TlsHandshake: "TlsHandshake";
+	readonly Upload: "Upload";
 	readonly Utf8Parse: "Utf8Parse";
 };
 
@@ -53,7 +157,8 @@ export interface FetchOptions {
 	 * documentation for the `Agent` options you can set with this, and the agent data you can access.
 	 * Notably an agent has a DNS cache, and may be configured to handle cookies and/or an HTTP cache.
 	 *
-	 * When not provided, a global default `Agent` is created on first use.
+	 * When not provided, the global agent is used: it is created with default options on first use,
+	 * and can be replaced with `setGlobalAgent()` or obtained with `getGlobalAgent()`.
 	 */
 	agent?: Agent;
 	/**
@@ -74,13 +179,20 @@ export interface FetchOptions {
 	 *
 	 * If `body` is a `URLSearchParams`, the `Content-Type` header will be set to
 	 * `application/x-www-form-urlencoded;charset=UTF-8` unless already specified.
+	 *
+	 * If `body` is a `Blob` with a `type`, the `Content-Type` header will be set to it unless already
+	 * specified.
+	 *
+	 * Views such as `DataView` and `TypedArray` only send the bytes they cover, respecting their
+	 * `byteOffset` and `byteLength`. Binary bodies are handed to the native side without copying.
 	 */
 	body?:
 		| string
 		| Buffer
-		| Uint8Array
+		| ArrayBufferView
 		| Array<number>
 		| ArrayBuffer
+		| Blob
 		| URLSearchParams;
 	/**
 	 * The cache mode you want to use for the request. This may be any one of the following values:
@@ -113,7 +225,8 @@ export interface FetchOptions {
 	 *
 	 * - `only-if-cached`: The client looks in its HTTP cache for a response matching the request.
 	 *   - If there is a match, fresh or stale, it will be returned from the cache.
-	 *   - If there is no match, a network error is returned.
+	 *   - If there is no match, or the agent has no cache, a `NotCached` error is returned without
+	 *     making a request.
 	 *
 	 * - `ignore-rules`: Custom to Fáith. Overrides the check that determines if a response can be cached
 	 *   to always return true on 200. Uses any response in the HTTP cache matching the request, not
@@ -128,6 +241,42 @@ export interface FetchOptions {
 		| "no-store"
 		| "only-if-cached"
 		| "reload";
+	/**
+	 * Custom to Fáith. Validates the response body against the checksum headers some servers and
+	 * object stores send, as it's read:
+	 *
+	 * - `content-md5`: the MD5 digest in `Content-Md5`, or in `md5=` of `x-goog-hash`.
+	 * - `crc32c`: the CRC32C in `x-amz-checksum-crc32c`, or in `crc32c=` of `x-goog-hash`.
+	 *
+	 * Responses without any of those headers aren't checked. Unlike `integrity`, this applies to all
+	 * ways of reading the body, including `body` and `chunks()`: the read which reaches the end of the
+	 * body fails with an `IntegrityMismatch` error if a checksum doesn't match.
+	 *
+	 * The checksums are of the body as sent: a response compressed with `Content-Encoding` is checked
+	 * before Fáith decompresses it.
+	 */
+	checksums?: Array<"content-md5" | "crc32c">;
+	/**
+	 * Custom to Fáith. Connects to this IP address (with an optional port, e.g. `"10.0.0.5:8443"`)
+	 * instead of the address the URL's host resolves to, like curl's `--connect-to`. Everything else
+	 * still uses the URL: the request line, the `Host` header, and the TLS server name and certificate
+	 * validation. This is useful for testing a specific backend, like a canary or a single pod.
+	 *
+	 * The URL's host must be a domain name. If the URL has an explicit port, it takes precedence over
+	 * the port given here. Requests with `connectTo` use a connection pool separate from the agent's
+	 * main pool.
+	 *
+	 * Throws if the address is invalid.
+	 */
+	connectTo?: string;
+	/**
+	 * Custom to Fáith. An identifier for the request, to stitch together the requests of an
+	 * operation in logs and traces. It's kept as the response's `correlationId`, and as the
+	 * `correlationId` property of the request's errors, including those from reading the body.
+	 *
+	 * Default: the ID of the current `withCorrelationId()` scope, if any.
+	 */
+	correlationId?: string;
 	/**
 	 * Controls whether or not the client sends credentials with the request, as well as whether any
 	 * `Set-Cookie` response headers are respected. Credentials are cookies, ~~TLS client certificates,~~
@@ -157,6 +306,17 @@ export interface FetchOptions {
 	 * This option must be present when `body` is a `ReadableStream`.
 	 */
 	duplex?: "half";
+	/**
+	 * Custom to Fáith. Sends a buffered `body` with chunked transfer encoding instead of with a
+	 * `Content-Length`, as is done for `ReadableStream` bodies. This only affects HTTP/1.1, as
+	 * HTTP/2 and HTTP/3 have their own framing; it throws a `Config` error with `http1.version10`.
+	 *
+	 * Fáith sets the `Content-Length` header for buffered bodies (and to zero for `POST` and `PUT`
+	 * requests without one), and the `Transfer-Encoding` header for chunked bodies. These headers
+	 * are forbidden, so they're dropped if given in `headers`. With the agent's `unsafe` option they
+	 * can be given, but an `InvalidHeader` error is thrown if they don't match how the body is sent.
+	 */
+	forceChunked?: boolean;
 	/**
 	 * Contains the subresource integrity value of the request.
 	 *
@@ -177,43 +337,188 @@ export interface FetchOptions {
 	 */
 	integrity?: string;
 	/**
-	 * Any headers you want to add to your request, contained within a `Headers` object or an object
-	 * literal whose keys are the names of headers and whose values are the header values.
+	 * Custom to Fáith. A value to send as the JSON request body, instead of `body`. It is serialised
+	 * natively rather than with `JSON.stringify()`, which saves a pass and a copy for large payloads.
+	 * The `Content-Type` header is set to `application/json` unless given in `headers`.
+	 *
+	 * The value is read as plain data, so unlike `JSON.stringify()`, `toJSON()` methods are not
+	 * called and `undefined` values become `null`. `BigInt` values are not supported. A top-level
+	 * `null` or `undefined` is the same as not setting this option.
+	 *
+	 * Throws a `Config` error if `body` is also set.
+	 */
+	json?: unknown;
+	/**
+	 * Any headers you want to add to your request, contained within a `Headers` or `FaithHeaders`
+	 * object, an array of `[name, value]` pairs (or any other iterable of pairs, such as a `Map`), or
+	 * an object literal whose keys are the names of headers and whose values are the header values.
+	 * Values which aren't strings are converted to strings.
+	 *
+	 * Like browsers, Fáith drops [forbidden request headers][1] such as `Cookie`, `Host`, or `Origin`,
+	 * unless the agent is created with `unsafe: true`.
+	 *
+	 * Request headers replace the agent's default headers of the same name. In an object literal, a
+	 * `null` value removes the default header instead, e.g. `{ "User-Agent": null }` sends the request
+	 * without a `User-Agent`. With a `strict` agent, it's sent as the string `"null"`, as in the spec.
 	 *
-	 * Fáith allows all request headers to be set (unlike browsers, which [forbid][1] a number of them).
+	 * With an `unsafe` agent, the `Host` header may be set to send a different host than the URL's,
+	 * e.g. to test virtual hosts. This does not change where the request connects to, nor the server
+	 * name used for TLS (see the agent's `tls.serverName` for that). Over HTTP/2 and HTTP/3, the
+	 * `:authority` pseudo-header is still derived from the URL.
 	 *
 	 * [1]: https://developer.mozilla.org/en-US/docs/Glossary/Forbidden_request_header
 	 */
-	headers?: Record<string, string> | Headers;
+	headers?:
+		| Record<string, string | number | null>
+		| Iterable<[string, string | number]>
+		| Headers
+		| FaithHeaders;
 	/**
 	 * The request method. Defaults to `GET`.
 	 */
 	method?: string;
+	/**
+	 * Custom to Fáith. Called with each event in the lifecycle of the request, in order: when it's
+	 * queued, when a new connection is opened for it (and its DNS lookup), when its body has been
+	 * streamed, when the response headers arrive, when the response body starts and ends, and when
+	 * it fails. See `RequestEventType` for the details.
+	 *
+	 * Events are delivered asynchronously on the main thread, so some may arrive after the promise
+	 * has resolved. Exceptions thrown by the callback are not caught.
+	 */
+	observe?: (event: RequestEvent) => void;
+	/**
+	 * Custom to Fáith. Values for the `{name}` placeholders in the URL, e.g. `{ id: 42 }` with
+	 * `/users/{id}`. Values are converted to strings, and everything but unreserved characters
+	 * (letters, digits, and `-._~`) is percent-encoded, so a value can't add path segments or a query.
+	 *
+	 * Placeholders are only filled when this option is set. Throws an `InvalidUrl` error if a
+	 * placeholder has no value. With the agent's `baseUrl`, the template is filled before it's joined.
+	 */
+	params?: Record<string, string | number | bigint | boolean>;
+	/**
+	 * Custom to Fáith. Names of headers which should be marked as sensitive for this request, such as
+	 * per-request tokens. Sensitive headers are redacted from debug output and are never added to the
+	 * HTTP/2 and HTTP/3 header compression tables. Names are matched case-insensitively, and may also
+	 * refer to the agent's default headers.
+	 *
+	 * Throws a `TypeError` if any of the names are invalid.
+	 */
+	sensitiveHeaders?: string[];
+	/**
+	 * Custom to Fáith. Rejects the request with a `FaithHttpError` (with the `HttpStatus` code) if
+	 * the response has a non-2xx status, instead of resolving with the response. The error has the
+	 * response's `status`, `statusText`, and `headers`, and up to the first KiB of its body as text
+	 * in `bodyPreview`. The rest of the body is not read.
+	 *
+	 * Defaults to the agent's `throwOnError`.
+	 */
+	throwOnError?: boolean;
 	/**
 	 * An `AbortSignal`. If this option is set, the request can be canceled by calling `abort()` on the
 	 * corresponding `AbortController`.
+	 *
+	 * The request then rejects with an `AbortError` (with the `Aborted` code) carrying the abort
+	 * reason: the error's message is the reason's message, or the reason itself if it's not an error,
+	 * and its `cause` is the reason value.
+	 *
+	 * To abort on any of several signals, combine them with `AbortSignal.any()`. If the signal that
+	 * fired is a timeout, like `AbortSignal.timeout()`, the request rejects with a `TimeoutError`
+	 * (with the `Timeout` code) instead.
+	 *
+	 * With a `strict` agent, the request rejects with the signal's `reason` itself, as in the spec.
 	 */
 	signal?: AbortSignal;
 	/**
 	 * Custom to Fáith. Cancels the request after this many milliseconds.
 	 *
-	 * This will give a different error to using `signal` with a timeout, which might be preferable in
-	 * some cases. It also has a slightly different internal behaviour: `signal` may abort the request
-	 * only until the response headers have been received, while `timeout` will apply through the entire
-	 * response receipt.
+	 * This rejects with a `TimeoutError` (with the `Timeout` code) whose `cause` is `"timeout"`, which
+	 * tells it apart from the agent's timeouts (`"agent.timeout.total"` and so on).
+	 *
+	 * It also differs from using `signal` with `AbortSignal.timeout()` in its internal behaviour:
+	 * `signal` may abort the request only until the response headers have been received, while
+	 * `timeout` will apply through the entire response receipt.
 	 */
 	timeout?: number;
+	/**
+	 * Custom to Fáith. Timeouts for this request, in milliseconds, which replace the agent's `timeout`
+	 * options of the same name, longer or shorter, so that endpoints with very different latencies
+	 * can share an agent:
+	 *
+	 * - `connect`: for connecting, if the request needs a new connection.
+	 * - `read`: without progress until the response arrives, and then between each chunk of its body.
+	 * - `total`: for the whole exchange, like `timeout`, which it takes precedence over.
+	 * - `body`: for reading the response body in full, from when the response arrived.
+	 *
+	 * These reject with a `TimeoutError` (with the `Timeout` code) whose `cause` is the option that
+	 * fired, e.g. `"timeouts.connect"`, or fail the body's read once the response has arrived.
+	 */
+	timeouts?: RequestTimeouts;
+	/**
+	 * Custom to Fáith. The top-level site of the request, as a URL or a host: the site of the page a
+	 * browser would have made it from, for the agent's `cookiePolicy`. Replaces the policy's
+	 * `topLevelSite`.
+	 */
+	topLevelSite?: string;
+	/**
+	 * Custom to Fáith. Limits how fast the request body is sent, in bytes per second, so that large
+	 * uploads don't saturate the uplink. The body is handed to the connection in slices of a tenth
+	 * of a second's worth of bytes, so the rate holds on average rather than for each packet.
+	 *
+	 * A buffered `body` keeps its `Content-Length`. Like a large `ReadableStream` body, a paced body
+	 * can't be sent again, so `307` and `308` redirects fail with a `BodyNotReplayable` error. Throws
+	 * a `Config` error if zero.
+	 */
+	uploadRateLimit?: number;
+	/**
+	 * Custom to Fáith. Sets the user agent string for this request, replacing the agent's `userAgent`.
+	 * The agent's `userAgentSuffix` is still appended. A `User-Agent` in `headers` takes precedence.
+	 */
+	userAgent?: string;
+	/**
+	 * Custom to Fáith. Pins the HTTP version used for this request, overriding both the `Agent`
+	 * configuration and any HTTP/3 upgrade decision from the Alt-Svc cache. This is intended for
+	 * debugging protocol-specific server behaviour.
+	 *
+	 * - `http/1.1`: use HTTP/1.1 (or HTTP/1.0 if the agent is configured with `http1.version10`).
+	 * - `h2`: use HTTP/2, without negotiation; the request fails if the server doesn't support it.
+	 * - `h3`: use HTTP/3 directly, without needing an Alt-Svc advertisement; the request fails if the
+	 *   server doesn't support it.
+	 *
+	 * Pinned HTTP/1.1 and HTTP/2 requests use a connection pool separate from the agent's main pool.
+	 */
+	version?: "http/1.1" | "h2" | "h3";
 }
 
 export interface PeerInformation {
 	/**
-	 * The IP address and port of the peer, if available.
+	 * The IP address and port of the peer, if available. When going through a proxy, this is the
+	 * proxy's address.
 	 */
 	address?: string;
 	/**
 	 * When connected over HTTPS, this is the DER-encoded leaf certificate of the peer.
 	 */
 	certificate?: Buffer;
+	/**
+	 * When the request went through a proxy, details about it.
+	 */
+	proxy?: {
+		/**
+		 * The proxy's URL, without credentials.
+		 */
+		url: string;
+		/**
+		 * Whether the connection was tunneled through the proxy with `CONNECT`, as for `https://`
+		 * requests through an HTTP proxy.
+		 */
+		tunnel: boolean;
+		/**
+		 * For tunnels, the status the proxy answered the `CONNECT` with. A tunnel is only established
+		 * on a `200` answer; any other is an error: `ProxyAuth` for `407`, `Network` otherwise.
+		 */
+		connectStatus?: number;
+	};
 }
 
 export class Response {
@@ -230,10 +535,17 @@ export class Response {
 	 * The `headers` read-only property of the `Response` interface contains the `Headers` object
 	 * associated with the response.
 	 *
-	 * Note that Fáith does not provide a custom `Headers` class; instead the Web API `Headers` structure
-	 * is used directly and constructed by Fáith when needed.
+	 * Note that this uses the Web API `Headers` structure, constructed by Fáith when needed. See
+	 * `faithHeaders` for Fáith's own `Headers`-like class.
 	 */
 	readonly headers: Headers;
+	/**
+	 * Custom to Fáith.
+	 *
+	 * The `faithHeaders` read-only property of the `Response` interface contains a `FaithHeaders`
+	 * object with the headers of the response. Each access returns a new copy.
+	 */
+	readonly faithHeaders: FaithHeaders;
 	/**
 	 * The `ok` read-only property of the `Response` interface contains a boolean stating whether the
 	 * response was successful (status in the range 200-299) or not.
@@ -254,6 +566,14 @@ export class Response {
 	 * cannot prevent it by aborting the fetch at this point.
 	 */
 	readonly redirected: boolean;
+	/**
+	 * The `history` read-only property of the `Response` interface contains the redirects which were
+	 * followed to get this response, in order: each has the `url` which answered with a redirect, its
+	 * `status`, and its `location`. It's empty if the request wasn't redirected, or if the response
+	 * came from the cache. The headers of redirect responses are not available. This is custom to
+	 * Fáith.
+	 */
+	readonly history: RedirectSnapshot[];
 	/**
 	 * The `status` read-only property of the `Response` interface contains the HTTP status codes of the
 	 * response. For example, 200 for success, 404 if the resource could not be found.
@@ -264,18 +584,29 @@ export class Response {
 	 * corresponding to the HTTP status code in `Response.status`. For example, this would be `OK` for a
 	 * status code `200`, `Continue` for `100`, `Not Found` for `404`.
 	 *
-	 * In HTTP/1, servers can send custom status text. This is returned here. In HTTP/2 and HTTP/3, custom
-	 * status text is not supported at all, and the `statusText` property is either empty or simulated
-	 * from well-known status codes.
+	 * In HTTP/1, servers can send custom status text. This is returned here, unless the agent has
+	 * `http1.canonicalStatusText`. In HTTP/2 and HTTP/3, custom status text is not supported at all,
+	 * and the `statusText` property is either empty or simulated from well-known status codes.
 	 */
 	readonly statusText: string;
+	/**
+	 * Custom to Fáith.
+	 *
+	 * The `reasonPhrase` read-only property contains the raw bytes of the reason phrase from the
+	 * HTTP/1 status line, e.g. `OK` in `HTTP/1.1 200 OK`, even when `statusText` is canonical.
+	 *
+	 * This is `null` for HTTP/2 and HTTP/3, which have no reason phrases, and for responses from
+	 * the cache.
+	 */
+	readonly reasonPhrase: Buffer | null;
 	/**
 	 * The `type` read-only property of the `Response` interface contains the type of the response. The
 	 * type determines whether scripts are able to access the response body and headers.
 	 *
-	 * In Fáith, this is always set to `basic`.
+	 * In Fáith, this is set to `basic`, or to `opaqueredirect` for redirects returned by a `strict`
+	 * agent with `redirect: "manual"`.
 	 */
-	readonly type: "basic";
+	readonly type: "basic" | "opaqueredirect";
 	/**
 	 * The `url` read-only property of the `Response` interface contains the URL of the response. The
 	 * value of the `url` property will be the final URL obtained after any redirects.
@@ -288,11 +619,67 @@ export class Response {
 	 * This is custom to Fáith.
 	 */
 	readonly version: string;
+	/**
+	 * The `httpVersion` read-only property of the `Response` interface contains the HTTP version of
+	 * the response in structured form, which is easier to check programmatically than `version`:
+	 *
+	 * - `major`: the major version number, e.g. `2` for HTTP/2.
+	 * - `minor`: the minor version number, e.g. `1` for HTTP/1.1.
+	 * - `alpn`: the ALPN protocol identifier for this version, e.g. `http/1.1`, `h2`, or `h3`. This is
+	 *   set regardless of whether the version was actually negotiated via ALPN.
+	 *
+	 * This is custom to Fáith.
+	 */
+	readonly httpVersion: HttpVersion;
+	/**
+	 * The `altSvc` read-only property of the `Response` interface contains what the agent's HTTP/3
+	 * upgrade did for this request:
+	 *
+	 * - `entry`: the Alt-Svc cache entry found for the origin: `advertised` by an `Alt-Svc` header or
+	 *   `http3.hints`, or `confirmed` by a previous HTTP/3 request. Absent if there was none.
+	 * - `attempted`: whether the request was first tried over HTTP/3.
+	 * - `recentlyFailed`: whether HTTP/3 failed for the origin within `http3.upgradeFailedTtl`.
+	 * - `fellBack`: whether the HTTP/3 attempt failed and the request was sent again over TCP.
+	 * - `fallbackAfter`: when `fellBack`, how long the HTTP/3 attempt took to fail, in milliseconds.
+	 *
+	 * This is `null` when the upgrade wasn't considered: when it's disabled, when the request pinned
+	 * its `version`, when the response came from the cache, or without HTTP/3 support.
+	 *
+	 * This is custom to Fáith.
+	 */
+	readonly altSvc: AltSvcDecision | null;
+	/**
+	 * The `timestamps` read-only property of the `Response` interface contains wall-clock times over
+	 * the course of the request, in milliseconds since the Unix epoch:
+	 *
+	 * - `requestStart`: when the request was started, before connecting or looking in the cache.
+	 * - `connectStart`, `connectEnd`: when a new connection was started and established for this
+	 *   request, including DNS, TCP, the proxy's `CONNECT` exchange, and TLS. Only set for the first
+	 *   request on a new HTTP/1.1 or HTTP/2 connection.
+	 * - `headersComplete`: when the response headers were received.
+	 * - `firstByte`: when the first chunk of the response body was received, once the body is being
+	 *   read. Never set for responses without a body.
+	 * - `bodyComplete`: when the response body was fully received, once it has been read to the end.
+	 *   For responses without a body, it's the same as `headersComplete`.
+	 *
+	 * This is custom to Fáith.
+	 */
+	readonly timestamps: ResponseTimestamps;
+	/**
+	 * The `correlationId` read-only property of the `Response` interface contains the request's
+	 * `correlationId`, from its options or the `withCorrelationId()` scope it was made in, or
+	 * `null`.
+	 *
+	 * This is custom to Fáith.
+	 */
+	readonly correlationId: string | null;
 
 	/**
 	 * The `body` read-only property of the `Response` interface is a `ReadableStream` of the body
-	 * contents, or `null` for any actual HTTP response that has no body, such as `HEAD` requests and
-	 * `204 No Content` responses.
+	 * contents, or `null` for any actual HTTP response that has no body: responses to `HEAD` requests,
+	 * and those with a null body status (`101`, `103`, `204`, `205`, and `304`). The `Content-Length`
+	 * header of a `HEAD` response is kept, so the size of the resource can still be read, except when
+	 * the response is compressed, as decompression removes it.
 	 *
 	 * Note that browsers currently do not return `null` for those responses, but the spec requires
 	 * it. Fáith chooses to respect the spec rather than the browsers in this case.
@@ -304,6 +691,49 @@ export class Response {
 	 */
 	readonly body: ReadableStream<Uint8Array> | null;
 
+	/**
+	 * Custom to Fáith. Returns an async iterator over the chunks of the body, as `Buffer`s:
+	 *
+	 * ```javascript
+	 * for await (const chunk of response.chunks()) {
+	 *   // ...
+	 * }
+	 * ```
+	 *
+	 * This reads the body directly rather than going through a `ReadableStream` like `body` does,
+	 * which avoids its overhead. Like `body`, this marks the body as used, and the `integrity` is not
+	 * checked. The iterator is empty for responses without a body.
+	 *
+	 * Throws a `ResponseAlreadyDisturbed` error if the body has already been used.
+	 */
+	chunks(): AsyncIterableIterator<Buffer>;
+
+	/**
+	 * Returns statistics on how the body is being read, to help diagnose memory growth when the body
+	 * is shared between clones:
+	 *
+	 * - `chunksDelivered`: how many chunks have been read, summed over all consumers.
+	 * - `bytesDelivered`: how many bytes have been read, summed over all consumers.
+	 * - `consumers`: how many readers of the body are currently open, across clones.
+	 * - `bufferedBytes`: how many bytes of the body are held in memory so clones can read them. This
+	 *   is everything read so far, until the response and its clones are dropped or discarded.
+	 *
+	 * This does not mark the body as used. This is custom to Fáith.
+	 */
+	bodyStats(): BodyStats;
+
+	/**
+	 * The `suggestedFilename()` method of the `Response` interface returns a safe filename to save the
+	 * body as: the `filename*` or `filename` parameter of the `Content-Disposition` header, decoding
+	 * RFC 8187 (formerly RFC 5987) encoded values, or else the last segment of the URL's path, or
+	 * `null` if there's neither.
+	 *
+	 * The filename can be used as is on any common filesystem: path separators, control characters,
+	 * leading dots, and names Windows reserves are removed or escaped. It should still be saved in a
+	 * directory chosen by the application. This is custom to Fáith.
+	 */
+	suggestedFilename(): string | null;
+
 	/**
 	 * The `trailers()` read-only property of the `Response` interface returns a promise that
 	 * resolves to either `null` or a `Headers` structure that contains the HTTP/2 or /3 trailing
@@ -381,9 +811,47 @@ export class Response {
 	 * in every way, but stored in a different variable.
 	 *
 	 * `clone()` throws an `Error` if the response body has already been used.
+	 *
+	 * The body is kept in memory until every clone has read it, unless the agent has `spill` set.
 	 */
 	clone(): Response;
 
+	/**
+	 * Custom to Fáith. Splits the response into `count` responses (default 2), which each read the
+	 * same body independently, so that one can be hashed or saved while another is parsed. This
+	 * response's body is then used.
+	 *
+	 * Unlike `clone()`, which keeps the body in memory until every clone has read it, the responses
+	 * from `tee()` only buffer up to 16 chunks ahead of the slowest of them. This means they must be
+	 * read concurrently: reading one to the end before starting another will stall once it gets
+	 * that far ahead. Call `discard()` on, or drop, those you don't need.
+	 *
+	 * `tee()` throws an `Error` if the response body has already been used.
+	 */
+	tee(count?: number): Response[];
+
+	/**
+	 * Custom to Fáith. Returns a snapshot of the response, without reading its body. See
+	 * `ResponseSnapshot`.
+	 *
+	 * This is called by `JSON.stringify()`.
+	 */
+	toJSON(): ResponseSnapshot;
+
+	/**
+	 * Custom to Fáith. Gives up this response, so that it can be read in another thread, and returns
+	 * a handle to it. Post the handle to a worker with `postMessage()`, and call
+	 * `receiveResponse()` with it there to get the response back, with its body still unread. The
+	 * body is then read in the worker straight from Fáith, rather than being copied over chunk by
+	 * chunk. This response's body is then used.
+	 *
+	 * A handle which is never received keeps the response, and its connection, until the process
+	 * exits: call `discard()` on the received response if its body isn't needed.
+	 *
+	 * `transfer()` throws an `Error` if the response body has already been used.
+	 */
+	transfer(): TransferredResponse;
+
 	/**
 	 * This is entirely custom to Fáith. It returns a Web API `Response` instead of Fáith's custom
 	 * `Response` class. However, it's not possible to construct a Web API `Response` that has all the
@@ -420,5 +888,454 @@ export declare function fetch(
 	/**
 	 * A `RequestInit` object containing any custom settings that you want to apply to the request.
 	 */
-	options?: FetchOptions,
+	options?: FetchOptions | null,
 ): Promise<Response>;
+
+type Resource = string | Request | URL | { toString(): string };
+type AgentFetchOptions = Omit<FetchOptions, "agent" | "method">;
+
+declare module "./index" {
+	interface Agent {
+		/** Makes a request with this agent, like `fetch()` with the `agent` option. */
+		fetch(resource: Resource, options?: Omit<FetchOptions, "agent"> | null): Promise<Response>;
+		/** Makes a `GET` request with this agent, like `fetch()`. */
+		get(resource: Resource, options?: AgentFetchOptions | null): Promise<Response>;
+		/** Makes a `HEAD` request with this agent, like `fetch()`. */
+		head(resource: Resource, options?: AgentFetchOptions | null): Promise<Response>;
+		/** Makes a `DELETE` request with this agent, like `fetch()`. */
+		delete(resource: Resource, options?: AgentFetchOptions | null): Promise<Response>;
+		/**
+		 * Makes a `POST` request with this agent, like `fetch()`. The `body` argument takes precedence
+		 * over `options.body`.
+		 */
+		post(
+			resource: Resource,
+			body?: FetchOptions["body"],
+			options?: AgentFetchOptions | null,
+		): Promise<Response>;
+		/**
+		 * Makes a `PUT` request with this agent, like `fetch()`. The `body` argument takes precedence
+		 * over `options.body`.
+		 */
+		put(
+			resource: Resource,
+			body?: FetchOptions["body"],
+			options?: AgentFetchOptions | null,
+		): Promise<Response>;
+		/**
+		 * Makes a `PATCH` request with this agent, like `fetch()`. The `body` argument takes precedence
+		 * over `options.body`.
+		 */
+		patch(
+			resource: Resource,
+			body?: FetchOptions["body"],
+			options?: AgentFetchOptions | null,
+		): Promise<Response>;
+		/**
+		 * Fetches the URL and parses the response body as JSON, in one go.
+		 *
+		 * This is equivalent to `fetch(url, { agent, ...options }).then(res => res.json())`, but without
+		 * the intermediate `Response` crossing into JavaScript. The request is sent with an
+		 * `Accept: application/json` header unless one is given in `options.headers`. The `body` may
+		 * only be a string, `Buffer`, `Uint8Array`, or `ArrayBuffer`, and `Request` objects are not
+		 * supported.
+		 *
+		 * Like `fetch()`, this does not reject on HTTP error statuses: the body is parsed regardless.
+		 */
+		json(resource: string | URL, options?: AgentFetchOptions | null): Promise<unknown>;
+		/**
+		 * Custom to Fáith. Sends a GraphQL operation with this agent, as a `POST` of the standard JSON
+		 * envelope, and resolves to the parsed result, with its `data` and any `extensions`.
+		 *
+		 * The `options` are as for `agent.json()`, except that the method and body are replaced. Rejects
+		 * with a `FaithError` with the `Graphql` code when the result has errors, which has the `errors`
+		 * and any partial `data` as properties. With `persisted`, the query is sent as only its hash
+		 * first, and in full if the server doesn't know it yet.
+		 *
+		 * ```js
+		 * const { data } = await agent.graphql("https://api.example.com/graphql", {
+		 *   query: "query ($id: ID!) { user(id: $id) { name } }",
+		 *   variables: { id: 1 },
+		 * });
+		 * ```
+		 */
+		graphql(
+			resource: string | URL,
+			request: GraphqlRequest,
+			options?: Omit<AgentFetchOptions, "body" | "json" | "method"> | null,
+		): Promise<{ data?: any; extensions?: Record<string, unknown> }>;
+		/**
+		 * Custom to Fáith. Makes a gRPC-web unary call with this agent: `resource` is the method's URL,
+		 * like `https://api.example.com/package.Service/Method`, and `message` is the serialised
+		 * request message, e.g. protobuf bytes.
+		 *
+		 * The `options` are as for `agent.json()`, except that the method and body are replaced, and
+		 * the `headers` are sent as the call's metadata. Like `fetch()`, this doesn't reject on gRPC
+		 * error statuses: check `status`, which is `0` for `OK`. HTTP error statuses reject with a
+		 * `FaithHttpError`, and malformed responses with a `FaithSyntaxError` with the `GrpcWeb` code.
+		 *
+		 * ```js
+		 * const { status, message } = await agent.grpcWeb(
+		 *   "https://api.example.com/helloworld.Greeter/SayHello",
+		 *   HelloRequest.encode({ name: "Ada" }).finish(),
+		 * );
+		 * ```
+		 */
+		grpcWeb(
+			resource: string | URL,
+			message: ArrayBuffer | ArrayBufferView | null,
+			options?: Omit<AgentFetchOptions, "body" | "json" | "method"> | null,
+		): Promise<GrpcWebResult>;
+		/**
+		 * Custom to Fáith. Iterates over the pages of a paginated resource, fetching each page with
+		 * this agent once the previous one has been handled.
+		 *
+		 * The next page's URL is taken from the response's `Link: <url>; rel="next"` header (RFC 8288)
+		 * by default, or from `nextFrom`. Iteration ends when there's no next page, or after
+		 * `maxPages`. The `options` are used for every page; streaming bodies are not supported.
+		 *
+		 * Pages which fail with a retriable error (see `errorCodes()`), or which have a `429` or `503`
+		 * status, are retried up to `retries` times, waiting for as long as their `Retry-After` header
+		 * says, or with an exponential backoff otherwise. This also applies to `HttpError`s with those
+		 * statuses, with `throwOnError`. The `signal` aborts the wait too.
+		 *
+		 * ```js
+		 * for await (const page of agent.paginate("https://api.example.com/items")) {
+		 *   for (const item of await page.json()) console.log(item);
+		 * }
+		 * ```
+		 */
+		paginate(
+			resource: string | URL | { toString(): string },
+			options?: AgentFetchOptions | null,
+			paginateOptions?: PaginateOptions | null,
+		): AsyncGenerator<Response, void, undefined>;
+		/**
+		 * Custom to Fáith. Fetches a resource into the agent's HTTP cache in the background, without
+		 * returning the response, for predictive loading. Resolves once the response is stored, or
+		 * rejects if it couldn't be fetched; the promise can be ignored.
+		 *
+		 * The `options` are as for `agent.json()`, except that the method is always `GET`, and there's
+		 * no body. At most `cache.prefetchConcurrency` prefetches are in flight at once, and
+		 * prefetching a URL which is already being prefetched joins that prefetch. The agent must have
+		 * a `cache.store`.
+		 */
+		prefetch(
+			resource: string | URL | { toString(): string },
+			options?: Omit<AgentFetchOptions, "body" | "json" | "method" | "signal"> | null,
+		): Promise<void>;
+		/**
+		 * Custom to Fáith. Prepares a request with this agent, to be sent later with `.send()`, possibly
+		 * many times. The URL, method, and headers are parsed and validated once, here.
+		 *
+		 * The `body` may only be a string, `Buffer`, `Uint8Array`, `ArrayBuffer`, or array of bytes,
+		 * and `Request` objects are not supported. Throws if the URL, method, or headers are invalid.
+		 */
+		prepare(
+			resource: string | URL | { toString(): string },
+			options?: Omit<AgentFetchOptions, "signal"> | null,
+		): PreparedRequest;
+	}
+
+	interface PreparedRequest {
+		/**
+		 * Sends the request, like `fetch()`. The `body` argument takes precedence over the `body`
+		 * given to `agent.prepare()`, and may be of the same types.
+		 */
+		send(
+			body?: string | ArrayBuffer | ArrayBufferView | number[] | null,
+			options?: { signal?: AbortSignal } | null,
+		): Promise<Response>;
+	}
+}
+
+export interface GrpcWebResult {
+	/** The gRPC status code, from the `grpc-status` trailer: `0` is `OK`. */
+	status: number;
+	/** The `grpc-message` trailer, percent-decoded, if any. */
+	statusMessage: string | null;
+	/** The serialised response message, or `null` if the server sent none, as with error statuses. */
+	message: Buffer | null;
+	/** The response headers, which carry the call's initial metadata. */
+	headers: Headers;
+	/** The trailers, which carry the call's trailing metadata. */
+	trailers: Headers;
+}
+
+export interface PaginateOptions {
+	/**
+	 * Where to find the next page's URL: `link-header` for the `rel="next"` link of the `Link`
+	 * header, or a function given a clone of each page's response (so it may read the body), which
+	 * returns the next page's URL, or `null` or `undefined` if it's the last page. Relative URLs
+	 * are resolved against the page's URL.
+	 *
+	 * Default: `link-header`.
+	 */
+	nextFrom?:
+		| "link-header"
+		| ((
+				response: Response,
+		  ) => string | URL | null | undefined | Promise<string | URL | null | undefined>);
+	/**
+	 * Stop after this many pages.
+	 *
+	 * Default: no limit.
+	 */
+	maxPages?: number;
+	/**
+	 * How many times to retry a page, after a retriable error or a `429` or `503` status.
+	 *
+	 * Default: 3.
+	 */
+	retries?: number;
+	/**
+	 * The delay before the first retry, in milliseconds, when the response has no `Retry-After`
+	 * header. It doubles with each retry.
+	 *
+	 * Default: 1000.
+	 */
+	retryDelay?: number;
+}
+
+export interface FetchAllOptions {
+	/**
+	 * Maximum number of requests in flight at once. Requests are started in order as others complete.
+	 *
+	 * Default: 16.
+	 */
+	concurrency?: number;
+	/**
+	 * Reject with the first error, instead of settling every request. Requests in flight are then
+	 * cancelled, and requests not yet started are not sent.
+	 *
+	 * Default: false.
+	 */
+	failFast?: boolean;
+	/** An `AbortSignal` to abort the whole batch. */
+	signal?: AbortSignal;
+}
+
+/**
+ * Custom to Fáith.
+ *
+ * Fetches many resources at once, with a single native task for the whole batch instead of one per
+ * request. This avoids most of the per-request overhead of crossing between JavaScript and native
+ * code, which adds up when making hundreds of small requests.
+ *
+ * Each request is either a URL, or an object with the URL and fetch options. Streaming bodies and
+ * per-request signals are not supported.
+ *
+ * Resolves to an array of results in the same order as the requests, like `Promise.allSettled()`.
+ * With `failFast`, rejects with the first error instead.
+ */
+export declare function fetchAll(
+	requests: Iterable<
+		| string
+		| URL
+		| { url: string | URL; options?: Omit<FetchOptions, "signal"> }
+	>,
+	options?: FetchAllOptions | null,
+): Promise<Array<PromiseSettledResult<Response>>>;
+
+/** The response from `faithFetchSync()`, with its body read in full. */
+export interface SyncResponse {
+	readonly status: number;
+	readonly statusText: string;
+	readonly ok: boolean;
+	readonly url: string;
+	readonly redirected: boolean;
+	readonly headers: Headers;
+	/** The whole body. */
+	readonly body: Buffer;
+	/** Returns the body. */
+	bytes(): Buffer;
+	/** Decodes the body as UTF-8. */
+	text(): string;
+	/** Parses the body as JSON. Throws a `SyntaxError` if it's not valid JSON. */
+	json(): any;
+}
+
+/**
+ * Custom to Fáith.
+ *
+ * Fetches a resource and reads its body in full, blocking the thread until it's done, for short
+ * CLI tools and config loaders which run before the event loop matters. Don't use it in servers,
+ * where it would stop everything else for the duration of the request.
+ *
+ * Takes the same options as `fetch()`, except that streaming and `Blob` bodies, and `signal`, throw
+ * a `TypeError`. The `timeout` option (or `timeouts.total`) is a hard limit on the whole request
+ * including reading the body, and defaults to 30 seconds: it fails with a `Timeout` error once it's over. The body is
+ * limited to the agent's `limits.responseBody`, or 16 MiB if it has none, and fails with a
+ * `ResponseTooLarge` error if it's larger. HTTP error statuses don't throw, unless the agent or
+ * the request has `throwOnError`.
+ *
+ * Callbacks which run on the JS thread, like the `observe` option and the agent's cookie filter,
+ * can't run while it's blocked: they run once it returns, so cookies subject to a filter are only
+ * stored then.
+ */
+export declare function faithFetchSync(
+	resource: string | URL,
+	options?: Omit<FetchOptions, "signal"> | null,
+): SyncResponse;
+
+/**
+ * Custom to Fáith.
+ *
+ * Takes a response given up with `response.transfer()`, typically in a worker thread the handle
+ * was posted to, so that its body can be read there without copying the chunks between threads.
+ * Each handle can only be received once.
+ *
+ * The response's body keeps being read from the network by Fáith's own threads. Its request's
+ * `observe` callback, and the performance timeline entry, stay with the thread which made the
+ * request.
+ *
+ * Throws a `ResponseAlreadyDisturbed` error if the handle was already received.
+ */
+export declare function receiveResponse(handle: TransferredResponse): Response;
+
+/**
+ * Custom to Fáith.
+ *
+ * An undici `Dispatcher` which makes its requests with Fáith, so that libraries which accept a
+ * `dispatcher` option, and undici's or Node's own `fetch()`, can be routed through an agent
+ * without code changes. It can also be installed with undici's `setGlobalDispatcher()`.
+ *
+ * Requests go through the given `agent`, or the global agent if there's none. Redirects,
+ * decompression, and cookies are handled by the agent as configured, so the responses handed to
+ * undici are the final ones, already decompressed. Handlers may use either undici's legacy API
+ * (`onConnect`, `onHeaders`, `onData`, `onComplete`, `onError`) or its newer one
+ * (`onRequestStart`, `onResponseStart`, `onResponseData`, `onResponseEnd`, `onResponseError`),
+ * including pausing and resuming. Upgrades and `CONNECT` requests are not supported, and fail with
+ * a `TypeError`.
+ *
+ * Only `dispatch()`, `close()`, and `destroy()` are implemented: undici's `request()`, `stream()`,
+ * and `pipeline()` are not.
+ */
+export declare class FaithDispatcher extends EventEmitter {
+	constructor(options?: { agent?: Agent } | null);
+	/** Whether `close()` or `destroy()` was called. */
+	readonly closed: boolean;
+	/** Whether `destroy()` was called. */
+	readonly destroyed: boolean;
+	/**
+	 * Makes a request, calling the handler with its response. Always returns true, as requests are
+	 * queued by the agent rather than the dispatcher.
+	 */
+	dispatch(options: DispatchOptions, handler: object): boolean;
+	/**
+	 * Stops accepting requests, and waits for the ones in flight to finish. Requests dispatched
+	 * afterwards fail.
+	 */
+	close(): Promise<void>;
+	close(callback: (error: Error | null) => void): void;
+	/** Stops accepting requests, and aborts the ones in flight with the `error`. */
+	destroy(error?: Error | null): Promise<void>;
+	destroy(callback: (error: Error | null) => void): void;
+	destroy(error: Error | null, callback: (error: Error | null) => void): void;
+}
+
+/**
+ * Custom to Fáith.
+ *
+ * A Node `http.Agent` which makes its requests with Fáith, so that libraries based on
+ * `http.request()`, like axios with its `httpAgent` and `httpsAgent` options, go through Fáith
+ * without being rewritten.
+ *
+ * The HTTP/1.1 request written by `http.request()` is parsed and made with `fetch()` through the
+ * given `agent`, or the global agent if there's none, and the response is written back as
+ * HTTP/1.1. Connections are pooled by the agent, not by this one, whose sockets each carry a single
+ * request. As `http.request()` checks that its agent is for the right protocol, use one with
+ * `protocol: "https:"` for `https.request()`: TLS is then done by Fáith, with the agent's `tls`
+ * options rather than those of the request.
+ *
+ * Redirects, decompression, and cookies are handled by the agent as configured. The `Host` header
+ * and socket timeouts set on the request are ignored: use the agent's `timeout` options instead.
+ * Upgrades and `Expect: 100-continue` are not supported.
+ */
+export declare class FaithHttpAgent extends HttpAgent {
+	constructor(
+		options?:
+			| ({ agent?: Agent; protocol?: "http:" | "https:" } & HttpAgentOptions)
+			| null,
+	);
+}
+
+/** The options of a request made with `FaithDispatcher.dispatch()`, as in undici. */
+export interface DispatchOptions {
+	origin: string | URL;
+	/** The path and query of the request. */
+	path: string;
+	method: string;
+	/** Alternating names and values, pairs, or an object whose values may be arrays. */
+	headers?:
+		| Array<string | Buffer>
+		| Iterable<[string, string]>
+		| Record<string, string | string[] | undefined>
+		| null;
+	/** Node streams and other async iterables are streamed. */
+	body?: string | Buffer | Uint8Array | Blob | AsyncIterable<Uint8Array> | null;
+	upgrade?: string | null;
+}
+
+/**
+ * Custom to Fáith.
+ *
+ * Replaces `globalThis.fetch` with Fáith's `fetch()`, so that frameworks and libraries which call
+ * the global fetch internally make their requests through Fáith. Requests go through the given
+ * `agent`, or the global agent if there's none, unless they give their own `agent` option. The
+ * global `Request` and `Headers` classes are accepted as they are, and responses are Fáith's
+ * `Response`, whose `headers` is a global `Headers`. Use a `strict` agent for code which relies on
+ * the spec's behaviour, such as its error types.
+ *
+ * Installing again replaces the agent. Returns `uninstallGlobal`.
+ */
+export declare function installGlobal(options?: { agent?: Agent } | null): () => void;
+
+/**
+ * Custom to Fáith.
+ *
+ * Restores the `globalThis.fetch` replaced by `installGlobal()`. Does nothing if it's not
+ * installed.
+ */
+export declare function uninstallGlobal(): void;
+
+export interface UploadOptions
+	extends Omit<FaithUploadOptions, "headersPerPart"> {
+	/** Headers sent with every part, in addition to the agent's default headers. */
+	headersPerPart?: FetchOptions["headers"];
+	/** An `AbortSignal` to abort the whole upload. */
+	signal?: AbortSignal;
+}
+
+/**
+ * Custom to Fáith.
+ *
+ * Uploads a file in parts, for multipart upload endpoints like S3's `UploadPart`. The file is read
+ * a part at a time, so only `concurrency` parts are in memory at once.
+ *
+ * Each part is sent to the URL with a `partNumber` query parameter (from 1) and a `Content-Range`
+ * header giving its place in the file, and is retried on network errors and `408`, `429`, and `5xx`
+ * responses. Starting and completing the upload, e.g. with S3's `CreateMultipartUpload` and
+ * `CompleteMultipartUpload`, is left to the caller: the parts' `etag`s are returned for this.
+ *
+ * Resolves to the parts in order. Rejects with the first error: an `Upload` error if the file can't
+ * be read or a part fails after its retries, or the error of a part's last attempt.
+ */
+export declare function upload(
+	resource: string | URL,
+	filePath: string,
+	options?: UploadOptions | null,
+): Promise<Array<FaithUploadPart>>;
+
+/**
+ * Custom to Fáith. Runs `fn` with a correlation ID for the requests it makes, using
+ * `AsyncLocalStorage`: requests made within it, including after it awaits, get this
+ * `correlationId` unless their options have one. Scopes can be nested: the innermost one applies.
+ *
+ * Returns what `fn` returns.
+ *
+ * ```js
+ * app.use((req, res, next) => withCorrelationId(req.headers["x-request-id"], next));
+ * ```
+ */
+export declare function withCorrelationId<T>(correlationId: string, fn: () => T): T;
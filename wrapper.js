@@ -7,16 +7,116 @@
  * instead of `FetchResponse`.
  */
 
+const { AsyncLocalStorage } = require("node:async_hooks");
+const { EventEmitter } = require("node:events");
+const http = require("node:http");
+const { isIPv6 } = require("node:net");
+const { Duplex } = require("node:stream");
+const { setTimeout: sleep } = require("node:timers/promises");
 const native = require("./index.js");
 const { faithFetch } = native;
 
 // Generate ERROR_CODES const enum from native error codes
 // e.g. { InvalidHeader: "InvalidHeader", InvalidMethod: "InvalidMethod", ... }
-const ERROR_CODES = native.errorCodes().reduce((acc, code) => {
+const ERROR_CODES = native.errorCodes().reduce((acc, { code }) => {
 	acc[code] = code;
 	return acc;
 }, {});
 
+// The correlation ID of the current withCorrelationId() scope
+const correlationStorage = new AsyncLocalStorage();
+
+// The codes of errors which are likely to be transient, for retries
+const RETRIABLE_CODES = new Set(
+	native
+		.errorCodes()
+		.filter(({ retriable }) => retriable)
+		.map(({ code }) => code),
+);
+
+/**
+ * Create the error for an aborted signal, carrying its reason
+ * @param {AbortSignal} signal
+ * @returns {Error}
+ *
+ * Like in browsers, the reason given to `controller.abort(reason)` is what the request rejects with,
+ * but as an `AbortError` with the `Aborted` code: its message is the reason's (or the reason itself,
+ * if it's not an error), and the reason value is the error's `cause`. Signals which abort because
+ * of a timeout, like `AbortSignal.timeout()`, give a `TimeoutError` with the `Timeout` code instead,
+ * including when combined with `AbortSignal.any()`.
+ */
+function abortError(signal) {
+	const { reason } = signal;
+	const timeout = reason?.name === "TimeoutError";
+	const message =
+		reason === undefined
+			? "the request was aborted"
+			: reason instanceof Error
+				? reason.message
+				: String(reason);
+	const code = timeout ? ERROR_CODES.Timeout : ERROR_CODES.Aborted;
+	const ErrorClass = timeout ? native.FaithTimeoutError : native.FaithAbortError;
+	return new ErrorClass(`${code}: ${message}`, { cause: reason, code });
+}
+
+/**
+ * Convert an error to the one the Fetch spec rejects with, for strict agents
+ * @param {Error} error
+ * @param {AbortSignal} [signal]
+ * @returns {any}
+ *
+ * Requests aborted by their signal reject with its reason itself, and other aborts with an
+ * `AbortError` DOMException. Network errors and timeouts become a `TypeError`. The Fáith error is
+ * kept as the `cause`, and other errors are returned as they are.
+ */
+function specError(error, signal) {
+	if (error?.code === ERROR_CODES.Aborted) {
+		if (signal?.aborted) {
+			return signal.reason;
+		}
+		const abort = new DOMException(error.message, "AbortError");
+		Object.defineProperty(abort, "cause", {
+			value: error,
+			configurable: true,
+			writable: true,
+		});
+		return abort;
+	}
+	if (
+		error instanceof native.FaithNetworkError ||
+		error instanceof native.FaithTimeoutError
+	) {
+		return new TypeError("fetch failed", { cause: error });
+	}
+	return error;
+}
+
+/**
+ * Replace the generic error of a request aborted by its signal with one carrying the reason
+ * @template T
+ * @param {Promise<T>} promise
+ * @param {AbortSignal} [signal]
+ * @param {boolean} [strict] - whether to reject with the spec's errors instead, see specError()
+ * @returns {Promise<T>}
+ */
+async function withAbortReason(promise, signal, strict) {
+	try {
+		return await promise;
+	} catch (error) {
+		if (strict) {
+			throw specError(error, signal);
+		}
+		if (signal?.aborted && error?.code === ERROR_CODES.Aborted) {
+			const abort = abortError(signal);
+			if (error.correlationId !== undefined) {
+				abort.correlationId = error.correlationId;
+			}
+			throw abort;
+		}
+		throw error;
+	}
+}
+
 /**
  * Response class that provides spec-compliant Fetch API
  */
@@ -71,6 +171,39 @@ class Response {
 		return this.#nativeResponse.body();
 	}
 
+	/**
+	 * Iterate over the response body's chunks, without a ReadableStream
+	 * @returns {AsyncIterableIterator<Buffer>}
+	 */
+	chunks() {
+		const chunks = this.#nativeResponse.chunks();
+		return (async function* () {
+			for (;;) {
+				const chunk = await chunks.next();
+				if (chunk === null || chunk === undefined) {
+					return;
+				}
+				yield chunk;
+			}
+		})();
+	}
+
+	/**
+	 * Statistics on how the body is being read
+	 * @returns {BodyStats}
+	 */
+	bodyStats() {
+		return this.#nativeResponse.bodyStats();
+	}
+
+	/**
+	 * A safe filename for the body, from Content-Disposition or the URL
+	 * @returns {string | null}
+	 */
+	suggestedFilename() {
+		return this.#nativeResponse.suggestedFilename();
+	}
+
 	/**
 	 * Convert response body to text (UTF-8)
 	 * @returns {Promise<string>}
@@ -132,6 +265,35 @@ class Response {
 		return new Response(this.#nativeResponse.clone());
 	}
 
+	/**
+	 * Split the response into several which read the same body concurrently
+	 * @param {number} [count=2] - How many responses to return
+	 * @returns {Response[]}
+	 * @throws {Error} If response body has already been read
+	 */
+	tee(count) {
+		return this.#nativeResponse
+			.tee(count)
+			.map((native) => new Response(native));
+	}
+
+	/**
+	 * Give up the response to read it in another thread, see receiveResponse()
+	 * @returns {TransferredResponse}
+	 * @throws {Error} If response body has already been read
+	 */
+	transfer() {
+		return this.#nativeResponse.transfer();
+	}
+
+	/**
+	 * Snapshot of the response for JSON.stringify(), which doesn't read the body
+	 * @returns {ResponseSnapshot}
+	 */
+	toJSON() {
+		return this.#nativeResponse.toJSON();
+	}
+
 	/**
 	 * Convert to a Web API Response object
 	 * @returns {Response} Web API Response object
@@ -154,7 +316,217 @@ class Response {
 	}
 }
 
-let defaultAgent;
+/**
+ * Take a response given up with response.transfer(), e.g. in the worker it was posted to
+ * @param {TransferredResponse} handle
+ * @returns {Response}
+ */
+function receiveResponse(handle) {
+	return new Response(native.receiveResponse(handle));
+}
+
+/**
+ * Convert a HeadersInit to the native array of [name, value] pairs
+ * @param {Headers|FaithHeaders|Iterable<[string, string]>|Record<string, string|null>} init
+ * @param {boolean} [strict] - whether to stringify null and undefined values as the spec does
+ * @returns {Array<[string, string|null]>}
+ *
+ * As in the Fetch spec, values are stringified, and each pair of an iterable must have exactly two
+ * items. Unlike the spec, a null value in a plain object removes the agent's default header of that
+ * name, and an undefined value is skipped, unless `strict` is set.
+ */
+function headersToNative(init, strict) {
+	if (init instanceof native.FaithHeaders) {
+		return init.entries();
+	}
+
+	if (init instanceof Headers) {
+		const headersArray = [];
+		init.forEach((value, name) => {
+			headersArray.push([name, value]);
+		});
+		return headersArray;
+	}
+
+	if (typeof init !== "object") {
+		throw new TypeError(
+			"headers must be a Headers or FaithHeaders object, an array of [name, value] pairs, or a plain object",
+		);
+	}
+
+	// Arrays of pairs, and other iterables like Map
+	if (typeof init[Symbol.iterator] === "function") {
+		const headersArray = [];
+		for (const pair of init) {
+			if (
+				pair === null ||
+				typeof pair !== "object" ||
+				typeof pair[Symbol.iterator] !== "function"
+			) {
+				throw new TypeError("headers pairs must be arrays of [name, value]");
+			}
+			const items = Array.from(pair);
+			if (items.length !== 2) {
+				throw new TypeError(
+					"headers pairs must have exactly two items: a name and a value",
+				);
+			}
+			headersArray.push([String(items[0]), String(items[1])]);
+		}
+		return headersArray;
+	}
+
+	const headersArray = [];
+	for (const [name, value] of Object.entries(init)) {
+		// null removes a default header set on the agent, undefined is skipped
+		if (strict) {
+			headersArray.push([name, String(value)]);
+		} else if (value === null) {
+			headersArray.push([name, null]);
+		} else if (value !== undefined) {
+			headersArray.push([name, String(value)]);
+		}
+	}
+	return headersArray;
+}
+
+/**
+ * View the bytes of an ArrayBuffer, TypedArray, or DataView as a Buffer, without copying
+ * @param {ArrayBuffer|ArrayBufferView} data
+ * @returns {Buffer}
+ *
+ * Only the bytes within a view's byteOffset and byteLength are included.
+ */
+function bufferView(data) {
+	if (Buffer.isBuffer(data)) {
+		return data;
+	}
+	if (ArrayBuffer.isView(data)) {
+		return Buffer.from(data.buffer, data.byteOffset, data.byteLength);
+	}
+	return Buffer.from(data);
+}
+
+/**
+ * Move plain object and array bodies to the json option, with the agent's json-api profile
+ * @param {object} nativeOptions
+ * @param {Agent} [agent] - the global agent if not given
+ */
+function profileBodyToNative(nativeOptions, agent) {
+	const body = nativeOptions.body;
+	if (typeof body !== "object" || body === null) return;
+
+	const prototype = Object.getPrototypeOf(body);
+	if (!Array.isArray(body) && prototype !== Object.prototype && prototype !== null) return;
+
+	if ((agent ?? native.getGlobalAgent()).profile === native.AgentProfile.JsonApi) {
+		nativeOptions.json = body;
+		delete nativeOptions.body;
+	}
+}
+
+/**
+ * Default the correlation ID to the one of the current withCorrelationId() scope, in place
+ * @param {object} nativeOptions
+ */
+function correlationIdToNative(nativeOptions) {
+	const correlationId = nativeOptions.correlationId ?? correlationStorage.getStore();
+	if (correlationId === undefined || correlationId === null) {
+		delete nativeOptions.correlationId;
+	} else {
+		nativeOptions.correlationId = String(correlationId);
+	}
+}
+
+/**
+ * Run a function with a correlation ID for the requests it makes
+ * @template T
+ * @param {string} correlationId
+ * @param {() => T} fn
+ * @returns {T}
+ *
+ * Requests made within the function, including after it awaits, are given this `correlationId`
+ * unless their options have one. Scopes can be nested: the innermost one applies.
+ */
+function withCorrelationId(correlationId, fn) {
+	return correlationStorage.run(String(correlationId), fn);
+}
+
+/**
+ * Convert URL template params to strings, in place
+ * @param {object} nativeOptions
+ */
+function paramsToNative(nativeOptions) {
+	const params = nativeOptions.params;
+	if (params === undefined || params === null) {
+		delete nativeOptions.params;
+		return;
+	}
+	if (typeof params !== "object") {
+		throw new TypeError("params must be an object");
+	}
+
+	nativeOptions.params = Object.fromEntries(
+		Object.entries(params).map(([name, value]) => [name, String(value)]),
+	);
+}
+
+/**
+ * Convert a non-streaming body to one the native binding accepts, in place
+ * @param {object} nativeOptions - with headers already converted to native format
+ *
+ * Native binding handles: string, Buffer, Uint8Array
+ * We convert: ArrayBuffer, TypedArray, DataView, Array<number>, Blob, URLSearchParams
+ *
+ * As per the Fetch spec, the Content-Type header is set for URLSearchParams and typed Blobs, if
+ * not already present.
+ */
+async function bodyToNative(nativeOptions) {
+	const body = nativeOptions.body;
+	if (body instanceof Blob) {
+		nativeOptions.body = Buffer.from(await body.arrayBuffer());
+		defaultContentType(nativeOptions, body.type);
+	} else {
+		bufferedBodyToNative(nativeOptions);
+	}
+}
+
+/**
+ * Convert a body which can be read synchronously, i.e. anything but a Blob, in place
+ * @param {object} nativeOptions - with headers already converted to native format
+ */
+function bufferedBodyToNative(nativeOptions) {
+	const body = nativeOptions.body;
+	if (body instanceof URLSearchParams) {
+		nativeOptions.body = body.toString();
+		defaultContentType(
+			nativeOptions,
+			"application/x-www-form-urlencoded;charset=UTF-8",
+		);
+	} else if (body instanceof ArrayBuffer || ArrayBuffer.isView(body)) {
+		nativeOptions.body = bufferView(body);
+	} else if (Array.isArray(body)) {
+		nativeOptions.body = Buffer.from(body);
+	}
+}
+
+/**
+ * Add a Content-Type header for the body, unless there's one already
+ * @param {object} nativeOptions - with headers already converted to native format
+ * @param {string} contentType - nothing is added if empty
+ */
+function defaultContentType(nativeOptions, contentType) {
+	if (!contentType) return;
+
+	nativeOptions.headers ??= [];
+	if (
+		!nativeOptions.headers.some(
+			([name]) => name.toLowerCase() === "content-type",
+		)
+	) {
+		nativeOptions.headers.push(["Content-Type", contentType]);
+	}
+}
 
 /**
  * Fetch function wrapper
@@ -170,14 +542,24 @@ let defaultAgent;
  *
  * Headers handling:
  * - Headers object: converted to array of [name, value] pairs
- * - Plain object: entries converted to array of [name, value] pairs
+ * - FaithHeaders object: converted to array of [name, value] pairs
+ * - Array of [name, value] pairs, or other iterable such as a Map: copied, values stringified
+ * - Plain object: entries converted to array of [name, value] pairs; a null value removes the
+ *   agent's default header of that name (e.g. User-Agent) for this request, unless the agent is
+ *   strict
  * - null/undefined: treated as no headers
  * - Invalid types: throws TypeError
+ *
+ * The options may be omitted, null, or undefined.
  */
-async function fetch(resource, options = {}) {
+async function fetch(resource, options) {
 	let url;
 	let nativeOptions;
 
+	if (options !== undefined && options !== null && typeof options !== "object") {
+		throw new TypeError("options must be an object");
+	}
+
 	// Handle Request object as resource
 	if (
 		typeof resource === "object" &&
@@ -210,6 +592,9 @@ async function fetch(resource, options = {}) {
 	} else if (typeof resource === "string") {
 		url = resource;
 		nativeOptions = { ...options };
+	} else if (resource instanceof URL) {
+		url = resource.href;
+		nativeOptions = { ...options };
 	} else if (resource && typeof resource.toString === "function") {
 		// Handle objects with stringifier (like URL objects)
 		url = resource.toString();
@@ -220,62 +605,28 @@ async function fetch(resource, options = {}) {
 		);
 	}
 
+	// Strict agents follow the spec for errors and headers, see AgentOptions.strict
+	const strict = (nativeOptions.agent ?? native.getGlobalAgent()).strict === true;
+
 	// Convert headers to native format
 	// This is the inverse of what Response does: Request headers go from
 	// Headers/Object -> Array<[string, string]>, while Response headers go from
 	// Array<[string, string]> -> Headers object
 	if (nativeOptions.headers !== undefined && nativeOptions.headers !== null) {
-		if (nativeOptions.headers instanceof Headers) {
-			// Convert Headers object to array of tuples
-			const headersArray = [];
-			nativeOptions.headers.forEach((value, name) => {
-				headersArray.push([name, value]);
-			});
-			nativeOptions.headers = headersArray;
-		} else if (
-			typeof nativeOptions.headers === "object" &&
-			!Array.isArray(nativeOptions.headers)
-		) {
-			// Convert plain object to array of tuples
-			const headersArray = [];
-			for (const [name, value] of Object.entries(nativeOptions.headers)) {
-				headersArray.push([name, value]);
-			}
-			nativeOptions.headers = headersArray;
-		} else {
-			throw new TypeError(
-				"headers must be a Headers object or a plain object",
-			);
-		}
+		nativeOptions.headers = headersToNative(nativeOptions.headers, strict);
 	} else if (nativeOptions.headers === null) {
 		// Convert null to undefined so Rust treats it as None
 		delete nativeOptions.headers;
 	}
+	paramsToNative(nativeOptions);
+	correlationIdToNative(nativeOptions);
+	profileBodyToNative(nativeOptions, nativeOptions.agent);
 
-	// Convert body to Buffer if needed
-	// Native binding handles: string, Buffer, Uint8Array
-	// We convert: ArrayBuffer, Array<number>, ReadableStream, URLSearchParams
+	// Convert body to Buffer if needed, see bodyToNative()
 	// Validate ReadableStream bodies require duplex option
 	if (nativeOptions.body !== undefined && nativeOptions.body !== null) {
-		// Handle URLSearchParams
-		if (nativeOptions.body instanceof URLSearchParams) {
-			nativeOptions.body = nativeOptions.body.toString();
-			// Set Content-Type if not already set (per Fetch spec)
-			if (!nativeOptions.headers) {
-				nativeOptions.headers = [];
-			}
-			const hasContentType = nativeOptions.headers.some(
-				([name]) => name.toLowerCase() === "content-type",
-			);
-			if (!hasContentType) {
-				nativeOptions.headers.push([
-					"Content-Type",
-					"application/x-www-form-urlencoded;charset=UTF-8",
-				]);
-			}
-		}
 		// Check if body is a ReadableStream
-		else if (
+		if (
 			typeof nativeOptions.body === "object" &&
 			typeof nativeOptions.body.getReader === "function"
 		) {
@@ -293,14 +644,6 @@ async function fetch(resource, options = {}) {
 			const originalStream = nativeOptions.body;
 			delete nativeOptions.body;
 
-			// Attach to the default agent if none is provided
-			if (!nativeOptions.agent) {
-				if (!defaultAgent) {
-					defaultAgent = new native.Agent();
-				}
-				nativeOptions.agent = defaultAgent;
-			}
-
 			// Extract signal to pass as separate parameter
 			const signal = nativeOptions.signal;
 			delete nativeOptions.signal;
@@ -308,12 +651,7 @@ async function fetch(resource, options = {}) {
 			// Check if signal is already aborted
 			if (signal && signal.aborted) {
 				sender.close();
-				const error = new Error(
-					"Aborted: the request was aborted before it could start",
-				);
-				error.name = "AbortError";
-				error.code = ERROR_CODES.Aborted;
-				throw error;
+				throw strict ? signal.reason : abortError(signal);
 			}
 
 			// Start the fetch with the StreamBody
@@ -334,11 +672,7 @@ async function fetch(resource, options = {}) {
 							sender.close();
 							break;
 						}
-						// Convert to Buffer if needed and push
-						const buffer = Buffer.isBuffer(value)
-							? value
-							: Buffer.from(value);
-						const sent = await sender.push(buffer);
+						const sent = await sender.push(bufferView(value));
 						if (!sent) {
 							// Receiver dropped (request completed/aborted)
 							break;
@@ -350,59 +684,1134 @@ async function fetch(resource, options = {}) {
 				}
 			})();
 
-			const nativeResponse = await responsePromise;
+			const nativeResponse = await withAbortReason(
+				responsePromise,
+				signal,
+				strict,
+			);
 			return new Response(nativeResponse);
-		} else if (nativeOptions.body instanceof ArrayBuffer) {
-			nativeOptions.body = Buffer.from(nativeOptions.body);
-		} else if (Array.isArray(nativeOptions.body)) {
-			nativeOptions.body = Buffer.from(nativeOptions.body);
+		} else {
+			await bodyToNative(nativeOptions);
 		}
 	} else if (nativeOptions.body === null) {
 		// Remove null body
 		delete nativeOptions.body;
 	}
 
-	// Attach to the default agent if none is provided
-	if (!nativeOptions.agent) {
-		if (!defaultAgent) {
-			defaultAgent = new native.Agent();
-		}
-		nativeOptions.agent = defaultAgent;
-	}
-
 	// Extract signal to pass as separate parameter
 	const signal = nativeOptions.signal;
 	delete nativeOptions.signal;
 
 	// Check if signal is already aborted
 	if (signal && signal.aborted) {
-		const error = new Error(
-			"Aborted: the request was aborted before it could start",
-		);
-		error.name = "AbortError";
-		error.code = ERROR_CODES.Aborted;
-		throw error;
+		throw strict ? signal.reason : abortError(signal);
 	}
 
-	const nativeResponse = await faithFetch(url, nativeOptions, signal, null);
+	const nativeResponse = await withAbortReason(
+		faithFetch(url, nativeOptions, signal, null),
+		signal,
+		strict,
+	);
 	return new Response(nativeResponse);
 }
 
+// Agent.fetch() and method shorthands. These go through fetch() so they accept the same resources,
+// options, and bodies, and return a wrapped Response; json() keeps the native single round-trip.
+native.Agent.prototype.fetch = function (resource, options) {
+	return fetch(resource, { ...options, agent: this });
+};
+for (const method of ["get", "head", "delete"]) {
+	native.Agent.prototype[method] = function (resource, options) {
+		return fetch(resource, {
+			...options,
+			method: method.toUpperCase(),
+			agent: this,
+		});
+	};
+}
+for (const method of ["post", "put", "patch"]) {
+	native.Agent.prototype[method] = function (resource, body, options) {
+		return fetch(resource, {
+			...options,
+			...(body !== undefined && { body }),
+			method: method.toUpperCase(),
+			agent: this,
+		});
+	};
+}
+
+/**
+ * Convert options to native format, for the APIs which don't support streaming bodies
+ * @param {FetchOptions} [options]
+ * @param {Agent} [agent] - the agent the request is made with, if not in the options
+ * @returns {Promise<FaithOptionsAndBody>}
+ */
+async function simpleOptionsToNative(options, agent) {
+	const nativeOptions = syncOptionsToNative(options, agent);
+	if (nativeOptions.body instanceof Blob) {
+		await bodyToNative(nativeOptions);
+	}
+	return nativeOptions;
+}
+
+/**
+ * Convert options to native format synchronously, leaving Blob bodies as they are
+ * @param {FetchOptions} [options]
+ * @param {Agent} [agent] - the agent the request is made with, if not in the options
+ * @returns {FaithOptionsAndBody}
+ */
+function syncOptionsToNative(options, agent) {
+	const { signal, ...nativeOptions } = options ?? {};
+
+	if (nativeOptions.headers !== undefined && nativeOptions.headers !== null) {
+		nativeOptions.headers = headersToNative(nativeOptions.headers);
+	} else {
+		delete nativeOptions.headers;
+	}
+	paramsToNative(nativeOptions);
+	correlationIdToNative(nativeOptions);
+	profileBodyToNative(nativeOptions, agent ?? nativeOptions.agent);
+
+	const body = nativeOptions.body;
+	if (body === undefined || body === null) {
+		delete nativeOptions.body;
+	} else if (typeof body === "object" && typeof body.getReader === "function") {
+		throw new TypeError("streaming bodies are not supported here");
+	} else {
+		bufferedBodyToNative(nativeOptions);
+	}
+
+	return nativeOptions;
+}
+
+/**
+ * @param {string|URL|{ toString(): string }} resource
+ * @returns {string}
+ */
+function resourceToUrl(resource) {
+	return resource instanceof URL ? resource.href : String(resource);
+}
+
+const nativeJson = native.Agent.prototype.json;
+native.Agent.prototype.json = async function json(resource, options) {
+	const signal = options?.signal;
+	if (signal?.aborted) {
+		throw abortError(signal);
+	}
+
+	return withAbortReason(
+		nativeJson.call(
+			this,
+			resourceToUrl(resource),
+			await simpleOptionsToNative(options, this),
+			signal,
+		),
+		signal,
+	);
+};
+
+const nativeGraphql = native.Agent.prototype.graphql;
+native.Agent.prototype.graphql = async function graphql(resource, request, options) {
+	const signal = options?.signal;
+	if (signal?.aborted) {
+		throw abortError(signal);
+	}
+
+	return withAbortReason(
+		nativeGraphql.call(
+			this,
+			resourceToUrl(resource),
+			request,
+			await simpleOptionsToNative(options, this),
+			signal,
+		),
+		signal,
+	);
+};
+
+const nativeGrpcWeb = native.Agent.prototype.grpcWeb;
+native.Agent.prototype.grpcWeb = async function grpcWeb(resource, message, options) {
+	const signal = options?.signal;
+	if (signal?.aborted) {
+		throw abortError(signal);
+	}
+
+	const response = await withAbortReason(
+		nativeGrpcWeb.call(
+			this,
+			resourceToUrl(resource),
+			message === undefined || message === null ? Buffer.alloc(0) : bufferView(message),
+			await simpleOptionsToNative(options, this),
+			signal,
+		),
+		signal,
+	);
+	return {
+		...response,
+		message: response.message ?? null,
+		statusMessage: response.statusMessage ?? null,
+		headers: new Headers(response.headers),
+		trailers: new Headers(response.trailers),
+	};
+};
+
+const nativePrefetch = native.Agent.prototype.prefetch;
+native.Agent.prototype.prefetch = async function prefetch(resource, options) {
+	// prefetches are always GET requests, without a body
+	const { body, json, method, ...rest } = options ?? {};
+	await nativePrefetch.call(
+		this,
+		resourceToUrl(resource),
+		await simpleOptionsToNative(rest, this),
+	);
+};
+
+const nativePrepare = native.Agent.prototype.prepare;
+native.Agent.prototype.prepare = function prepare(resource, options) {
+	const { signal, ...nativeOptions } = options ?? {};
+
+	if (nativeOptions.headers !== undefined && nativeOptions.headers !== null) {
+		nativeOptions.headers = headersToNative(nativeOptions.headers);
+	} else {
+		delete nativeOptions.headers;
+	}
+	paramsToNative(nativeOptions);
+	correlationIdToNative(nativeOptions);
+	profileBodyToNative(nativeOptions, this);
+
+	nativeOptions.body = preparedBodyToNative(nativeOptions.body);
+	if (nativeOptions.body === undefined) {
+		delete nativeOptions.body;
+	}
+
+	return nativePrepare.call(this, resourceToUrl(resource), nativeOptions);
+};
+
+const nativeSend = native.PreparedRequest.prototype.send;
+native.PreparedRequest.prototype.send = async function send(body, options) {
+	const signal = options?.signal;
+	if (signal?.aborted) {
+		throw abortError(signal);
+	}
+
+	const nativeResponse = await withAbortReason(
+		nativeSend.call(this, preparedBodyToNative(body), signal),
+		signal,
+	);
+	return new Response(nativeResponse);
+};
+
+/**
+ * Convert a body for a prepared request, which only takes bodies that convert synchronously
+ * @param {string|ArrayBuffer|ArrayBufferView|number[]|null} [body]
+ * @returns {string|Buffer|undefined}
+ */
+function preparedBodyToNative(body) {
+	if (body === undefined || body === null) {
+		return undefined;
+	} else if (typeof body === "string") {
+		return body;
+	} else if (body instanceof ArrayBuffer || ArrayBuffer.isView(body)) {
+		return bufferView(body);
+	} else if (Array.isArray(body)) {
+		return Buffer.from(body);
+	}
+
+	throw new TypeError("prepared requests only take string and byte bodies");
+}
+
+// Statuses which mean the server is rate-limiting or temporarily unavailable, for retries
+const RETRY_STATUSES = new Set([429, 503]);
+
+/**
+ * Iterate over the pages of a paginated resource
+ * @param {string|URL|{ toString(): string }} resource - The URL of the first page
+ * @param {FetchOptions} [options] - Fetch options, used for every page
+ * @param {PaginateOptions} [paginateOptions]
+ * @returns {AsyncGenerator<Response>}
+ *
+ * Each page is fetched once the previous one has been handled. The next page's URL is taken from
+ * the `Link: <url>; rel="next"` header by default, or from `nextFrom(response)`, which is given a
+ * clone of the page's response so it can read the body too. Iteration ends when there's no next
+ * page, or after `maxPages`.
+ *
+ * Pages which fail with a retriable error, or which have a 429 or 503 status, are retried up to
+ * `retries` times, waiting for as long as their `Retry-After` header says, or with an exponential
+ * backoff from `retryDelay` milliseconds otherwise.
+ */
+native.Agent.prototype.paginate = async function* paginate(
+	resource,
+	options,
+	paginateOptions,
+) {
+	const {
+		nextFrom = "link-header",
+		maxPages = Infinity,
+		retries = 3,
+		retryDelay = 1000,
+	} = paginateOptions ?? {};
+	if (nextFrom !== "link-header" && typeof nextFrom !== "function") {
+		throw new TypeError('nextFrom must be "link-header" or a function');
+	}
+
+	let url = resourceToUrl(resource);
+	for (let page = 0; url !== undefined && page < maxPages; page++) {
+		const response = await fetchPage(this, url, options, retries, retryDelay);
+		const next =
+			nextFrom === "link-header"
+				? nextLink(response.headers.get("link"))
+				: await nextFrom(response.clone());
+		url =
+			next === undefined || next === null
+				? undefined
+				: new URL(resourceToUrl(next), response.url).href;
+		yield response;
+	}
+};
+
+/**
+ * Fetch a page for paginate(), with retries
+ * @param {Agent} agent
+ * @param {string} url
+ * @param {FetchOptions} [options]
+ * @param {number} retries
+ * @param {number} retryDelay
+ * @returns {Promise<Response>}
+ */
+async function fetchPage(agent, url, options, retries, retryDelay) {
+	for (let attempt = 0; ; attempt++) {
+		let response;
+		let error;
+		try {
+			response = await fetch(url, { ...options, agent });
+		} catch (err) {
+			error = err;
+		}
+
+		const retriable =
+			RETRY_STATUSES.has(response?.status ?? error?.status) ||
+			RETRIABLE_CODES.has(error?.code);
+		if (!retriable || attempt >= retries) {
+			if (error) throw error;
+			return response;
+		}
+
+		await response?.discard();
+		const wait =
+			retryAfter((response ?? error).headers?.get("retry-after")) ??
+			retryDelay * 2 ** attempt;
+		const signal = options?.signal;
+		try {
+			await sleep(wait, undefined, { signal });
+		} catch (err) {
+			if (signal?.aborted) throw abortError(signal);
+			throw err;
+		}
+	}
+}
+
+/**
+ * Parse a Retry-After header
+ * @param {string|null|undefined} value - delay in seconds, or an HTTP date
+ * @returns {number|undefined} milliseconds to wait
+ */
+function retryAfter(value) {
+	if (!value) return undefined;
+	const seconds = Number(value);
+	if (Number.isFinite(seconds)) return Math.max(0, seconds * 1000);
+	const date = Date.parse(value);
+	return Number.isNaN(date) ? undefined : Math.max(0, date - Date.now());
+}
+
+/**
+ * Find the `rel="next"` target of a Link header (RFC 8288)
+ * @param {string|null} header
+ * @returns {string|undefined} the target, which may be relative
+ */
+function nextLink(header) {
+	if (!header) return undefined;
+	for (const [, target, params] of header.matchAll(/<([^>]*)>([^,]*)/g)) {
+		for (const param of params.split(";")) {
+			const [name, value = ""] = param.split("=").map((part) => part.trim());
+			if (
+				name.toLowerCase() === "rel" &&
+				value
+					.replace(/^"|"$/g, "")
+					.toLowerCase()
+					.split(/\s+/)
+					.includes("next")
+			) {
+				return target;
+			}
+		}
+	}
+	return undefined;
+}
+
+/**
+ * Fetch many resources at once
+ * @param {Array<string|URL|{ url: string|URL, options?: FetchOptions }>} requests
+ * @param {{ concurrency?: number, failFast?: boolean, signal?: AbortSignal }} [options]
+ * @returns {Promise<Array<PromiseSettledResult<Response>>>}
+ *
+ * Requests are either a URL, or an object with the URL and fetch options. Streaming bodies and
+ * per-request signals are not supported; the `signal` option aborts the whole batch.
+ */
+async function fetchAll(requests, options) {
+	const nativeRequests = await Promise.all(
+		Array.from(requests, async (request) => {
+			if (typeof request === "string" || request instanceof URL) {
+				return { url: resourceToUrl(request) };
+			}
+			return {
+				url: resourceToUrl(request.url),
+				options: await simpleOptionsToNative(request.options),
+			};
+		}),
+	);
+
+	const { signal, ...batchOptions } = options ?? {};
+	if (signal?.aborted) {
+		throw abortError(signal);
+	}
+
+	const results = await withAbortReason(
+		native.faithFetchAll(nativeRequests, batchOptions, signal),
+		signal,
+	);
+	return results.map((result) =>
+		result.status === "fulfilled"
+			? { status: "fulfilled", value: new Response(result.value) }
+			: result,
+	);
+}
+
+/**
+ * Upload a file in parts
+ * @param {string|URL} resource
+ * @param {string} filePath
+ * @param {{ agent?: Agent, concurrency?: number, headersPerPart?: HeadersInit, method?: string, partSize?: number, retries?: number, signal?: AbortSignal }} [options]
+ * @returns {Promise<Array<import('./index').FaithUploadPart>>}
+ */
+async function upload(resource, filePath, options) {
+	const { signal, ...uploadOptions } = options ?? {};
+	if (uploadOptions.headersPerPart !== undefined && uploadOptions.headersPerPart !== null) {
+		uploadOptions.headersPerPart = headersToNative(uploadOptions.headersPerPart);
+	} else {
+		delete uploadOptions.headersPerPart;
+	}
+
+	if (signal?.aborted) {
+		throw abortError(signal);
+	}
+
+	return withAbortReason(
+		native.faithUpload(resourceToUrl(resource), String(filePath), uploadOptions, signal),
+		signal,
+	);
+}
+
+/**
+ * Fetch a resource and read its body, blocking until it's done
+ * @param {string|URL|{ toString(): string }} resource
+ * @param {FetchOptions} [options] - without streaming or Blob bodies, nor signal
+ * @returns {SyncResponse}
+ */
+function faithFetchSync(resource, options) {
+	if (options?.signal) {
+		throw new TypeError("faithFetchSync() doesn't support signal");
+	}
+	const nativeOptions = syncOptionsToNative(options);
+	if (nativeOptions.body instanceof Blob) {
+		throw new TypeError("faithFetchSync() doesn't support Blob bodies");
+	}
+
+	const { headers, body, ...response } = native.faithFetchSync(
+		resourceToUrl(resource),
+		nativeOptions,
+	);
+	return {
+		...response,
+		headers: new Headers(headers),
+		body,
+		bytes: () => body,
+		text: () => body.toString("utf8"),
+		json: () => JSON.parse(body.toString("utf8")),
+	};
+}
+
+/**
+ * Convert the headers of an undici dispatch to [name, value] pairs
+ * @param {string[]|Buffer[]|Record<string, string|string[]>|Iterable<[string, string]>} [headers]
+ * @returns {Array<[string, string]>|Iterable<[string, string]>|undefined}
+ *
+ * undici takes flat arrays of alternating names and values, and objects whose values may be arrays
+ * for repeated headers. Buffers are decoded as latin1, like undici does.
+ */
+function dispatchHeaders(headers) {
+	if (headers === undefined || headers === null) return undefined;
+
+	const text = (value) =>
+		Buffer.isBuffer(value) ? value.toString("latin1") : String(value);
+	const pairs = [];
+	const add = (name, value) => {
+		for (const item of Array.isArray(value) ? value : [value]) {
+			pairs.push([text(name), text(item)]);
+		}
+	};
+	if (Array.isArray(headers)) {
+		if (headers.length > 0 && Array.isArray(headers[0])) {
+			for (const [name, value] of headers) add(name, value);
+		} else {
+			for (let i = 0; i + 1 < headers.length; i += 2) {
+				add(headers[i], headers[i + 1]);
+			}
+		}
+		return pairs;
+	}
+	if (typeof headers[Symbol.iterator] === "function") {
+		return headers;
+	}
+	for (const [name, value] of Object.entries(headers)) {
+		if (value !== undefined) add(name, value);
+	}
+	return pairs;
+}
+
+/**
+ * Convert the body of an undici dispatch to one fetch() takes
+ * @param {any} body
+ * @returns {any}
+ *
+ * Node streams and other async iterables become a ReadableStream; other bodies are left as they are.
+ */
+function dispatchBody(body) {
+	if (body === undefined || body === null) return undefined;
+	if (
+		typeof body === "object" &&
+		typeof body[Symbol.asyncIterator] === "function" &&
+		typeof body.getReader !== "function"
+	) {
+		return ReadableStream.from(body);
+	}
+	return body;
+}
+
+/**
+ * Make the controller of an undici dispatch, for its handler to abort, pause, and resume it
+ * @param {AbortController} aborter
+ *
+ * This is the controller of undici's newer handler API. The legacy API uses its abort() and
+ * resume() as plain functions, and pauses when the handler returns false.
+ */
+function dispatchController(aborter) {
+	let paused = null;
+	const { signal } = aborter;
+	const controller = {
+		get aborted() {
+			return signal.aborted;
+		},
+		get reason() {
+			return signal.reason;
+		},
+		get paused() {
+			return paused !== null;
+		},
+		abort: (reason) => aborter.abort(reason),
+		pause() {
+			if (paused !== null) return;
+			paused = {};
+			paused.promise = new Promise((resolve, reject) => {
+				paused.resolve = resolve;
+				paused.reject = reject;
+			});
+		},
+		resume() {
+			paused?.resolve();
+			paused = null;
+		},
+		// resolves once resumed, or rejects if aborted
+		resumed() {
+			if (signal.aborted) return Promise.reject(signal.reason);
+			return paused?.promise ?? Promise.resolve();
+		},
+	};
+	signal.addEventListener("abort", () => paused?.reject(signal.reason), {
+		once: true,
+	});
+	return controller;
+}
+
+/**
+ * Run an undici dispatch through fetch(), calling its handler
+ * @param {Agent} [agent]
+ * @param {object} options - undici's DispatchOptions
+ * @param {object} handler - undici's DispatchHandler, with either the legacy or the newer API
+ * @param {AbortController} aborter
+ */
+async function dispatchThrough(agent, options, handler, aborter) {
+	const legacy = typeof handler.onResponseStart !== "function";
+	const controller = dispatchController(aborter);
+	let done = false;
+	try {
+		if (options.upgrade || options.method === "CONNECT") {
+			throw new TypeError("upgrade and CONNECT are not supported");
+		}
+
+		if (legacy) {
+			handler.onConnect?.(controller.abort);
+		} else {
+			handler.onRequestStart?.(controller, {});
+		}
+		if (aborter.signal.aborted) throw aborter.signal.reason;
+
+		const response = await fetch(new URL(options.path, options.origin), {
+			...(agent && { agent }),
+			method: options.method,
+			headers: dispatchHeaders(options.headers),
+			body: dispatchBody(options.body),
+			duplex: "half",
+			signal: aborter.signal,
+		});
+
+		const headers = [...response.headers];
+		if (legacy) {
+			const raw = headers.flat().map((item) => Buffer.from(item, "latin1"));
+			const more = handler.onHeaders?.(
+				response.status,
+				raw,
+				controller.resume,
+				response.statusText,
+			);
+			if (more === false) controller.pause();
+		} else {
+			handler.onResponseStart?.(
+				controller,
+				response.status,
+				headersRecord(headers),
+				response.statusText,
+			);
+		}
+		await controller.resumed();
+
+		for await (const chunk of response.chunks()) {
+			if (aborter.signal.aborted) throw aborter.signal.reason;
+			if (legacy) {
+				if (handler.onData?.(chunk) === false) controller.pause();
+			} else {
+				handler.onResponseData?.(controller, chunk);
+			}
+			await controller.resumed();
+		}
+
+		const trailers = [...((await response.trailers) ?? [])];
+		done = true;
+		if (legacy) {
+			handler.onComplete?.(
+				trailers.flat().map((item) => Buffer.from(item, "latin1")),
+			);
+		} else {
+			handler.onResponseEnd?.(controller, headersRecord(trailers));
+		}
+	} catch (error) {
+		if (done) throw error;
+		const reason = aborter.signal.aborted ? aborter.signal.reason : error;
+		if (legacy) {
+			handler.onError?.(reason);
+		} else {
+			handler.onResponseError?.(controller, reason);
+		}
+	}
+}
+
+/**
+ * Convert [name, value] pairs to an object, with arrays for repeated names
+ * @param {Array<[string, string]>} pairs
+ * @returns {Record<string, string|string[]>}
+ */
+function headersRecord(pairs) {
+	const record = {};
+	for (const [name, value] of pairs) {
+		const existing = record[name];
+		if (existing === undefined) {
+			record[name] = value;
+		} else if (Array.isArray(existing)) {
+			existing.push(value);
+		} else {
+			record[name] = [existing, value];
+		}
+	}
+	return record;
+}
+
+/**
+ * An undici Dispatcher which makes its requests with Fáith
+ *
+ * Pass it as the `dispatcher` option of undici's or Node's fetch(), or to libraries which take one,
+ * or install it with undici's setGlobalDispatcher(). Only dispatch(), close(), and destroy() are
+ * implemented: undici's request(), stream(), and pipeline() are not.
+ *
+ * Redirects, decompression, and cookies are handled by the agent as configured, so the responses
+ * handed to undici are the final ones, already decompressed.
+ */
+class FaithDispatcher extends EventEmitter {
+	/** @type {Agent|undefined} */
+	#agent;
+	/** @type {Map<AbortController, Promise<void>>} */
+	#requests = new Map();
+	#closed = false;
+	#destroyed = false;
+
+	/**
+	 * @param {{ agent?: Agent }} [options] - the agent to make requests with, the global agent if none
+	 */
+	constructor(options) {
+		super();
+		this.#agent = options?.agent;
+	}
+
+	get closed() {
+		return this.#closed;
+	}
+
+	get destroyed() {
+		return this.#destroyed;
+	}
+
+	/**
+	 * Make a request, calling the handler with its response
+	 * @param {object} options - undici's DispatchOptions
+	 * @param {object} handler - undici's DispatchHandler
+	 * @returns {boolean} always true, as requests are queued by the agent
+	 */
+	dispatch(options, handler) {
+		const aborter = new AbortController();
+		if (this.#closed) {
+			aborter.abort(new Error("the dispatcher is closed"));
+		}
+
+		// errors thrown by the handler once the request is complete are uncaught, as with undici
+		const request = dispatchThrough(this.#agent, options, handler, aborter)
+			.catch((error) =>
+				process.nextTick(() => {
+					throw error;
+				}),
+			)
+			.finally(() => this.#requests.delete(aborter));
+		this.#requests.set(aborter, request);
+		return true;
+	}
+
+	/**
+	 * Stop accepting requests, and wait for the ones in flight to finish
+	 * @param {(error: Error|null) => void} [callback]
+	 * @returns {Promise<void>|undefined} a promise if there's no callback
+	 */
+	close(callback) {
+		this.#closed = true;
+		return this.#settled(callback);
+	}
+
+	/**
+	 * Stop accepting requests, and abort the ones in flight
+	 * @param {Error|null} [error] - the reason the requests are aborted with
+	 * @param {(error: Error|null) => void} [callback]
+	 * @returns {Promise<void>|undefined} a promise if there's no callback
+	 */
+	destroy(error, callback) {
+		if (typeof error === "function") {
+			callback = error;
+			error = null;
+		}
+		this.#closed = true;
+		this.#destroyed = true;
+		for (const aborter of this.#requests.keys()) {
+			aborter.abort(
+				error ?? new DOMException("the dispatcher was destroyed", "AbortError"),
+			);
+		}
+		return this.#settled(callback);
+	}
+
+	#settled(callback) {
+		const settled = Promise.allSettled(this.#requests.values()).then(() => {});
+		if (typeof callback !== "function") return settled;
+		settled.then(() => callback(null));
+	}
+}
+
+// Statuses whose responses have no body, which are written without chunked framing
+const NULL_BODY_STATUSES = new Set([101, 103, 204, 205, 304]);
+
+// Response headers which are about the connection to the server, not the one to http.request()
+const HOP_BY_HOP_HEADER = /^(connection|keep-alive|transfer-encoding)$/i;
+
+/**
+ * The socket FaithHttpAgent gives to http.request(), which makes its requests with fetch()
+ *
+ * It parses the HTTP/1.1 request written to it, makes it through the agent, and writes the
+ * response back as HTTP/1.1 with chunked framing. Each socket carries a single request.
+ */
+class FaithAgentSocket extends Duplex {
+	/** @type {Agent|undefined} */
+	#agent;
+	#origin;
+	#aborter = new AbortController();
+	/** Bytes written which haven't been parsed yet */
+	#buffer = Buffer.alloc(0);
+	/** What's being parsed: head, length, size, data, data-end, trailers, or done */
+	#state = "head";
+	/** Bytes left of the body with Content-Length, or of the current chunk */
+	#remaining = 0;
+	/** @type {ReadableStreamDefaultController|null} */
+	#body = null;
+	/** @type {(() => void)|null} */
+	#drain = null;
+
+	/**
+	 * @param {Agent} [agent]
+	 * @param {string} origin
+	 */
+	constructor(agent, origin) {
+		super();
+		this.#agent = agent;
+		this.#origin = origin;
+	}
+
+	// http.request() configures these on sockets; timeouts are the agent's instead
+	setTimeout() {
+		return this;
+	}
+	setNoDelay() {
+		return this;
+	}
+	setKeepAlive() {
+		return this;
+	}
+	ref() {
+		return this;
+	}
+	unref() {
+		return this;
+	}
+
+	_write(chunk, _encoding, callback) {
+		this.#buffer =
+			this.#buffer.length === 0 ? chunk : Buffer.concat([this.#buffer, chunk]);
+		try {
+			this.#parse();
+			callback();
+		} catch (error) {
+			callback(error);
+		}
+	}
+
+	_read() {
+		this.#drain?.();
+		this.#drain = null;
+	}
+
+	_destroy(error, callback) {
+		const reason =
+			error ?? new DOMException("the socket was destroyed", "AbortError");
+		this.#aborter.abort(reason);
+		try {
+			this.#body?.error(reason);
+		} catch {
+			// the body stream was already closed
+		}
+		callback(error);
+	}
+
+	#take(length) {
+		const taken = this.#buffer.subarray(0, length);
+		this.#buffer = this.#buffer.subarray(taken.length);
+		return taken;
+	}
+
+	#parse() {
+		for (;;) {
+			switch (this.#state) {
+				case "head": {
+					const end = this.#buffer.indexOf("\r\n\r\n");
+					if (end === -1) return;
+					const head = this.#take(end + 4).toString("latin1");
+					this.#start(head.slice(0, -4));
+					break;
+				}
+				case "length":
+				case "data": {
+					const chunk = this.#take(this.#remaining);
+					if (chunk.length === 0) return;
+					this.#remaining -= chunk.length;
+					this.#body.enqueue(chunk);
+					if (this.#remaining > 0) return;
+					if (this.#state === "length") {
+						this.#endBody();
+					} else {
+						this.#state = "data-end";
+					}
+					break;
+				}
+				case "data-end": {
+					if (this.#buffer.length < 2) return;
+					this.#take(2);
+					this.#state = "size";
+					break;
+				}
+				case "size": {
+					const end = this.#buffer.indexOf("\r\n");
+					if (end === -1) return;
+					const line = this.#take(end + 2).toString("latin1");
+					const size = Number.parseInt(line.split(";")[0], 16);
+					if (Number.isNaN(size)) {
+						throw new TypeError(`invalid chunk size: ${line.trim()}`);
+					}
+					this.#remaining = size;
+					this.#state = size === 0 ? "trailers" : "data";
+					break;
+				}
+				case "trailers": {
+					// http.request() doesn't send request trailers, so they're skipped
+					const end = this.#buffer.indexOf("\r\n");
+					if (end === -1) return;
+					this.#take(end + 2);
+					if (end === 0) this.#endBody();
+					break;
+				}
+				default:
+					this.#buffer = Buffer.alloc(0);
+					return;
+			}
+		}
+	}
+
+	#start(head) {
+		const [requestLine, ...lines] = head.split("\r\n");
+		const [method, target] = requestLine.split(" ");
+		const headers = [];
+		let length = 0;
+		let chunked = false;
+		for (const line of lines) {
+			const colon = line.indexOf(":");
+			const name = line.slice(0, colon).trim();
+			const value = line.slice(colon + 1).trim();
+			if (/^content-length$/i.test(name)) {
+				length = Number(value);
+			} else if (/^transfer-encoding$/i.test(name)) {
+				chunked = /chunked/i.test(value);
+			} else if (!/^(connection|keep-alive|host)$/i.test(name)) {
+				headers.push([name, value]);
+			}
+		}
+
+		let body;
+		if (chunked || length > 0) {
+			body = new ReadableStream({
+				start: (controller) => {
+					this.#body = controller;
+				},
+			});
+			this.#state = chunked ? "size" : "length";
+			this.#remaining = length;
+		} else {
+			this.#state = "done";
+		}
+
+		fetch(new URL(target, this.#origin), {
+			...(this.#agent && { agent: this.#agent }),
+			method,
+			headers,
+			body,
+			duplex: "half",
+			signal: this.#aborter.signal,
+		})
+			.then((response) => this.#respond(method, response))
+			.catch((error) => this.destroy(error));
+	}
+
+	#endBody() {
+		this.#body.close();
+		this.#body = null;
+		this.#state = "done";
+	}
+
+	async #push(data) {
+		if (!this.push(data)) {
+			await new Promise((resolve) => {
+				this.#drain = resolve;
+			});
+		}
+	}
+
+	async #respond(method, response) {
+		const hasBody =
+			method.toUpperCase() !== "HEAD" && !NULL_BODY_STATUSES.has(response.status);
+
+		let head = `HTTP/1.1 ${response.status} ${response.statusText}\r\n`;
+		for (const [name, value] of response.headers) {
+			if (HOP_BY_HOP_HEADER.test(name)) continue;
+			if (hasBody && /^content-length$/i.test(name)) continue;
+			head += `${name}: ${value}\r\n`;
+		}
+		if (hasBody) head += "Transfer-Encoding: chunked\r\n";
+		head += "Connection: close\r\n\r\n";
+		await this.#push(Buffer.from(head, "latin1"));
+
+		if (hasBody) {
+			for await (const chunk of response.chunks()) {
+				if (this.destroyed) return;
+				await this.#push(Buffer.from(`${chunk.length.toString(16)}\r\n`));
+				await this.#push(chunk);
+				await this.#push(Buffer.from("\r\n"));
+			}
+
+			let end = "0\r\n";
+			for (const [name, value] of (await response.trailers) ?? []) {
+				end += `${name}: ${value}\r\n`;
+			}
+			await this.#push(Buffer.from(`${end}\r\n`, "latin1"));
+		}
+		this.push(null);
+	}
+}
+
+/**
+ * A Node http.Agent which makes its requests with Fáith, for libraries based on http.request()
+ *
+ * Requests are parsed from what http.request() writes, and made with fetch() through the agent
+ * given, or the global agent. As http.request() checks that its agent is for the right protocol,
+ * use one with `protocol: "https:"` for https.request(): TLS is then done by Fáith.
+ */
+class FaithHttpAgent extends http.Agent {
+	/** @type {Agent|undefined} */
+	#agent;
+
+	/**
+	 * @param {{ agent?: Agent, protocol?: "http:" | "https:" } & import("node:http").AgentOptions} [options]
+	 */
+	constructor(options) {
+		const { agent, protocol = "http:", ...agentOptions } = options ?? {};
+		if (protocol !== "http:" && protocol !== "https:") {
+			throw new TypeError('protocol must be "http:" or "https:"');
+		}
+		// connections are Fáith's to pool, so each socket carries one request
+		super({ ...agentOptions, keepAlive: false });
+		this.protocol = protocol;
+		this.defaultPort = protocol === "https:" ? 443 : 80;
+		this.#agent = agent;
+	}
+
+	createConnection(options) {
+		const host = options.host ?? options.hostname ?? "localhost";
+		const port = options.port ?? this.defaultPort;
+		const origin = `${this.protocol}//${isIPv6(host) ? `[${host}]` : host}:${port}`;
+		return new FaithAgentSocket(this.#agent, origin);
+	}
+}
+
+// The globalThis.fetch property replaced by installGlobal(), restored by uninstallGlobal()
+let originalFetch;
+let globalInstalled = false;
+
+/**
+ * Replace globalThis.fetch with Fáith's, so code which calls the global fetch uses an agent
+ * @param {{ agent?: Agent }} [options]
+ * @returns {() => void} a function which uninstalls it, see uninstallGlobal()
+ *
+ * Requests made with the global fetch go through the given agent, or the global agent if there's
+ * none, unless they give their own `agent`. They resolve with Fáith's `Response`. Installing again
+ * replaces the agent, and uninstalling restores the original fetch.
+ */
+function installGlobal(options) {
+	const agent = options?.agent;
+	if (!globalInstalled) {
+		originalFetch = Object.getOwnPropertyDescriptor(globalThis, "fetch");
+		globalInstalled = true;
+	}
+
+	// as a method, so it's named "fetch" without shadowing fetch() here
+	const installed = {
+		fetch(resource, init) {
+			return fetch(resource, { ...(agent && { agent }), ...init });
+		},
+	}.fetch;
+	Object.defineProperty(globalThis, "fetch", {
+		value: installed,
+		writable: true,
+		enumerable: originalFetch?.enumerable ?? true,
+		configurable: true,
+	});
+	return uninstallGlobal;
+}
+
+/**
+ * Restore the globalThis.fetch replaced by installGlobal(), if it's installed
+ */
+function uninstallGlobal() {
+	if (!globalInstalled) return;
+
+	if (originalFetch === undefined) {
+		delete globalThis.fetch;
+	} else {
+		Object.defineProperty(globalThis, "fetch", originalFetch);
+	}
+	originalFetch = undefined;
+	globalInstalled = false;
+}
+
 module.exports = {
 	Agent: native.Agent,
+	AgentProfile: native.AgentProfile,
+	AltSvcSource: native.AltSvcSource,
 	CacheMode: native.CacheMode,
 	CacheStore: native.CacheStore,
+	capabilities: native.capabilities,
+	ChecksumAlgorithm: native.ChecksumAlgorithm,
+	configureRuntime: native.configureRuntime,
 	createStreamBodyPair: native.createStreamBodyPair,
 	Credentials: native.CredentialsOption,
+	DnsFamily: native.DnsFamily,
 	Duplex: native.DuplexOption,
+	EchDohProvider: native.EchDohProvider,
 	ERROR_CODES,
+	errorCodes: native.errorCodes,
 	FAITH_VERSION: native.FAITH_VERSION,
+	FaithAbortError: native.FaithAbortError,
+	FaithDispatcher,
+	FaithError: native.FaithError,
+	FaithHeaders: native.FaithHeaders,
+	FaithHttpAgent,
+	FaithHttpError: native.FaithHttpError,
+	FaithNetworkError: native.FaithNetworkError,
+	faithFetchSync,
+	faithShutdown: native.faithShutdown,
+	FaithSyntaxError: native.FaithSyntaxError,
+	FaithTimeoutError: native.FaithTimeoutError,
+	FaithTypeError: native.FaithTypeError,
 	fetch,
+	fetchAll,
+	getGlobalAgent: native.getGlobalAgent,
 	Http3Congestion: native.Http3Congestion,
+	installGlobal,
+	MiddlewareStage: native.MiddlewareStage,
+	OcspStatus: native.OcspStatus,
+	PreparedRequest: native.PreparedRequest,
+	ProxyMode: native.ProxyMode,
+	receiveResponse,
 	Redirect: native.Redirect,
 	REQWEST_VERSION: native.REQWEST_VERSION,
+	RequestEventType: native.RequestEventType,
 	Response,
+	setGlobalAgent: native.setGlobalAgent,
 	StreamBody: native.StreamBody,
 	StreamBodySender: native.StreamBodySender,
+	TlsCryptoProvider: native.TlsCryptoProvider,
+	uninstallGlobal,
+	UrlCredentials: native.UrlCredentials,
+	UrlIdn: native.UrlIdn,
+	upload,
 	USER_AGENT: native.USER_AGENT,
+	Version: native.VersionOption,
+	withCorrelationId,
 };
@@ -2,18 +2,49 @@ import wrapper from "./wrapper.js";
 
 export const {
 	Agent,
+	AgentProfile,
+	AltSvcSource,
 	CacheMode,
 	CacheStore,
+	capabilities,
+	ChecksumAlgorithm,
+	configureRuntime,
 	Credentials,
+	DnsFamily,
 	Duplex,
 	ERROR_CODES,
+	errorCodes,
 	FAITH_VERSION,
+	FaithAbortError,
+	FaithDispatcher,
+	FaithError,
+	FaithHeaders,
+	FaithHttpAgent,
+	FaithHttpError,
+	FaithNetworkError,
+	faithShutdown,
+	FaithSyntaxError,
+	FaithTimeoutError,
+	FaithTypeError,
 	fetch,
+	fetchAll,
+	getGlobalAgent,
 	Http3Congestion,
+	installGlobal,
+	MiddlewareStage,
+	PreparedRequest,
+	ProxyMode,
 	Redirect,
 	REQWEST_VERSION,
 	Response,
+	setGlobalAgent,
+	uninstallGlobal,
+	UrlCredentials,
+	UrlIdn,
+	upload,
 	USER_AGENT,
+	Version,
+	withCorrelationId,
 } = wrapper;
 
 export default wrapper;